@@ -2,14 +2,63 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use tw_coin_entry::error::prelude::SigningResult;
-use tw_utxo::address::standard_bitcoin::StandardBitcoinAddress;
+use std::str::FromStr;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::derivation::Derivation;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_entry::prefix::Prefix;
+use tw_keypair::tw::PublicKey;
+use tw_utxo::address::standard_bitcoin::{StandardBitcoinAddress, StandardBitcoinPrefix};
 use tw_utxo::context::{AddressPrefixes, UtxoContext};
 use tw_utxo::script::Script;
 
+/// Parameters of a chain the [`crate::entry::UtxoEntry`] is instantiated with.
+/// The signing parameters are defined by [`UtxoContext`], this trait adds the address management.
+pub trait ChainParams: UtxoContext + Default {
+    type AddressPrefix: Prefix;
+
+    fn parse_address(
+        coin: &dyn CoinContext,
+        address: &str,
+        prefix: Option<Self::AddressPrefix>,
+    ) -> AddressResult<Self::Address>;
+
+    fn parse_address_unchecked(address: &str) -> AddressResult<Self::Address> {
+        Self::Address::from_str(address)
+    }
+
+    fn derive_address(
+        coin: &dyn CoinContext,
+        public_key: PublicKey,
+        derivation: Derivation,
+        prefix: Option<Self::AddressPrefix>,
+    ) -> AddressResult<Self::Address>;
+}
+
 #[derive(Default)]
 pub struct StandardBitcoinContext;
 
+impl ChainParams for StandardBitcoinContext {
+    type AddressPrefix = StandardBitcoinPrefix;
+
+    fn parse_address(
+        coin: &dyn CoinContext,
+        address: &str,
+        prefix: Option<Self::AddressPrefix>,
+    ) -> AddressResult<Self::Address> {
+        StandardBitcoinAddress::from_str_with_coin_and_prefix(coin, address, prefix)
+    }
+
+    fn derive_address(
+        coin: &dyn CoinContext,
+        public_key: PublicKey,
+        derivation: Derivation,
+        prefix: Option<Self::AddressPrefix>,
+    ) -> AddressResult<Self::Address> {
+        StandardBitcoinAddress::derive_as_tw(coin, &public_key, derivation, prefix)
+    }
+}
+
 impl UtxoContext for StandardBitcoinContext {
     type Address = StandardBitcoinAddress;
 
@@ -21,8 +70,31 @@ impl UtxoContext for StandardBitcoinContext {
             StandardBitcoinAddress::Legacy(legacy) => {
                 legacy.to_script_pubkey(prefixes.p2pkh_prefix, prefixes.p2sh_prefix)
             },
-            StandardBitcoinAddress::Segwit(segwit) => segwit.to_script_pubkey(),
-            StandardBitcoinAddress::Taproot(taproot) => taproot.to_script_pubkey(),
+            StandardBitcoinAddress::Segwit(segwit) => {
+                check_hrp(addr, segwit.hrp(), &prefixes)?;
+                segwit.to_script_pubkey()
+            },
+            StandardBitcoinAddress::Taproot(taproot) => {
+                check_hrp(addr, taproot.hrp(), &prefixes)?;
+                taproot.to_script_pubkey()
+            },
         }
     }
 }
+
+/// Checks that the segwit address belongs to the network of the chain,
+/// e.g. a testnet `tb1` address is not sent to on mainnet.
+fn check_hrp(
+    addr: &StandardBitcoinAddress,
+    hrp: &str,
+    prefixes: &AddressPrefixes,
+) -> SigningResult<()> {
+    match prefixes.hrp {
+        Some(ref expected) if expected != hrp => {
+            SigningError::err(SigningErrorType::Error_invalid_address).context(format!(
+                "The given '{addr}' address has unexpected HRP. Expected hrp={expected}"
+            ))
+        },
+        _ => Ok(()),
+    }
+}
@@ -1,11 +1,11 @@
-use crate::context::StandardBitcoinContext;
+use crate::context::{ChainParams, StandardBitcoinContext};
 use crate::modules::compiler::BitcoinCompiler;
 use crate::modules::planner::BitcoinPlanner;
 use crate::modules::signer::BitcoinSigner;
 use crate::modules::transaction_util::BitcoinTransactionUtil;
-use std::str::FromStr;
+use std::marker::PhantomData;
 use tw_coin_entry::coin_context::CoinContext;
-use tw_coin_entry::coin_entry::{CoinEntry, PublicKeyBytes, SignatureBytes};
+use tw_coin_entry::coin_entry::{CoinAddress, CoinEntry, PublicKeyBytes, SignatureBytes};
 use tw_coin_entry::derivation::Derivation;
 use tw_coin_entry::error::prelude::*;
 use tw_coin_entry::modules::json_signer::NoJsonSigner;
@@ -14,20 +14,42 @@ use tw_coin_entry::modules::transaction_decoder::NoTransactionDecoder;
 use tw_coin_entry::modules::wallet_connector::NoWalletConnector;
 use tw_keypair::tw::PublicKey;
 use tw_proto::BitcoinV2::Proto;
-use tw_utxo::address::standard_bitcoin::{StandardBitcoinAddress, StandardBitcoinPrefix};
 
-pub struct BitcoinEntry;
+pub type BitcoinEntry = UtxoEntry<StandardBitcoinContext>;
 
-impl CoinEntry for BitcoinEntry {
-    type AddressPrefix = StandardBitcoinPrefix;
-    type Address = StandardBitcoinAddress;
+/// Coin entry of a Bitcoin-like chain, where the chain specifics are defined by the `Params`.
+pub struct UtxoEntry<Params> {
+    _phantom: PhantomData<Params>,
+}
+
+impl<Params> UtxoEntry<Params> {
+    pub const fn new() -> Self {
+        UtxoEntry {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Params> Default for UtxoEntry<Params> {
+    fn default() -> Self {
+        UtxoEntry::new()
+    }
+}
+
+impl<Params> CoinEntry for UtxoEntry<Params>
+where
+    Params: ChainParams,
+    Params::Address: CoinAddress,
+{
+    type AddressPrefix = Params::AddressPrefix;
+    type Address = Params::Address;
     type SigningInput<'a> = Proto::SigningInput<'a>;
     type SigningOutput = Proto::SigningOutput<'static>;
     type PreSigningOutput = Proto::PreSigningOutput<'static>;
 
     // Optional modules:
     type JsonSigner = NoJsonSigner;
-    type PlanBuilder = BitcoinPlanner<StandardBitcoinContext>;
+    type PlanBuilder = BitcoinPlanner<Params>;
     type MessageSigner = NoMessageSigner;
     type WalletConnector = NoWalletConnector;
     type TransactionDecoder = NoTransactionDecoder;
@@ -40,12 +62,12 @@ impl CoinEntry for BitcoinEntry {
         address: &str,
         prefix: Option<Self::AddressPrefix>,
     ) -> AddressResult<Self::Address> {
-        StandardBitcoinAddress::from_str_with_coin_and_prefix(coin, address, prefix)
+        Params::parse_address(coin, address, prefix)
     }
 
     #[inline]
     fn parse_address_unchecked(&self, address: &str) -> AddressResult<Self::Address> {
-        StandardBitcoinAddress::from_str(address)
+        Params::parse_address_unchecked(address)
     }
 
     #[inline]
@@ -56,12 +78,12 @@ impl CoinEntry for BitcoinEntry {
         derivation: Derivation,
         prefix: Option<Self::AddressPrefix>,
     ) -> AddressResult<Self::Address> {
-        StandardBitcoinAddress::derive_as_tw(coin, &public_key, derivation, prefix)
+        Params::derive_address(coin, public_key, derivation, prefix)
     }
 
     #[inline]
     fn sign(&self, coin: &dyn CoinContext, proto: Self::SigningInput<'_>) -> Self::SigningOutput {
-        BitcoinSigner::<StandardBitcoinContext>::sign(coin, &proto)
+        BitcoinSigner::<Params>::sign(coin, &proto)
     }
 
     #[inline]
@@ -70,7 +92,7 @@ impl CoinEntry for BitcoinEntry {
         coin: &dyn CoinContext,
         proto: Proto::SigningInput<'_>,
     ) -> Self::PreSigningOutput {
-        BitcoinCompiler::<StandardBitcoinContext>::preimage_hashes(coin, proto)
+        BitcoinCompiler::<Params>::preimage_hashes(coin, proto)
     }
 
     #[inline]
@@ -81,12 +103,12 @@ impl CoinEntry for BitcoinEntry {
         signatures: Vec<SignatureBytes>,
         public_keys: Vec<PublicKeyBytes>,
     ) -> Self::SigningOutput {
-        BitcoinCompiler::<StandardBitcoinContext>::compile(coin, proto, signatures, public_keys)
+        BitcoinCompiler::<Params>::compile(coin, proto, signatures, public_keys)
     }
 
     #[inline]
     fn plan_builder(&self) -> Option<Self::PlanBuilder> {
-        Some(BitcoinPlanner::<StandardBitcoinContext>::default())
+        Some(BitcoinPlanner::<Params>::default())
     }
 
     #[inline]
@@ -4,7 +4,10 @@
 
 use crate::modules::protobuf_builder::ProtobufBuilder;
 use crate::modules::psbt_request::PsbtRequest;
+use crate::modules::session_request::SessionRequest;
 use crate::modules::signing_request::SigningRequestBuilder;
+use crate::modules::silent_payments::SilentPayments;
+use crate::modules::tx_builder::script_parser::StandardScriptParser;
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use tw_coin_entry::coin_context::CoinContext;
@@ -22,8 +25,13 @@ use tw_utxo::modules::sighash_verifier::SighashVerifier;
 use tw_utxo::modules::tx_compiler::TxCompiler;
 use tw_utxo::modules::tx_planner::TxPlanner;
 use tw_utxo::modules::utxo_selector::SelectResult;
+use tw_utxo::script::{Script, Witness};
 use tw_utxo::signing_mode::SigningMethod;
-use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::spending_data::SpendingData;
+use tw_utxo::transaction::standard_transaction::{Transaction, WeightBreakdown};
+use tw_utxo::transaction::transaction_interface::{TransactionInterface, TxOutputInterface};
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
 
 pub struct BitcoinCompiler<Context: UtxoContext> {
     _phantom: PhantomData<Context>,
@@ -45,20 +53,46 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
         coin: &dyn CoinContext,
         input: Proto::SigningInput<'_>,
     ) -> SigningResult<Proto::PreSigningOutput<'static>> {
+        let mut session = Vec::default();
         let unsigned_tx = match input.transaction {
             TransactionType::builder(ref tx_builder) => {
+                Self::check_no_silent_payments(tx_builder)?;
                 let request = SigningRequestBuilder::<Context>::build(coin, &input, tx_builder)?;
-                TxPlanner::plan(request)?.unsigned_tx
+                let unsigned_tx = TxPlanner::plan(request)?.unsigned_tx;
+                session =
+                    SessionRequest::<Context>::capture(coin, &input, tx_builder, &unsigned_tx)?;
+                unsigned_tx
             },
             TransactionType::psbt(ref psbt) => {
                 PsbtRequest::<Context>::build(&input, psbt)?.unsigned_tx
             },
+            TransactionType::session(ref session) => {
+                SessionRequest::<Context>::build(coin, session)?.unsigned_tx
+            },
             TransactionType::None => {
                 return SigningError::err(SigningErrorType::Error_invalid_params)
                     .context("Either `TransactionBuilder` or `Psbt` should be set")
             },
         };
 
+        let output_script_types = unsigned_tx
+            .transaction()
+            .outputs()
+            .iter()
+            .map(|output| StandardScriptParser.output_script_type(output.script_pubkey()))
+            .collect();
+
+        let WeightBreakdown {
+            inputs,
+            outputs,
+            overhead,
+        } = unsigned_tx.estimate_transaction().weight_breakdown();
+        let weight_breakdown = Proto::mod_PreSigningOutput::WeightBreakdown {
+            inputs: inputs.into_iter().map(|weight| weight as u64).collect(),
+            outputs: outputs.into_iter().map(|weight| weight as u64).collect(),
+            overhead: overhead as u64,
+        };
+
         let TxPreimage { sighashes } = SighashComputer::preimage_tx(&unsigned_tx)?;
 
         let sighashes: Vec<_> = sighashes
@@ -68,11 +102,15 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
                 sighash: Cow::from(sighash.sighash.to_vec()),
                 signing_method: signing_method(sighash.signing_method),
                 tweak: taproot_tweak(sighash.taproot_tweak),
+                preimage: Cow::from(sighash.preimage),
             })
             .collect();
 
         Ok(Proto::PreSigningOutput {
             sighashes,
+            output_script_types,
+            weight_breakdown: Some(weight_breakdown),
+            session: Cow::from(session),
             ..Proto::PreSigningOutput::default()
         })
     }
@@ -99,6 +137,9 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
                 Self::compile_with_tx_builder(coin, &input, tx, signatures)
             },
             TransactionType::psbt(ref psbt) => Self::compile_psbt(coin, &input, psbt, signatures),
+            TransactionType::session(ref session) => {
+                Self::compile_session(coin, session, signatures)
+            },
             TransactionType::None => SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("No transaction type specified"),
         }
@@ -110,11 +151,17 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
         tx_builder_input: &Proto::TransactionBuilder,
         signatures: Vec<SignatureBytes>,
     ) -> SigningResult<Proto::SigningOutput<'static>> {
+        Self::check_no_silent_payments(tx_builder_input)?;
         let request = SigningRequestBuilder::<Context>::build(coin, input, tx_builder_input)?;
         let SelectResult { unsigned_tx, plan } = TxPlanner::plan(request)?;
 
         SighashVerifier::verify_signatures(&unsigned_tx, &signatures)?;
         let signed_tx = TxCompiler::compile(unsigned_tx, &signatures)?;
+        let warnings = SigningRequestBuilder::<Context>::check_relay_policy(
+            input,
+            &signed_tx,
+            signed_tx.vsize(),
+        )?;
         let tx_proto = ProtobufBuilder::tx_to_proto(&signed_tx);
 
         Ok(Proto::SigningOutput {
@@ -126,6 +173,7 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
             weight: signed_tx.weight() as u64,
             // `fee` should haven't been changed since it's a difference between `sum(inputs)` and `sum(outputs)`.
             fee: plan.fee_estimate,
+            warnings,
             ..Proto::SigningOutput::default()
         })
     }
@@ -137,10 +185,37 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
         signatures: Vec<SignatureBytes>,
     ) -> SigningResult<Proto::SigningOutput<'static>> {
         let PsbtRequest { unsigned_tx, .. } = PsbtRequest::<Context>::build(input, psbt)?;
+        Self::compile_unsigned_tx(input, unsigned_tx, signatures)
+    }
+
+    fn compile_session(
+        coin: &dyn CoinContext,
+        session: &[u8],
+        signatures: Vec<SignatureBytes>,
+    ) -> SigningResult<Proto::SigningOutput<'static>> {
+        let SessionRequest {
+            signing_input,
+            unsigned_tx,
+            ..
+        } = SessionRequest::<Context>::build(coin, session)?;
+        Self::compile_unsigned_tx(&signing_input, unsigned_tx, signatures)
+    }
+
+    /// Compiles the transaction, which inputs and outputs are final, i.e. the fee is their difference.
+    fn compile_unsigned_tx(
+        input: &Proto::SigningInput,
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        signatures: Vec<SignatureBytes>,
+    ) -> SigningResult<Proto::SigningOutput<'static>> {
         let fee = unsigned_tx.fee()?;
 
         SighashVerifier::verify_signatures(&unsigned_tx, &signatures)?;
         let signed_tx = TxCompiler::compile(unsigned_tx, &signatures)?;
+        let warnings = SigningRequestBuilder::<Context>::check_relay_policy(
+            input,
+            &signed_tx,
+            signed_tx.vsize(),
+        )?;
         let tx_proto = ProtobufBuilder::tx_to_proto(&signed_tx);
 
         Ok(Proto::SigningOutput {
@@ -151,9 +226,145 @@ impl<Context: UtxoContext> BitcoinCompiler<Context> {
             vsize: signed_tx.vsize() as u64,
             weight: signed_tx.weight() as u64,
             fee,
+            warnings,
             ..Proto::SigningOutput::default()
         })
     }
+
+    /// Compiles the transaction replacing the signature of the `input_index` input only,
+    /// e.g. after a cosigner re-signed it.
+    /// The claiming scripts of other inputs are taken from the previously compiled `signed_tx`,
+    /// so there is no need to re-supply their signatures.
+    #[inline]
+    pub fn replace_signature(
+        coin: &dyn CoinContext,
+        input: Proto::SigningInput<'_>,
+        signed_tx: &Proto::Transaction,
+        input_index: usize,
+        signature: SignatureBytes,
+    ) -> Proto::SigningOutput<'static> {
+        Self::replace_signature_impl(coin, input, signed_tx, input_index, signature)
+            .unwrap_or_else(|e| signing_output_error!(Proto::SigningOutput, e))
+    }
+
+    fn replace_signature_impl(
+        coin: &dyn CoinContext,
+        input: Proto::SigningInput<'_>,
+        signed_tx: &Proto::Transaction,
+        input_index: usize,
+        signature: SignatureBytes,
+    ) -> SigningResult<Proto::SigningOutput<'static>> {
+        let (unsigned_tx, fee) = Self::unsigned_tx_with_fee(coin, &input)?;
+
+        // The signed transaction must be compiled from the same signing input.
+        let unsigned_tx_proto = ProtobufBuilder::tx_to_proto(unsigned_tx.transaction());
+        if without_claims(&unsigned_tx_proto) != without_claims(signed_tx) {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The signed transaction does not correspond to the signing input");
+        }
+
+        let claims = signed_tx
+            .inputs
+            .iter()
+            .map(|txin| {
+                let mut witness = Witness::default();
+                for item in txin.witness_items.iter() {
+                    witness.push_item(Script::from(item.to_vec()));
+                }
+                SpendingData {
+                    script_sig: Script::from(txin.script_sig.to_vec()),
+                    witness,
+                }
+            })
+            .collect();
+
+        let signed_tx =
+            TxCompiler::replace_signature(unsigned_tx, claims, input_index, &signature)?;
+        let tx_proto = ProtobufBuilder::tx_to_proto(&signed_tx);
+
+        Ok(Proto::SigningOutput {
+            transaction: Some(tx_proto),
+            encoded: Cow::from(signed_tx.encode_out()),
+            txid: Cow::from(signed_tx.txid()),
+            vsize: signed_tx.vsize() as u64,
+            weight: signed_tx.weight() as u64,
+            fee,
+            ..Proto::SigningOutput::default()
+        })
+    }
+
+    /// Returns the transaction that will be signed with the given [`Proto::SigningInput`],
+    /// serialized with empty scriptSigs and witnesses (except for foreign inputs).
+    /// Allows to inspect or policy-check the transaction before any key is touched.
+    ///
+    /// Please note that the output `txid` is left empty as it may change once the transaction is signed,
+    /// and `vsize`, `weight` are estimated.
+    #[inline]
+    pub fn preview(
+        coin: &dyn CoinContext,
+        input: Proto::SigningInput<'_>,
+    ) -> Proto::SigningOutput<'static> {
+        Self::preview_impl(coin, input)
+            .unwrap_or_else(|e| signing_output_error!(Proto::SigningOutput, e))
+    }
+
+    fn preview_impl(
+        coin: &dyn CoinContext,
+        input: Proto::SigningInput<'_>,
+    ) -> SigningResult<Proto::SigningOutput<'static>> {
+        let (unsigned_tx, fee) = Self::unsigned_tx_with_fee(coin, &input)?;
+        let estimated_tx = unsigned_tx.estimate_transaction();
+        let unsigned_tx = unsigned_tx.into_transaction();
+
+        Ok(Proto::SigningOutput {
+            transaction: Some(ProtobufBuilder::tx_to_proto(&unsigned_tx)),
+            encoded: Cow::from(unsigned_tx.encode_out()),
+            vsize: estimated_tx.vsize() as u64,
+            weight: estimated_tx.weight() as u64,
+            fee,
+            ..Proto::SigningOutput::default()
+        })
+    }
+
+    /// Silent Payment output keys are derived from the input private keys,
+    /// so such transactions cannot be signed externally.
+    pub(crate) fn check_no_silent_payments(
+        tx_builder: &Proto::TransactionBuilder,
+    ) -> SigningResult<()> {
+        if SilentPayments::has_recipients(tx_builder) {
+            return SigningError::err(SigningErrorType::Error_not_supported)
+                .context("Silent Payment outputs require the private keys to be signed");
+        }
+        Ok(())
+    }
+
+    /// Builds the unsigned transaction and returns it along with the fee.
+    fn unsigned_tx_with_fee(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput<'_>,
+    ) -> SigningResult<(UnsignedTransaction<Transaction>, Amount)> {
+        match input.transaction {
+            TransactionType::builder(ref tx_builder) => {
+                Self::check_no_silent_payments(tx_builder)?;
+                let request = SigningRequestBuilder::<Context>::build(coin, input, tx_builder)?;
+                let SelectResult { unsigned_tx, plan } = TxPlanner::plan(request)?;
+                Ok((unsigned_tx, plan.fee_estimate))
+            },
+            TransactionType::psbt(ref psbt) => {
+                let PsbtRequest { unsigned_tx, .. } = PsbtRequest::<Context>::build(input, psbt)?;
+                let fee = unsigned_tx.fee()?;
+                Ok((unsigned_tx, fee))
+            },
+            TransactionType::session(ref session) => {
+                let SessionRequest { unsigned_tx, .. } =
+                    SessionRequest::<Context>::build(coin, session)?;
+                let fee = unsigned_tx.fee()?;
+                Ok((unsigned_tx, fee))
+            },
+            TransactionType::None => SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("No transaction type specified"),
+        }
+    }
 }
 
 pub fn signing_method(s: SigningMethod) -> ProtoSigningMethod {
@@ -170,8 +381,45 @@ pub fn taproot_tweak(tweak: Option<TaprootTweak>) -> Option<ProtoTaprootTweak<'s
             Some(root) => root.to_vec(),
             None => Vec::default(),
         };
+        let contract_commitment = match tweak.contract_commitment {
+            Some(commitment) => commitment.to_vec(),
+            None => Vec::default(),
+        };
         ProtoTaprootTweak {
             merkle_root: Cow::from(merkle_root),
+            contract_commitment: Cow::from(contract_commitment),
         }
     })
 }
+
+/// Returns a copy of the transaction with empty claiming scripts.
+fn without_claims(tx: &Proto::Transaction) -> Proto::Transaction<'static> {
+    let inputs = tx
+        .inputs
+        .iter()
+        .map(|txin| Proto::mod_Transaction::TransactionInput {
+            out_point: txin.out_point.clone().map(|out_point| Proto::OutPoint {
+                hash: Cow::Owned(out_point.hash.to_vec()),
+                vout: out_point.vout,
+            }),
+            sequence: txin.sequence,
+            script_sig: Cow::default(),
+            witness_items: Vec::default(),
+        })
+        .collect();
+    let outputs = tx
+        .outputs
+        .iter()
+        .map(|txout| Proto::mod_Transaction::TransactionOutput {
+            script_pubkey: Cow::Owned(txout.script_pubkey.to_vec()),
+            value: txout.value,
+        })
+        .collect();
+
+    Proto::Transaction {
+        version: tx.version,
+        lock_time: tx.lock_time,
+        inputs,
+        outputs,
+    }
+}
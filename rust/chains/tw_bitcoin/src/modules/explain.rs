@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Human-readable transaction summaries for confirmation screens and audits.
+
+use crate::modules::psbt_request::PsbtRequest;
+use crate::modules::signing_request::SigningRequestBuilder;
+use crate::modules::tx_builder::script_parser::StandardScriptParser;
+use crate::modules::tx_builder::BitcoinChainInfo;
+use std::fmt;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_SigningInput::OneOftransaction as TransactionType;
+use tw_utxo::constants::LOCKTIME_THRESHOLD;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::modules::tx_planner::TxPlanner;
+use tw_utxo::modules::utxo_selector::SelectResult;
+use tw_utxo::script::Script;
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::{Amount, Txid};
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LockTimeReport {
+    /// The transaction is not time-locked.
+    None,
+    /// The transaction cannot be included in a block lower than the height.
+    BlockHeight(u32),
+    /// The transaction cannot be included in a block with the median time past lower than the UNIX timestamp.
+    Timestamp(u32),
+}
+
+impl LockTimeReport {
+    pub fn new(lock_time: u32) -> LockTimeReport {
+        match lock_time {
+            0 => LockTimeReport::None,
+            height if height < LOCKTIME_THRESHOLD => LockTimeReport::BlockHeight(height),
+            timestamp => LockTimeReport::Timestamp(timestamp),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InputReport {
+    pub txid: Txid,
+    pub vout: u32,
+    pub value: Amount,
+    /// The type of the spent scriptPubkey.
+    pub script_type: Proto::OutputScriptType,
+    /// The address of the spent UTXO, if it can be represented as an address.
+    pub address: Option<String>,
+    pub sequence: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputReport {
+    pub value: Amount,
+    pub script_type: Proto::OutputScriptType,
+    /// The destination address, if the scriptPubkey can be represented as an address.
+    pub address: Option<String>,
+    /// The raw scriptPubkey.
+    pub script_pubkey: Script,
+    /// Whether the output sends the change back to the sender.
+    /// Set only if the output is derived from the change xpub or pays to one of the spent scriptPubkeys.
+    pub is_change: bool,
+}
+
+/// A structured summary of a transaction.
+/// Use [`fmt::Display`] to get a human-readable text.
+#[derive(Clone, Debug)]
+pub struct TransactionReport {
+    pub version: i32,
+    pub lock_time: LockTimeReport,
+    pub inputs: Vec<InputReport>,
+    pub outputs: Vec<OutputReport>,
+    pub total_input: Amount,
+    pub total_output: Amount,
+    pub fee: Amount,
+    /// The estimated `vsize` if the transaction is not signed yet.
+    pub vsize: u64,
+}
+
+impl TransactionReport {
+    /// Returns the fee rate in satoshis per vbyte.
+    pub fn fee_rate(&self) -> f64 {
+        if self.vsize == 0 {
+            return 0.0;
+        }
+        self.fee as f64 / self.vsize as f64
+    }
+
+    /// Returns the total amount sent back to the sender.
+    pub fn change(&self) -> Amount {
+        self.outputs
+            .iter()
+            .filter(|output| output.is_change)
+            .map(|output| output.value)
+            .sum()
+    }
+}
+
+impl fmt::Display for TransactionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Version: {}", self.version)?;
+        match self.lock_time {
+            LockTimeReport::None => writeln!(f, "Lock time: none")?,
+            LockTimeReport::BlockHeight(height) => writeln!(f, "Lock time: block {height}")?,
+            LockTimeReport::Timestamp(timestamp) => {
+                writeln!(f, "Lock time: timestamp {timestamp}")?
+            },
+        }
+
+        writeln!(f, "Inputs ({}):", self.inputs.len())?;
+        for (i, input) in self.inputs.iter().enumerate() {
+            writeln!(
+                f,
+                "  #{i} {}:{} {:?} {} {} sat",
+                input.txid,
+                input.vout,
+                input.script_type,
+                input.address.as_deref().unwrap_or("-"),
+                input.value,
+            )?;
+        }
+
+        writeln!(f, "Outputs ({}):", self.outputs.len())?;
+        for (i, output) in self.outputs.iter().enumerate() {
+            let change = if output.is_change { " (change)" } else { "" };
+            writeln!(
+                f,
+                "  #{i} {:?} {} {} sat{change}",
+                output.script_type,
+                output.address.as_deref().unwrap_or("-"),
+                output.value,
+            )?;
+        }
+
+        writeln!(f, "Total input: {} sat", self.total_input)?;
+        writeln!(f, "Total output: {} sat", self.total_output)?;
+        writeln!(f, "Change: {} sat", self.change())?;
+        writeln!(f, "Fee: {} sat", self.fee)?;
+        write!(
+            f,
+            "Fee rate: {:.2} sat/vB ({} vbytes)",
+            self.fee_rate(),
+            self.vsize
+        )
+    }
+}
+
+pub struct BitcoinExplain<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> BitcoinExplain<Context> {
+    /// Explains the transaction that would be signed with the given [`Proto::SigningInput`].
+    /// Please note that the `vsize` and the fee rate are estimated.
+    pub fn explain(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+    ) -> SigningResult<TransactionReport> {
+        let chain_info = SigningRequestBuilder::<Context>::chain_info(coin, &input.chain_info)?;
+
+        match input.transaction {
+            TransactionType::builder(ref tx_builder) => {
+                let request = SigningRequestBuilder::<Context>::build(coin, input, tx_builder)?;
+                let SelectResult { unsigned_tx, plan } = TxPlanner::plan(request)?;
+
+                // Change outputs are appended after the requested outputs.
+                // Only consider those that are provably the wallet's own:
+                // derived from the account xpub, or sending funds back to one of the spent scriptPubkeys.
+                let has_change = (tx_builder.change_output.is_some()
+                    || tx_builder.change_xpub.is_some())
+                    && tx_builder.max_amount_output.is_none();
+                let is_xpub_change = tx_builder.change_xpub.is_some();
+                let spent_scripts = Self::spent_scripts(&unsigned_tx);
+                let is_change = |output_idx: usize, script_pubkey: &Script| {
+                    has_change
+                        && output_idx >= tx_builder.outputs.len()
+                        && (is_xpub_change || spent_scripts.contains(script_pubkey))
+                };
+
+                Self::report(
+                    &unsigned_tx,
+                    &chain_info,
+                    plan.fee_estimate,
+                    plan.vsize_estimate as u64,
+                    is_change,
+                )
+            },
+            TransactionType::psbt(ref psbt) => {
+                let PsbtRequest { unsigned_tx, .. } = PsbtRequest::<Context>::build(input, psbt)?;
+                let fee = unsigned_tx.fee()?;
+                let vsize = unsigned_tx.estimate_transaction().vsize() as u64;
+
+                // PSBT doesn't tell which output is change,
+                // so consider outputs that send funds back to one of the spent scriptPubkeys.
+                let spent_scripts = Self::spent_scripts(&unsigned_tx);
+                let is_change =
+                    |_: usize, script_pubkey: &Script| spent_scripts.contains(script_pubkey);
+
+                Self::report(&unsigned_tx, &chain_info, fee, vsize, is_change)
+            },
+            TransactionType::session(_) => SigningError::err(SigningErrorType::Error_not_supported)
+                .context("Signing session can only be passed to `preimage_hashes` and `compile`"),
+            TransactionType::None => SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Either `TransactionBuilder` or `Psbt` should be set"),
+        }
+    }
+
+    /// Explains the transaction compiled or signed with the given [`Proto::SigningInput`].
+    /// Unlike [`BitcoinExplain::explain`], the actual `vsize` of the signed transaction is used.
+    pub fn explain_signed(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        output: &Proto::SigningOutput,
+    ) -> SigningResult<TransactionReport> {
+        if output.error != tw_proto::Common::Proto::SigningError::OK {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Cannot explain a failed signing output");
+        }
+
+        let mut report = Self::explain(coin, input)?;
+        report.vsize = output.vsize;
+        report.fee = output.fee;
+        Ok(report)
+    }
+
+    fn spent_scripts(unsigned_tx: &UnsignedTransaction<Transaction>) -> Vec<Script> {
+        unsigned_tx
+            .input_args()
+            .iter()
+            .map(|utxo_args| utxo_args.prevout_script_pubkey.clone())
+            .collect()
+    }
+
+    fn report<F>(
+        unsigned_tx: &UnsignedTransaction<Transaction>,
+        chain_info: &BitcoinChainInfo,
+        fee: Amount,
+        vsize: u64,
+        is_change: F,
+    ) -> SigningResult<TransactionReport>
+    where
+        F: Fn(usize, &Script) -> bool,
+    {
+        let tx = unsigned_tx.transaction();
+
+        let inputs = tx
+            .inputs
+            .iter()
+            .zip(unsigned_tx.input_args())
+            .map(|(txin, utxo_args)| InputReport {
+                txid: Txid::from(txin.previous_output.hash),
+                vout: txin.previous_output.index,
+                value: utxo_args.amount,
+                script_type: StandardScriptParser
+                    .output_script_type(&utxo_args.prevout_script_pubkey),
+                address: script_to_address(&utxo_args.prevout_script_pubkey, chain_info),
+                sequence: txin.sequence,
+            })
+            .collect();
+
+        let outputs = tx
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(i, txout)| OutputReport {
+                value: txout.value,
+                script_type: StandardScriptParser.output_script_type(&txout.script_pubkey),
+                address: script_to_address(&txout.script_pubkey, chain_info),
+                script_pubkey: txout.script_pubkey.clone(),
+                is_change: is_change(i, &txout.script_pubkey),
+            })
+            .collect();
+
+        Ok(TransactionReport {
+            version: tx.version,
+            lock_time: LockTimeReport::new(tx.locktime),
+            inputs,
+            outputs,
+            total_input: unsigned_tx.total_input()?,
+            total_output: unsigned_tx.total_output()?,
+            fee,
+            vsize,
+        })
+    }
+}
+
+fn script_to_address(script: &Script, chain_info: &BitcoinChainInfo) -> Option<String> {
+    StandardScriptParser
+        .parse(script)
+        .ok()?
+        .try_to_address(chain_info)
+        .ok()?
+        .map(|address| address.to_string())
+}
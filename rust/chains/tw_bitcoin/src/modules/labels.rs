@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! BIP-329 wallet labels export.
+//! https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+
+use crate::modules::explain::TransactionReport;
+use serde::Serialize;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::transaction::transaction_parts::Txid;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelType {
+    Tx,
+    Addr,
+    Output,
+}
+
+/// A BIP-329 label record.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct LabelRecord {
+    #[serde(rename = "type")]
+    pub ty: LabelType,
+    /// The txid, the address or the `txid:vout` outpoint depending on the [`LabelRecord::ty`].
+    #[serde(rename = "ref")]
+    pub reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Whether the output can be spent by the wallet. Only set for the change outputs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spendable: Option<bool>,
+}
+
+impl LabelRecord {
+    fn new(ty: LabelType, reference: String, label: &str) -> LabelRecord {
+        let label = (!label.is_empty()).then(|| label.to_string());
+        LabelRecord {
+            ty,
+            reference,
+            label,
+            spendable: None,
+        }
+    }
+}
+
+/// Generates the label records of the signed transaction: the transaction itself,
+/// every output, and the addresses of the detected change outputs.
+pub fn label_records(
+    txid: &Txid,
+    report: &TransactionReport,
+    request: &Proto::LabelsRequest,
+) -> Vec<LabelRecord> {
+    let mut records = vec![LabelRecord::new(
+        LabelType::Tx,
+        txid.to_string(),
+        &request.tx_label,
+    )];
+    let mut change_addresses = Vec::new();
+
+    for (vout, output) in report.outputs.iter().enumerate() {
+        let outpoint = format!("{txid}:{vout}");
+        if !output.is_change {
+            let label = request
+                .output_labels
+                .get(vout)
+                .map(|label| label.as_ref())
+                .unwrap_or_default();
+            records.push(LabelRecord::new(LabelType::Output, outpoint, label));
+            continue;
+        }
+
+        records.push(LabelRecord {
+            spendable: Some(true),
+            ..LabelRecord::new(LabelType::Output, outpoint, &request.change_label)
+        });
+        if let Some(ref address) = output.address {
+            if !change_addresses.contains(address) {
+                change_addresses.push(address.clone());
+            }
+        }
+    }
+
+    records.extend(
+        change_addresses
+            .into_iter()
+            .map(|address| LabelRecord::new(LabelType::Addr, address, &request.change_label)),
+    );
+    records
+}
+
+/// Serializes the label records in the JSON Lines format, one record per line.
+pub fn to_json_lines(records: &[LabelRecord]) -> SigningResult<String> {
+    let lines = records
+        .iter()
+        .map(|record| {
+            serde_json::to_string(record)
+                .tw_err(|_| SigningErrorType::Error_internal)
+                .context("Error serializing a BIP-329 label record")
+        })
+        .collect::<SigningResult<Vec<_>>>()?;
+    Ok(lines.join("\n"))
+}
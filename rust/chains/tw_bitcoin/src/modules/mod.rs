@@ -3,11 +3,22 @@
 // Copyright © 2017 Trust Wallet.
 
 pub mod compiler;
+pub mod explain;
+pub mod labels;
+pub mod native_builder;
+pub mod payjoin;
 pub mod planner;
 pub mod protobuf_builder;
 pub mod psbt;
 pub mod psbt_request;
+pub mod psbt_util;
+pub mod psbt_v2;
+pub mod session_request;
 pub mod signer;
+pub mod signing_policy;
 pub mod signing_request;
+pub mod silent_payments;
 pub mod transaction_util;
 pub mod tx_builder;
+pub mod tx_chaining;
+pub mod vault;
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use std::fmt;
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+
+pub const SATOSHIS_PER_BTC: u64 = 100_000_000;
+/// The maximum number of satoshis that can ever exist.
+pub const MAX_MONEY: u64 = 21_000_000 * SATOSHIS_PER_BTC;
+
+const BTC_DECIMALS: usize = 8;
+
+/// An amount of satoshis.
+/// Always within the `[0, MAX_MONEY]` range, so it can be safely converted to the Protobuf `int64` value.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const ONE_SAT: Amount = Amount(1);
+    pub const ONE_BTC: Amount = Amount(SATOSHIS_PER_BTC);
+    pub const MAX: Amount = Amount(MAX_MONEY);
+    /// The default dust threshold, the same as the one used by Bitcoin Core for P2PKH outputs.
+    pub const DEFAULT_DUST_THRESHOLD: Amount = Amount(546);
+
+    pub fn from_sat(sat: u64) -> SigningResult<Amount> {
+        if sat > MAX_MONEY {
+            return SigningError::err(SigningErrorType::Error_invalid_utxo_amount)
+                .context(format!("Amount exceeds the max money: {sat} sat"));
+        }
+        Ok(Amount(sat))
+    }
+
+    /// Parses a decimal BTC amount, e.g. "0.0015".
+    /// At most 8 fractional digits are allowed.
+    pub fn from_btc_str(btc: &str) -> SigningResult<Amount> {
+        let (whole, fraction) = btc.split_once('.').unwrap_or((btc, ""));
+
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context(format!("Invalid BTC amount: '{btc}'"));
+        }
+        if fraction.len() > BTC_DECIMALS {
+            return SigningError::err(SigningErrorType::Error_input_parse).context(format!(
+                "BTC amount has more than {BTC_DECIMALS} decimals: '{btc}'"
+            ));
+        }
+
+        let parse = |s: &str| -> SigningResult<u64> {
+            if s.is_empty() {
+                return Ok(0);
+            }
+            u64::from_str(s)
+                .tw_err(|_| SigningErrorType::Error_invalid_utxo_amount)
+                .with_context(|| format!("BTC amount is too large: '{btc}'"))
+        };
+
+        let whole_sat = parse(whole)?
+            .checked_mul(SATOSHIS_PER_BTC)
+            .or_tw_err(SigningErrorType::Error_invalid_utxo_amount)
+            .with_context(|| format!("BTC amount is too large: '{btc}'"))?;
+        // Right-pad the fraction with zeros up to 8 digits.
+        let fraction_sat = parse(fraction)? * 10_u64.pow((BTC_DECIMALS - fraction.len()) as u32);
+
+        let sat = whole_sat
+            .checked_add(fraction_sat)
+            .or_tw_err(SigningErrorType::Error_invalid_utxo_amount)
+            .with_context(|| format!("BTC amount is too large: '{btc}'"))?;
+        Amount::from_sat(sat)
+    }
+
+    pub fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    /// Formats the amount as a decimal BTC string with 8 fractional digits, e.g. "0.00150000".
+    pub fn to_btc_string(self) -> String {
+        format!(
+            "{}.{:08}",
+            self.0 / SATOSHIS_PER_BTC,
+            self.0 % SATOSHIS_PER_BTC
+        )
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0
+            .checked_add(other.0)
+            .filter(|sat| *sat <= MAX_MONEY)
+            .map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<Amount> {
+        self.0
+            .checked_mul(factor)
+            .filter(|sat| *sat <= MAX_MONEY)
+            .map(Amount)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} BTC", self.to_btc_string())
+    }
+}
+
+/// Converts a Protobuf `int64` value.
+impl TryFrom<i64> for Amount {
+    type Error = SigningError;
+
+    fn try_from(sat: i64) -> Result<Self, Self::Error> {
+        let sat = u64::try_from(sat)
+            .tw_err(|_| SigningErrorType::Error_invalid_utxo_amount)
+            .context("Amount cannot be negative")?;
+        Amount::from_sat(sat)
+    }
+}
+
+/// Converts to a Protobuf `int64` value.
+impl From<Amount> for i64 {
+    fn from(amount: Amount) -> Self {
+        // Cannot overflow as `MAX_MONEY < i64::MAX`.
+        amount.0 as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_from_btc_str() {
+        assert_eq!(Amount::from_btc_str("1").unwrap(), Amount::ONE_BTC);
+        assert_eq!(Amount::from_btc_str("0.0015").unwrap().to_sat(), 150_000);
+        assert_eq!(Amount::from_btc_str(".5").unwrap().to_sat(), 50_000_000);
+        assert_eq!(Amount::from_btc_str("2.").unwrap().to_sat(), 200_000_000);
+        assert_eq!(Amount::from_btc_str("0.00000001").unwrap(), Amount::ONE_SAT);
+        assert_eq!(Amount::from_btc_str("21000000").unwrap(), Amount::MAX);
+
+        assert!(Amount::from_btc_str("").is_err());
+        assert!(Amount::from_btc_str(".").is_err());
+        assert!(Amount::from_btc_str("-1").is_err());
+        assert!(Amount::from_btc_str("1.2.3").is_err());
+        assert!(Amount::from_btc_str("0.000000001").is_err());
+        assert!(Amount::from_btc_str("21000000.00000001").is_err());
+        assert!(Amount::from_btc_str("184467440737.09551616").is_err());
+    }
+
+    #[test]
+    fn test_amount_to_btc_string() {
+        assert_eq!(Amount::ZERO.to_btc_string(), "0.00000000");
+        assert_eq!(Amount::ONE_SAT.to_btc_string(), "0.00000001");
+        assert_eq!(
+            Amount::from_sat(150_000).unwrap().to_string(),
+            "0.00150000 BTC"
+        );
+        assert_eq!(Amount::MAX.to_btc_string(), "21000000.00000000");
+    }
+
+    #[test]
+    fn test_amount_checked_arithmetic() {
+        let one_btc = Amount::ONE_BTC;
+        assert_eq!(
+            one_btc.checked_add(Amount::ONE_SAT).unwrap().to_sat(),
+            100_000_001
+        );
+        assert_eq!(Amount::MAX.checked_add(Amount::ONE_SAT), None);
+        assert_eq!(Amount::ZERO.checked_sub(Amount::ONE_SAT), None);
+        assert_eq!(one_btc.checked_mul(21_000_000), Some(Amount::MAX));
+        assert_eq!(one_btc.checked_mul(21_000_001), None);
+    }
+
+    #[test]
+    fn test_amount_proto_conversion() {
+        assert_eq!(Amount::try_from(546_i64).unwrap().to_sat(), 546);
+        assert!(Amount::try_from(-1_i64).is_err());
+        assert!(Amount::try_from(i64::MAX).is_err());
+        assert_eq!(i64::from(Amount::MAX), 2_100_000_000_000_000);
+    }
+}
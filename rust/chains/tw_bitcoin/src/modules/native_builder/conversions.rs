@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Conversions from the `rust-bitcoin` types into the `BitcoinV2` Protobuf messages.
+//!
+//! Both the `rust-bitcoin` types and the Protobuf messages are foreign to this crate,
+//! so the conversions are provided as extension traits rather than `From` impls.
+
+use crate::modules::native_builder::amount::Amount;
+use bitcoin::hashes::Hash;
+use std::borrow::Cow;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_Input::OneOfclaiming_script as ClaimingScriptType;
+use tw_proto::BitcoinV2::Proto::mod_Output::OneOfto_recipient as RecipientType;
+use tw_utxo::sighash::SighashType;
+
+impl TryFrom<bitcoin::Amount> for Amount {
+    type Error = SigningError;
+
+    fn try_from(amount: bitcoin::Amount) -> Result<Self, Self::Error> {
+        Amount::from_sat(amount.to_sat())
+    }
+}
+
+impl From<Amount> for bitcoin::Amount {
+    fn from(amount: Amount) -> Self {
+        bitcoin::Amount::from_sat(amount.to_sat())
+    }
+}
+
+pub trait ToProtoRecipient {
+    /// Returns the recipient of an output.
+    fn to_proto_recipient(&self) -> RecipientType<'static>;
+
+    /// Returns an output that pays the given `value` to the recipient.
+    fn to_proto_output_with_value(&self, value: Amount) -> Proto::Output<'static> {
+        Proto::Output {
+            value: value.into(),
+            to_recipient: self.to_proto_recipient(),
+            ..Proto::Output::default()
+        }
+    }
+}
+
+impl ToProtoRecipient for bitcoin::Address {
+    fn to_proto_recipient(&self) -> RecipientType<'static> {
+        RecipientType::to_address(Cow::Owned(self.to_string()))
+    }
+}
+
+impl ToProtoRecipient for bitcoin::Script {
+    fn to_proto_recipient(&self) -> RecipientType<'static> {
+        RecipientType::custom_script_pubkey(Cow::Owned(self.to_bytes()))
+    }
+}
+
+impl ToProtoRecipient for bitcoin::ScriptBuf {
+    fn to_proto_recipient(&self) -> RecipientType<'static> {
+        self.as_script().to_proto_recipient()
+    }
+}
+
+pub trait ToProtoOutput {
+    fn to_proto_output(&self) -> SigningResult<Proto::Output<'static>>;
+}
+
+impl ToProtoOutput for bitcoin::TxOut {
+    fn to_proto_output(&self) -> SigningResult<Proto::Output<'static>> {
+        let value = Amount::from_sat(self.value)?;
+        Ok(self.script_pubkey.to_proto_output_with_value(value))
+    }
+}
+
+pub trait ToProtoOutPoint {
+    fn to_proto_out_point(&self) -> Proto::OutPoint<'static>;
+}
+
+impl ToProtoOutPoint for bitcoin::OutPoint {
+    fn to_proto_out_point(&self) -> Proto::OutPoint<'static> {
+        Proto::OutPoint {
+            // `Txid` is stored in the internal byte order as expected by `Proto::OutPoint`.
+            hash: Cow::Owned(self.txid.to_byte_array().to_vec()),
+            vout: self.vout,
+        }
+    }
+}
+
+pub trait ToProtoInput {
+    /// Returns an input that spends the `prevout` UTXO.
+    /// The UTXO is claimed by its `script_pubkey` with the default `SIGHASH_ALL` sighash type.
+    fn to_proto_input(&self, prevout: &bitcoin::TxOut) -> SigningResult<Proto::Input<'static>>;
+}
+
+impl ToProtoInput for bitcoin::OutPoint {
+    fn to_proto_input(&self, prevout: &bitcoin::TxOut) -> SigningResult<Proto::Input<'static>> {
+        let value = Amount::from_sat(prevout.value)?;
+        Ok(Proto::Input {
+            out_point: Some(self.to_proto_out_point()),
+            value: value.into(),
+            sighash_type: SighashType::default().raw_sighash(),
+            claiming_script: ClaimingScriptType::script_data(Cow::Owned(
+                prevout.script_pubkey.to_bytes(),
+            )),
+            ..Proto::Input::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const TXID: &str = "8d0e94ed369601d13f32bd653071844dfe7139508d2e71d9c105d1faa58230b3";
+    const ADDRESS: &str = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+    const SCRIPT_PUBKEY: &str = "001460d7ee599766db323fb1916c7f9e5d818aaf8c1b";
+
+    fn script_pubkey() -> bitcoin::ScriptBuf {
+        bitcoin::ScriptBuf::from_hex(SCRIPT_PUBKEY).unwrap()
+    }
+
+    #[test]
+    fn test_address_to_proto_output() {
+        let address = bitcoin::Address::from_str(ADDRESS)
+            .unwrap()
+            .require_network(bitcoin::Network::Bitcoin)
+            .unwrap();
+
+        let output = address.to_proto_output_with_value(Amount::from_sat(1_000).unwrap());
+        assert_eq!(output.value, 1_000);
+        assert_eq!(
+            output.to_recipient,
+            RecipientType::to_address(ADDRESS.into())
+        );
+    }
+
+    #[test]
+    fn test_tx_out_to_proto_output() {
+        let tx_out = bitcoin::TxOut {
+            value: 4_863,
+            script_pubkey: script_pubkey(),
+        };
+
+        let output = tx_out.to_proto_output().unwrap();
+        assert_eq!(output.value, 4_863);
+        assert_eq!(
+            output.to_recipient,
+            RecipientType::custom_script_pubkey(script_pubkey().to_bytes().into())
+        );
+
+        let invalid = bitcoin::TxOut {
+            value: u64::MAX,
+            script_pubkey: script_pubkey(),
+        };
+        assert!(invalid.to_proto_output().is_err());
+    }
+
+    #[test]
+    fn test_out_point_to_proto_input() {
+        let out_point = bitcoin::OutPoint::new(bitcoin::Txid::from_str(TXID).unwrap(), 1);
+        let prevout = bitcoin::TxOut {
+            value: 4_863,
+            script_pubkey: script_pubkey(),
+        };
+
+        let input = out_point.to_proto_input(&prevout).unwrap();
+        let out_point = input.out_point.unwrap();
+        // `Txid::from_str` parses the RPC (reversed) byte order.
+        let mut expected_hash = tw_encoding::hex::decode(TXID).unwrap();
+        expected_hash.reverse();
+        assert_eq!(out_point.hash.as_ref(), expected_hash.as_slice());
+        assert_eq!(out_point.vout, 1);
+        assert_eq!(input.value, 4_863);
+        assert_eq!(
+            input.claiming_script,
+            ClaimingScriptType::script_data(script_pubkey().to_bytes().into())
+        );
+    }
+}
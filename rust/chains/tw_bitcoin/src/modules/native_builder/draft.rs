@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use std::borrow::Cow;
+use tw_coin_entry::error::prelude::*;
+use tw_memory::Data;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_SigningInput::OneOftransaction as TransactionType;
+
+/// An in-progress transaction that can be saved as [`Proto::TransactionDraft`] and restored later.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut draft = TxDraft::new(&signing_input)?;
+/// draft.set_session(&preimage_output)?;
+/// draft.add_signature(0, first_signature)?;
+/// store(draft.snapshot()?);
+///
+/// // Later, e.g. after the app is restarted.
+/// let mut draft = TxDraft::restore(&load())?;
+/// draft.add_signature(1, second_signature)?;
+/// let output = compile(&draft.compile_input()?, draft.signatures()?);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TxDraft {
+    /// Binary encoded [`Proto::SigningInput`] without private keys.
+    signing_input: Data,
+    /// Binary encoded [`Proto::SigningSession`], empty if the sighashes are not computed yet.
+    session: Data,
+    /// The signatures in the same order as the session sighashes.
+    signatures: Vec<Option<Data>>,
+}
+
+impl TxDraft {
+    /// Creates a draft of the transaction to be signed.
+    /// The private keys are not kept in the draft.
+    pub fn new(input: &Proto::SigningInput) -> SigningResult<TxDraft> {
+        let mut draft = TxDraft::default();
+        draft.update(input)?;
+        Ok(draft)
+    }
+
+    /// Replaces the transaction, e.g. once the outputs or the fee rate are edited.
+    /// Discards the signing session and the collected signatures.
+    pub fn update(&mut self, input: &Proto::SigningInput) -> SigningResult<()> {
+        let input = Proto::SigningInput {
+            private_keys: Vec::default(),
+            ..input.clone()
+        };
+        self.signing_input = tw_proto::serialize(&input)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error serializing the signing input")?;
+        self.session.clear();
+        self.signatures.clear();
+        Ok(())
+    }
+
+    pub fn signing_input(&self) -> SigningResult<Proto::SigningInput<'_>> {
+        tw_proto::deserialize(&self.signing_input)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error deserializing the signing input")
+    }
+
+    /// Stores the signing session returned by the `preimage_hashes` method.
+    /// Discards the collected signatures as the sighashes could be changed.
+    pub fn set_session(&mut self, preimage: &Proto::PreSigningOutput) -> SigningResult<()> {
+        if preimage.error != tw_proto::Common::Proto::SigningError::OK {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Cannot store the session of a failed 'preimage_hashes' call");
+        }
+        if preimage.session.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("No signing session in the 'preimage_hashes' output");
+        }
+        self.session = preimage.session.to_vec();
+        self.signatures = vec![None; preimage.sighashes.len()];
+        Ok(())
+    }
+
+    /// Returns the binary encoded [`Proto::SigningSession`] if the sighashes are computed already.
+    pub fn session(&self) -> Option<&[u8]> {
+        (!self.session.is_empty()).then_some(self.session.as_slice())
+    }
+
+    /// Adds a signature of the sighash at the `index` of `PreSigningOutput.sighashes`.
+    pub fn add_signature(&mut self, index: usize, signature: Data) -> SigningResult<()> {
+        if signature.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Signature cannot be empty");
+        }
+        let slot = self
+            .signatures
+            .get_mut(index)
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("There is no sighash '{index}' in the signing session"))?;
+        *slot = Some(signature);
+        Ok(())
+    }
+
+    /// Returns the indexes of the sighashes that are not signed yet.
+    pub fn missing_signatures(&self) -> Vec<usize> {
+        self.signatures
+            .iter()
+            .enumerate()
+            .filter(|(_, signature)| signature.is_none())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns the signatures to be passed to the `compile` method.
+    pub fn signatures(&self) -> SigningResult<Vec<Data>> {
+        if self.session.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The sighashes are not computed yet");
+        }
+        self.signatures
+            .iter()
+            .enumerate()
+            .map(|(index, signature)| {
+                signature
+                    .clone()
+                    .or_tw_err(SigningErrorType::Error_signing)
+                    .with_context(|| format!("Sighash '{index}' is not signed yet"))
+            })
+            .collect()
+    }
+
+    /// Returns the signing input to be passed to the `compile` method along with [`TxDraft::signatures`].
+    pub fn compile_input(&self) -> SigningResult<Proto::SigningInput<'_>> {
+        let session = self
+            .session()
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("The sighashes are not computed yet")?;
+        Ok(Proto::SigningInput {
+            transaction: TransactionType::session(Cow::Borrowed(session)),
+            ..Proto::SigningInput::default()
+        })
+    }
+
+    /// Returns binary encoded [`Proto::TransactionDraft`].
+    pub fn snapshot(&self) -> SigningResult<Data> {
+        let draft = Proto::TransactionDraft {
+            signing_input: Some(self.signing_input()?),
+            session: Cow::Borrowed(&self.session),
+            signatures: self
+                .signatures
+                .iter()
+                .map(|signature| Cow::Borrowed(signature.as_deref().unwrap_or_default()))
+                .collect(),
+        };
+        tw_proto::serialize(&draft)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error serializing the transaction draft")
+    }
+
+    /// Restores the draft from binary encoded [`Proto::TransactionDraft`].
+    pub fn restore(snapshot: &[u8]) -> SigningResult<TxDraft> {
+        let draft: Proto::TransactionDraft = tw_proto::deserialize(snapshot)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid transaction draft")?;
+        let signing_input = draft
+            .signing_input
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("No signing input in the transaction draft")?;
+        if draft.session.is_empty() && !draft.signatures.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Transaction draft has signatures but no signing session");
+        }
+
+        let mut restored = TxDraft::new(&signing_input)?;
+        restored.session = draft.session.to_vec();
+        restored.signatures = draft
+            .signatures
+            .iter()
+            .map(|signature| (!signature.is_empty()).then(|| signature.to_vec()))
+            .collect();
+        Ok(restored)
+    }
+}
@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Typed builders of the `BitcoinV2` Protobuf messages
+//! for Rust-native consumers of the crate.
+
+pub mod amount;
+pub mod conversions;
+pub mod draft;
+pub mod signing_input;
+pub mod tx_builder;
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::modules::native_builder::tx_builder::TxBuilder;
+use std::borrow::Cow;
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::ecdsa;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_SigningInput::OneOftransaction as TransactionType;
+use tw_utxo::transaction::transaction_parts::Txid;
+
+/// A typed builder of [`Proto::SigningInput`].
+///
+/// Defaults to the transaction version 2, [`Proto::InputSelector::UseAll`] input selector,
+/// and `SIGHASH_ALL` sighash type of the inputs added via the typed [`TxBuilder`] methods.
+#[derive(Clone, Debug)]
+pub struct SigningInputBuilder {
+    private_keys: Vec<Cow<'static, [u8]>>,
+    public_keys: Vec<Cow<'static, [u8]>>,
+    chain_info: Option<Proto::ChainInfo<'static>>,
+    tx_builder: TxBuilder,
+}
+
+impl Default for SigningInputBuilder {
+    fn default() -> Self {
+        SigningInputBuilder {
+            private_keys: Vec::default(),
+            public_keys: Vec::default(),
+            chain_info: None,
+            tx_builder: TxBuilder::new()
+                .version(Proto::TransactionVersion::V2)
+                .input_selector(Proto::InputSelector::UseAll),
+        }
+    }
+}
+
+impl SigningInputBuilder {
+    pub fn new() -> Self {
+        SigningInputBuilder::default()
+    }
+
+    pub fn add_private_key(mut self, private_key: &ecdsa::secp256k1::PrivateKey) -> Self {
+        let private_key = private_key.to_zeroizing_vec();
+        self.private_keys.push(Cow::Owned(private_key.to_vec()));
+        self
+    }
+
+    /// Adds a public key that is used to find a corresponding signature
+    /// when the transaction is compiled with external signatures.
+    pub fn add_public_key(mut self, public_key: &ecdsa::secp256k1::PublicKey) -> Self {
+        self.public_keys.push(Cow::Owned(public_key.to_vec()));
+        self
+    }
+
+    /// Sets the chain info used to parse and validate the input/output addresses.
+    pub fn chain_info(mut self, p2pkh_prefix: u8, p2sh_prefix: u8, hrp: Option<&str>) -> Self {
+        self.chain_info = Some(Proto::ChainInfo {
+            p2pkh_prefix: p2pkh_prefix as u32,
+            p2sh_prefix: p2sh_prefix as u32,
+            hrp: Cow::Owned(hrp.unwrap_or_default().to_string()),
+        });
+        self
+    }
+
+    /// Replaces the transaction builder.
+    /// Please note that it resets the defaults set by [`SigningInputBuilder::new`].
+    pub fn tx_builder(mut self, tx_builder: TxBuilder) -> Self {
+        self.tx_builder = tx_builder;
+        self
+    }
+
+    /// Updates the transaction builder in place, keeping the defaults.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let input = SigningInputBuilder::new()
+    ///     .add_private_key(&my_private_key)
+    ///     .transaction(|tx| tx.add_p2wpkh_input(txid, 0, value, &my_pubkey).fee_rate(10))
+    ///     .build()?;
+    /// ```
+    pub fn transaction<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(TxBuilder) -> TxBuilder,
+    {
+        self.tx_builder = f(self.tx_builder);
+        self
+    }
+
+    /// Validates the transaction and builds the [`Proto::SigningInput`].
+    pub fn build(self) -> SigningResult<Proto::SigningInput<'static>> {
+        let tx_builder = self.tx_builder.build();
+        validate_tx_builder(&tx_builder)?;
+
+        Ok(Proto::SigningInput {
+            private_keys: self.private_keys,
+            public_keys: self.public_keys,
+            chain_info: self.chain_info,
+            transaction: TransactionType::builder(tx_builder),
+            ..Proto::SigningInput::default()
+        })
+    }
+}
+
+fn validate_tx_builder(tx_builder: &Proto::TransactionBuilder) -> SigningResult<()> {
+    if tx_builder.inputs.is_empty() {
+        return SigningError::err(SigningErrorType::Error_missing_input_utxos)
+            .context("No inputs provided");
+    }
+
+    if tx_builder.outputs.is_empty() && tx_builder.max_amount_output.is_none() {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("Neither outputs nor a max amount output provided");
+    }
+
+    if tx_builder.fee_per_vb < 0 {
+        return SigningError::err(SigningErrorType::Error_wrong_fee)
+            .context("Fee rate cannot be negative");
+    }
+
+    for (i, input) in tx_builder.inputs.iter().enumerate() {
+        let out_point = input
+            .out_point
+            .as_ref()
+            .or_tw_err(SigningErrorType::Error_invalid_utxo)
+            .with_context(|| format!("Input '{i}' has no out-point"))?;
+        Txid::from_internal_bytes(&out_point.hash)
+            .with_context(|| format!("Input '{i}' has an invalid txid"))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::modules::native_builder::amount::Amount;
+use std::borrow::Cow;
+use tw_keypair::{ecdsa, schnorr};
+use tw_misc::traits::ToBytesVec;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_Input::mod_InputBuilder::OneOfvariant as InputBuilderType;
+use tw_proto::BitcoinV2::Proto::mod_Input::OneOfclaiming_script as ClaimingScriptType;
+use tw_proto::BitcoinV2::Proto::mod_LockTime::OneOfvariant as LockTimeType;
+use tw_proto::BitcoinV2::Proto::mod_Output::mod_OutputBuilder::OneOfvariant as OutputBuilderType;
+use tw_proto::BitcoinV2::Proto::mod_Output::OneOfto_recipient as RecipientType;
+use tw_proto::BitcoinV2::Proto::mod_PublicKeyOrHash::OneOfvariant as PublicKeyOrHashType;
+use tw_proto::BitcoinV2::Proto::mod_TransactionBuilder::OneOfdust_policy as DustPolicyType;
+use tw_utxo::sighash::SighashType;
+use tw_utxo::transaction::transaction_parts::Txid;
+
+/// A typed builder of [`Proto::TransactionBuilder`].
+///
+/// # Example
+///
+/// ```ignore
+/// let tx_builder = TxBuilder::new()
+///     .add_p2wpkh_input(txid, 0, Amount::from_sat(10_000)?, &my_pubkey)
+///     .add_address_output("bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2", Amount::from_btc_str("0.00005")?)
+///     .change_address("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp")
+///     .fee_rate(10)
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct TxBuilder {
+    version: Proto::TransactionVersion,
+    lock_time: u32,
+    typed_lock_time: Option<Proto::LockTime>,
+    inputs: Vec<Proto::Input<'static>>,
+    outputs: Vec<Proto::Output<'static>>,
+    change_output: Option<Proto::Output<'static>>,
+    change_xpub: Option<Proto::ChangeXpub<'static>>,
+    change_split: Option<Proto::ChangeSplit<'static>>,
+    split_change_outputs: Vec<Proto::Output<'static>>,
+    max_amount_output: Option<Proto::Output<'static>>,
+    input_selector: Proto::InputSelector,
+    fee_per_vb: i64,
+    dust_threshold: Amount,
+    validate_custom_scripts: bool,
+}
+
+impl Default for TxBuilder {
+    fn default() -> Self {
+        TxBuilder {
+            version: Proto::TransactionVersion::UseDefault,
+            lock_time: 0,
+            typed_lock_time: None,
+            inputs: Vec::default(),
+            outputs: Vec::default(),
+            change_output: None,
+            change_xpub: None,
+            change_split: None,
+            split_change_outputs: Vec::default(),
+            max_amount_output: None,
+            input_selector: Proto::InputSelector::SelectAscending,
+            fee_per_vb: 0,
+            dust_threshold: Amount::DEFAULT_DUST_THRESHOLD,
+            validate_custom_scripts: false,
+        }
+    }
+}
+
+impl TxBuilder {
+    pub fn new() -> Self {
+        TxBuilder::default()
+    }
+
+    pub fn version(mut self, version: Proto::TransactionVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Sets a block height lock time. The height must be less than 500,000,000.
+    pub fn lock_time_blocks(mut self, height: u32) -> Self {
+        self.typed_lock_time = Some(Proto::LockTime {
+            variant: LockTimeType::blocks(height),
+        });
+        self
+    }
+
+    /// Sets a UNIX timestamp lock time. The timestamp must be at least 500,000,000.
+    pub fn lock_time_seconds(mut self, timestamp: u32) -> Self {
+        self.typed_lock_time = Some(Proto::LockTime {
+            variant: LockTimeType::seconds(timestamp),
+        });
+        self
+    }
+
+    /// Adds an arbitrary input.
+    pub fn add_input(mut self, input: Proto::Input<'static>) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Adds a P2PKH input.
+    pub fn add_p2pkh_input(
+        self,
+        txid: Txid,
+        vout: u32,
+        value: Amount,
+        pubkey: &ecdsa::secp256k1::PublicKey,
+    ) -> Self {
+        let variant = InputBuilderType::p2pkh(Proto::PublicKeyOrHash {
+            variant: PublicKeyOrHashType::pubkey(Cow::Owned(pubkey.compressed().to_vec())),
+        });
+        self.add_input(input_with_builder(txid, vout, value, variant))
+    }
+
+    /// Adds a P2WPKH input.
+    pub fn add_p2wpkh_input(
+        self,
+        txid: Txid,
+        vout: u32,
+        value: Amount,
+        pubkey: &ecdsa::secp256k1::PublicKey,
+    ) -> Self {
+        let variant = InputBuilderType::p2wpkh(Proto::PublicKeyOrHash {
+            variant: PublicKeyOrHashType::pubkey(Cow::Owned(pubkey.compressed().to_vec())),
+        });
+        self.add_input(input_with_builder(txid, vout, value, variant))
+    }
+
+    /// Adds a P2TR key-path input. The public key must not be tweaked.
+    pub fn add_p2tr_key_path_input(
+        self,
+        txid: Txid,
+        vout: u32,
+        value: Amount,
+        pubkey: &schnorr::PublicKey,
+    ) -> Self {
+        let variant = InputBuilderType::p2tr_key_path(Cow::Owned(pubkey.to_vec()));
+        self.add_input(input_with_builder(txid, vout, value, variant))
+    }
+
+    /// Adds an input that is claimed by the given address.
+    pub fn add_address_input(self, txid: Txid, vout: u32, value: Amount, address: &str) -> Self {
+        self.add_input(Proto::Input {
+            out_point: Some(out_point(txid, vout)),
+            value: value.into(),
+            sighash_type: SighashType::default().raw_sighash(),
+            claiming_script: ClaimingScriptType::receiver_address(Cow::Owned(address.to_string())),
+            ..Proto::Input::default()
+        })
+    }
+
+    /// Adds an input that belongs to another party, e.g. a CoinJoin participant.
+    /// The input is always spent but never signed.
+    pub fn add_foreign_input(
+        self,
+        txid: Txid,
+        vout: u32,
+        value: Amount,
+        foreign: Proto::mod_Input::ForeignInput<'static>,
+    ) -> Self {
+        self.add_input(Proto::Input {
+            out_point: Some(out_point(txid, vout)),
+            value: value.into(),
+            sighash_type: SighashType::default().raw_sighash(),
+            claiming_script: ClaimingScriptType::foreign(foreign),
+            ..Proto::Input::default()
+        })
+    }
+
+    /// Forces the input selector to spend the previously added `txid:vout` UTXO.
+    pub fn must_spend(mut self, txid: Txid, vout: u32) -> Self {
+        let out_point = out_point(txid, vout);
+        self.inputs
+            .iter_mut()
+            .filter(|input| input.out_point.as_ref() == Some(&out_point))
+            .for_each(|input| input.must_spend = true);
+        self
+    }
+
+    /// Protects the previously added `txid:vout` UTXO from being spent.
+    pub fn freeze(mut self, txid: Txid, vout: u32) -> Self {
+        let out_point = out_point(txid, vout);
+        self.inputs
+            .iter_mut()
+            .filter(|input| input.out_point.as_ref() == Some(&out_point))
+            .for_each(|input| input.frozen = true);
+        self
+    }
+
+    /// Adds an arbitrary output.
+    pub fn add_output(mut self, output: Proto::Output<'static>) -> Self {
+        self.outputs.push(output);
+        self
+    }
+
+    /// Adds an output that pays to the given address.
+    pub fn add_address_output(self, address: &str, value: Amount) -> Self {
+        self.add_output(address_output(address, value))
+    }
+
+    /// Adds an output that pays to the given BIP-352 Silent Payment address.
+    pub fn add_silent_payment_output(mut self, address: &str, value: Amount) -> Self {
+        self.outputs.push(Proto::Output {
+            value: value.into(),
+            to_recipient: RecipientType::silent_payment_address(Cow::Owned(address.to_string())),
+            ..Proto::Output::default()
+        });
+        self
+    }
+
+    /// Adds a P2WPKH output.
+    pub fn add_p2wpkh_output(self, pubkey: &ecdsa::secp256k1::PublicKey, value: Amount) -> Self {
+        let variant = OutputBuilderType::p2wpkh(Proto::PublicKeyOrHash {
+            variant: PublicKeyOrHashType::pubkey(Cow::Owned(pubkey.compressed().to_vec())),
+        });
+        self.add_output(output_with_builder(value, variant))
+    }
+
+    /// Adds a Pay-to-Anchor (P2A) output that can be spent by anyone to bump the fee via CPFP.
+    pub fn add_p2a_output(self, value: Amount) -> Self {
+        self.add_output(output_with_builder(value, OutputBuilderType::p2a(true)))
+    }
+
+    /// Adds an OP_RETURN output with zero value.
+    pub fn add_op_return_output(self, data: &[u8]) -> Self {
+        let variant = OutputBuilderType::op_return(Cow::Owned(data.to_vec()));
+        self.add_output(output_with_builder(Amount::ZERO, variant))
+    }
+
+    /// Adds an OP_RETURN output with zero value, where each of the `pushes` is pushed separately.
+    pub fn add_op_return_pushes_output(self, pushes: &[&[u8]]) -> Self {
+        let variant = OutputBuilderType::op_return_pushes(Proto::mod_Output::OpReturnPushes {
+            pushes: pushes
+                .iter()
+                .map(|data| Cow::Owned(data.to_vec()))
+                .collect(),
+        });
+        self.add_output(output_with_builder(Amount::ZERO, variant))
+    }
+
+    /// Sets an address where the change will be sent to.
+    pub fn change_address(mut self, address: &str) -> Self {
+        self.change_output = Some(address_output(address, Amount::ZERO));
+        self
+    }
+
+    /// Derives the change output from the account extended public key.
+    pub fn change_xpub(mut self, change_xpub: Proto::ChangeXpub<'static>) -> Self {
+        self.change_xpub = Some(change_xpub);
+        self
+    }
+
+    /// Splits the change into at most `outputs_count` outputs of randomized sizes.
+    /// The same `seed` must be used to plan and sign the transaction.
+    /// Each change output needs a distinct address, see [`TxBuilder::split_change_address`].
+    pub fn change_split(mut self, outputs_count: u32, min_amount: Amount, seed: &[u8]) -> Self {
+        self.change_split = Some(Proto::ChangeSplit {
+            outputs_count,
+            min_amount: min_amount.into(),
+            seed: Cow::Owned(seed.to_vec()),
+            change_outputs: Vec::default(),
+        });
+        self
+    }
+
+    /// Adds a split change output address following the [`TxBuilder::change_address`].
+    /// Not needed if the change is derived from [`TxBuilder::change_xpub`].
+    pub fn split_change_address(mut self, address: &str) -> Self {
+        self.split_change_outputs
+            .push(address_output(address, Amount::ZERO));
+        self
+    }
+
+    /// Sends all the inputs (except for the fee) to the given address.
+    /// Other outputs and the change address will be ignored.
+    pub fn max_amount_address(mut self, address: &str) -> Self {
+        self.max_amount_output = Some(address_output(address, Amount::ZERO));
+        self
+    }
+
+    pub fn input_selector(mut self, input_selector: Proto::InputSelector) -> Self {
+        self.input_selector = input_selector;
+        self
+    }
+
+    /// Sets the fee rate in satoshis per virtual byte.
+    pub fn fee_rate(mut self, fee_per_vb: i64) -> Self {
+        self.fee_per_vb = fee_per_vb;
+        self
+    }
+
+    pub fn dust_threshold(mut self, dust_threshold: Amount) -> Self {
+        self.dust_threshold = dust_threshold;
+        self
+    }
+
+    /// Whether to reject non-standard and provably unspendable custom scriptPubkey's.
+    pub fn validate_custom_scripts(mut self, validate: bool) -> Self {
+        self.validate_custom_scripts = validate;
+        self
+    }
+
+    pub fn build(self) -> Proto::TransactionBuilder<'static> {
+        Proto::TransactionBuilder {
+            version: self.version,
+            lock_time: self.lock_time,
+            typed_lock_time: self.typed_lock_time,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            input_selector: self.input_selector,
+            fee_per_vb: self.fee_per_vb,
+            change_output: self.change_output,
+            change_xpub: self.change_xpub,
+            change_split: self.change_split.map(|split| Proto::ChangeSplit {
+                change_outputs: self.split_change_outputs,
+                ..split
+            }),
+            max_amount_output: self.max_amount_output,
+            dust_policy: DustPolicyType::fixed_dust_threshold(self.dust_threshold.into()),
+            validate_custom_scripts: self.validate_custom_scripts,
+        }
+    }
+}
+
+fn out_point(txid: Txid, vout: u32) -> Proto::OutPoint<'static> {
+    Proto::OutPoint {
+        hash: Cow::Owned(txid.to_internal_bytes().to_vec()),
+        vout,
+    }
+}
+
+fn input_with_builder(
+    txid: Txid,
+    vout: u32,
+    value: Amount,
+    variant: InputBuilderType<'static>,
+) -> Proto::Input<'static> {
+    Proto::Input {
+        out_point: Some(out_point(txid, vout)),
+        value: value.into(),
+        sighash_type: SighashType::default().raw_sighash(),
+        claiming_script: ClaimingScriptType::script_builder(Proto::mod_Input::InputBuilder {
+            variant,
+        }),
+        ..Proto::Input::default()
+    }
+}
+
+fn output_with_builder(
+    value: Amount,
+    variant: OutputBuilderType<'static>,
+) -> Proto::Output<'static> {
+    Proto::Output {
+        value: value.into(),
+        to_recipient: RecipientType::builder(Proto::mod_Output::OutputBuilder { variant }),
+        ..Proto::Output::default()
+    }
+}
+
+fn address_output(address: &str, value: Amount) -> Proto::Output<'static> {
+    Proto::Output {
+        value: value.into(),
+        to_recipient: RecipientType::to_address(Cow::Owned(address.to_string())),
+        ..Proto::Output::default()
+    }
+}
@@ -0,0 +1,609 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Sender-side Payjoin (BIP-78) support.
+//! https://github.com/bitcoin/bips/blob/master/bip-0078.mediawiki
+
+use crate::modules::psbt_util::BitcoinPsbtUtil;
+use crate::modules::tx_builder::script_parser::StandardScriptParser;
+use bitcoin::psbt::Psbt;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_memory::Data;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::script::Script;
+
+/// Parameters of a Payjoin request, usually taken from the BIP-21 URI.
+#[derive(Clone, Debug, Default)]
+pub struct PayjoinParams {
+    /// The scriptPubkey of the receiver's payment output.
+    pub payment_script_pubkey: Data,
+    /// The index of the sender's output the receiver may subtract the additional fee from
+    /// (`additionalfeeoutputindex`). Usually, the change output.
+    pub additional_fee_output_index: Option<usize>,
+    /// The max amount the receiver may subtract from the fee output (`maxadditionalfeecontribution`).
+    pub max_additional_fee_contribution: u64,
+    /// Whether the receiver must not substitute the payment output (`pjos=0`).
+    pub disable_output_substitution: bool,
+    /// The min fee rate of the Payjoin transaction in satoshis per vbyte (`minfeerate`).
+    pub min_fee_rate: Option<u64>,
+}
+
+pub struct BitcoinPayjoin<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> BitcoinPayjoin<Context> {
+    /// Creates the Original PSBT that is sent to the receiver.
+    /// The PSBT is signed and finalized with [`Proto::SigningInput::private_keys`],
+    /// so the receiver can broadcast it if the Payjoin fails.
+    pub fn create_original(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        tx_builder: &Proto::TransactionBuilder,
+    ) -> SigningResult<Data> {
+        let unsigned_psbt = BitcoinPsbtUtil::<Context>::create(coin, input, tx_builder)?;
+        let private_keys: Vec<Data> = input.private_keys.iter().map(|pk| pk.to_vec()).collect();
+        BitcoinPsbtUtil::<Context>::sign(coin, &unsigned_psbt, &private_keys)
+    }
+
+    /// Validates the receiver's Payjoin proposal against the Original PSBT,
+    /// and signs the sender's inputs of the proposal.
+    /// Returns the signed PSBT that can be finalized via [`BitcoinPsbtUtil::finalize`].
+    pub fn sign_proposal(
+        coin: &dyn CoinContext,
+        original: &[u8],
+        proposal: &[u8],
+        params: &PayjoinParams,
+        private_keys: &[Data],
+    ) -> SigningResult<Data> {
+        let original = deserialize(original)?;
+        let mut proposal = deserialize(proposal)?;
+
+        Self::validate_proposal(&original, &proposal, params)?;
+
+        // The receiver must have cleared the sender's UTXOs, so restore them from the Original PSBT.
+        for (txin, utxo_psbt) in proposal
+            .unsigned_tx
+            .input
+            .iter()
+            .zip(proposal.inputs.iter_mut())
+        {
+            if let Some(original_idx) = find_input(&original, &txin.previous_output) {
+                let original_utxo = &original.inputs[original_idx];
+                utxo_psbt.witness_utxo = original_utxo.witness_utxo.clone();
+                utxo_psbt.non_witness_utxo = original_utxo.non_witness_utxo.clone();
+            }
+        }
+
+        let signed = BitcoinPsbtUtil::<Context>::sign(coin, &proposal.serialize(), private_keys)?;
+
+        if let Some(min_fee_rate) = params.min_fee_rate {
+            let signed_psbt = deserialize(&signed)?;
+            let fee = psbt_fee(&signed_psbt, &signed_psbt)?;
+            let vsize = Self::final_vsize(&signed)
+                .context("The Payjoin transaction must be finalized to check its fee rate")?;
+            if fee < min_fee_rate * vsize {
+                return SigningError::err(SigningErrorType::Error_wrong_fee).context(format!(
+                    "Payjoin transaction fee rate is less than {min_fee_rate} sat/vB"
+                ));
+            }
+        }
+
+        Ok(signed)
+    }
+
+    /// Checks the Payjoin proposal as specified in the "Senders' payjoin proposal checklist".
+    pub fn validate_proposal(
+        original: &Psbt,
+        proposal: &Psbt,
+        params: &PayjoinParams,
+    ) -> SigningResult<()> {
+        if original.unsigned_tx.version != proposal.unsigned_tx.version {
+            return invalid_proposal("the transaction version has been changed");
+        }
+        if original.unsigned_tx.lock_time != proposal.unsigned_tx.lock_time {
+            return invalid_proposal("the transaction lock time has been changed");
+        }
+
+        Self::validate_proposal_inputs(original, proposal)?;
+        Self::validate_proposal_outputs(original, proposal, params)
+    }
+
+    fn validate_proposal_inputs(original: &Psbt, proposal: &Psbt) -> SigningResult<()> {
+        let mut sender_inputs = HashSet::new();
+        let mut input_script_type = None;
+
+        // The receiver may add inputs of any type if the sender's inputs are of different types already.
+        let original_script_types = (0..original.unsigned_tx.input.len())
+            .map(|original_idx| {
+                let prevout = prevout(original, original_idx)?;
+                Ok(StandardScriptParser
+                    .output_script_type(&Script::from(prevout.script_pubkey.to_bytes())))
+            })
+            .collect::<SigningResult<HashSet<_>>>()?;
+        let check_script_types = original_script_types.len() <= 1;
+
+        for (i, (txin, utxo_psbt)) in proposal
+            .unsigned_tx
+            .input
+            .iter()
+            .zip(proposal.inputs.iter())
+            .enumerate()
+        {
+            if !utxo_psbt.bip32_derivation.is_empty() || !utxo_psbt.tap_key_origins.is_empty() {
+                return invalid_proposal(format!("input '{i}' contains key paths"));
+            }
+            if !utxo_psbt.partial_sigs.is_empty()
+                || utxo_psbt.tap_key_sig.is_some()
+                || !utxo_psbt.tap_script_sigs.is_empty()
+            {
+                return invalid_proposal(format!("input '{i}' contains partial signatures"));
+            }
+            // All inputs must have the same sequence number.
+            if txin.sequence != proposal.unsigned_tx.input[0].sequence {
+                return invalid_proposal("inputs have different sequence numbers");
+            }
+
+            let prevout = match find_input(original, &txin.previous_output) {
+                // The sender's input.
+                Some(original_idx) => {
+                    if txin.sequence != original.unsigned_tx.input[original_idx].sequence {
+                        return invalid_proposal(format!(
+                            "the sender's input '{i}' sequence has been changed"
+                        ));
+                    }
+                    if is_finalized(utxo_psbt) {
+                        return invalid_proposal(format!(
+                            "the sender's input '{i}' must not be finalized"
+                        ));
+                    }
+                    if utxo_psbt.witness_utxo.is_some() || utxo_psbt.non_witness_utxo.is_some() {
+                        return invalid_proposal(format!(
+                            "the sender's input '{i}' must not contain the UTXO"
+                        ));
+                    }
+                    sender_inputs.insert(original_idx);
+                    prevout(original, original_idx)?
+                },
+                // The receiver's input.
+                None => {
+                    if !is_finalized(utxo_psbt) {
+                        return invalid_proposal(format!(
+                            "the receiver's input '{i}' must be finalized"
+                        ));
+                    }
+                    prevout(proposal, i)?
+                },
+            };
+
+            // The proposal must not introduce mixed input types.
+            let script_type = StandardScriptParser
+                .output_script_type(&Script::from(prevout.script_pubkey.to_bytes()));
+            match input_script_type {
+                Some(expected) if check_script_types && expected != script_type => {
+                    return invalid_proposal("the proposal contains mixed input types");
+                },
+                _ => input_script_type = Some(script_type),
+            }
+        }
+
+        if sender_inputs.len() != original.unsigned_tx.input.len() {
+            return invalid_proposal("some of the sender's inputs are missing");
+        }
+        Ok(())
+    }
+
+    fn validate_proposal_outputs(
+        original: &Psbt,
+        proposal: &Psbt,
+        params: &PayjoinParams,
+    ) -> SigningResult<()> {
+        for (i, output_psbt) in proposal.outputs.iter().enumerate() {
+            if !output_psbt.bip32_derivation.is_empty() || !output_psbt.tap_key_origins.is_empty() {
+                return invalid_proposal(format!("output '{i}' contains key paths"));
+            }
+        }
+
+        let original_fee = psbt_fee(original, original)?;
+        let proposal_fee = psbt_fee(original, proposal)?;
+        if proposal_fee < original_fee {
+            return invalid_proposal("the absolute fee has been decreased");
+        }
+
+        let mut matched_outputs = HashSet::new();
+        for (original_idx, original_out) in original.unsigned_tx.output.iter().enumerate() {
+            let is_payment = original_out.script_pubkey.as_bytes() == params.payment_script_pubkey;
+            if is_payment && !params.disable_output_substitution {
+                // The receiver is allowed to substitute the payment output.
+                continue;
+            }
+
+            let proposal_idx = proposal
+                .unsigned_tx
+                .output
+                .iter()
+                .enumerate()
+                .find(|(idx, out)| {
+                    !matched_outputs.contains(idx)
+                        && out.script_pubkey == original_out.script_pubkey
+                })
+                .map(|(idx, _)| idx);
+            let Some(proposal_idx) = proposal_idx else {
+                return invalid_proposal(format!("output '{original_idx}' is missing"));
+            };
+            matched_outputs.insert(proposal_idx);
+
+            let proposal_out = &proposal.unsigned_tx.output[proposal_idx];
+            if proposal_out.value >= original_out.value {
+                continue;
+            }
+            if params.additional_fee_output_index != Some(original_idx) {
+                return invalid_proposal(format!("output '{original_idx}' value has decreased"));
+            }
+
+            let contribution = original_out.value - proposal_out.value;
+            if contribution > params.max_additional_fee_contribution {
+                return invalid_proposal("the fee contribution exceeds the max allowed amount");
+            }
+            if contribution > proposal_fee - original_fee {
+                return invalid_proposal("the fee contribution is used not only to pay the fee");
+            }
+
+            // The contribution can only pay for the additional inputs at the original fee rate.
+            let original_vsize = Self::final_vsize(&original.serialize())
+                .context("The Original PSBT must be finalized to check the fee contribution")?;
+            let original_fee_rate = original_fee / original_vsize;
+            let new_inputs =
+                (proposal.unsigned_tx.input.len() - original.unsigned_tx.input.len()) as u64;
+            let input_vsize = sender_input_vsize(original)?;
+            if contribution > original_fee_rate * input_vsize * new_inputs {
+                return invalid_proposal(
+                    "the fee contribution exceeds the fee of the additional inputs",
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the `vsize` of the finalized PSBT transaction.
+    /// The unsigned transaction can't be used, as it doesn't account for the script sigs and witnesses.
+    fn final_vsize(psbt: &[u8]) -> SigningResult<u64> {
+        let final_tx = BitcoinPsbtUtil::<Context>::finalize(psbt)?;
+        let final_tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&final_tx)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error deserializing the finalized transaction")?;
+        Ok(final_tx.vsize() as u64)
+    }
+}
+
+fn deserialize(psbt: &[u8]) -> SigningResult<Psbt> {
+    Psbt::deserialize(psbt)
+        .tw_err(|_| SigningErrorType::Error_input_parse)
+        .context("Error deserializing PSBT")
+}
+
+fn invalid_proposal<T, S: Into<String>>(reason: S) -> SigningResult<T> {
+    SigningError::err(SigningErrorType::Error_invalid_params)
+        .context(format!("Invalid Payjoin proposal: {}", reason.into()))
+}
+
+fn find_input(psbt: &Psbt, out_point: &bitcoin::OutPoint) -> Option<usize> {
+    psbt.unsigned_tx
+        .input
+        .iter()
+        .position(|txin| txin.previous_output == *out_point)
+}
+
+fn is_finalized(utxo_psbt: &bitcoin::psbt::Input) -> bool {
+    utxo_psbt.final_script_sig.is_some() || utxo_psbt.final_script_witness.is_some()
+}
+
+/// Returns the output spent by the `input_idx` input.
+fn prevout(psbt: &Psbt, input_idx: usize) -> SigningResult<bitcoin::TxOut> {
+    let utxo_psbt = &psbt.inputs[input_idx];
+    if let Some(ref witness_utxo) = utxo_psbt.witness_utxo {
+        return Ok(witness_utxo.clone());
+    }
+
+    let vout = psbt.unsigned_tx.input[input_idx].previous_output.vout as usize;
+    utxo_psbt
+        .non_witness_utxo
+        .as_ref()
+        .and_then(|prev_tx| prev_tx.output.get(vout))
+        .cloned()
+        .or_tw_err(SigningErrorType::Error_invalid_utxo)
+        .with_context(|| format!("PSBT input '{input_idx}' does not contain the UTXO"))
+}
+
+/// Calculates the `psbt` fee.
+/// The sender's UTXOs are taken from the `original` PSBT as they may be cleared in the Payjoin proposal.
+fn psbt_fee(original: &Psbt, psbt: &Psbt) -> SigningResult<u64> {
+    let mut total_in = 0_u64;
+    for (i, txin) in psbt.unsigned_tx.input.iter().enumerate() {
+        let prevout = match find_input(original, &txin.previous_output) {
+            Some(original_idx) => prevout(original, original_idx)?,
+            None => prevout(psbt, i)?,
+        };
+        total_in = total_in
+            .checked_add(prevout.value)
+            .or_tw_err(SigningErrorType::Error_invalid_utxo_amount)
+            .context("Sum of PSBT input amounts is too big")?;
+    }
+
+    let total_out = psbt
+        .unsigned_tx
+        .output
+        .iter()
+        .try_fold(0_u64, |total, out| total.checked_add(out.value))
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("Sum of PSBT output amounts is too big")?;
+
+    total_in
+        .checked_sub(total_out)
+        .or_tw_err(SigningErrorType::Error_not_enough_utxos)
+        .context("sum(input) < sum(output)")
+}
+
+/// Returns the estimated `vsize` of the sender's input type.
+fn sender_input_vsize(original: &Psbt) -> SigningResult<u64> {
+    let prevout = prevout(original, 0)?;
+    let script_type =
+        StandardScriptParser.output_script_type(&Script::from(prevout.script_pubkey.to_bytes()));
+    match script_type {
+        Proto::OutputScriptType::P2PKH => Ok(148),
+        Proto::OutputScriptType::P2WPKH => Ok(68),
+        Proto::OutputScriptType::P2TR => Ok(58),
+        _ => SigningError::err(SigningErrorType::Error_not_supported)
+            .context("Payjoin is supported for P2PKH, P2WPKH and P2TR inputs only"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::StandardBitcoinContext;
+    use crate::modules::native_builder::amount::Amount;
+    use crate::modules::native_builder::conversions::ToProtoRecipient;
+    use crate::modules::native_builder::signing_input::SigningInputBuilder;
+    use crate::modules::native_builder::tx_builder::TxBuilder;
+    use std::str::FromStr;
+    use tw_coin_entry::test_utils::test_context::TestCoinContext;
+    use tw_hash::ripemd::bitcoin_hash_160;
+    use tw_keypair::{ecdsa, schnorr};
+    use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+    use tw_proto::BitcoinV2::Proto::mod_SigningInput::OneOftransaction as TransactionType;
+    use tw_utxo::transaction::transaction_parts::Txid;
+
+    type Payjoin = BitcoinPayjoin<StandardBitcoinContext>;
+
+    const SENDER_PRIVATE_KEY: &str =
+        "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    const RECEIVER_PRIVATE_KEY: &str =
+        "f00ffbe44c5c2838c13d2778854ac66b75e04eb6054f0241989e223223ad5e55";
+    const SENDER_TXID: &str = "8d0e94ed369601d13f32bd653071844dfe7139508d2e71d9c105d1faa58230b3";
+    const RECEIVER_TXID: &str = "17a6adb5db1e33c87467a58aa31cddbb3800052315015cf3cf1c2b0119310e20";
+    const SENDER_UTXO: u64 = 100_000;
+    const RECEIVER_UTXO: u64 = 30_000;
+    const PAYMENT: u64 = 50_000;
+    // The original fee is 2_000.
+    const SENDER_CHANGE: u64 = 48_000;
+
+    fn sender_private() -> ecdsa::secp256k1::PrivateKey {
+        ecdsa::secp256k1::PrivateKey::try_from(SENDER_PRIVATE_KEY).unwrap()
+    }
+
+    fn receiver_public() -> ecdsa::secp256k1::PublicKey {
+        ecdsa::secp256k1::PrivateKey::try_from(RECEIVER_PRIVATE_KEY)
+            .unwrap()
+            .public()
+    }
+
+    fn p2wpkh_script(public: &ecdsa::secp256k1::PublicKey) -> bitcoin::ScriptBuf {
+        let pubkey_hash = bitcoin_hash_160(public.compressed().as_slice());
+        let mut script = vec![0x00, 0x14];
+        script.extend_from_slice(&pubkey_hash);
+        bitcoin::ScriptBuf::from_bytes(script)
+    }
+
+    fn sat(sat: u64) -> Amount {
+        Amount::from_sat(sat).unwrap()
+    }
+
+    fn params() -> PayjoinParams {
+        PayjoinParams {
+            payment_script_pubkey: p2wpkh_script(&receiver_public()).to_bytes(),
+            additional_fee_output_index: Some(1),
+            max_additional_fee_contribution: 1_000,
+            disable_output_substitution: false,
+            min_fee_rate: None,
+        }
+    }
+
+    fn create_original() -> Data {
+        create_original_with(|tx| tx)
+    }
+
+    /// Creates the Original PSBT with additional sender's inputs added by `f`.
+    fn create_original_with<F>(f: F) -> Data
+    where
+        F: FnOnce(TxBuilder) -> TxBuilder,
+    {
+        let sender_private = sender_private();
+        let sender_public = sender_private.public();
+        // The sender's change output the receiver may subtract the additional fee from.
+        let change_output =
+            p2wpkh_script(&sender_public).to_proto_output_with_value(sat(SENDER_CHANGE));
+
+        let input = SigningInputBuilder::new()
+            .add_private_key(&sender_private)
+            .chain_info(0, 5, Some("bc"))
+            .transaction(|tx| {
+                let tx = tx
+                    .add_p2wpkh_input(
+                        Txid::from_str(SENDER_TXID).unwrap(),
+                        0,
+                        sat(SENDER_UTXO),
+                        &sender_public,
+                    )
+                    .add_p2wpkh_output(&receiver_public(), sat(PAYMENT))
+                    .add_output(change_output)
+                    .fee_rate(10);
+                f(tx)
+            })
+            .build()
+            .unwrap();
+        let TransactionType::builder(ref tx_builder) = input.transaction else {
+            unreachable!()
+        };
+
+        Payjoin::create_original(&TestCoinContext::default(), &input, tx_builder).unwrap()
+    }
+
+    /// Emulates the receiver that adds its input and pays `contribution` from the sender's change.
+    fn make_proposal(original: &[u8], contribution: u64) -> Psbt {
+        let mut proposal = Psbt::deserialize(original).unwrap();
+
+        // The receiver clears the sender's inputs.
+        for utxo_psbt in proposal.inputs.iter_mut() {
+            *utxo_psbt = bitcoin::psbt::Input::default();
+        }
+
+        // The receiver adds its own finalized input.
+        let sequence = proposal.unsigned_tx.input[0].sequence;
+        proposal.unsigned_tx.input.push(bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::new(
+                bitcoin::Txid::from_str(RECEIVER_TXID).unwrap(),
+                1,
+            ),
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence,
+            witness: bitcoin::Witness::new(),
+        });
+        proposal.inputs.push(bitcoin::psbt::Input {
+            witness_utxo: Some(bitcoin::TxOut {
+                value: RECEIVER_UTXO,
+                script_pubkey: p2wpkh_script(&receiver_public()),
+            }),
+            // The sender does not verify the receiver's signature.
+            final_script_witness: Some(bitcoin::Witness::from_slice(&[
+                vec![0x30; 71],
+                receiver_public().to_vec(),
+            ])),
+            ..bitcoin::psbt::Input::default()
+        });
+
+        proposal.unsigned_tx.output[0].value += RECEIVER_UTXO;
+        proposal.unsigned_tx.output[1].value -= contribution;
+        proposal
+    }
+
+    fn sign_proposal(original: &[u8], proposal: &Psbt) -> SigningResult<Data> {
+        let private_keys = vec![sender_private().to_zeroizing_vec().to_vec()];
+        Payjoin::sign_proposal(
+            &TestCoinContext::default(),
+            original,
+            &proposal.serialize(),
+            &params(),
+            &private_keys,
+        )
+    }
+
+    #[test]
+    fn test_payjoin_sign_proposal() {
+        let original = create_original();
+        let proposal = make_proposal(&original, 500);
+
+        let signed = sign_proposal(&original, &proposal).unwrap();
+        let encoded = BitcoinPsbtUtil::<StandardBitcoinContext>::finalize(&signed).unwrap();
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&encoded).unwrap();
+
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.output[0].value, PAYMENT + RECEIVER_UTXO);
+        assert_eq!(tx.output[1].value, SENDER_CHANGE - 500);
+        // The sender's input is signed, and the receiver's input is kept as is.
+        assert_eq!(tx.input[0].witness.len(), 2);
+        assert_eq!(
+            tx.input[1].witness,
+            proposal.inputs[1].final_script_witness.clone().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_payjoin_mixed_original_inputs() {
+        // The sender spends P2WPKH and P2TR inputs.
+        let sender_schnorr = schnorr::PrivateKey::try_from(SENDER_PRIVATE_KEY).unwrap();
+        let original = create_original_with(|tx| {
+            tx.add_p2tr_key_path_input(
+                Txid::from_str(SENDER_TXID).unwrap(),
+                1,
+                sat(2_000),
+                &sender_schnorr.public(),
+            )
+        });
+        let original_psbt = Psbt::deserialize(&original).unwrap();
+        assert_eq!(original_psbt.unsigned_tx.input.len(), 2);
+
+        // The receiver's P2WPKH input doesn't introduce mixed input types, as they are mixed already.
+        let proposal = make_proposal(&original, 500);
+        Payjoin::validate_proposal(&original_psbt, &proposal, &params()).unwrap();
+    }
+
+    #[test]
+    fn test_payjoin_fee_contribution_original_vsize() {
+        let original = create_original();
+        let original_psbt = Psbt::deserialize(&original).unwrap();
+        let params = PayjoinParams {
+            max_additional_fee_contribution: 2_000,
+            ..params()
+        };
+
+        // The Original fee rate is 14 sat/vB of the finalized 141 vbytes transaction,
+        // so the contribution can pay up to 14 * 68 = 952 for the additional P2WPKH input.
+        // The unsigned transaction would allow up to 17 * 68 = 1_156.
+        let proposal = make_proposal(&original, 950);
+        Payjoin::validate_proposal(&original_psbt, &proposal, &params).unwrap();
+        let proposal = make_proposal(&original, 1_000);
+        Payjoin::validate_proposal(&original_psbt, &proposal, &params).unwrap_err();
+
+        // The Original PSBT must be finalized to check the fee contribution.
+        let mut unfinalized = original_psbt.clone();
+        unfinalized.inputs[0].final_script_witness = None;
+        let proposal = make_proposal(&original, 500);
+        Payjoin::validate_proposal(&unfinalized, &proposal, &params).unwrap_err();
+    }
+
+    #[test]
+    fn test_payjoin_invalid_proposal() {
+        let original = create_original();
+
+        // The receiver takes more than the max additional fee contribution.
+        let proposal = make_proposal(&original, 1_500);
+        let err = sign_proposal(&original, &proposal).unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+        // The receiver changes the lock time.
+        let mut proposal = make_proposal(&original, 500);
+        proposal.unsigned_tx.lock_time = bitcoin::absolute::LockTime::from_consensus(1);
+        assert!(sign_proposal(&original, &proposal).is_err());
+
+        // The receiver does not clear the sender's input.
+        let mut proposal = make_proposal(&original, 500);
+        proposal.inputs[0] = Psbt::deserialize(&original).unwrap().inputs[0].clone();
+        assert!(sign_proposal(&original, &proposal).is_err());
+
+        // The receiver decreases the sender's output which is not allowed to pay the fee.
+        let proposal = make_proposal(&original, 500);
+        let params = PayjoinParams {
+            additional_fee_output_index: None,
+            ..params()
+        };
+        let original_psbt = Psbt::deserialize(&original).unwrap();
+        assert!(Payjoin::validate_proposal(&original_psbt, &proposal, &params).is_err());
+    }
+}
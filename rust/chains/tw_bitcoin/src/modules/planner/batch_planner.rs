@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::modules::planner::BitcoinPlanner;
+use crate::modules::tx_builder::utxo_protobuf::parse_out_point;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+
+/// Plans several independent transactions funded from one UTXO pool.
+pub struct BatchPlanner<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> BatchPlanner<Context> {
+    /// Plans a transaction per each of the `payments`, where every payment is a list of the transaction outputs.
+    ///
+    /// The UTXO pool, the input selector, the fee rate and the change output are taken from `input.builder`,
+    /// which outputs are ignored. The payments are planned in order, and every transaction spends
+    /// only the UTXOs not selected by the previous ones, so the transactions don't conflict with each other.
+    /// Each transaction sends its own change to the `change_output`.
+    pub fn plan_batch<'a>(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput<'a>,
+        payments: &[Vec<Proto::Output<'a>>],
+    ) -> SigningResult<Vec<Proto::TransactionPlan<'a>>> {
+        use Proto::mod_SigningInput::OneOftransaction as TransactionType;
+
+        let TransactionType::builder(ref tx_builder) = input.transaction else {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Batch transactions must be constructed by `TransactionBuilder`");
+        };
+        if tx_builder.max_amount_output.is_some() {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(
+                "`max_amount_output` cannot be used in a batch, it would spend the whole UTXO pool",
+            );
+        }
+
+        let mut utxo_pool = tx_builder.inputs.clone();
+        let mut plans = Vec::with_capacity(payments.len());
+
+        for (payment_idx, outputs) in payments.iter().enumerate() {
+            let payment_input = Proto::SigningInput {
+                transaction: TransactionType::builder(Proto::TransactionBuilder {
+                    inputs: utxo_pool.clone(),
+                    outputs: outputs.clone(),
+                    ..tx_builder.clone()
+                }),
+                ..input.clone()
+            };
+            let plan = BitcoinPlanner::<Context>::plan_impl(coin, &payment_input)
+                .with_context(|| format!("Error planning the payment '{payment_idx}'"))?;
+
+            // Remove the selected UTXOs from the pool, so they're not spent by the next transactions.
+            let selected = plan
+                .inputs
+                .iter()
+                .map(|utxo| parse_out_point(&utxo.out_point))
+                .collect::<SigningResult<HashSet<_>>>()?;
+            let mut remaining = Vec::with_capacity(utxo_pool.len());
+            for utxo in utxo_pool {
+                if !selected.contains(&parse_out_point(&utxo.out_point)?) {
+                    remaining.push(utxo);
+                }
+            }
+            utxo_pool = remaining;
+
+            plans.push(plan);
+        }
+
+        Ok(plans)
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::modules::planner::BitcoinPlanner;
+use crate::modules::signing_request::SigningRequestBuilder;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::transaction::transaction_parts::Amount;
+
+/// The number of UTXOs requested from [`UtxoProvider`] at once by default.
+pub const DEFAULT_UTXO_BATCH_SIZE: usize = 100;
+
+/// A page of the candidate UTXOs requested from [`UtxoProvider`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UtxoQuery {
+    /// Only the UTXOs with at least this amount are requested.
+    pub min_amount: Amount,
+    /// The number of the matching UTXOs already returned by the previous queries.
+    pub offset: usize,
+    /// The max number of UTXOs to return.
+    pub limit: usize,
+}
+
+/// A source of the candidate UTXOs, e.g. a wallet database,
+/// that is queried lazily instead of passing the whole UTXO set in `TransactionBuilder.inputs`.
+pub trait UtxoProvider {
+    /// Returns at most `query.limit` UTXOs with amounts not less than `query.min_amount`, skipping first `query.offset` ones.
+    /// The UTXOs must be ordered by amount descending, and the order must be stable between the queries.
+    /// Returning less than `query.limit` UTXOs means there are no more candidates.
+    fn query(&mut self, query: &UtxoQuery) -> SigningResult<Vec<Proto::Input<'static>>>;
+
+    /// The number of UTXOs to request at once.
+    fn batch_size(&self) -> usize {
+        DEFAULT_UTXO_BATCH_SIZE
+    }
+}
+
+/// Plans a transaction fetching the candidate UTXOs from [`UtxoProvider`] on demand.
+pub struct LazyPlanner<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> LazyPlanner<Context> {
+    /// Plans a transaction constructed by `input.builder`, where the candidate UTXOs are fetched from the `provider`
+    /// page by page, starting from the largest ones, until there are enough funds to cover the outputs and the fee.
+    ///
+    /// The UTXOs specified in `input.builder.inputs` are candidates as well and must not be returned by the provider.
+    /// The input selector chooses among the fetched UTXOs only, so the result is not necessarily the same
+    /// as if the whole UTXO set was passed. UTXOs below the dust threshold are not requested.
+    /// If `max_amount_output` is set, all UTXOs are fetched.
+    ///
+    /// The returned plan contains the selected UTXOs, that should be passed in `TransactionBuilder.inputs` to sign the transaction.
+    pub fn plan_with_provider<'a>(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput<'a>,
+        provider: &mut dyn UtxoProvider,
+    ) -> SigningResult<Proto::TransactionPlan<'a>> {
+        use Proto::mod_SigningInput::OneOftransaction as TransactionType;
+
+        let TransactionType::builder(ref tx_builder) = input.transaction else {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("UTXO provider can be used with `TransactionBuilder` only");
+        };
+
+        let dust_policy = SigningRequestBuilder::<Context>::dust_policy(&tx_builder.dust_policy)?;
+        let send_max = tx_builder.max_amount_output.is_some();
+        let send_amount = tx_builder
+            .outputs
+            .iter()
+            .try_fold(0, |total: Amount, output| total.checked_add(output.value))
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("Sum of output amounts is too big")?;
+        let batch_size = provider.batch_size().max(1);
+
+        let mut utxos = Vec::default();
+        let mut available: Amount = 0;
+        push_utxos(&mut utxos, &mut available, tx_builder.inputs.clone())?;
+
+        // The fee is unknown until the transaction is planned, so the margin on top of the output amounts
+        // is doubled after each attempt failed due to the fee, limiting the number of attempts.
+        let mut fee_margin: Amount = 0;
+        let mut offset = 0;
+        loop {
+            let query = UtxoQuery {
+                min_amount: dust_policy.dust_threshold(),
+                offset,
+                limit: batch_size,
+            };
+            let batch = provider
+                .query(&query)
+                .context("Error fetching UTXOs from the provider")?;
+            if batch.len() > batch_size {
+                return SigningError::err(SigningErrorType::Error_internal)
+                    .context("UTXO provider returned more UTXOs than requested");
+            }
+            let exhausted = batch.len() < batch_size;
+            offset += batch.len();
+            push_utxos(&mut utxos, &mut available, batch)?;
+
+            // Postpone planning until all UTXOs are fetched or the outputs and the fee margin are covered.
+            if !exhausted && (send_max || available < send_amount.saturating_add(fee_margin)) {
+                continue;
+            }
+
+            let planning_input = Proto::SigningInput {
+                transaction: TransactionType::builder(Proto::TransactionBuilder {
+                    inputs: utxos.clone(),
+                    ..tx_builder.clone()
+                }),
+                ..input.clone()
+            };
+            match BitcoinPlanner::<Context>::plan_impl(coin, &planning_input) {
+                // The fetched UTXOs are not enough to cover the fee, fetch more.
+                Err(e)
+                    if !exhausted
+                        && *e.error_type() == SigningErrorType::Error_not_enough_utxos =>
+                {
+                    fee_margin = fee_margin
+                        .saturating_mul(2)
+                        .max(tx_builder.fee_per_vb.max(1));
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Appends the `batch` to the `utxos`, and adds the amounts of the spendable ones to the `available` amount.
+fn push_utxos<'a>(
+    utxos: &mut Vec<Proto::Input<'a>>,
+    available: &mut Amount,
+    batch: Vec<Proto::Input<'a>>,
+) -> SigningResult<()> {
+    for utxo in batch {
+        if !utxo.frozen {
+            *available = available
+                .checked_add(utxo.value)
+                .or_tw_err(SigningErrorType::Error_invalid_params)
+                .context("Sum of UTXO amounts is too big")?;
+        }
+        utxos.push(utxo);
+    }
+    Ok(())
+}
@@ -16,6 +16,8 @@ use tw_utxo::context::UtxoContext;
 use tw_utxo::modules::tx_planner::TxPlanner;
 use tw_utxo::modules::utxo_selector::SelectResult;
 
+pub mod batch_planner;
+pub mod lazy_planner;
 pub mod psbt_planner;
 
 #[derive(Default)]
@@ -35,6 +37,8 @@ impl<Context: UtxoContext> BitcoinPlanner<Context> {
             TransactionType::psbt(ref psbt) => {
                 psbt_planner::PsbtPlanner::<Context>::plan_psbt(coin, input, psbt)
             },
+            TransactionType::session(_) => SigningError::err(SigningErrorType::Error_not_supported)
+                .context("Signing session can only be passed to `preimage_hashes` and `compile`"),
             TransactionType::None => SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("Either `TransactionBuilder` or `Psbt` should be set"),
         }
@@ -55,7 +59,7 @@ impl<Context: UtxoContext> BitcoinPlanner<Context> {
             let key = parse_out_point(&utxo.out_point)?;
             if inputs_map.insert(key, utxo).is_some() {
                 // Found a duplicate UTXO. Return an error.
-                return SigningError::err(SigningErrorType::Error_invalid_utxo)
+                return SigningError::err(SigningErrorType::Error_duplicate_utxo)
                     .context("Provided duplicate UTXOs with the same OutPoint");
             }
         }
@@ -82,9 +86,16 @@ impl<Context: UtxoContext> BitcoinPlanner<Context> {
             outputs_proto.push(Proto::Output {
                 value: selected_output.value,
                 to_recipient,
+                ..Proto::Output::default()
             })
         }
 
+        let warnings = SigningRequestBuilder::<Context>::check_relay_policy(
+            input,
+            unsigned_tx.transaction(),
+            plan.vsize_estimate,
+        )?;
+
         Ok(Proto::TransactionPlan {
             inputs: selected_inputs_proto,
             outputs: outputs_proto,
@@ -93,6 +104,7 @@ impl<Context: UtxoContext> BitcoinPlanner<Context> {
             vsize_estimate: plan.vsize_estimate as u64,
             fee_estimate: plan.fee_estimate,
             change: plan.change,
+            warnings,
             ..Proto::TransactionPlan::default()
         })
     }
@@ -33,7 +33,12 @@ impl<Context: UtxoContext> PsbtPlanner<Context> {
         let total_input = unsigned_tx.total_input()?;
         let fee_estimate = unsigned_tx.fee()?;
 
-        let vsize_estimate = unsigned_tx.estimate_transaction().vsize() as u64;
+        let vsize_estimate = unsigned_tx.estimate_transaction().vsize();
+        let warnings = SigningRequestBuilder::<Context>::check_relay_policy(
+            input,
+            unsigned_tx.transaction(),
+            vsize_estimate,
+        )?;
 
         let inputs: Vec<_> = unsigned_tx
             .input_args()
@@ -53,9 +58,10 @@ impl<Context: UtxoContext> PsbtPlanner<Context> {
             outputs,
             available_amount: total_input,
             send_amount: total_input,
-            vsize_estimate,
+            vsize_estimate: vsize_estimate as u64,
             fee_estimate,
             change: 0,
+            warnings,
             ..Proto::TransactionPlan::default()
         })
     }
@@ -86,6 +92,8 @@ impl<Context: UtxoContext> PsbtPlanner<Context> {
             sighash_type: unsigned_txin.sighash_ty.raw_sighash(),
             sequence: Some(sequence),
             claiming_script: ClaimingScriptProto::receiver_address(from_address.into()),
+            must_spend: unsigned_txin.must_spend,
+            ..Proto::Input::default()
         })
     }
 
@@ -105,6 +113,7 @@ impl<Context: UtxoContext> PsbtPlanner<Context> {
         Ok(Proto::Output {
             value: output.value,
             to_recipient,
+            ..Proto::Output::default()
         })
     }
 }
@@ -4,6 +4,7 @@
 
 use crate::modules::psbt_request::output_psbt::OutputPsbt;
 use crate::modules::psbt_request::utxo_psbt::UtxoPsbt;
+use crate::modules::psbt_v2::{deserialize_psbt, PsbtVersion};
 use crate::modules::signing_request::SigningRequestBuilder;
 use bitcoin::psbt::Psbt;
 use std::marker::PhantomData;
@@ -19,15 +20,15 @@ pub mod utxo_psbt;
 
 pub struct PsbtRequest<Context: UtxoContext> {
     pub psbt: Psbt,
+    /// The version of the original PSBT. PSBTv2 is converted to PSBTv0.
+    pub version: PsbtVersion,
     pub unsigned_tx: UnsignedTransaction<Transaction>,
     _phantom: PhantomData<Context>,
 }
 
 impl<Context: UtxoContext> PsbtRequest<Context> {
     pub fn build(input: &Proto::SigningInput, psbt_input: &Proto::Psbt) -> SigningResult<Self> {
-        let psbt = Psbt::deserialize(&psbt_input.psbt)
-            .tw_err(|_| SigningErrorType::Error_input_parse)
-            .context("Error deserializing PSBT")?;
+        let (psbt, version) = deserialize_psbt(&psbt_input.psbt)?;
 
         let version = psbt
             .unsigned_tx
@@ -46,9 +47,17 @@ impl<Context: UtxoContext> PsbtRequest<Context> {
         for (txin, txin_psbt) in psbt.unsigned_tx.input.iter().zip(psbt.inputs.iter()) {
             let utxo_builder = UtxoPsbt::new(txin, txin_psbt, &public_keys);
 
-            let (utxo, utxo_args) = utxo_builder
-                .build()
-                .context("Error creating UTXO from PSBT")?;
+            let (utxo, utxo_args) = match utxo_builder.build() {
+                // The UTXO belongs to another party, so leave it unsigned.
+                Err(e)
+                    if input.allow_partial_signing
+                        && *e.error_type() == SigningErrorType::Error_missing_private_key =>
+                {
+                    utxo_builder.build_unowned()
+                },
+                other => other,
+            }
+            .context("Error creating UTXO from PSBT")?;
             builder.push_input(utxo, utxo_args);
         }
 
@@ -63,6 +72,7 @@ impl<Context: UtxoContext> PsbtRequest<Context> {
         let unsigned_tx = builder.build()?;
         Ok(PsbtRequest {
             psbt,
+            version,
             unsigned_tx,
             _phantom: PhantomData,
         })
@@ -4,10 +4,11 @@
 
 use crate::modules::tx_builder::public_keys::PublicKeys;
 use crate::modules::tx_builder::script_parser::{StandardScript, StandardScriptParser};
+use crate::modules::tx_builder::{p2pkh_with_pubkey_data, p2wpkh_with_pubkey_data};
 use secp256k1::ThirtyTwoByteHash;
 use tw_coin_entry::error::prelude::*;
 use tw_hash::H256;
-use tw_utxo::script::Script;
+use tw_utxo::script::{Script, Witness};
 use tw_utxo::sighash::SighashType;
 use tw_utxo::transaction::standard_transaction::builder::UtxoBuilder;
 use tw_utxo::transaction::standard_transaction::TransactionInput;
@@ -33,7 +34,11 @@ impl<'a> UtxoPsbt<'a> {
         }
     }
 
-    pub fn build(self) -> SigningResult<(TransactionInput, UtxoToSign)> {
+    pub fn build(&self) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        if self.is_finalized() {
+            return self.build_finalized();
+        }
+
         if let Some(ref non_witness_utxo) = self.utxo_psbt.non_witness_utxo {
             self.build_non_witness_utxo(non_witness_utxo)
         } else if let Some(ref witness_utxo) = self.utxo_psbt.witness_utxo {
@@ -72,6 +77,38 @@ impl<'a> UtxoPsbt<'a> {
         self.build_utxo_with_script(builder, &script)
     }
 
+    /// Builds a UTXO that has been finalized already, e.g. by a Payjoin receiver.
+    /// Such UTXO is not signed, but its final `script_sig` and `witness` are kept as is.
+    pub fn build_finalized(&self) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let prevout = self.prevout()?;
+
+        let script_sig = self
+            .utxo_psbt
+            .final_script_sig
+            .as_ref()
+            .map(|script_sig| Script::from(script_sig.to_bytes()))
+            .unwrap_or_default();
+        let mut witness = Witness::default();
+        if let Some(ref final_witness) = self.utxo_psbt.final_script_witness {
+            for item in final_witness.iter() {
+                witness.push_item(Script::from(item.to_vec()));
+            }
+        }
+
+        self.prepare_builder(prevout.value)?.foreign(
+            Script::from(prevout.script_pubkey.to_bytes()),
+            script_sig,
+            witness,
+        )
+    }
+
+    /// Builds a UTXO which public key is unknown, so it cannot be signed.
+    /// The PSBT input is left as is, so it can be signed by another party later.
+    pub fn build_unowned(&self) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        // A non-finalized PSBT input leads to empty `script_sig` and `witness`.
+        self.build_finalized()
+    }
+
     fn build_utxo_with_script(
         &self,
         builder: UtxoBuilder,
@@ -80,12 +117,12 @@ impl<'a> UtxoPsbt<'a> {
         match StandardScriptParser.parse(script)? {
             StandardScript::P2PK(pubkey) => builder.p2pk(&pubkey),
             StandardScript::P2PKH(pubkey_hash) => {
-                let pubkey = self.public_keys.get_ecdsa_public_key(&pubkey_hash)?;
-                builder.p2pkh(&pubkey)
+                let pubkey_data = self.public_keys.get_public_key(&pubkey_hash)?;
+                p2pkh_with_pubkey_data(builder, pubkey_data)
             },
             StandardScript::P2WPKH(pubkey_hash) => {
-                let pubkey = self.public_keys.get_ecdsa_public_key(&pubkey_hash)?;
-                builder.p2wpkh(&pubkey)
+                let pubkey_data = self.public_keys.get_public_key(&pubkey_hash)?;
+                p2wpkh_with_pubkey_data(builder, pubkey_data)
             },
             StandardScript::P2TR(tweaked_pubkey) => {
                 if self.has_tap_scripts() {
@@ -98,6 +135,8 @@ impl<'a> UtxoPsbt<'a> {
                 SigningError::err(SigningErrorType::Error_not_supported)
                     .context("P2SH and P2WSH scriptPubkey's are not supported yet")
             },
+            StandardScript::P2A => SigningError::err(SigningErrorType::Error_not_supported)
+                .context("P2A scriptPubkey is not supported yet"),
             StandardScript::OpReturn(_) => SigningError::err(SigningErrorType::Error_invalid_utxo)
                 .context("Cannot spend an OP_RETURN output"),
         }
@@ -126,6 +165,33 @@ impl<'a> UtxoPsbt<'a> {
             .amount(amount))
     }
 
+    /// Returns the spent output from either `witness_utxo` or `non_witness_utxo`.
+    fn prevout(&self) -> SigningResult<bitcoin::TxOut> {
+        if let Some(ref witness_utxo) = self.utxo_psbt.witness_utxo {
+            return Ok(witness_utxo.clone());
+        }
+
+        let non_witness_utxo = self
+            .utxo_psbt
+            .non_witness_utxo
+            .as_ref()
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("Neither 'witness_utxo' nor 'non_witness_utxo' are set in the PSBT")?;
+        let prev_out_idx = self.utxo.previous_output.vout as usize;
+        non_witness_utxo
+            .output
+            .get(prev_out_idx)
+            .cloned()
+            .or_tw_err(SigningErrorType::Error_invalid_utxo)
+            .with_context(|| {
+                format!("'Psbt::non_witness_utxo' does not contain '{prev_out_idx}' output")
+            })
+    }
+
+    fn is_finalized(&self) -> bool {
+        self.utxo_psbt.final_script_sig.is_some() || self.utxo_psbt.final_script_witness.is_some()
+    }
+
     fn has_tap_scripts(&self) -> bool {
         !self.utxo_psbt.tap_scripts.is_empty()
     }
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::modules::compiler::BitcoinCompiler;
+use crate::modules::psbt_v2::{
+    append_psbt, deserialize_psbt, serialize_modifiable_psbt, serialize_psbt, PsbtVersion,
+    INPUTS_MODIFIABLE, OUTPUTS_MODIFIABLE,
+};
+use crate::modules::signer::BitcoinSigner;
+use crate::modules::signing_request::SigningRequestBuilder;
+use bitcoin::psbt::Psbt;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_memory::Data;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::modules::tx_planner::TxPlanner;
+
+/// Standalone PSBT operations that operate on serialized PSBT bytes.
+/// Used by the platform SDKs to exchange PSBTs with external signers (e.g. hardware wallets).
+pub struct BitcoinPsbtUtil<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> BitcoinPsbtUtil<Context> {
+    /// Creates an unsigned PSBT from the given [`Proto::TransactionBuilder`].
+    /// Each PSBT input is provided with a `witness_utxo`, so it can be signed without previous transactions.
+    /// Silent Payment outputs are not supported as their keys are derived from the input private keys.
+    pub fn create(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        tx_builder: &Proto::TransactionBuilder,
+    ) -> SigningResult<Data> {
+        let psbt = Self::create_psbt(coin, input, tx_builder)?;
+        serialize_psbt(&psbt, PsbtVersion::V0)
+    }
+
+    /// Creates an unsigned PSBTv2 from the given [`Proto::TransactionBuilder`].
+    /// Inputs and outputs are left modifiable, so other parties can add theirs via [`BitcoinPsbtUtil::append`].
+    pub fn create_v2(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        tx_builder: &Proto::TransactionBuilder,
+    ) -> SigningResult<Data> {
+        let psbt = Self::create_psbt(coin, input, tx_builder)?;
+        serialize_modifiable_psbt(&psbt, INPUTS_MODIFIABLE | OUTPUTS_MODIFIABLE)
+    }
+
+    /// Adds the inputs and outputs of the `addition` PSBTv2 to the `psbt` one.
+    /// The `psbt` must allow adding inputs and/or outputs, i.e. must not be signed yet.
+    pub fn append(psbt: &[u8], addition: &[u8]) -> SigningResult<Data> {
+        let appended = append_psbt(psbt, addition)?;
+        // Make sure the result is a valid PSBT.
+        Self::deserialize(&appended)?;
+        Ok(appended)
+    }
+
+    fn create_psbt(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        tx_builder: &Proto::TransactionBuilder,
+    ) -> SigningResult<Psbt> {
+        BitcoinCompiler::<Context>::check_no_silent_payments(tx_builder)?;
+        let request = SigningRequestBuilder::<Context>::build(coin, input, tx_builder)?;
+        let unsigned_tx = TxPlanner::plan(request)?.unsigned_tx;
+
+        let bitcoin_tx: bitcoin::Transaction =
+            bitcoin::consensus::deserialize(&unsigned_tx.transaction().encode_out())
+                .tw_err(|_| SigningErrorType::Error_internal)
+                .context("Error converting the unsigned transaction")?;
+
+        let mut psbt = Psbt::from_unsigned_tx(bitcoin_tx)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error creating PSBT from the unsigned transaction")?;
+
+        for (utxo_psbt, utxo_args) in psbt.inputs.iter_mut().zip(unsigned_tx.input_args()) {
+            let value = utxo_args
+                .amount
+                .try_into()
+                .tw_err(|_| SigningErrorType::Error_invalid_utxo_amount)
+                .context("UTXO amount cannot be negative")?;
+
+            utxo_psbt.witness_utxo = Some(bitcoin::TxOut {
+                value,
+                script_pubkey: bitcoin::ScriptBuf::from_bytes(
+                    utxo_args.prevout_script_pubkey.to_vec(),
+                ),
+            });
+        }
+
+        Ok(psbt)
+    }
+
+    /// Signs the PSBT inputs with the given private keys and sets their final `script_sig` and/or `witness`.
+    pub fn sign(coin: &dyn CoinContext, psbt: &[u8], private_keys: &[Data]) -> SigningResult<Data> {
+        let psbt_input = Proto::Psbt {
+            psbt: Cow::Borrowed(psbt),
+        };
+        let input = Proto::SigningInput {
+            private_keys: private_keys
+                .iter()
+                .map(|private| Cow::Borrowed(private.as_slice()))
+                .collect(),
+            transaction: Proto::mod_SigningInput::OneOftransaction::psbt(psbt_input.clone()),
+            ..Proto::SigningInput::default()
+        };
+
+        let output = BitcoinSigner::<Context>::sign_psbt(coin, &input, &psbt_input)?;
+        let signed_psbt = output
+            .psbt
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Expected a signed PSBT")?;
+        Ok(signed_psbt.psbt.into_owned())
+    }
+
+    /// Extracts the network-serialized transaction from the PSBT.
+    /// Every PSBT input must be finalized, i.e. have a final `script_sig` and/or `witness`.
+    pub fn finalize(psbt: &[u8]) -> SigningResult<Data> {
+        let (psbt, _) = Self::deserialize(psbt)?;
+
+        for (i, utxo_psbt) in psbt.inputs.iter().enumerate() {
+            if utxo_psbt.final_script_sig.is_none() && utxo_psbt.final_script_witness.is_none() {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context(format!("PSBT input '{i}' is not signed"));
+            }
+        }
+
+        Ok(bitcoin::consensus::serialize(&psbt.extract_tx()))
+    }
+
+    /// Combines several PSBTs of the same unsigned transaction into one.
+    /// The result has the same version as the first PSBT.
+    pub fn combine(psbts: &[Data]) -> SigningResult<Data> {
+        let mut psbts = psbts.iter();
+        let (mut combined, version) = match psbts.next() {
+            Some(first) => Self::deserialize(first)?,
+            None => {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("No PSBTs provided")
+            },
+        };
+
+        for psbt in psbts {
+            let (psbt, _) = Self::deserialize(psbt)?;
+            combined
+                .combine(psbt)
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Cannot combine PSBTs of different transactions")?;
+        }
+
+        serialize_psbt(&combined, version)
+    }
+
+    fn deserialize(psbt: &[u8]) -> SigningResult<(Psbt, PsbtVersion)> {
+        deserialize_psbt(psbt)
+    }
+}
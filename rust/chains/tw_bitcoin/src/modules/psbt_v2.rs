@@ -0,0 +1,592 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! PSBT version 2 support.
+//! https://github.com/bitcoin/bips/blob/master/bip-0370.mediawiki
+//!
+//! PSBTv2 is converted to PSBTv0 that is used internally, and back when serialized.
+//! The PSBTv2 fields that have no PSBTv0 equivalent, i.e. `PSBT_GLOBAL_TX_MODIFIABLE` and the required lock times,
+//! are kept as unknown PSBTv0 fields, so they survive the conversion.
+
+use bitcoin::psbt::Psbt;
+use tw_coin_entry::error::prelude::*;
+use tw_memory::Data;
+use tw_utxo::encode::compact_integer::CompactInteger;
+use tw_utxo::encode::stream::Stream;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_SEPARATOR: u8 = 0x00;
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u8 = 0x06;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+/// `PSBT_GLOBAL_TX_MODIFIABLE` flag allowing to add inputs.
+pub const INPUTS_MODIFIABLE: u8 = 0x01;
+/// `PSBT_GLOBAL_TX_MODIFIABLE` flag allowing to add outputs.
+pub const OUTPUTS_MODIFIABLE: u8 = 0x02;
+
+const DEFAULT_SEQUENCE: u32 = 0xffff_ffff;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PsbtVersion {
+    #[default]
+    V0,
+    V2,
+}
+
+/// Deserializes a PSBT of version 0 or 2. PSBTv2 is converted to PSBTv0.
+pub fn deserialize_psbt(bytes: &[u8]) -> SigningResult<(Psbt, PsbtVersion)> {
+    let raw = RawPsbt::deserialize(bytes)?;
+    let version = raw.version()?;
+
+    let v0_bytes = match version {
+        PsbtVersion::V0 => bytes.to_vec(),
+        PsbtVersion::V2 => raw.into_v0()?.serialize(),
+    };
+    let psbt = Psbt::deserialize(&v0_bytes)
+        .tw_err(|_| SigningErrorType::Error_input_parse)
+        .context("Error deserializing PSBT")?;
+    Ok((psbt, version))
+}
+
+/// Serializes the PSBT as the given `version`.
+pub fn serialize_psbt(psbt: &Psbt, version: PsbtVersion) -> SigningResult<Data> {
+    Ok(RawPsbt::from_psbt(psbt, version)?.serialize())
+}
+
+/// Serializes the PSBT with signed inputs as the given `version`.
+/// Inputs and outputs cannot be added to a PSBTv2 after it's signed, as that would invalidate the signatures.
+pub fn serialize_signed_psbt(psbt: &Psbt, version: PsbtVersion) -> SigningResult<Data> {
+    let mut raw = RawPsbt::from_psbt(psbt, version)?;
+    raw.global.remove(PSBT_GLOBAL_TX_MODIFIABLE);
+    Ok(raw.serialize())
+}
+
+/// Serializes an unsigned PSBT as version 2 with the given `PSBT_GLOBAL_TX_MODIFIABLE` flags.
+pub fn serialize_modifiable_psbt(psbt: &Psbt, modifiable: u8) -> SigningResult<Data> {
+    let mut raw = RawPsbt::from_psbt(psbt, PsbtVersion::V2)?;
+    raw.global
+        .insert(PSBT_GLOBAL_TX_MODIFIABLE, vec![modifiable]);
+    Ok(raw.serialize())
+}
+
+/// Adds the inputs and outputs of the `addition` PSBTv2 to the `psbt` one.
+/// The `psbt` must allow adding inputs and/or outputs via `PSBT_GLOBAL_TX_MODIFIABLE`.
+pub fn append_psbt(psbt: &[u8], addition: &[u8]) -> SigningResult<Data> {
+    let mut raw = RawPsbt::deserialize(psbt)?;
+    let addition = RawPsbt::deserialize(addition)?;
+    if raw.version()? != PsbtVersion::V2 || addition.version()? != PsbtVersion::V2 {
+        return SigningError::err(SigningErrorType::Error_not_supported)
+            .context("Inputs and outputs can be added to PSBTv2 only");
+    }
+    if raw.global.get(PSBT_GLOBAL_TX_VERSION) != addition.global.get(PSBT_GLOBAL_TX_VERSION) {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("PSBTs have different transaction versions");
+    }
+
+    let modifiable = match raw.global.get(PSBT_GLOBAL_TX_MODIFIABLE) {
+        Some([flags]) => *flags,
+        Some(_) => {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Invalid PSBT_GLOBAL_TX_MODIFIABLE")
+        },
+        None => 0,
+    };
+    if !addition.inputs.is_empty() && modifiable & INPUTS_MODIFIABLE == 0 {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("PSBT inputs are not modifiable");
+    }
+    if !addition.outputs.is_empty() && modifiable & OUTPUTS_MODIFIABLE == 0 {
+        return SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("PSBT outputs are not modifiable");
+    }
+
+    raw.inputs.extend(addition.inputs);
+    raw.outputs.extend(addition.outputs);
+    raw.global
+        .insert(PSBT_GLOBAL_INPUT_COUNT, compact_size(raw.inputs.len()));
+    raw.global
+        .insert(PSBT_GLOBAL_OUTPUT_COUNT, compact_size(raw.outputs.len()));
+    // Check the inputs still agree on the lock time type.
+    raw.lock_time()
+        .context("Added inputs require an incompatible lock time")?;
+
+    Ok(raw.serialize())
+}
+
+struct KeyValue {
+    /// The key type followed by the key data. Never empty.
+    key: Data,
+    value: Data,
+}
+
+#[derive(Default)]
+struct RawMap(Vec<KeyValue>);
+
+impl RawMap {
+    /// Returns the value of the key consisting of the key type only.
+    fn get(&self, key_type: u8) -> Option<&[u8]> {
+        self.0
+            .iter()
+            .find(|pair| pair.key == [key_type])
+            .map(|pair| pair.value.as_slice())
+    }
+
+    fn get_u32(&self, key_type: u8) -> SigningResult<Option<u32>> {
+        let Some(value) = self.get(key_type) else {
+            return Ok(None);
+        };
+        let bytes = <[u8; 4]>::try_from(value)
+            .tw_err(|_| SigningErrorType::Error_input_parse)
+            .with_context(|| format!("Expected 4 bytes value of the PSBT key type '{key_type}'"))?;
+        Ok(Some(u32::from_le_bytes(bytes)))
+    }
+
+    fn require(&self, key_type: u8) -> SigningResult<&[u8]> {
+        self.get(key_type)
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .with_context(|| format!("Missing required PSBTv2 key type '{key_type}'"))
+    }
+
+    /// Removes all the keys of the given type.
+    fn remove(&mut self, key_type: u8) {
+        self.0.retain(|pair| pair.key[0] != key_type);
+    }
+
+    /// Replaces the value of the key consisting of the key type only.
+    /// The new key is appended to the end of the map.
+    fn insert(&mut self, key_type: u8, value: Data) {
+        self.remove(key_type);
+        self.0.push(KeyValue {
+            key: vec![key_type],
+            value,
+        });
+    }
+
+    fn deserialize(reader: &mut Reader) -> SigningResult<RawMap> {
+        let mut pairs = Vec::default();
+        loop {
+            let key_len = reader.read_compact_size()?;
+            if key_len == 0 {
+                return Ok(RawMap(pairs));
+            }
+            let key = reader.read_slice(key_len)?.to_vec();
+            let value_len = reader.read_compact_size()?;
+            let value = reader.read_slice(value_len)?.to_vec();
+
+            if pairs.iter().any(|pair: &KeyValue| pair.key == key) {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context("Duplicate PSBT key");
+            }
+            pairs.push(KeyValue { key, value });
+        }
+    }
+
+    fn serialize(&self, stream: &mut Stream) {
+        for pair in self.0.iter() {
+            stream
+                .append(&CompactInteger::from(pair.key.len()))
+                .append_raw_slice(&pair.key)
+                .append(&CompactInteger::from(pair.value.len()))
+                .append_raw_slice(&pair.value);
+        }
+        stream.append(&PSBT_SEPARATOR);
+    }
+}
+
+/// PSBT as a list of key-value maps.
+struct RawPsbt {
+    global: RawMap,
+    inputs: Vec<RawMap>,
+    outputs: Vec<RawMap>,
+}
+
+impl RawPsbt {
+    fn deserialize(bytes: &[u8]) -> SigningResult<RawPsbt> {
+        let mut reader = Reader { bytes };
+        if reader.read_slice(PSBT_MAGIC.len())? != PSBT_MAGIC {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Invalid PSBT magic bytes");
+        }
+
+        let global = RawMap::deserialize(&mut reader)?;
+        let (inputs_count, outputs_count) = match global.get(PSBT_GLOBAL_UNSIGNED_TX) {
+            Some(unsigned_tx) => {
+                let unsigned_tx: bitcoin::Transaction =
+                    bitcoin::consensus::deserialize(unsigned_tx)
+                        .tw_err(|_| SigningErrorType::Error_input_parse)
+                        .context("Invalid PSBT unsigned transaction")?;
+                (unsigned_tx.input.len(), unsigned_tx.output.len())
+            },
+            None => (
+                read_count(global.require(PSBT_GLOBAL_INPUT_COUNT)?)?,
+                read_count(global.require(PSBT_GLOBAL_OUTPUT_COUNT)?)?,
+            ),
+        };
+
+        let inputs = (0..inputs_count)
+            .map(|_| RawMap::deserialize(&mut reader))
+            .collect::<SigningResult<Vec<_>>>()?;
+        let outputs = (0..outputs_count)
+            .map(|_| RawMap::deserialize(&mut reader))
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        if !reader.bytes.is_empty() {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Unexpected bytes after the PSBT outputs");
+        }
+        Ok(RawPsbt {
+            global,
+            inputs,
+            outputs,
+        })
+    }
+
+    fn serialize(&self) -> Data {
+        let mut stream = Stream::default();
+        stream.append_raw_slice(PSBT_MAGIC);
+        self.global.serialize(&mut stream);
+        for map in self.inputs.iter().chain(self.outputs.iter()) {
+            map.serialize(&mut stream);
+        }
+        stream.out()
+    }
+
+    fn version(&self) -> SigningResult<PsbtVersion> {
+        let has_unsigned_tx = self.global.get(PSBT_GLOBAL_UNSIGNED_TX).is_some();
+        match self
+            .global
+            .get_u32(PSBT_GLOBAL_VERSION)?
+            .unwrap_or_default()
+        {
+            0 if has_unsigned_tx => Ok(PsbtVersion::V0),
+            2 if !has_unsigned_tx => Ok(PsbtVersion::V2),
+            0 | 2 => SigningError::err(SigningErrorType::Error_input_parse)
+                .context("PSBT_GLOBAL_UNSIGNED_TX is required in PSBTv0 and forbidden in PSBTv2"),
+            version => SigningError::err(SigningErrorType::Error_not_supported)
+                .context(format!("PSBT version '{version}' is not supported")),
+        }
+    }
+
+    /// Determines the lock time by the inputs required lock times.
+    /// Height-based lock time is preferred if the inputs allow both.
+    fn lock_time(&self) -> SigningResult<u32> {
+        let mut has_required = false;
+        let (mut all_height, mut all_time) = (true, true);
+        let (mut max_height, mut max_time) = (0, 0);
+
+        for input in self.inputs.iter() {
+            let height = input.get_u32(PSBT_IN_REQUIRED_HEIGHT_LOCKTIME)?;
+            let time = input.get_u32(PSBT_IN_REQUIRED_TIME_LOCKTIME)?;
+            if height.is_none() && time.is_none() {
+                continue;
+            }
+            has_required = true;
+
+            match height {
+                Some(height) => max_height = max_height.max(height),
+                None => all_height = false,
+            }
+            match time {
+                Some(time) => max_time = max_time.max(time),
+                None => all_time = false,
+            }
+        }
+
+        if !has_required {
+            let fallback = self.global.get_u32(PSBT_GLOBAL_FALLBACK_LOCKTIME)?;
+            return Ok(fallback.unwrap_or_default());
+        }
+        if all_height {
+            return Ok(max_height);
+        }
+        if all_time {
+            return Ok(max_time);
+        }
+        SigningError::err(SigningErrorType::Error_invalid_params)
+            .context("PSBT inputs require both height and time based lock times")
+    }
+
+    /// Converts PSBTv2 to PSBTv0 by constructing the unsigned transaction from the inputs and outputs fields.
+    fn into_v0(mut self) -> SigningResult<RawPsbt> {
+        let tx_version = self
+            .global
+            .get_u32(PSBT_GLOBAL_TX_VERSION)?
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .context("Missing PSBT_GLOBAL_TX_VERSION")?;
+        let lock_time = self.lock_time()?;
+
+        let mut unsigned_tx = Stream::default();
+        unsigned_tx
+            .append(&tx_version)
+            .append(&CompactInteger::from(self.inputs.len()));
+        for input in self.inputs.iter_mut() {
+            let previous_txid = input.require(PSBT_IN_PREVIOUS_TXID)?;
+            if previous_txid.len() != 32 {
+                return SigningError::err(SigningErrorType::Error_input_parse)
+                    .context("Invalid PSBT_IN_PREVIOUS_TXID");
+            }
+            let output_index = input
+                .get_u32(PSBT_IN_OUTPUT_INDEX)?
+                .or_tw_err(SigningErrorType::Error_input_parse)
+                .context("Missing PSBT_IN_OUTPUT_INDEX")?;
+            let sequence = input.get_u32(PSBT_IN_SEQUENCE)?.unwrap_or(DEFAULT_SEQUENCE);
+
+            unsigned_tx
+                .append_raw_slice(previous_txid)
+                .append(&output_index)
+                // Empty script_sig.
+                .append(&CompactInteger::from(0_usize))
+                .append(&sequence);
+
+            for key_type in [
+                PSBT_IN_PREVIOUS_TXID,
+                PSBT_IN_OUTPUT_INDEX,
+                PSBT_IN_SEQUENCE,
+            ] {
+                input.remove(key_type);
+            }
+        }
+
+        unsigned_tx.append(&CompactInteger::from(self.outputs.len()));
+        for output in self.outputs.iter_mut() {
+            let amount = <[u8; 8]>::try_from(output.require(PSBT_OUT_AMOUNT)?)
+                .tw_err(|_| SigningErrorType::Error_input_parse)
+                .context("Invalid PSBT_OUT_AMOUNT")?;
+            let script = output.require(PSBT_OUT_SCRIPT)?;
+
+            unsigned_tx
+                .append_raw_slice(&amount)
+                .append(&CompactInteger::from(script.len()))
+                .append_raw_slice(script);
+
+            output.remove(PSBT_OUT_AMOUNT);
+            output.remove(PSBT_OUT_SCRIPT);
+        }
+        unsigned_tx.append(&lock_time);
+
+        for key_type in [
+            PSBT_GLOBAL_TX_VERSION,
+            PSBT_GLOBAL_FALLBACK_LOCKTIME,
+            PSBT_GLOBAL_INPUT_COUNT,
+            PSBT_GLOBAL_OUTPUT_COUNT,
+            PSBT_GLOBAL_VERSION,
+        ] {
+            self.global.remove(key_type);
+        }
+        self.global
+            .insert(PSBT_GLOBAL_UNSIGNED_TX, unsigned_tx.out());
+        Ok(self)
+    }
+
+    /// Converts PSBTv0 to the given version.
+    fn from_psbt(psbt: &Psbt, version: PsbtVersion) -> SigningResult<RawPsbt> {
+        let mut raw = RawPsbt::deserialize(&psbt.serialize())?;
+
+        if version == PsbtVersion::V0 {
+            // PSBTv2 fields must be excluded from PSBTv0.
+            raw.global.remove(PSBT_GLOBAL_TX_MODIFIABLE);
+            for input in raw.inputs.iter_mut() {
+                input.remove(PSBT_IN_REQUIRED_TIME_LOCKTIME);
+                input.remove(PSBT_IN_REQUIRED_HEIGHT_LOCKTIME);
+            }
+            return Ok(raw);
+        }
+
+        let tx = &psbt.unsigned_tx;
+        raw.global.remove(PSBT_GLOBAL_UNSIGNED_TX);
+        raw.global.insert(
+            PSBT_GLOBAL_TX_VERSION,
+            (tx.version as u32).to_le_bytes().to_vec(),
+        );
+        raw.global.insert(
+            PSBT_GLOBAL_FALLBACK_LOCKTIME,
+            tx.lock_time.to_consensus_u32().to_le_bytes().to_vec(),
+        );
+        raw.global
+            .insert(PSBT_GLOBAL_INPUT_COUNT, compact_size(tx.input.len()));
+        raw.global
+            .insert(PSBT_GLOBAL_OUTPUT_COUNT, compact_size(tx.output.len()));
+        raw.global
+            .insert(PSBT_GLOBAL_VERSION, 2_u32.to_le_bytes().to_vec());
+
+        for (input, txin) in raw.inputs.iter_mut().zip(tx.input.iter()) {
+            // Serialized as 32 bytes txid followed by 4 bytes output index.
+            let out_point = bitcoin::consensus::serialize(&txin.previous_output);
+            let (previous_txid, output_index) = out_point.split_at(32);
+            input.insert(PSBT_IN_PREVIOUS_TXID, previous_txid.to_vec());
+            input.insert(PSBT_IN_OUTPUT_INDEX, output_index.to_vec());
+            input.insert(
+                PSBT_IN_SEQUENCE,
+                txin.sequence.to_consensus_u32().to_le_bytes().to_vec(),
+            );
+        }
+        for (output, txout) in raw.outputs.iter_mut().zip(tx.output.iter()) {
+            output.insert(PSBT_OUT_AMOUNT, txout.value.to_le_bytes().to_vec());
+            output.insert(PSBT_OUT_SCRIPT, txout.script_pubkey.to_bytes());
+        }
+        Ok(raw)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_slice(&mut self, len: u64) -> SigningResult<&'a [u8]> {
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= self.bytes.len())
+            .or_tw_err(SigningErrorType::Error_input_parse)
+            .context("Unexpected end of PSBT")?;
+        let (slice, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(slice)
+    }
+
+    fn read_compact_size(&mut self) -> SigningResult<u64> {
+        let read_le = |reader: &mut Self, len: u64| -> SigningResult<u64> {
+            let mut bytes = [0; 8];
+            bytes[..len as usize].copy_from_slice(reader.read_slice(len)?);
+            Ok(u64::from_le_bytes(bytes))
+        };
+
+        match self.read_slice(1)?[0] {
+            0xfd => read_le(self, 2),
+            0xfe => read_le(self, 4),
+            0xff => read_le(self, 8),
+            value => Ok(u64::from(value)),
+        }
+    }
+}
+
+fn read_count(value: &[u8]) -> SigningResult<usize> {
+    let mut reader = Reader { bytes: value };
+    let count = reader.read_compact_size()?;
+    usize::try_from(count)
+        .ok()
+        .filter(|_| reader.bytes.is_empty())
+        .or_tw_err(SigningErrorType::Error_input_parse)
+        .context("Invalid PSBT inputs or outputs count")
+}
+
+fn compact_size(value: usize) -> Data {
+    let mut stream = Stream::default();
+    stream.append(&CompactInteger::from(value));
+    stream.out()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::{DecodeHex, ToHex};
+
+    /// PSBTv0 spending one P2WPKH input to three outputs.
+    const PSBT_V0: &str = "70736274ff0100bc0200000001147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c60000000000ffffffff0360ea000000000000160014f22a703617035ef7f490743d50f26ae08c30d0a70000000000000000426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a35303e12000000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d000000000001011f6603010000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d00000000";
+    /// [`PSBT_V0`] as PSBTv2.
+    const PSBT_V2: &str = "70736274ff0102040200000001030400000000010401010105010301fb04020000000001011f6603010000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d010e20147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c6010f0400000000011004ffffffff0001030860ea0000000000000104160014f22a703617035ef7f490743d50f26ae08c30d0a70001030800000000000000000104426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a3530000103083e120000000000000104160014b139199ec796f36fc42e637f42da8e3e6720aa9d00";
+
+    #[test]
+    fn test_psbt_v2_conversion() {
+        let (psbt_v0, version) = deserialize_psbt(&PSBT_V0.decode_hex().unwrap()).unwrap();
+        assert_eq!(version, PsbtVersion::V0);
+        assert_eq!(
+            serialize_psbt(&psbt_v0, PsbtVersion::V2).unwrap().to_hex(),
+            PSBT_V2
+        );
+
+        let (psbt_v2, version) = deserialize_psbt(&PSBT_V2.decode_hex().unwrap()).unwrap();
+        assert_eq!(version, PsbtVersion::V2);
+        assert_eq!(psbt_v2, psbt_v0);
+        assert_eq!(
+            serialize_psbt(&psbt_v2, PsbtVersion::V0).unwrap().to_hex(),
+            PSBT_V0
+        );
+    }
+
+    #[test]
+    fn test_psbt_v2_required_lock_time() {
+        let lock_time = |height: Option<u32>, time: Option<u32>| {
+            let mut raw = RawPsbt::deserialize(&PSBT_V2.decode_hex().unwrap()).unwrap();
+            raw.global.insert(
+                PSBT_GLOBAL_FALLBACK_LOCKTIME,
+                100_u32.to_le_bytes().to_vec(),
+            );
+            if let Some(height) = height {
+                raw.inputs[0].insert(
+                    PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+                    height.to_le_bytes().to_vec(),
+                );
+            }
+            if let Some(time) = time {
+                raw.inputs[0].insert(PSBT_IN_REQUIRED_TIME_LOCKTIME, time.to_le_bytes().to_vec());
+            }
+
+            let (psbt, _) = deserialize_psbt(&raw.serialize()).unwrap();
+            psbt.unsigned_tx.lock_time.to_consensus_u32()
+        };
+
+        assert_eq!(lock_time(None, None), 100);
+        assert_eq!(lock_time(Some(800_000), None), 800_000);
+        assert_eq!(lock_time(None, Some(1_700_000_000)), 1_700_000_000);
+        // Height is preferred if the input allows both.
+        assert_eq!(lock_time(Some(800_000), Some(1_700_000_000)), 800_000);
+    }
+
+    #[test]
+    fn test_psbt_v2_append() {
+        let (psbt, _) = deserialize_psbt(&PSBT_V2.decode_hex().unwrap()).unwrap();
+        let modifiable =
+            serialize_modifiable_psbt(&psbt, INPUTS_MODIFIABLE | OUTPUTS_MODIFIABLE).unwrap();
+        let addition = PSBT_V2.decode_hex().unwrap();
+
+        let appended = append_psbt(&modifiable, &addition).unwrap();
+        let (appended, version) = deserialize_psbt(&appended).unwrap();
+        assert_eq!(version, PsbtVersion::V2);
+        assert_eq!(appended.unsigned_tx.input.len(), 2);
+        assert_eq!(appended.unsigned_tx.output.len(), 6);
+        assert_eq!(appended.inputs.len(), 2);
+
+        // Only outputs can be added.
+        let outputs_only = serialize_modifiable_psbt(&psbt, OUTPUTS_MODIFIABLE).unwrap();
+        append_psbt(&outputs_only, &addition).unwrap_err();
+        // PSBTv0 cannot be modified.
+        append_psbt(&PSBT_V0.decode_hex().unwrap(), &addition).unwrap_err();
+    }
+
+    #[test]
+    fn test_psbt_v2_invalid() {
+        // PSBTv2 must not contain the unsigned transaction.
+        let mut raw = RawPsbt::deserialize(&PSBT_V0.decode_hex().unwrap()).unwrap();
+        raw.global
+            .insert(PSBT_GLOBAL_VERSION, 2_u32.to_le_bytes().to_vec());
+        deserialize_psbt(&raw.serialize()).unwrap_err();
+
+        // PSBTv2 input must have the previous txid.
+        let mut raw = RawPsbt::deserialize(&PSBT_V2.decode_hex().unwrap()).unwrap();
+        raw.inputs[0].remove(PSBT_IN_PREVIOUS_TXID);
+        deserialize_psbt(&raw.serialize()).unwrap_err();
+
+        // Unsupported version.
+        let mut raw = RawPsbt::deserialize(&PSBT_V2.decode_hex().unwrap()).unwrap();
+        raw.global
+            .insert(PSBT_GLOBAL_VERSION, 3_u32.to_le_bytes().to_vec());
+        deserialize_psbt(&raw.serialize()).unwrap_err();
+    }
+}
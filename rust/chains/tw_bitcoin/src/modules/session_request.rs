@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::modules::signing_request::SigningRequestBuilder;
+use crate::modules::tx_builder::utxo_protobuf::parse_out_point;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_memory::Data;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::modules::tx_planner::RequestType;
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+use Proto::mod_Output::OneOfto_recipient as RecipientType;
+use Proto::mod_SigningInput::OneOftransaction as TransactionType;
+
+/// The transaction planned by the `preimage_hashes` method and restored from [`Proto::SigningSession`].
+pub struct SessionRequest<'a, Context: UtxoContext> {
+    /// Signing input with the session transaction builder.
+    pub signing_input: Proto::SigningInput<'a>,
+    pub unsigned_tx: UnsignedTransaction<Transaction>,
+    _phantom: PhantomData<Context>,
+}
+
+impl<'a, Context: UtxoContext> SessionRequest<'a, Context> {
+    /// Captures the `unsigned_tx` planned from the `tx_builder`.
+    /// Returns binary encoded [`Proto::SigningSession`].
+    pub fn capture(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        tx_builder: &Proto::TransactionBuilder,
+        unsigned_tx: &UnsignedTransaction<Transaction>,
+    ) -> SigningResult<Data> {
+        let chain_info = SigningRequestBuilder::<Context>::chain_info(coin, &input.chain_info)?;
+
+        // Take the selected inputs in the transaction order.
+        let mut inputs = Vec::with_capacity(unsigned_tx.inputs().len());
+        for txin in unsigned_tx.transaction().inputs() {
+            let mut utxo_proto = None;
+            for candidate in tx_builder.inputs.iter().filter(|utxo| !utxo.frozen) {
+                if parse_out_point(&candidate.out_point)? == txin.previous_output {
+                    utxo_proto = Some(candidate.clone());
+                    break;
+                }
+            }
+            let utxo_proto = utxo_proto
+                .or_tw_err(SigningErrorType::Error_internal)
+                .context("The selected UTXO is not found in the transaction builder")?;
+            inputs.push(utxo_proto);
+        }
+
+        // The outputs are final, i.e. the change and the subtracted fee are already applied.
+        let outputs = unsigned_tx
+            .transaction()
+            .outputs()
+            .iter()
+            .map(|output| Proto::Output {
+                value: output.value,
+                to_recipient: RecipientType::custom_script_pubkey(Cow::from(
+                    output.script_pubkey.to_vec(),
+                )),
+                ..Proto::Output::default()
+            })
+            .collect();
+
+        let session = Proto::SigningSession {
+            transaction: Some(Proto::TransactionBuilder {
+                version: tx_builder.version,
+                inputs,
+                outputs,
+                input_selector: Proto::InputSelector::UseAll,
+                dust_policy: tx_builder.dust_policy.clone(),
+                lock_time: tx_builder.lock_time,
+                typed_lock_time: tx_builder.typed_lock_time.clone(),
+                ..Proto::TransactionBuilder::default()
+            }),
+            chain_info: Some(Proto::ChainInfo {
+                p2pkh_prefix: chain_info.p2pkh_prefix as u32,
+                p2sh_prefix: chain_info.p2sh_prefix as u32,
+                hrp: Cow::from(chain_info.hrp.unwrap_or_default()),
+            }),
+            public_keys: input.public_keys.clone(),
+        };
+        tw_proto::serialize(&session)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error serializing the signing session")
+    }
+
+    /// Restores the transaction from the binary encoded [`Proto::SigningSession`].
+    /// The UTXOs are not selected again, and the outputs are taken as is.
+    pub fn build(coin: &dyn CoinContext, session: &'a [u8]) -> SigningResult<Self> {
+        let session: Proto::SigningSession = tw_proto::deserialize(session)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid signing session")?;
+        let tx_builder = session
+            .transaction
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("No transaction in the signing session")?;
+
+        let signing_input = Proto::SigningInput {
+            public_keys: session.public_keys,
+            chain_info: session.chain_info,
+            transaction: TransactionType::builder(tx_builder.clone()),
+            ..Proto::SigningInput::default()
+        };
+        let request = SigningRequestBuilder::<Context>::build(coin, &signing_input, &tx_builder)?;
+        let unsigned_tx = match request.ty {
+            RequestType::SendMax { unsigned_tx } | RequestType::SendExact { unsigned_tx, .. } => {
+                unsigned_tx
+            },
+        };
+
+        Ok(SessionRequest {
+            signing_input,
+            unsigned_tx,
+            _phantom: PhantomData,
+        })
+    }
+}
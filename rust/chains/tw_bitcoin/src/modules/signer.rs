@@ -2,15 +2,22 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::modules::explain::BitcoinExplain;
+use crate::modules::labels::{label_records, to_json_lines};
 use crate::modules::protobuf_builder::ProtobufBuilder;
 use crate::modules::psbt::update_psbt_signed;
 use crate::modules::psbt_request::PsbtRequest;
+use crate::modules::psbt_v2::serialize_signed_psbt;
+use crate::modules::signing_policy::{policy_rejection, PolicyRules, SigningPolicy};
 use crate::modules::signing_request::SigningRequestBuilder;
+use crate::modules::silent_payments::SilentPayments;
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::error::prelude::*;
 use tw_coin_entry::signing_output_error;
+use tw_hash::H256;
+use tw_keypair::schnorr::AuxRand;
 use tw_keypair::{ecdsa, schnorr};
 use tw_proto::BitcoinV2::Proto;
 use tw_utxo::context::UtxoContext;
@@ -21,6 +28,7 @@ use tw_utxo::modules::utxo_selector::SelectResult;
 use tw_utxo::signing_mode::SigningMethod;
 use tw_utxo::transaction::standard_transaction::Transaction;
 use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Txid;
 use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
 
 pub struct BitcoinSigner<Context: UtxoContext> {
@@ -36,18 +44,65 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
             .unwrap_or_else(|e| signing_output_error!(Proto::SigningOutput, e))
     }
 
+    /// Signs the transaction if it complies with the given `policy`.
+    /// [`Proto::SigningInput::policy`] is checked as well if set.
+    pub fn sign_with_policy(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput<'_>,
+        policy: &dyn SigningPolicy,
+    ) -> Proto::SigningOutput<'static> {
+        Self::sign_with_policy_impl(coin, input, policy)
+            .unwrap_or_else(|e| signing_output_error!(Proto::SigningOutput, e))
+    }
+
+    pub fn sign_with_policy_impl(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        policy: &dyn SigningPolicy,
+    ) -> SigningResult<Proto::SigningOutput<'static>> {
+        let violations = Self::check_policy(coin, input, policy)?;
+        if !violations.is_empty() {
+            return Ok(policy_rejection(violations));
+        }
+        Self::sign_impl(coin, input)
+    }
+
     pub fn sign_impl(
         coin: &dyn CoinContext,
         input: &Proto::SigningInput,
     ) -> SigningResult<Proto::SigningOutput<'static>> {
         use Proto::mod_SigningInput::OneOftransaction as TransactionType;
 
-        match input.transaction {
-            TransactionType::builder(ref tx) => Self::sign_with_tx_builder(coin, input, tx),
-            TransactionType::psbt(ref psbt) => Self::sign_psbt(coin, input, psbt),
-            TransactionType::None => SigningError::err(SigningErrorType::Error_invalid_params)
-                .context("Either `TransactionBuilder` or `Psbt` should be set"),
+        if let Some(ref policy) = input.policy {
+            let chain_info = SigningRequestBuilder::<Context>::chain_info(coin, &input.chain_info)?;
+            let rules = PolicyRules::from_proto::<Context>(&chain_info, policy)
+                .context("Invalid signing policy")?;
+            let violations = Self::check_policy(coin, input, &rules)?;
+            if !violations.is_empty() {
+                return Ok(policy_rejection(violations));
+            }
+        }
+
+        let mut output = match input.transaction {
+            TransactionType::builder(ref tx) => Self::sign_with_tx_builder(coin, input, tx)?,
+            TransactionType::psbt(ref psbt) => Self::sign_psbt(coin, input, psbt)?,
+            TransactionType::session(_) => {
+                return SigningError::err(SigningErrorType::Error_not_supported).context(
+                    "Signing session can only be passed to `preimage_hashes` and `compile`",
+                )
+            },
+            TransactionType::None => {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("Either `TransactionBuilder` or `Psbt` should be set")
+            },
+        };
+
+        if let Some(ref labels) = input.labels {
+            let labels = Self::export_labels(coin, input, &output, labels)
+                .context("Error exporting BIP-329 labels")?;
+            output.labels = Cow::from(labels);
         }
+        Ok(output)
     }
 
     pub fn sign_with_tx_builder(
@@ -56,16 +111,33 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
         tx_builder_input: &Proto::TransactionBuilder,
     ) -> SigningResult<Proto::SigningOutput<'static>> {
         let request = SigningRequestBuilder::<Context>::build(coin, input, tx_builder_input)?;
-        let SelectResult { unsigned_tx, plan } = TxPlanner::plan(request)?;
+        let SelectResult {
+            mut unsigned_tx,
+            plan,
+        } = TxPlanner::plan(request)?;
+
+        let chain_info = SigningRequestBuilder::<Context>::chain_info(coin, &input.chain_info)?;
+        SilentPayments::derive_outputs(
+            &chain_info,
+            &input.private_keys,
+            tx_builder_input,
+            &mut unsigned_tx,
+        )?;
 
         let keys_manager = Self::keys_manager_for_tx(
             &input.private_keys,
             &unsigned_tx,
-            input.dangerous_use_fixed_schnorr_rng,
+            Self::schnorr_aux_rand(input)?,
+            Self::ecdsa_extra_entropy(input)?,
         )?;
 
-        let signed_tx =
-            TxSigner::sign_tx(unsigned_tx, &keys_manager).context("Error signing transaction")?;
+        let (signed_tx, unsigned_inputs) = Self::sign_tx(input, unsigned_tx, &keys_manager)
+            .context("Error signing transaction")?;
+        let warnings = SigningRequestBuilder::<Context>::check_relay_policy(
+            input,
+            &signed_tx,
+            signed_tx.vsize(),
+        )?;
 
         Ok(Proto::SigningOutput {
             transaction: Some(ProtobufBuilder::tx_to_proto(&signed_tx)),
@@ -76,6 +148,8 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
             // `fee` should haven't been changed since it's a difference between `sum(inputs)` and `sum(outputs)`.
             fee: plan.fee_estimate,
             weight: signed_tx.weight() as u64,
+            unsigned_inputs,
+            warnings,
             ..Proto::SigningOutput::default()
         })
     }
@@ -87,6 +161,7 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
     ) -> SigningResult<Proto::SigningOutput<'static>> {
         let PsbtRequest {
             mut psbt,
+            version,
             unsigned_tx,
             ..
         } = PsbtRequest::<Context>::build(input, psbt_input)?;
@@ -96,13 +171,21 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
         let keys_manager = Self::keys_manager_for_tx(
             &input.private_keys,
             &unsigned_tx,
-            input.dangerous_use_fixed_schnorr_rng,
+            Self::schnorr_aux_rand(input)?,
+            Self::ecdsa_extra_entropy(input)?,
         )?;
 
-        let signed_tx =
-            TxSigner::sign_tx(unsigned_tx, &keys_manager).context("Error signing transaction")?;
+        let (signed_tx, unsigned_inputs) = Self::sign_tx(input, unsigned_tx, &keys_manager)
+            .context("Error signing transaction")?;
+        let warnings = SigningRequestBuilder::<Context>::check_relay_policy(
+            input,
+            &signed_tx,
+            signed_tx.vsize(),
+        )?;
 
         update_psbt_signed(&mut psbt, &signed_tx);
+        // Serialize the signed PSBT as the same version it was provided.
+        let signed_psbt = serialize_signed_psbt(&psbt, version)?;
 
         Ok(Proto::SigningOutput {
             transaction: Some(ProtobufBuilder::tx_to_proto(&signed_tx)),
@@ -112,17 +195,62 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
             vsize: signed_tx.vsize() as u64,
             fee,
             weight: signed_tx.weight() as u64,
+            unsigned_inputs,
+            warnings,
             psbt: Some(Proto::Psbt {
-                psbt: Cow::from(psbt.serialize()),
+                psbt: Cow::from(signed_psbt),
             }),
             ..Proto::SigningOutput::default()
         })
     }
 
+    /// Checks the transaction that would be signed against the `policy`.
+    /// Returns the violated rules.
+    fn check_policy(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        policy: &dyn SigningPolicy,
+    ) -> SigningResult<Vec<Proto::PolicyViolation<'static>>> {
+        let report = BitcoinExplain::<Context>::explain(coin, input)
+            .context("Error explaining the transaction to check the signing policy")?;
+        Ok(policy.check(&report))
+    }
+
+    /// Exports BIP-329 labels of the signed transaction as JSON Lines.
+    fn export_labels(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        output: &Proto::SigningOutput,
+        request: &Proto::LabelsRequest,
+    ) -> SigningResult<String> {
+        let report = BitcoinExplain::<Context>::explain_signed(coin, input, output)?;
+        let txid = Txid::from_rpc_bytes(&output.txid)?;
+        to_json_lines(&label_records(&txid, &report, request))
+    }
+
+    /// Signs the transaction, or only the inputs which private keys are provided
+    /// if [`Proto::SigningInput::allow_partial_signing`] is set.
+    /// Returns the signed transaction and the indexes of the inputs left unsigned.
+    fn sign_tx(
+        input: &Proto::SigningInput,
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        keys_manager: &KeysManager,
+    ) -> SigningResult<(Transaction, Vec<u32>)> {
+        if !input.allow_partial_signing {
+            let signed_tx = TxSigner::sign_tx(unsigned_tx, keys_manager)?;
+            return Ok((signed_tx, Vec::default()));
+        }
+
+        let (signed_tx, unsigned_inputs) = TxSigner::sign_tx_partially(unsigned_tx, keys_manager)?;
+        let unsigned_inputs = unsigned_inputs.into_iter().map(|i| i as u32).collect();
+        Ok((signed_tx, unsigned_inputs))
+    }
+
     fn keys_manager_for_tx<P>(
         private_keys: &[P],
         unsigned_tx: &UnsignedTransaction<Transaction>,
-        dangerous_use_fixed_schnorr_rng: bool,
+        schnorr_aux_rand: AuxRand,
+        ecdsa_extra_entropy: Option<H256>,
     ) -> SigningResult<KeysManager>
     where
         P: AsRef<[u8]>,
@@ -139,6 +267,10 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
             let ecdsa_private = ecdsa::secp256k1::PrivateKey::try_from(private.as_ref())
                 .into_tw()
                 .context("Invalid ecdsa secp256k1 private key")?;
+            let ecdsa_private = match ecdsa_extra_entropy {
+                Some(extra_entropy) => ecdsa_private.with_extra_entropy(extra_entropy),
+                None => ecdsa_private,
+            };
             keys_manager.add_ecdsa_private(ecdsa_private);
 
             if has_taproot {
@@ -146,14 +278,39 @@ impl<Context: UtxoContext> BitcoinSigner<Context> {
                     .into_tw()
                     .context("Invalid schnorr private key")?;
 
-                if dangerous_use_fixed_schnorr_rng {
-                    keys_manager.add_schnorr_private(schnorr_private.no_aux_rand());
-                } else {
-                    keys_manager.add_schnorr_private(schnorr_private);
-                }
+                keys_manager.add_schnorr_private(schnorr_private.aux_rand(schnorr_aux_rand));
             }
         }
 
         Ok(keys_manager)
     }
+
+    fn schnorr_aux_rand(input: &Proto::SigningInput) -> SigningResult<AuxRand> {
+        if input.schnorr_aux_rand.is_empty() {
+            return Ok(if input.dangerous_use_fixed_schnorr_rng {
+                AuxRand::Disabled
+            } else {
+                AuxRand::Random
+            });
+        }
+        if input.dangerous_use_fixed_schnorr_rng {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(
+                "Schnorr auxiliary random data cannot be set together with a fixed Schnorr RNG",
+            );
+        }
+        H256::try_from(input.schnorr_aux_rand.as_ref())
+            .map(AuxRand::Fixed)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Schnorr auxiliary random data must be 32 bytes")
+    }
+
+    fn ecdsa_extra_entropy(input: &Proto::SigningInput) -> SigningResult<Option<H256>> {
+        if input.ecdsa_extra_entropy.is_empty() {
+            return Ok(None);
+        }
+        H256::try_from(input.ecdsa_extra_entropy.as_ref())
+            .map(Some)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("ECDSA extra entropy must be 32 bytes")
+    }
 }
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Policy checks applied to a transaction before it is signed,
+//! e.g. by custodial services embedding the library server-side.
+
+use crate::modules::explain::TransactionReport;
+use crate::modules::tx_builder::BitcoinChainInfo;
+use std::borrow::Cow;
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_PolicyViolation::Rule;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::script::Script;
+use tw_utxo::transaction::transaction_parts::Amount;
+
+/// A pluggable check invoked before the transaction is signed.
+pub trait SigningPolicy {
+    /// Returns the rules violated by the transaction, or an empty list if the transaction can be signed.
+    fn check(&self, report: &TransactionReport) -> Vec<Proto::PolicyViolation<'static>>;
+}
+
+/// The built-in rules configured by [`Proto::SigningPolicy`].
+/// Change outputs are not subject to the amount and destination rules,
+/// see [`OutputReport::is_change`](crate::modules::explain::OutputReport::is_change).
+#[derive(Clone, Debug, Default)]
+pub struct PolicyRules {
+    /// The max total amount sent to non-change outputs.
+    pub max_amount: Option<Amount>,
+    pub max_fee: Option<Amount>,
+    /// The only scriptPubkeys the funds can be sent to.
+    pub allowed_destinations: Option<Vec<Script>>,
+    /// The scriptPubkeys the funds must not be sent to.
+    pub denied_destinations: Vec<Script>,
+    /// The only output script types allowed, including change outputs.
+    pub allowed_script_types: Option<Vec<Proto::OutputScriptType>>,
+}
+
+impl PolicyRules {
+    pub fn from_proto<Context: UtxoContext>(
+        chain_info: &BitcoinChainInfo,
+        policy: &Proto::SigningPolicy,
+    ) -> SigningResult<PolicyRules> {
+        // Addresses are compared by their scriptPubkeys, so different encodings of the same address match.
+        let to_scripts = |addresses: &[Cow<'_, str>]| -> SigningResult<Vec<Script>> {
+            addresses
+                .iter()
+                .map(|addr_str| {
+                    let addr = Context::Address::from_str(addr_str)
+                        .into_tw()
+                        .with_context(|| format!("Invalid policy address: {addr_str}"))?;
+                    Context::addr_to_script_pubkey(&addr, chain_info.to_address_prefixes())
+                })
+                .collect()
+        };
+
+        let allowed_destinations = if policy.allowed_addresses.is_empty() {
+            None
+        } else {
+            Some(to_scripts(&policy.allowed_addresses)?)
+        };
+        let allowed_script_types = if policy.allowed_script_types.is_empty() {
+            None
+        } else {
+            Some(policy.allowed_script_types.clone())
+        };
+
+        Ok(PolicyRules {
+            max_amount: amount_limit(policy.max_amount, "max_amount")?,
+            max_fee: amount_limit(policy.max_fee, "max_fee")?,
+            allowed_destinations,
+            denied_destinations: to_scripts(&policy.denied_addresses)?,
+            allowed_script_types,
+        })
+    }
+}
+
+impl SigningPolicy for PolicyRules {
+    fn check(&self, report: &TransactionReport) -> Vec<Proto::PolicyViolation<'static>> {
+        let mut violations = Vec::default();
+
+        if let Some(max_amount) = self.max_amount {
+            let sent_amount: Amount = report
+                .outputs
+                .iter()
+                .filter(|output| !output.is_change)
+                .map(|output| output.value)
+                .sum();
+            if sent_amount > max_amount {
+                violations.push(violation(
+                    Rule::MaxAmount,
+                    0,
+                    format!("Sent amount '{sent_amount}' exceeds {max_amount}"),
+                ));
+            }
+        }
+
+        if let Some(max_fee) = self.max_fee {
+            if report.fee > max_fee {
+                violations.push(violation(
+                    Rule::MaxFee,
+                    0,
+                    format!("Fee '{}' exceeds {max_fee}", report.fee),
+                ));
+            }
+        }
+
+        for (output_index, output) in report.outputs.iter().enumerate() {
+            let output_index = output_index as u32;
+            let destination = output.address.as_deref().unwrap_or("-");
+
+            if let Some(ref allowed_script_types) = self.allowed_script_types {
+                if !allowed_script_types.contains(&output.script_type) {
+                    violations.push(violation(
+                        Rule::AllowedScriptTypes,
+                        output_index,
+                        format!(
+                            "Output '{output_index}' script type {:?} is not allowed",
+                            output.script_type
+                        ),
+                    ));
+                }
+            }
+
+            if output.is_change {
+                continue;
+            }
+
+            if self.denied_destinations.contains(&output.script_pubkey) {
+                violations.push(violation(
+                    Rule::DeniedAddresses,
+                    output_index,
+                    format!("Output '{output_index}' destination {destination} is denied"),
+                ));
+            }
+            if let Some(ref allowed_destinations) = self.allowed_destinations {
+                if !allowed_destinations.contains(&output.script_pubkey) {
+                    violations.push(violation(
+                        Rule::AllowedAddresses,
+                        output_index,
+                        format!("Output '{output_index}' destination {destination} is not allowed"),
+                    ));
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// Returns the output of a transaction rejected by the policy.
+pub fn policy_rejection(
+    violations: Vec<Proto::PolicyViolation<'static>>,
+) -> Proto::SigningOutput<'static> {
+    let error_message = violations
+        .iter()
+        .map(|violation| violation.message.as_ref())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    Proto::SigningOutput {
+        error: SigningErrorType::Error_policy_violation,
+        error_message: Cow::from(error_message),
+        policy_violations: violations,
+        ..Proto::SigningOutput::default()
+    }
+}
+
+fn amount_limit(limit: i64, name: &str) -> SigningResult<Option<Amount>> {
+    match limit {
+        0 => Ok(None),
+        limit if limit < 0 => SigningError::err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("Policy '{name}' must not be negative")),
+        limit => Ok(Some(limit)),
+    }
+}
+
+fn violation(rule: Rule, output_index: u32, message: String) -> Proto::PolicyViolation<'static> {
+    Proto::PolicyViolation {
+        rule,
+        output_index,
+        message: Cow::from(message),
+    }
+}
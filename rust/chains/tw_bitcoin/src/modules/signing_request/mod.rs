@@ -4,23 +4,33 @@
 
 use crate::modules::tx_builder::output_protobuf::OutputProtobuf;
 use crate::modules::tx_builder::public_keys::PublicKeys;
-use crate::modules::tx_builder::utxo_protobuf::UtxoProtobuf;
+use crate::modules::tx_builder::utxo_protobuf::{parse_out_point, UtxoProtobuf};
 use crate::modules::tx_builder::BitcoinChainInfo;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::marker::PhantomData;
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::error::prelude::*;
 use tw_misc::traits::OptionalEmpty;
 use tw_proto::BitcoinV2::Proto;
+use tw_utxo::constants::{LOCKTIME_THRESHOLD, MAX_SEQUENCE_NONFINAL};
 use tw_utxo::context::UtxoContext;
 use tw_utxo::dust::DustPolicy;
+use tw_utxo::modules::account_xpub::{AccountXpub, DerivationScheme, ScriptType};
+use tw_utxo::modules::truc_policy::TrucPolicy;
 use tw_utxo::modules::tx_planner::{PlanRequest, RequestType};
+use tw_utxo::modules::utxo_selector::change_split::{ChangeSplit, MAX_CHANGE_SPLIT_OUTPUTS};
 use tw_utxo::modules::utxo_selector::InputSelector;
-use tw_utxo::transaction::standard_transaction::builder::TransactionBuilder;
-use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::script::Script;
+use tw_utxo::transaction::standard_transaction::builder::{OutputBuilder, TransactionBuilder};
+use tw_utxo::transaction::standard_transaction::{Transaction, TransactionOutput};
+use tw_utxo::transaction::transaction_interface::TransactionInterface;
+use tw_utxo::transaction::transaction_parts::Amount;
+use Proto::mod_Input::OneOfclaiming_script as ClaimingScriptType;
+use Proto::mod_SigningInput::OneOftransaction as TransactionType;
 use Proto::mod_TransactionBuilder::OneOfdust_policy as ProtoDustPolicy;
 
-const DEFAULT_TX_VERSION: u32 = 1;
-
 pub type StandardSigningRequest = PlanRequest<Transaction>;
 
 pub struct SigningRequestBuilder<Context: UtxoContext> {
@@ -37,27 +47,55 @@ impl<Context: UtxoContext> SigningRequestBuilder<Context> {
         let dust_policy = Self::dust_policy(&transaction_builder.dust_policy)?;
         let fee_per_vbyte = transaction_builder.fee_per_vb;
         let version = Self::transaction_version(&transaction_builder.version);
+        let validate_custom_scripts = transaction_builder.validate_custom_scripts;
+        let lock_time = Self::lock_time(transaction_builder)?;
 
         let public_keys = Self::get_public_keys(input)?;
 
         let mut builder = TransactionBuilder::default();
-        builder
-            .version(version)
-            .lock_time(transaction_builder.lock_time);
+        builder.version(version).lock_time(lock_time);
 
         // Parse all UTXOs.
-        for utxo_proto in transaction_builder.inputs.iter() {
+        let mut total_input: Amount = 0;
+        for (i, utxo_proto) in transaction_builder.inputs.iter().enumerate() {
+            if utxo_proto.frozen {
+                // Foreign UTXOs are always spent.
+                let is_foreign =
+                    matches!(utxo_proto.claiming_script, ClaimingScriptType::foreign(_));
+                if utxo_proto.must_spend || is_foreign {
+                    return SigningError::err(SigningErrorType::Error_invalid_params)
+                        .context(format!("Input '{i}' cannot be frozen as it must be spent"));
+                }
+                // Frozen UTXOs must never be spent regardless of the input selector.
+                continue;
+            }
+
             let utxo_builder = UtxoProtobuf::<Context>::new(&chain_info, utxo_proto, &public_keys);
 
-            let (utxo, utxo_args) = utxo_builder
+            let (mut utxo, utxo_args) = utxo_builder
                 .utxo_from_proto()
-                .context("Error creating UTXO from Protobuf")?;
+                .with_context(|| format!("Error creating UTXO '{i}' from Protobuf"))?;
+            total_input = total_input.saturating_add(utxo_args.amount);
+            // `nLockTime` is ignored if all inputs have the final sequence.
+            if lock_time != 0 && utxo_proto.sequence.is_none() {
+                utxo.sequence = MAX_SEQUENCE_NONFINAL;
+            }
             builder.push_input(utxo, utxo_args);
         }
+        let max_money = chain_info.max_money::<Context>();
+        if matches!(max_money, Some(max_money) if total_input > max_money) {
+            return SigningError::err(SigningErrorType::Error_invalid_utxo_amount)
+                .context("Total UTXO amount exceeds the max money of the chain");
+        }
 
         // If `max_amount_output` is set, construct a transaction with only one output.
         if let Some(max_output_proto) = transaction_builder.max_amount_output.as_ref() {
-            let output_builder = OutputProtobuf::<Context>::new(&chain_info, max_output_proto);
+            if max_output_proto.subtract_fee {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("The fee is always deducted from the max amount output");
+            }
+            let output_builder = OutputProtobuf::<Context>::new(&chain_info, max_output_proto)
+                .validate_custom_script(validate_custom_scripts);
 
             let max_output = output_builder
                 .output_from_proto()
@@ -73,23 +111,37 @@ impl<Context: UtxoContext> SigningRequestBuilder<Context> {
         }
 
         // `max_amount_output` isn't set, parse all Outputs.
-        for output_proto in transaction_builder.outputs.iter() {
+        let mut total_output: Amount = 0;
+        for (i, output_proto) in transaction_builder.outputs.iter().enumerate() {
             let output = OutputProtobuf::<Context>::new(&chain_info, output_proto)
+                .validate_custom_script(validate_custom_scripts)
                 .output_from_proto()
-                .context("Error creating Output from Proto")?;
+                .with_context(|| format!("Error creating Output '{i}' from Proto"))?;
+            if output.value == 0 && !Self::is_zero_value_allowed(&output.script_pubkey) {
+                return SigningError::err(SigningErrorType::Error_zero_amount_requested)
+                    .context(format!(
+                        "Output '{i}' amount must be positive unless it is an OP_RETURN or Pay-to-Anchor output"
+                    ));
+            }
+            total_output = total_output.saturating_add(output.value);
             builder.push_output(output);
         }
+        if matches!(max_money, Some(max_money) if total_output > max_money) {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Total output amount exceeds the max money of the chain");
+        }
+
+        let subtract_fee_from = Self::subtract_fee_from(&transaction_builder.outputs);
 
-        // Parse change output if it was provided.
-        let change_output = transaction_builder
-            .change_output
-            .as_ref()
-            .map(|change_output_proto| {
-                OutputProtobuf::<Context>::new(&chain_info, change_output_proto)
-                    .output_from_proto()
-                    .context("Error creating Change Output from Proto")
-            })
-            .transpose()?;
+        let change_split = Self::change_split(&transaction_builder.change_split)?;
+
+        // Parse change output(s) if it was provided.
+        let change_outputs = Self::change_outputs(
+            &chain_info,
+            transaction_builder,
+            change_split.as_ref(),
+            validate_custom_scripts,
+        )?;
 
         let input_selector = Self::input_selector(&transaction_builder.input_selector);
 
@@ -97,8 +149,10 @@ impl<Context: UtxoContext> SigningRequestBuilder<Context> {
         Ok(StandardSigningRequest {
             ty: RequestType::SendExact {
                 unsigned_tx,
-                change_output,
+                change_outputs,
+                change_split,
                 input_selector,
+                subtract_fee_from,
             },
             dust_policy,
             fee_per_vbyte,
@@ -121,6 +175,130 @@ impl<Context: UtxoContext> SigningRequestBuilder<Context> {
         Ok(public_keys)
     }
 
+    /// Only OP_RETURN and Pay-to-Anchor outputs are standard with zero value.
+    fn is_zero_value_allowed(script_pubkey: &Script) -> bool {
+        conditions::is_op_return(script_pubkey) || conditions::is_p2a(script_pubkey)
+    }
+
+    /// Returns the change outputs, one per split change output if `change_split` is set.
+    fn change_outputs(
+        chain_info: &BitcoinChainInfo,
+        transaction_builder: &Proto::TransactionBuilder,
+        change_split: Option<&ChangeSplit>,
+        validate_custom_scripts: bool,
+    ) -> SigningResult<Vec<TransactionOutput>> {
+        let outputs_count = change_split.map_or(1, |split| split.outputs_count);
+
+        match (
+            &transaction_builder.change_output,
+            &transaction_builder.change_xpub,
+        ) {
+            (Some(_), Some(_)) => SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Either `change_output` or `change_xpub` should be set"),
+            (Some(change_output_proto), None) => {
+                // The split change outputs follow the change output.
+                let split_outputs = match transaction_builder.change_split {
+                    Some(ref split) if change_split.is_some() => split.change_outputs.as_slice(),
+                    _ => &[],
+                };
+
+                std::iter::once(change_output_proto)
+                    .chain(split_outputs)
+                    .enumerate()
+                    .map(|(i, change_output_proto)| {
+                        if change_output_proto.subtract_fee {
+                            return SigningError::err(SigningErrorType::Error_invalid_params)
+                                .context("The fee cannot be deducted from the change output");
+                        }
+                        OutputProtobuf::<Context>::new(chain_info, change_output_proto)
+                            .validate_custom_script(validate_custom_scripts)
+                            .output_from_proto()
+                            .with_context(|| {
+                                format!("Error creating Change Output '{i}' from Proto")
+                            })
+                    })
+                    .collect()
+            },
+            (None, Some(change_xpub)) => {
+                let scheme = match change_xpub.scheme {
+                    Proto::DerivationScheme::BIP84 => DerivationScheme::Bip84,
+                    Proto::DerivationScheme::BIP86 => DerivationScheme::Bip86,
+                    Proto::DerivationScheme::BIP44 => DerivationScheme::Bip44,
+                    Proto::DerivationScheme::BIP49 => DerivationScheme::Bip49,
+                };
+                let script_type = match change_xpub.script_type {
+                    Proto::ChangeScriptType::DefaultChangeType => scheme.script_type(),
+                    Proto::ChangeScriptType::ChangeP2PKH => ScriptType::P2pkh,
+                    Proto::ChangeScriptType::ChangeP2SH_P2WPKH => ScriptType::P2shP2wpkh,
+                    Proto::ChangeScriptType::ChangeP2WPKH => ScriptType::P2wpkh,
+                    Proto::ChangeScriptType::ChangeP2TR => ScriptType::P2tr,
+                };
+                let account = AccountXpub::new(&change_xpub.xpub, scheme)
+                    .map(|account| account.with_script_type(script_type))
+                    .context("Error deriving Change Output from the account xpub")?;
+
+                // Each split change output is derived at the next index.
+                (0..outputs_count as u32)
+                    .map(|i| {
+                        let index = change_xpub
+                            .index
+                            .checked_add(i)
+                            .or_tw_err(SigningErrorType::Error_invalid_params)
+                            .context("Change xpub index overflow")?;
+                        let script_pubkey = account
+                            .derive_change_script_pubkey(index)
+                            .with_context(|| {
+                                format!("Error deriving Change Output at index '{index}' from the account xpub")
+                            })?;
+                        // The value will be overwritten by the planner.
+                        Ok(OutputBuilder::new(0).custom_script_pubkey(script_pubkey))
+                    })
+                    .collect()
+            },
+            (None, None) => Ok(Vec::default()),
+        }
+    }
+
+    fn change_split(proto: &Option<Proto::ChangeSplit>) -> SigningResult<Option<ChangeSplit>> {
+        let Some(split) = proto else {
+            return Ok(None);
+        };
+        // There is nothing to split.
+        if split.outputs_count <= 1 {
+            return Ok(None);
+        }
+
+        if split.outputs_count as usize > MAX_CHANGE_SPLIT_OUTPUTS {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "The change cannot be split into more than {MAX_CHANGE_SPLIT_OUTPUTS} outputs"
+            ));
+        }
+        if split.min_amount < 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Change split min amount cannot be negative");
+        }
+        if split.seed.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Change split seed is required to randomize the change outputs");
+        }
+
+        Ok(Some(ChangeSplit {
+            outputs_count: split.outputs_count as usize,
+            min_amount: split.min_amount,
+            seed: split.seed.to_vec(),
+        }))
+    }
+
+    /// Returns the indexes of the outputs the transaction fee should be deducted from.
+    fn subtract_fee_from(outputs: &[Proto::Output]) -> Vec<usize> {
+        outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.subtract_fee)
+            .map(|(output_idx, _)| output_idx)
+            .collect()
+    }
+
     fn input_selector(selector: &Proto::InputSelector) -> InputSelector {
         match selector {
             Proto::InputSelector::SelectAscending => InputSelector::Ascending,
@@ -130,19 +308,76 @@ impl<Context: UtxoContext> SigningRequestBuilder<Context> {
         }
     }
 
-    fn dust_policy(proto: &ProtoDustPolicy) -> SigningResult<DustPolicy> {
+    pub fn dust_policy(proto: &ProtoDustPolicy) -> SigningResult<DustPolicy> {
         match proto {
             ProtoDustPolicy::fixed_dust_threshold(fixed) => Ok(DustPolicy::FixedAmount(*fixed)),
-            ProtoDustPolicy::None => SigningError::err(SigningErrorType::Error_invalid_params)
+            ProtoDustPolicy::None => Context::DEFAULT_DUST_POLICY
+                .or_tw_err(SigningErrorType::Error_invalid_params)
                 .context("No dust policy provided"),
         }
     }
 
+    /// Checks whether the transaction conforms the TRUC (v3) policy.
+    /// Returns warnings on the rules that cannot be verified without the mempool state,
+    /// and on the outputs that are non-standard under some relay policies.
+    pub fn check_relay_policy(
+        input: &Proto::SigningInput,
+        tx: &Transaction,
+        vsize: usize,
+    ) -> SigningResult<Vec<Cow<'static, str>>> {
+        let mut unconfirmed_parents = HashSet::default();
+        // PSBT doesn't specify the dust threshold, so flag zero-value outputs only.
+        let mut dust_threshold = 1;
+        if let TransactionType::builder(ref tx_builder) = input.transaction {
+            for utxo_proto in tx_builder.inputs.iter().filter(|utxo| utxo.unconfirmed) {
+                unconfirmed_parents.insert(parse_out_point(&utxo_proto.out_point)?.hash);
+            }
+            dust_threshold = Self::dust_policy(&tx_builder.dust_policy)?.dust_threshold();
+        }
+
+        let mut warnings = TrucPolicy::check(tx, vsize, &unconfirmed_parents, dust_threshold)?;
+        for (i, output) in tx.outputs().iter().enumerate() {
+            if conditions::match_p2pk(&output.script_pubkey).is_some() {
+                warnings.push(format!(
+                    "Output '{i}' is a bare P2PK output, which is non-standard under some relay policies"
+                ));
+            }
+        }
+        Ok(warnings.into_iter().map(Cow::from).collect())
+    }
+
+    fn lock_time(transaction_builder: &Proto::TransactionBuilder) -> SigningResult<u32> {
+        use Proto::mod_LockTime::OneOfvariant as LockTimeType;
+
+        let Some(ref typed_lock_time) = transaction_builder.typed_lock_time else {
+            return Ok(transaction_builder.lock_time);
+        };
+        if transaction_builder.lock_time != 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Either `lock_time` or `typed_lock_time` should be set");
+        }
+
+        match typed_lock_time.variant {
+            LockTimeType::blocks(height) if height < LOCKTIME_THRESHOLD => Ok(height),
+            LockTimeType::blocks(_) => SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!(
+                    "Block height lock time must be less than {LOCKTIME_THRESHOLD}"
+                )),
+            LockTimeType::seconds(timestamp) if timestamp >= LOCKTIME_THRESHOLD => Ok(timestamp),
+            LockTimeType::seconds(_) => SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!(
+                    "Timestamp lock time must be at least {LOCKTIME_THRESHOLD}"
+                )),
+            LockTimeType::None => Ok(0),
+        }
+    }
+
     fn transaction_version(proto: &Proto::TransactionVersion) -> u32 {
         match proto {
-            Proto::TransactionVersion::UseDefault => DEFAULT_TX_VERSION,
+            Proto::TransactionVersion::UseDefault => Context::DEFAULT_TX_VERSION,
             Proto::TransactionVersion::V1 => 1,
             Proto::TransactionVersion::V2 => 2,
+            Proto::TransactionVersion::V3 => 3,
         }
     }
 
@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! BIP-352 Silent Payments support.
+//! Outputs paying to Silent Payment addresses are planned with a placeholder P2TR scriptPubkey,
+//! and the one-time output keys are derived right before signing, when the inputs are known.
+
+use crate::modules::tx_builder::BitcoinChainInfo;
+use bitcoin::key::TapTweak;
+use secp256k1::{KeyPair, PublicKey, SecretKey, SECP256K1};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H264;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::address::silent_payment::SilentPaymentAddress;
+use tw_utxo::modules::silent_payments::{InputPrivateKey, SilentPaymentSender};
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::transaction::standard_transaction::Transaction;
+use tw_utxo::transaction::transaction_parts::OutPoint;
+use tw_utxo::transaction::unsigned_transaction::UnsignedTransaction;
+use tw_utxo::transaction::UtxoToSign;
+use Proto::mod_Output::OneOfto_recipient as RecipientType;
+
+pub struct SilentPayments;
+
+impl SilentPayments {
+    /// Whether any of the outputs, including the change, pays to a Silent Payment address.
+    pub fn has_recipients(tx_builder: &Proto::TransactionBuilder) -> bool {
+        tx_builder
+            .outputs
+            .iter()
+            .chain(tx_builder.change_output.iter())
+            .chain(
+                tx_builder
+                    .change_split
+                    .iter()
+                    .flat_map(|split| split.change_outputs.iter()),
+            )
+            .chain(tx_builder.max_amount_output.iter())
+            .any(|output| {
+                matches!(
+                    output.to_recipient,
+                    RecipientType::silent_payment_address(_)
+                )
+            })
+    }
+
+    /// Replaces the placeholder scriptPubkeys of the Silent Payment outputs with the derived one-time P2TR outputs.
+    /// Must be called after the inputs are selected, as the output keys depend on the inputs.
+    pub fn derive_outputs<P>(
+        chain_info: &BitcoinChainInfo,
+        private_keys: &[P],
+        tx_builder: &Proto::TransactionBuilder,
+        unsigned_tx: &mut UnsignedTransaction<Transaction>,
+    ) -> SigningResult<()>
+    where
+        P: AsRef<[u8]>,
+    {
+        let recipients = Self::recipients(chain_info, tx_builder, unsigned_tx.outputs().len())?;
+        if recipients.is_empty() {
+            return Ok(());
+        }
+
+        let private_keys = private_keys
+            .iter()
+            .map(|private| {
+                SecretKey::from_slice(private.as_ref())
+                    .tw_err(|_| SigningErrorType::Error_invalid_private_key)
+                    .context("Invalid secp256k1 private key")
+            })
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        let mut input_keys = Vec::default();
+        for (i, utxo) in unsigned_tx.input_args().iter().enumerate() {
+            if let Some(input_key) =
+                Self::input_private_key(&private_keys, utxo).with_context(|| {
+                    format!("Input '{i}' cannot be used to pay to a Silent Payment address")
+                })?
+            {
+                input_keys.push(input_key);
+            }
+        }
+        let out_points: Vec<OutPoint> = unsigned_tx
+            .inputs()
+            .iter()
+            .map(|input| input.previous_output)
+            .collect();
+
+        let (indexes, addresses): (Vec<usize>, Vec<SilentPaymentAddress>) =
+            recipients.into_iter().unzip();
+        let output_keys =
+            SilentPaymentSender::new(&input_keys, &out_points)?.derive_outputs(&addresses)?;
+
+        let outputs = unsigned_tx.outputs_mut();
+        for (index, output_key) in indexes.into_iter().zip(output_keys) {
+            outputs[index].script_pubkey =
+                conditions::new_p2tr_dangerous_assume_tweaked(&output_key);
+        }
+        Ok(())
+    }
+
+    /// Returns the Silent Payment recipients along with their output indexes.
+    /// The transaction outputs follow the builder outputs, and the change outputs are appended at the end.
+    fn recipients(
+        chain_info: &BitcoinChainInfo,
+        tx_builder: &Proto::TransactionBuilder,
+        outputs_count: usize,
+    ) -> SigningResult<Vec<(usize, SilentPaymentAddress)>> {
+        let parse = |output: &Proto::Output| match output.to_recipient {
+            RecipientType::silent_payment_address(ref address) => {
+                chain_info.silent_payment_address(address).map(Some)
+            },
+            _ => Ok(None),
+        };
+
+        if let Some(ref max_output) = tx_builder.max_amount_output {
+            return Ok(parse(max_output)?
+                .map(|address| (0, address))
+                .into_iter()
+                .collect());
+        }
+
+        let mut recipients = Vec::default();
+        for (index, output) in tx_builder.outputs.iter().enumerate() {
+            if let Some(address) = parse(output)? {
+                recipients.push((index, address));
+            }
+        }
+        // The split change outputs follow the change output.
+        let change_outputs = tx_builder.change_output.iter().chain(
+            tx_builder
+                .change_split
+                .iter()
+                .flat_map(|split| split.change_outputs.iter()),
+        );
+        let change_indexes = tx_builder.outputs.len()..outputs_count;
+        for (index, change_output) in change_indexes.zip(change_outputs) {
+            if let Some(address) = parse(change_output)? {
+                recipients.push((index, address));
+            }
+        }
+        Ok(recipients)
+    }
+
+    /// Returns the private key of the UTXO if it's eligible for the shared secret derivation.
+    fn input_private_key(
+        private_keys: &[SecretKey],
+        utxo: &UtxoToSign,
+    ) -> SigningResult<Option<InputPrivateKey>> {
+        let script = &utxo.prevout_script_pubkey;
+
+        let is_ecdsa = conditions::is_p2pkh(script) || conditions::is_p2wpkh(script);
+        let is_taproot = conditions::is_p2tr(script);
+        if !is_ecdsa && !is_taproot {
+            if conditions::is_p2sh(script) {
+                // P2SH-P2WPKH inputs are eligible, but cannot be distinguished from other P2SH inputs.
+                return SigningError::err(SigningErrorType::Error_not_supported)
+                    .context("P2SH inputs are not supported");
+            }
+            return Ok(None);
+        }
+
+        if utxo.foreign {
+            return SigningError::err(SigningErrorType::Error_not_supported)
+                .context("Foreign inputs are not supported");
+        }
+
+        if is_ecdsa {
+            // Uncompressed public keys are not eligible.
+            if utxo.spender_public_key.len() != H264::LEN {
+                return Ok(None);
+            }
+            return private_keys
+                .iter()
+                .find(|private| {
+                    PublicKey::from_secret_key(SECP256K1, private).serialize()
+                        == utxo.spender_public_key.as_slice()
+                })
+                .map(|private| Some(InputPrivateKey::Ecdsa(*private)))
+                .or_tw_err(SigningErrorType::Error_missing_private_key)
+                .context("No private key provided");
+        }
+
+        if utxo.leaf_hash_code_separator.is_some() {
+            return SigningError::err(SigningErrorType::Error_not_supported)
+                .context("P2TR script-path inputs are not supported");
+        }
+        // P2TR key-path UTXOs are locked to the tweaked output key.
+        private_keys
+            .iter()
+            .map(|private| {
+                KeyPair::from_secret_key(SECP256K1, private)
+                    .tap_tweak(SECP256K1, None)
+                    .to_inner()
+            })
+            .find(|tweaked| {
+                tweaked.x_only_public_key().0.serialize() == utxo.spender_public_key.as_slice()
+            })
+            .map(|tweaked| Some(InputPrivateKey::Taproot(SecretKey::from_keypair(&tweaked))))
+            .or_tw_err(SigningErrorType::Error_missing_private_key)
+            .context("No private key provided")
+    }
+}
@@ -2,7 +2,22 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use tw_utxo::context::AddressPrefixes;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+use tw_keypair::{ecdsa, schnorr};
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::address::silent_payment::{
+    SilentPaymentAddress, SILENT_PAYMENT_MAINNET_HRP, SILENT_PAYMENT_TESTNET_HRP,
+};
+use tw_utxo::constants::MAX_MONEY;
+use tw_utxo::context::{AddressPrefixes, UtxoContext};
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::script::taproot_tree::{TaprootLeaf, TaprootTree};
+use tw_utxo::script::Script;
+use tw_utxo::transaction::standard_transaction::builder::UtxoBuilder;
+use tw_utxo::transaction::standard_transaction::TransactionInput;
+use tw_utxo::transaction::transaction_parts::Amount;
+use tw_utxo::transaction::UtxoToSign;
 
 pub mod output_protobuf;
 pub mod public_keys;
@@ -21,6 +36,160 @@ impl BitcoinChainInfo {
         AddressPrefixes {
             p2pkh_prefix: self.p2pkh_prefix,
             p2sh_prefix: self.p2sh_prefix,
+            hrp: self.hrp.clone(),
         }
     }
+
+    /// Returns the max amount of an input, an output, or their totals in satoshis. `None` if not limited.
+    /// [`UtxoContext::MAX_MONEY`] is used if set, otherwise the Bitcoin networks are recognized by their HRP,
+    /// as the same context can be shared by several chains, e.g. Bitcoin and Dogecoin.
+    pub fn max_money<Context: UtxoContext>(&self) -> Option<Amount> {
+        if Context::MAX_MONEY.is_some() {
+            return Context::MAX_MONEY;
+        }
+        match self.hrp.as_deref() {
+            Some(BITCOIN_MAINNET_HRP | BITCOIN_TESTNET_HRP | BITCOIN_REGTEST_HRP) => {
+                Some(MAX_MONEY)
+            },
+            _ => None,
+        }
+    }
+
+    /// Parses a Silent Payment address of this chain.
+    /// The mainnet addresses start with `sp1`, and the testnet and regtest ones with `tsp1`.
+    /// Silent Payments are defined for Bitcoin only.
+    pub fn silent_payment_address(&self, address: &str) -> SigningResult<SilentPaymentAddress> {
+        let expected_hrp = match self.hrp.as_deref() {
+            Some(BITCOIN_MAINNET_HRP) => SILENT_PAYMENT_MAINNET_HRP,
+            Some(BITCOIN_TESTNET_HRP | BITCOIN_REGTEST_HRP) => SILENT_PAYMENT_TESTNET_HRP,
+            _ => {
+                return SigningError::err(SigningErrorType::Error_not_supported)
+                    .context("Silent Payments are supported on Bitcoin chains only")
+            },
+        };
+        SilentPaymentAddress::from_str_checked(address, expected_hrp)
+            .into_tw()
+            .context("Invalid Silent Payment address")
+    }
+}
+
+/// Segwit HRP of the Bitcoin mainnet.
+const BITCOIN_MAINNET_HRP: &str = "bc";
+/// Segwit HRP of the Bitcoin testnet and signet.
+const BITCOIN_TESTNET_HRP: &str = "tb";
+/// Segwit HRP of the Bitcoin regtest.
+const BITCOIN_REGTEST_HRP: &str = "bcrt";
+
+/// Checks if the given public key can be used in a segwit script.
+/// Segwit scripts allow compressed public keys only, see BIP-143.
+pub fn check_segwit_public_key(pubkey: &[u8]) -> SigningResult<()> {
+    if pubkey.len() == ecdsa::secp256k1::PublicKey::UNCOMPRESSED {
+        return SigningError::err(SigningErrorType::Error_segwit_uncompressed_public_key)
+            .context("Uncompressed public keys are not allowed in segwit scripts");
+    }
+    Ok(())
+}
+
+/// Creates a Taproot leaf. The zero `leaf_version` stands for the BIP-342 Tapscript version.
+pub fn taproot_leaf(script: &[u8], leaf_version: u32) -> SigningResult<TaprootLeaf> {
+    let script = Script::from(script.to_vec());
+    if leaf_version == 0 {
+        return Ok(TaprootLeaf::tapscript(script));
+    }
+    let leaf_version = u8::try_from(leaf_version)
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Taproot leaf version must fit in one byte")?;
+    Ok(TaprootLeaf::new(script, leaf_version))
+}
+
+/// Builds a Taproot script tree either by the leaves depths or with Huffman weighting.
+pub fn taproot_tree_from_proto(tree: &Proto::TaprootScriptTree) -> SigningResult<TaprootTree> {
+    let leaf =
+        |leaf: &Proto::mod_TaprootScriptTree::Leaf| taproot_leaf(&leaf.script, leaf.leaf_version);
+
+    if tree.huffman {
+        let leaves = tree
+            .leaves
+            .iter()
+            .map(|proto_leaf| Ok((proto_leaf.weight, leaf(proto_leaf)?)))
+            .collect::<SigningResult<Vec<_>>>()?;
+        return TaprootTree::with_huffman_weights(leaves);
+    }
+
+    let leaves = tree
+        .leaves
+        .iter()
+        .map(|proto_leaf| {
+            let depth = u8::try_from(proto_leaf.depth)
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Invalid Taproot leaf depth")?;
+            Ok((depth, leaf(proto_leaf)?))
+        })
+        .collect::<SigningResult<Vec<_>>>()?;
+    TaprootTree::with_depths(leaves)
+}
+
+/// Parses the internal public key and the commitment of a pay-to-contract Taproot output.
+pub fn pay_to_contract_from_proto(
+    contract: &Proto::PayToContract,
+) -> SigningResult<(schnorr::PublicKey, H256)> {
+    let internal_pubkey = schnorr::PublicKey::try_from(contract.internal_key.as_ref())
+        .into_tw()
+        .context("Invalid PayToContract.internal_key. Must be a schnorr public key")?;
+    let commitment = H256::try_from(contract.commitment.as_ref())
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Invalid PayToContract.commitment. Expected 32 bytes")?;
+    Ok((internal_pubkey, commitment))
+}
+
+/// Builds an M-of-N multisig redeem script, sorting the public keys if `sortedmulti()` semantics is requested.
+pub fn multisig_from_proto(multisig: &Proto::mod_Output::Multisig) -> SigningResult<Script> {
+    let threshold = u8::try_from(multisig.threshold)
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Invalid multisig threshold")?;
+    let pubkeys = multisig
+        .public_keys
+        .iter()
+        .map(|pubkey| {
+            ecdsa::secp256k1::PublicKey::try_from(pubkey.as_ref())
+                .into_tw()
+                .context("Invalid multisig public key")
+                .map(|pubkey| pubkey.compressed())
+        })
+        .collect::<SigningResult<Vec<_>>>()?;
+
+    if multisig.sorted {
+        conditions::new_sorted_multisig(threshold, &pubkeys)
+    } else {
+        conditions::new_multisig(threshold, &pubkeys)
+    }
+}
+
+/// Spends a P2PKH UTXO locked to either a compressed or uncompressed public key.
+pub fn p2pkh_with_pubkey_data(
+    builder: UtxoBuilder,
+    pubkey_data: &[u8],
+) -> SigningResult<(TransactionInput, UtxoToSign)> {
+    let pubkey = parse_ecdsa_public_key(pubkey_data)?;
+    if pubkey_data.len() == ecdsa::secp256k1::PublicKey::UNCOMPRESSED {
+        builder.p2pkh_uncompressed(&pubkey)
+    } else {
+        builder.p2pkh(&pubkey)
+    }
+}
+
+/// Spends a P2WPKH UTXO. Returns an error if the public key is uncompressed.
+pub fn p2wpkh_with_pubkey_data(
+    builder: UtxoBuilder,
+    pubkey_data: &[u8],
+) -> SigningResult<(TransactionInput, UtxoToSign)> {
+    check_segwit_public_key(pubkey_data).context("P2WPKH builder")?;
+    let pubkey = parse_ecdsa_public_key(pubkey_data)?;
+    builder.p2wpkh(&pubkey)
+}
+
+fn parse_ecdsa_public_key(pubkey_data: &[u8]) -> SigningResult<ecdsa::secp256k1::PublicKey> {
+    ecdsa::secp256k1::PublicKey::try_from(pubkey_data)
+        .into_tw()
+        .context("Expected a valid ecdsa secp256k1 public key")
 }
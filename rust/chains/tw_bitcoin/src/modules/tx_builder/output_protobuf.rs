@@ -2,7 +2,12 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::modules::tx_builder::BitcoinChainInfo;
+use crate::modules::tx_builder::script_parser::{StandardScript, StandardScriptParser};
+use crate::modules::tx_builder::{
+    check_segwit_public_key, multisig_from_proto, pay_to_contract_from_proto,
+    taproot_tree_from_proto, BitcoinChainInfo,
+};
+use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::str::FromStr;
 use tw_coin_entry::error::prelude::*;
@@ -14,12 +19,18 @@ use tw_memory::Data;
 use tw_proto::BitcoinV2::Proto;
 use tw_utxo::context::UtxoContext;
 use tw_utxo::script::Script;
-use tw_utxo::transaction::standard_transaction::builder::OutputBuilder;
+use tw_utxo::transaction::standard_transaction::builder::{OutputBuilder, OP_RETURN_SCRIPT_LIMIT};
 use tw_utxo::transaction::standard_transaction::TransactionOutput;
 
+/// The max size of a scriptPubkey that can ever be spent.
+const MAX_SCRIPT_SIZE: usize = 10_000;
+/// The size of an uncompressed public key including the `0x04` prefix.
+const UNCOMPRESSED_PUBKEY_LEN: usize = 65;
+
 pub struct OutputProtobuf<'a, Context: UtxoContext> {
     chain_info: &'a BitcoinChainInfo,
     output: &'a Proto::Output<'a>,
+    validate_custom_script: bool,
     _phantom: PhantomData<Context>,
 }
 
@@ -28,10 +39,17 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
         OutputProtobuf {
             chain_info,
             output,
+            validate_custom_script: false,
             _phantom: PhantomData,
         }
     }
 
+    /// Whether to reject non-standard and provably unspendable custom scriptPubkey's.
+    pub fn validate_custom_script(mut self, validate: bool) -> Self {
+        self.validate_custom_script = validate;
+        self
+    }
+
     pub fn output_from_proto(self) -> SigningResult<TransactionOutput> {
         use Proto::mod_Output::mod_OutputBuilder::OneOfvariant as BuilderType;
         use Proto::mod_Output::OneOfto_recipient as RecipientType;
@@ -50,11 +68,17 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
                 },
                 BuilderType::brc20_inscribe(ref inscription) => self.brc20_inscribe(inscription),
                 BuilderType::op_return(ref data) => self.op_return(data),
+                BuilderType::op_return_pushes(ref data) => self.op_return_pushes(&data.pushes),
+                BuilderType::p2a(_) => self.p2a(),
+                BuilderType::p2tr_contract(ref contract) => self.p2tr_contract(contract),
                 BuilderType::None => SigningError::err(SigningErrorType::Error_invalid_params)
                     .context("No Output Builder type provided"),
             },
             RecipientType::custom_script_pubkey(ref script) => self.custom_script(script.to_vec()),
             RecipientType::to_address(ref address) => self.recipient_address(address),
+            RecipientType::silent_payment_address(ref address) => {
+                self.silent_payment_address(address)
+            },
             RecipientType::None => SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("No Output recipient type provided"),
         }
@@ -69,10 +93,14 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
         Ok(self.prepare_builder()?.p2sh_from_hash(&redeem_hash))
     }
 
-    pub fn p2pk(&self, pubkey: &[u8]) -> SigningResult<TransactionOutput> {
-        let pubkey = ecdsa::secp256k1::PublicKey::try_from(pubkey)
+    pub fn p2pk(&self, pubkey_data: &[u8]) -> SigningResult<TransactionOutput> {
+        let pubkey = ecdsa::secp256k1::PublicKey::try_from(pubkey_data)
             .into_tw()
             .context("Invalid P2PK public key")?;
+        // Keep the uncompressed public key as is.
+        if pubkey_data.len() == UNCOMPRESSED_PUBKEY_LEN {
+            return Ok(self.prepare_builder()?.p2pk_uncompressed(&pubkey));
+        }
         Ok(self.prepare_builder()?.p2pk(&pubkey))
     }
 
@@ -96,6 +124,11 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
         &self,
         pubkey_or_hash: &Proto::PublicKeyOrHash,
     ) -> SigningResult<TransactionOutput> {
+        use Proto::mod_PublicKeyOrHash::OneOfvariant as PublicKeyOrHashType;
+
+        if let PublicKeyOrHashType::pubkey(ref pubkey) = pubkey_or_hash.variant {
+            check_segwit_public_key(pubkey).context("P2WPKH builder")?;
+        }
         let pubkey_hash = Self::pubkey_hash_from_proto(pubkey_or_hash).context("P2WPKH builder")?;
         Ok(self.prepare_builder()?.p2wpkh_from_hash(&pubkey_hash))
     }
@@ -114,11 +147,24 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
         let tweaked_x_only = H256::try_from(tweaked_pubkey)
             .tw_err(|_| SigningErrorType::Error_invalid_params)
             .context("Invalid P2TR tweaked public key. Expected 32 bytes x-only public key")?;
+        // Otherwise, the output is unspendable.
+        schnorr::XOnlyPublicKey::try_from(tweaked_x_only.as_slice())
+            .into_tw()
+            .context("Invalid P2TR tweaked public key. Must be a valid x-only public key")?;
         Ok(self
             .prepare_builder()?
             .p2tr_dangerous_assume_tweaked(&tweaked_x_only))
     }
 
+    pub fn p2tr_contract(
+        &self,
+        contract: &Proto::PayToContract,
+    ) -> SigningResult<TransactionOutput> {
+        let (internal_pubkey, commitment) = pay_to_contract_from_proto(contract)?;
+        self.prepare_builder()?
+            .p2tr_contract(&internal_pubkey, &commitment)
+    }
+
     pub fn p2tr_script_path(
         &self,
         taproot_script_path: &Proto::mod_Output::OutputTaprootScriptPath,
@@ -129,9 +175,21 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
                 "Invalid OutputTaprootScriptPath.internal_key. Must be a schnorr public key",
             )?;
 
-        let merkle_root = H256::try_from(taproot_script_path.merkle_root.as_ref())
-            .tw_err(|_| SigningErrorType::Error_invalid_params)
-            .context("Invalid OutputTaprootScriptPath.merkle_root. Must be a 32 byte array")?;
+        let merkle_root = match taproot_script_path.tree {
+            Some(ref tree) => {
+                if !taproot_script_path.merkle_root.is_empty() {
+                    return SigningError::err(SigningErrorType::Error_invalid_params).context(
+                        "Either OutputTaprootScriptPath.merkle_root or tree must be specified",
+                    );
+                }
+                taproot_tree_from_proto(tree)
+                    .context("Invalid OutputTaprootScriptPath.tree")?
+                    .merkle_root(&public_key)?
+            },
+            None => H256::try_from(taproot_script_path.merkle_root.as_ref())
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Invalid OutputTaprootScriptPath.merkle_root. Must be a 32 byte array")?,
+        };
 
         Ok(self
             .prepare_builder()?
@@ -152,6 +210,9 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
 
     pub fn custom_script(&self, script_data: Data) -> SigningResult<TransactionOutput> {
         let script = Script::from(script_data);
+        if self.validate_custom_script {
+            self.check_custom_script(&script)?;
+        }
         Ok(self.prepare_builder()?.custom_script_pubkey(script))
     }
 
@@ -165,10 +226,30 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
             .with_context(|| format!("Error handling {addr_str} output address"))
     }
 
+    /// Returns a placeholder P2TR output paying to the spend public key.
+    /// The actual output key is derived when the inputs are selected, see [`crate::modules::silent_payments::SilentPayments::derive_outputs`].
+    pub fn silent_payment_address(&self, addr_str: &str) -> SigningResult<TransactionOutput> {
+        let address = self.chain_info.silent_payment_address(addr_str)?;
+        let placeholder = H256::try_from(&address.spend_pubkey().as_slice()[1..])
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Expected 32 bytes x-only public key")?;
+        Ok(self
+            .prepare_builder()?
+            .p2tr_dangerous_assume_tweaked(&placeholder))
+    }
+
+    pub fn p2a(&self) -> SigningResult<TransactionOutput> {
+        Ok(self.prepare_builder()?.p2a())
+    }
+
     pub fn op_return(&self, op_return_data: &[u8]) -> SigningResult<TransactionOutput> {
         self.prepare_builder()?.op_return(op_return_data)
     }
 
+    pub fn op_return_pushes(&self, pushes: &[Cow<[u8]>]) -> SigningResult<TransactionOutput> {
+        self.prepare_builder()?.op_return_pushes(pushes)
+    }
+
     /// Tries to convert [`Proto::RedeemScriptOrHash`] to [`Hash<N>`] using a specific `hasher` function.
     /// Please note `P2SH` and `P2WSH` use different hashing functions.
     pub fn redeem_hash_from_proto<const N: usize, F>(
@@ -183,6 +264,9 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
         let hash_data = match input.variant {
             RedeemOrHashType::redeem_script(ref redeem) => hasher(redeem.as_ref()),
             RedeemOrHashType::hash(ref hash) => hash.to_vec(),
+            RedeemOrHashType::multisig(ref multisig) => {
+                hasher(multisig_from_proto(multisig)?.as_slice())
+            },
             RedeemOrHashType::None => {
                 return SigningError::err(SigningErrorType::Error_invalid_params)
                     .context("Expected a redeem script or its hash")
@@ -193,11 +277,45 @@ impl<'a, Context: UtxoContext> OutputProtobuf<'a, Context> {
             .with_context(|| format!("Expected exactly {N} bytes redeem script hash"))
     }
 
+    /// Checks if the custom scriptPubkey is standard and spendable.
+    /// OP_RETURN outputs are allowed as long as they do not burn any funds.
+    fn check_custom_script(&self, script: &Script) -> SigningResult<()> {
+        let script_size = script.as_slice().len();
+        if script_size > MAX_SCRIPT_SIZE {
+            return SigningError::err(SigningErrorType::Error_script_output).context(format!(
+                "Custom scriptPubkey is provably unspendable: exceeds {MAX_SCRIPT_SIZE} bytes"
+            ));
+        }
+
+        let standard_script = StandardScriptParser
+            .parse(script)
+            .context("Custom scriptPubkey is non-standard")?;
+        if let StandardScript::OpReturn(_) = standard_script {
+            if self.output.value != 0 {
+                return SigningError::err(SigningErrorType::Error_script_output)
+                    .context("OP_RETURN output is provably unspendable, its value must be zero");
+            }
+            if script_size > OP_RETURN_SCRIPT_LIMIT {
+                return SigningError::err(SigningErrorType::Error_script_output).context(format!(
+                    "OP_RETURN scriptPubkey is non-standard: exceeds {OP_RETURN_SCRIPT_LIMIT} bytes"
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn prepare_builder(&self) -> SigningResult<OutputBuilder> {
         if self.output.value < 0 {
             return SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("Transaction Output amount cannot be negative");
         }
+        if let Some(max_money) = self.chain_info.max_money::<Context>() {
+            if self.output.value > max_money {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "Transaction Output amount cannot exceed {max_money} satoshis"
+                ));
+            }
+        }
         Ok(OutputBuilder::new(self.output.value))
     }
 
@@ -26,12 +26,16 @@ impl PublicKeys {
         self
     }
 
-    /// Adds a public key derived from the ecdsa secp256k1 `private`.
+    /// Adds both compressed and uncompressed public keys derived from the ecdsa secp256k1 `private`.
+    /// The uncompressed key is required to spend legacy P2PKH UTXOs of old wallets.
     pub fn add_public_with_ecdsa_private(&mut self, private: &[u8]) -> SigningResult<&mut Self> {
         let private = ecdsa::secp256k1::PrivateKey::try_from(private)
             .into_tw()
             .context("Given an invalid ecdsa secp256k1 private key")?;
-        Ok(self.add_public_key(private.public().compressed().to_vec()))
+        let public = private.public();
+        Ok(self
+            .add_public_key(public.compressed().to_vec())
+            .add_public_key(public.uncompressed().to_vec()))
     }
 
     pub fn get_public_key(&self, pubkey_hash: &H160) -> SigningResult<&[u8]> {
@@ -7,6 +7,7 @@ use tw_coin_entry::error::prelude::*;
 use tw_hash::{H160, H256};
 use tw_keypair::{ecdsa, schnorr};
 use tw_memory::Data;
+use tw_proto::BitcoinV2::Proto;
 use tw_utxo::address::legacy::LegacyAddress;
 use tw_utxo::address::segwit::SegwitAddress;
 use tw_utxo::address::standard_bitcoin::StandardBitcoinAddress;
@@ -28,11 +29,26 @@ pub enum StandardScript {
     /// Tweaked public key.
     /// The public key can be tweaked as either key-path or script-path,
     P2TR(schnorr::XOnlyPublicKey),
+    /// Pay-to-Anchor keyless output.
+    P2A,
     /// OP_RETURN payload.
     OpReturn(Data),
 }
 
 impl StandardScript {
+    pub fn output_script_type(&self) -> Proto::OutputScriptType {
+        match self {
+            StandardScript::P2PK(_) => Proto::OutputScriptType::P2PK,
+            StandardScript::P2PKH(_) => Proto::OutputScriptType::P2PKH,
+            StandardScript::P2SH(_) => Proto::OutputScriptType::P2SH,
+            StandardScript::P2WPKH(_) => Proto::OutputScriptType::P2WPKH,
+            StandardScript::P2WSH(_) => Proto::OutputScriptType::P2WSH,
+            StandardScript::P2TR(_) => Proto::OutputScriptType::P2TR,
+            StandardScript::P2A => Proto::OutputScriptType::P2A,
+            StandardScript::OpReturn(_) => Proto::OutputScriptType::OpReturn,
+        }
+    }
+
     pub fn try_to_address(
         &self,
         chain_info: &BitcoinChainInfo,
@@ -71,7 +87,8 @@ impl StandardScript {
                     .map(StandardBitcoinAddress::Taproot)
                     .map(Some)
             },
-            StandardScript::OpReturn(_) => Ok(None),
+            // P2A is a 2-byte witness v1 program that is not supported by `TaprootAddress`.
+            StandardScript::P2A | StandardScript::OpReturn(_) => Ok(None),
         }
     }
 }
@@ -79,6 +96,14 @@ impl StandardScript {
 pub struct StandardScriptParser;
 
 impl StandardScriptParser {
+    /// Detects the type of the given scriptPubkey.
+    /// Returns [`Proto::OutputScriptType::NonStandard`] if the script cannot be parsed.
+    pub fn output_script_type(&self, script: &Script) -> Proto::OutputScriptType {
+        self.parse(script)
+            .map(|standard| standard.output_script_type())
+            .unwrap_or(Proto::OutputScriptType::NonStandard)
+    }
+
     /// Later, this method can be moved to a trait.
     pub fn parse(&self, script: &Script) -> SigningResult<StandardScript> {
         if let Some(pubkey) = conditions::match_p2pk(script) {
@@ -106,6 +131,9 @@ impl StandardScriptParser {
                     .into_tw()
                     .context("P2TR scriptPubkey must contain a valid tweaked schnorr public key")?;
             Ok(StandardScript::P2TR(tweaked_pubkey_x_only))
+        } else if conditions::is_p2a(script) {
+            // P2A
+            Ok(StandardScript::P2A)
         } else if let Some(payload) = conditions::match_op_return(script) {
             // OP_RETURN
             Ok(StandardScript::OpReturn(payload))
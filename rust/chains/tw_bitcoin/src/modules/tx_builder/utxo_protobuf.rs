@@ -4,21 +4,30 @@
 
 use crate::modules::tx_builder::public_keys::PublicKeys;
 use crate::modules::tx_builder::script_parser::{StandardScript, StandardScriptParser};
-use crate::modules::tx_builder::BitcoinChainInfo;
+use crate::modules::tx_builder::{
+    p2pkh_with_pubkey_data, p2wpkh_with_pubkey_data, pay_to_contract_from_proto, taproot_leaf,
+    taproot_tree_from_proto, BitcoinChainInfo,
+};
+use bitcoin::hashes::Hash;
 use std::marker::PhantomData;
 use std::str::FromStr;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::{H160, H256};
+use tw_hash::H160;
 use tw_keypair::{ecdsa, schnorr};
 use tw_memory::Data;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_utxo::context::UtxoContext;
-use tw_utxo::script::Script;
-use tw_utxo::sighash::SighashType;
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::script::{Script, Witness};
+#[cfg(feature = "anyprevout")]
+use tw_utxo::sighash::AnyPrevout;
+use tw_utxo::sighash::{SighashAlgorithm, SighashType};
+use tw_utxo::signing_mode::SigningMethod;
+use tw_utxo::spending_data::standard_constructor::{Template, TemplateItem};
 use tw_utxo::transaction::standard_transaction::builder::UtxoBuilder;
 use tw_utxo::transaction::standard_transaction::TransactionInput;
-use tw_utxo::transaction::transaction_parts::OutPoint;
+use tw_utxo::transaction::transaction_parts::{Amount, OutPoint, Txid};
 use tw_utxo::transaction::UtxoToSign;
 
 pub struct UtxoProtobuf<'a, Context: UtxoContext> {
@@ -43,6 +52,15 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
     }
 
     pub fn utxo_from_proto(self) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let (utxo, utxo_args) = self.claim_utxo()?;
+        check_utxo_amount(&utxo_args)?;
+        if !self.input.prev_tx.is_empty() {
+            verify_prev_tx(&self.input.prev_tx, &utxo, &utxo_args)?;
+        }
+        Ok((utxo, utxo_args))
+    }
+
+    fn claim_utxo(&self) -> SigningResult<(TransactionInput, UtxoToSign)> {
         use Proto::mod_Input::mod_InputBuilder::OneOfvariant as BuilderType;
         use Proto::mod_Input::OneOfclaiming_script as ScriptType;
 
@@ -54,13 +72,16 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
                 // BuilderType::p2wsh(ref redeem_script) => self.p2wsh(redeem_script.to_vec()),
                 BuilderType::p2wpkh(ref pubkey_or_hash) => self.p2wpkh(pubkey_or_hash),
                 BuilderType::p2tr_key_path(ref key_path) => self.p2tr_key_path(key_path),
-                // BuilderType::p2tr_script_path(ref script) => self.p2tr_script_path(script),
+                BuilderType::p2tr_script_path(ref script) => self.p2tr_script_path(script),
                 BuilderType::brc20_inscribe(ref inscription) => self.brc20_inscribe(inscription),
+                BuilderType::p2tr_contract(ref contract) => self.p2tr_contract(contract),
                 BuilderType::None => SigningError::err(SigningErrorType::Error_invalid_params)
                     .context("No Input Builder type provided"),
             },
             ScriptType::script_data(ref script) => self.custom_script(script.to_vec()),
             ScriptType::receiver_address(ref address) => self.recipient_address(address),
+            ScriptType::foreign(ref foreign) => self.foreign(foreign),
+            ScriptType::custom(ref custom) => self.custom(custom),
             ScriptType::None => SigningError::err(SigningErrorType::Error_invalid_params)
                 .context("No Input claiming script provided"),
         }
@@ -84,8 +105,8 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
         &self,
         pubkey_or_hash: &Proto::PublicKeyOrHash,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        let pubkey_hash = self.get_ecdsa_pubkey_from_proto(pubkey_or_hash)?;
-        self.prepare_builder()?.p2pkh(&pubkey_hash)
+        let pubkey_data = self.get_ecdsa_pubkey_data_from_proto(pubkey_or_hash)?;
+        p2pkh_with_pubkey_data(self.prepare_builder()?, pubkey_data)
     }
 
     // TODO next iteration
@@ -98,8 +119,8 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
         &self,
         pubkey_or_hash: &Proto::PublicKeyOrHash,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        let pubkey = self.get_ecdsa_pubkey_from_proto(pubkey_or_hash)?;
-        self.prepare_builder()?.p2wpkh(&pubkey)
+        let pubkey_data = self.get_ecdsa_pubkey_data_from_proto(pubkey_or_hash)?;
+        p2wpkh_with_pubkey_data(self.prepare_builder()?, pubkey_data)
     }
 
     pub fn p2tr_key_path(&self, pubkey: &[u8]) -> SigningResult<(TransactionInput, UtxoToSign)> {
@@ -107,16 +128,39 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
         self.prepare_builder()?.p2tr_key_path(&public_key)
     }
 
-    // TODO next iteration
-    // pub fn p2tr_script_path(
-    //     &self,
-    //     taproot_script_path: &Proto::mod_Input::InputTaprootScriptPath,
-    // ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-    //     let payload = Script::from(taproot_script_path.payload.to_vec());
-    //     // let x = taproot_script_path.
-    //     self.prepare_builder()?
-    //         .p2tr_script_path(payload, taproot_script_path.control_block.to_vec())
-    // }
+    pub fn p2tr_script_path(
+        &self,
+        taproot_script_path: &Proto::mod_Input::InputTaprootScriptPath,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let internal_pubkey =
+            schnorr::PublicKey::try_from(taproot_script_path.internal_key.as_ref())
+                .into_tw()
+                .context(
+                    "Invalid InputTaprootScriptPath.internal_key. Must be a schnorr public key",
+                )?;
+        let tree = taproot_script_path
+            .tree
+            .as_ref()
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("InputTaprootScriptPath.tree must be specified")?;
+        let tree = taproot_tree_from_proto(tree).context("Invalid InputTaprootScriptPath.tree")?;
+
+        let leaf = taproot_leaf(
+            &taproot_script_path.payload,
+            taproot_script_path.leaf_version,
+        )?;
+        self.prepare_builder()?
+            .p2tr_script_path_with_tree(&internal_pubkey, leaf, &tree)
+    }
+
+    pub fn p2tr_contract(
+        &self,
+        contract: &Proto::PayToContract,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let (internal_pubkey, commitment) = pay_to_contract_from_proto(contract)?;
+        self.prepare_builder()?
+            .p2tr_contract(&internal_pubkey, &commitment)
+    }
 
     pub fn brc20_inscribe(
         &self,
@@ -140,12 +184,12 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
         match StandardScriptParser.parse(&script)? {
             StandardScript::P2PK(pk) => builder.p2pk(&pk),
             StandardScript::P2PKH(pubkey_hash) => {
-                let pubkey = self.public_keys.get_ecdsa_public_key(&pubkey_hash)?;
-                builder.p2pkh(&pubkey)
+                let pubkey_data = self.public_keys.get_public_key(&pubkey_hash)?;
+                p2pkh_with_pubkey_data(builder, pubkey_data)
             },
             StandardScript::P2WPKH(pubkey_hash) => {
-                let pubkey = self.public_keys.get_ecdsa_public_key(&pubkey_hash)?;
-                builder.p2wpkh(&pubkey)
+                let pubkey_data = self.public_keys.get_public_key(&pubkey_hash)?;
+                p2wpkh_with_pubkey_data(builder, pubkey_data)
             },
             StandardScript::P2TR(tweaked_pubkey) => {
                 builder.p2tr_key_path_with_tweaked_pubkey(&tweaked_pubkey)
@@ -154,6 +198,8 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
                 SigningError::err(SigningErrorType::Error_not_supported)
                     .context("P2SH and P2WSH scriptPubkey's are not supported yet")
             },
+            StandardScript::P2A => SigningError::err(SigningErrorType::Error_not_supported)
+                .context("P2A scriptPubkey is not supported yet"),
             StandardScript::OpReturn(_) => SigningError::err(SigningErrorType::Error_invalid_utxo)
                 .context("Cannot spend an OP_RETURN output"),
         }
@@ -172,14 +218,97 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
             .with_context(|| format!("Error handling {addr_str} input recipient"))
     }
 
+    pub fn foreign(
+        &self,
+        foreign: &Proto::mod_Input::ForeignInput,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let script_pubkey = Script::from(foreign.script_pubkey.to_vec());
+        if script_pubkey.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Foreign input scriptPubkey is not provided");
+        }
+
+        let script_sig = Script::from(foreign.script_sig.to_vec());
+        let mut witness = Witness::default();
+        for item in foreign.witness_items.iter() {
+            witness.push_item(Script::from(item.to_vec()));
+        }
+
+        self.prepare_builder()?
+            .foreign(script_pubkey, script_sig, witness)
+    }
+
+    pub fn custom(
+        &self,
+        custom: &Proto::mod_Input::CustomInput,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        use Proto::mod_Input::mod_TemplateItem::OneOfitem as TemplateItemType;
+
+        let script_pubkey = Script::from(custom.script_pubkey.to_vec());
+        let script_code = Script::from(custom.script_code.to_vec());
+        if script_pubkey.is_empty() || script_code.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Custom input scriptPubkey and scriptCode must be provided");
+        }
+        let pubkey = ecdsa::secp256k1::PublicKey::try_from(custom.public_key.as_ref())
+            .into_tw()
+            .context("Invalid custom input public key")?;
+
+        let template_items = |items: &[Proto::mod_Input::TemplateItem]| {
+            items
+                .iter()
+                .map(|item| match item.item {
+                    TemplateItemType::data(ref data) => Ok(TemplateItem::Data(data.to_vec())),
+                    TemplateItemType::signature(_) => Ok(TemplateItem::Signature),
+                    TemplateItemType::None => {
+                        SigningError::err(SigningErrorType::Error_invalid_params)
+                            .context("Empty custom input template item")
+                    },
+                })
+                .collect::<SigningResult<Vec<_>>>()
+        };
+        let template = Template {
+            script_sig: template_items(&custom.script_sig)?,
+            witness: template_items(&custom.witness)?,
+        };
+        let has_signature = template
+            .script_sig
+            .iter()
+            .chain(template.witness.iter())
+            .any(|item| matches!(item, TemplateItem::Signature));
+        if !has_signature {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Custom input templates must contain a signature placeholder");
+        }
+
+        let signing_method = if custom.segwit {
+            SigningMethod::Segwit
+        } else {
+            SigningMethod::Legacy
+        };
+        self.prepare_builder()?.custom(
+            script_pubkey,
+            script_code,
+            signing_method,
+            &pubkey,
+            template,
+        )
+    }
+
     pub fn prepare_builder(&self) -> SigningResult<UtxoBuilder> {
         let OutPoint { hash, index } = parse_out_point(&self.input.out_point)?;
-        let sighash_ty = SighashType::from_u32(self.input.sighash_type)?;
+        let sighash_ty = self.sighash_type()?;
 
         if self.input.value < 0 {
             return SigningError::err(SigningErrorType::Error_invalid_utxo_amount)
                 .context("UTXO amount cannot be negative");
         }
+        if let Some(max_money) = self.chain_info.max_money::<Context>() {
+            if self.input.value > max_money {
+                return SigningError::err(SigningErrorType::Error_invalid_utxo_amount)
+                    .context(format!("UTXO amount cannot exceed {max_money} satoshis"));
+            }
+        }
 
         let sequence = self
             .input
@@ -194,35 +323,109 @@ impl<'a, Context: UtxoContext> UtxoProtobuf<'a, Context> {
             .prev_index(index)
             .sequence(sequence)
             .amount(self.input.value)
-            .sighash_type(sighash_ty))
+            .sighash_type(sighash_ty)
+            .must_spend(self.input.must_spend)
+            .one_prevout(self.input.one_prevout))
+    }
+
+    /// Parses the input sighash type, applying the experimental BIP-118 modifier if requested.
+    fn sighash_type(&self) -> SigningResult<SighashType> {
+        let mut sighash_ty = SighashType::from_u32(self.input.sighash_type)?;
+        // Fork chains reject signatures without the replay protection flag.
+        if Context::SIGHASH_ALGORITHM == SighashAlgorithm::ForkId {
+            sighash_ty = sighash_ty.with_fork_id();
+        }
+        match self.input.anyprevout {
+            Proto::AnyPrevoutType::NoAnyPrevout => Ok(sighash_ty),
+            #[cfg(feature = "anyprevout")]
+            Proto::AnyPrevoutType::AnyPrevout => {
+                Ok(sighash_ty.with_anyprevout(AnyPrevout::AnyPrevout))
+            },
+            #[cfg(feature = "anyprevout")]
+            Proto::AnyPrevoutType::AnyPrevoutAnyScript => {
+                Ok(sighash_ty.with_anyprevout(AnyPrevout::AnyPrevoutAnyScript))
+            },
+            #[cfg(not(feature = "anyprevout"))]
+            Proto::AnyPrevoutType::AnyPrevout | Proto::AnyPrevoutType::AnyPrevoutAnyScript => {
+                SigningError::err(SigningErrorType::Error_not_supported)
+                    .context("ANYPREVOUT sighash type requires the `anyprevout` feature")
+            },
+        }
     }
 
-    /// Tries to convert [`Proto::PublicKeyOrHash`] to [`Hash<N>`].
+    /// Tries to get the public key bytes from [`Proto::PublicKeyOrHash`].
     /// Please note `P2PKH` and `P2WPKH` use the same `ripemd(sha256(x))` hash function.
-    fn get_ecdsa_pubkey_from_proto(
-        &self,
-        input: &Proto::PublicKeyOrHash,
-    ) -> SigningResult<ecdsa::secp256k1::PublicKey> {
+    fn get_ecdsa_pubkey_data_from_proto<'b>(
+        &'b self,
+        input: &'b Proto::PublicKeyOrHash,
+    ) -> SigningResult<&'b [u8]> {
         use Proto::mod_PublicKeyOrHash::OneOfvariant as PublicKeyOrHashType;
 
-        let pubkey_data = match input.variant {
-            PublicKeyOrHashType::pubkey(ref pubkey) => pubkey.as_ref(),
+        match input.variant {
+            PublicKeyOrHashType::pubkey(ref pubkey) => Ok(pubkey.as_ref()),
             PublicKeyOrHashType::hash(ref hash) => {
                 let hash = H160::try_from(hash.as_ref())
                     .tw_err(|_| SigningErrorType::Error_invalid_params)
                     .context("Expected 20 bytes public key hash")?;
-                self.public_keys.get_public_key(&hash)?
+                self.public_keys.get_public_key(&hash)
             },
-            PublicKeyOrHashType::None => {
-                return SigningError::err(SigningErrorType::Error_invalid_params)
-                    .context("Expected a public key or its hash")
-            },
-        };
+            PublicKeyOrHashType::None => SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Expected a public key or its hash"),
+        }
+    }
+}
 
-        ecdsa::secp256k1::PublicKey::try_from(pubkey_data)
-            .into_tw()
-            .context("Expected a valid ecdsa secp256k1 public key")
+/// Checks that the UTXO amount is plausible for its scriptPubkey type.
+/// Zero-value outputs are non-standard except for Pay-to-Anchor, and OP_RETURN outputs can never be spent.
+fn check_utxo_amount(utxo_args: &UtxoToSign) -> SigningResult<()> {
+    let script_pubkey = &utxo_args.prevout_script_pubkey;
+    if conditions::is_op_return(script_pubkey) {
+        return SigningError::err(SigningErrorType::Error_invalid_utxo)
+            .context("OP_RETURN output is provably unspendable");
     }
+    if utxo_args.amount == 0 && !conditions::is_p2a(script_pubkey) {
+        return SigningError::err(SigningErrorType::Error_invalid_utxo_amount)
+            .context("UTXO amount must be positive unless it is a Pay-to-Anchor output");
+    }
+    Ok(())
+}
+
+/// Verifies that the UTXO is the `vout` output of the given previous transaction.
+fn verify_prev_tx(
+    prev_tx: &[u8],
+    utxo: &TransactionInput,
+    utxo_args: &UtxoToSign,
+) -> SigningResult<()> {
+    let prev_tx: bitcoin::Transaction = bitcoin::consensus::deserialize(prev_tx)
+        .tw_err(|_| SigningErrorType::Error_input_parse)
+        .context("Error parsing the previous transaction")?;
+
+    let prev_txid = prev_tx.txid().to_byte_array();
+    if prev_txid.as_slice() != utxo.previous_output.hash.as_slice() {
+        return SigningError::err(SigningErrorType::Error_invalid_utxo)
+            .context("The previous transaction does not match the out-point txid");
+    }
+
+    let vout = utxo.previous_output.index as usize;
+    let prevout = prev_tx
+        .output
+        .get(vout)
+        .or_tw_err(SigningErrorType::Error_invalid_utxo)
+        .with_context(|| format!("The previous transaction has no output '{vout}'"))?;
+
+    if prevout.value as Amount != utxo_args.amount {
+        return SigningError::err(SigningErrorType::Error_invalid_utxo_amount).context(format!(
+            "The UTXO amount '{}' differs from the previous transaction output '{}'",
+            utxo_args.amount, prevout.value
+        ));
+    }
+
+    if prevout.script_pubkey.as_bytes() != utxo_args.prevout_script_pubkey.as_slice() {
+        return SigningError::err(SigningErrorType::Error_invalid_utxo)
+            .context("The claimed scriptPubkey differs from the previous transaction output");
+    }
+
+    Ok(())
 }
 
 pub fn parse_out_point(maybe_out_point: &Option<Proto::OutPoint>) -> SigningResult<OutPoint> {
@@ -231,12 +434,11 @@ pub fn parse_out_point(maybe_out_point: &Option<Proto::OutPoint>) -> SigningResu
         .or_tw_err(SigningErrorType::Error_invalid_params)
         .context("No OutPoint provided for a UTXO")?;
 
-    let hash = H256::try_from(out_point.hash.as_ref())
-        .tw_err(|_| SigningErrorType::Error_invalid_params)
-        .context("Invalid previous txid")?;
+    let txid =
+        Txid::from_internal_bytes(out_point.hash.as_ref()).context("Invalid previous txid")?;
 
     Ok(OutPoint {
-        hash,
+        hash: txid.into(),
         index: out_point.vout,
     })
 }
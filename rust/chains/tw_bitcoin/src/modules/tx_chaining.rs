@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Chaining transactions on the unconfirmed change, e.g. to pay several recipients one by one
+//! without waiting for the previous payment to be confirmed.
+
+use crate::modules::explain::BitcoinExplain;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::sighash::SighashBase;
+use tw_utxo::transaction::transaction_parts::Txid;
+
+type ClaimingScript<'a> = Proto::mod_Input::OneOfclaiming_script<'a>;
+type TransactionType<'a> = Proto::mod_SigningInput::OneOftransaction<'a>;
+
+pub struct BitcoinTxChaining<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> BitcoinTxChaining<Context> {
+    /// Returns the change outputs of the transaction signed with the given `input` as UTXOs,
+    /// so they can be spent before the transaction is confirmed.
+    /// The UTXOs are marked as `unconfirmed`.
+    pub fn change_utxos(
+        coin: &dyn CoinContext,
+        input: &Proto::SigningInput,
+        output: &Proto::SigningOutput,
+    ) -> SigningResult<Vec<Proto::Input<'static>>> {
+        let report = BitcoinExplain::<Context>::explain_signed(coin, input, output)
+            .context("Error explaining the previous transaction")?;
+        let txid = Txid::from_rpc_bytes(&output.txid)?;
+
+        let change_utxos = report
+            .outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, tx_output)| tx_output.is_change)
+            .map(|(vout, tx_output)| Proto::Input {
+                out_point: Some(Proto::OutPoint {
+                    hash: Cow::from(txid.to_internal_bytes().to_vec()),
+                    vout: vout as u32,
+                }),
+                value: tx_output.value,
+                sighash_type: SighashBase::All as u32,
+                claiming_script: ClaimingScript::script_data(Cow::from(
+                    tx_output.script_pubkey.to_vec(),
+                )),
+                unconfirmed: true,
+                ..Proto::Input::default()
+            })
+            .collect();
+        Ok(change_utxos)
+    }
+
+    /// Wires the change of the previously signed transaction into the `next` signing input,
+    /// so the follow-up transaction spends the change regardless of the input selector.
+    /// The change UTXOs are prepended to the `next` transaction builder inputs.
+    pub fn chain_on_change<'a>(
+        coin: &dyn CoinContext,
+        prev_input: &Proto::SigningInput,
+        prev_output: &Proto::SigningOutput,
+        mut next: Proto::SigningInput<'a>,
+    ) -> SigningResult<Proto::SigningInput<'a>> {
+        let TransactionType::builder(ref mut tx_builder) = next.transaction else {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The follow-up transaction must be constructed by `TransactionBuilder`");
+        };
+
+        let change_utxos = Self::change_utxos(coin, prev_input, prev_output)?;
+        if change_utxos.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The previous transaction has no change outputs to spend");
+        }
+
+        let change_utxos = change_utxos.into_iter().map(|utxo| Proto::Input {
+            must_spend: true,
+            ..utxo
+        });
+        tx_builder.inputs.splice(0..0, change_utxos);
+        Ok(next)
+    }
+}
@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Pre-signed vaults.
+//!
+//! The deposit output is locked to a one-time vault key, which is used to pre-sign
+//! the unvault and the emergency-sweep transactions, and must be deleted right after.
+//! So the deposit can be spent by the pre-signed transactions only:
+//!
+//! 1. The unvault transaction moves the funds to the unvault output, which can be spent
+//!    by the hot key once the output is `unvault_delay` blocks old.
+//! 2. Within the delay, the emergency-sweep transaction moves the funds from the unvault output
+//!    to the emergency destination, e.g. if the unvault transaction is broadcast by an attacker.
+
+use crate::modules::signer::BitcoinSigner;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::error::prelude::*;
+use tw_keypair::schnorr;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::context::UtxoContext;
+use tw_utxo::script::standard_script::conditions;
+use tw_utxo::script::Script;
+use tw_utxo::sighash::SighashBase;
+use tw_utxo::transaction::transaction_parts::{Amount, Txid};
+
+type InputBuilderType<'a> = Proto::mod_Input::mod_InputBuilder::OneOfvariant<'a>;
+type OutputBuilderType<'a> = Proto::mod_Output::mod_OutputBuilder::OneOfvariant<'a>;
+type RecipientType<'a> = Proto::mod_Output::OneOfto_recipient<'a>;
+type TransactionType<'a> = Proto::mod_SigningInput::OneOftransaction<'a>;
+
+pub struct VaultTemplate<'a> {
+    /// One-time key the deposit is locked to. Must be deleted once the bundle is built.
+    pub vault_private_key: schnorr::PrivateKey,
+    /// Amount of satoshis locked in the vault.
+    pub deposit_amount: Amount,
+    /// Key that spends the unvault output after the `unvault_delay`.
+    pub hot_public_key: schnorr::PublicKey,
+    /// Number of blocks the unvault output can be swept to the emergency destination within.
+    pub unvault_delay: u16,
+    /// Fixed fee of the unvault transaction.
+    pub unvault_fee: Amount,
+    /// Emergency destination, usually a cold wallet. The output value is ignored.
+    pub emergency_recipient: RecipientType<'a>,
+    /// Fixed fee of the emergency-sweep transaction.
+    pub emergency_fee: Amount,
+}
+
+/// The deposit transaction and the transactions pre-signed with the vault key.
+pub struct VaultBundle {
+    pub deposit: Proto::SigningOutput<'static>,
+    pub unvault: Proto::SigningOutput<'static>,
+    pub emergency: Proto::SigningOutput<'static>,
+}
+
+pub struct BitcoinVault<Context: UtxoContext> {
+    _phantom: PhantomData<Context>,
+}
+
+impl<Context: UtxoContext> BitcoinVault<Context> {
+    /// Signs the deposit transaction constructed by `deposit_input.builder`, where the deposit output
+    /// is prepended to the builder outputs, then pre-signs the unvault and the emergency-sweep transactions.
+    ///
+    /// Please note the deposit transaction must be broadcast only after the bundle is stored,
+    /// and the vault private key is deleted.
+    pub fn build_bundle(
+        coin: &dyn CoinContext,
+        deposit_input: &Proto::SigningInput,
+        template: &VaultTemplate,
+    ) -> SigningResult<VaultBundle> {
+        let TransactionType::builder(ref deposit_builder) = deposit_input.transaction else {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The deposit transaction must be constructed by `TransactionBuilder`");
+        };
+        if template.unvault_delay == 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The unvault delay must be at least one block");
+        }
+        if template.unvault_fee <= 0 || template.emergency_fee <= 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The pre-signed transactions must pay a fee");
+        }
+        if template.deposit_amount <= 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The deposit amount must be positive");
+        }
+        let fees_not_covered = || {
+            SigningError::new(SigningErrorType::Error_invalid_params)
+                .context("The deposit amount doesn't cover the pre-signed transactions fees")
+        };
+        let unvault_amount = template
+            .deposit_amount
+            .checked_sub(template.unvault_fee)
+            .ok_or_else(fees_not_covered)?;
+        let emergency_amount = unvault_amount
+            .checked_sub(template.emergency_fee)
+            .filter(|emergency_amount| *emergency_amount > 0)
+            .ok_or_else(fees_not_covered)?;
+
+        let vault_pubkey = template.vault_private_key.public();
+        // The pre-signed transactions borrow the key, so it's not copied out of the zeroizing storage.
+        let vault_private_key = template.vault_private_key.to_zeroizing_vec();
+        let hot_pubkey = template.hot_public_key.x_only().bytes();
+
+        // 1. Sign the deposit transaction.
+        let mut deposit_builder = deposit_builder.clone();
+        deposit_builder.outputs.insert(
+            0,
+            Proto::Output {
+                value: template.deposit_amount,
+                to_recipient: recipient_builder(OutputBuilderType::p2tr_key_path(Cow::from(
+                    vault_pubkey.to_vec(),
+                ))),
+                ..Proto::Output::default()
+            },
+        );
+        let deposit_script = conditions::new_p2tr_key_path(&vault_pubkey.compressed());
+        let deposit = sign_checked::<Context>(
+            coin,
+            &Proto::SigningInput {
+                transaction: TransactionType::builder(deposit_builder.clone()),
+                ..deposit_input.clone()
+            },
+        )
+        .context("Error signing the deposit transaction")?;
+        let deposit_out_point = find_out_point(&deposit, &deposit_script)?;
+
+        // 2. Pre-sign the unvault transaction that spends the deposit.
+        let emergency_leaf = conditions::new_tapscript_checksig(&vault_pubkey.x_only().bytes());
+        let hot_leaf = conditions::new_tapscript_csv_checksig(template.unvault_delay, &hot_pubkey);
+        let unvault_tree = Proto::TaprootScriptTree {
+            leaves: [&emergency_leaf, &hot_leaf]
+                .into_iter()
+                .map(|leaf| Proto::mod_TaprootScriptTree::Leaf {
+                    script: Cow::from(leaf.to_vec()),
+                    depth: 1,
+                    ..Proto::mod_TaprootScriptTree::Leaf::default()
+                })
+                .collect(),
+            huffman: false,
+        };
+
+        let unvault_input = Proto::Input {
+            out_point: Some(deposit_out_point),
+            value: template.deposit_amount,
+            sighash_type: SighashBase::All as u32,
+            claiming_script: claiming_builder(InputBuilderType::p2tr_key_path(Cow::from(
+                vault_pubkey.to_vec(),
+            ))),
+            ..Proto::Input::default()
+        };
+        let unvault_output = Proto::Output {
+            value: unvault_amount,
+            to_recipient: recipient_builder(OutputBuilderType::p2tr_script_path(
+                Proto::mod_Output::OutputTaprootScriptPath {
+                    internal_key: Cow::from(vault_pubkey.to_vec()),
+                    tree: Some(unvault_tree.clone()),
+                    ..Proto::mod_Output::OutputTaprootScriptPath::default()
+                },
+            )),
+            ..Proto::Output::default()
+        };
+        let unvault = sign_checked::<Context>(
+            coin,
+            &presigned_input(
+                deposit_input,
+                &deposit_builder,
+                &vault_private_key,
+                unvault_input,
+                unvault_output,
+            ),
+        )
+        .context("Error pre-signing the unvault transaction")?;
+
+        // 3. Pre-sign the emergency-sweep transaction that spends the unvault output via the vault key leaf.
+        let unvault_out_point = Proto::OutPoint {
+            hash: Cow::from(
+                Txid::from_rpc_bytes(&unvault.txid)?
+                    .to_internal_bytes()
+                    .to_vec(),
+            ),
+            vout: 0,
+        };
+        let emergency_input = Proto::Input {
+            out_point: Some(unvault_out_point),
+            value: unvault_amount,
+            sighash_type: SighashBase::All as u32,
+            claiming_script: claiming_builder(InputBuilderType::p2tr_script_path(
+                Proto::mod_Input::InputTaprootScriptPath {
+                    internal_key: Cow::from(vault_pubkey.to_vec()),
+                    payload: Cow::from(emergency_leaf.to_vec()),
+                    tree: Some(unvault_tree),
+                    ..Proto::mod_Input::InputTaprootScriptPath::default()
+                },
+            )),
+            ..Proto::Input::default()
+        };
+        let emergency_output = Proto::Output {
+            value: emergency_amount,
+            to_recipient: template.emergency_recipient.clone(),
+            ..Proto::Output::default()
+        };
+        let emergency = sign_checked::<Context>(
+            coin,
+            &presigned_input(
+                deposit_input,
+                &deposit_builder,
+                &vault_private_key,
+                emergency_input,
+                emergency_output,
+            ),
+        )
+        .context("Error pre-signing the emergency-sweep transaction")?;
+
+        Ok(VaultBundle {
+            deposit,
+            unvault,
+            emergency,
+        })
+    }
+}
+
+/// Creates a signing input of a pre-signed transaction that spends the whole `input` to the `output`,
+/// so the fee is fixed to the difference.
+fn presigned_input<'a>(
+    deposit_input: &Proto::SigningInput<'a>,
+    deposit_builder: &Proto::TransactionBuilder<'a>,
+    vault_private_key: &'a [u8],
+    input: Proto::Input<'a>,
+    output: Proto::Output<'a>,
+) -> Proto::SigningInput<'a> {
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![input],
+        outputs: vec![output],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: deposit_builder.dust_policy.clone(),
+        ..Proto::TransactionBuilder::default()
+    };
+    Proto::SigningInput {
+        private_keys: vec![Cow::Borrowed(vault_private_key)],
+        chain_info: deposit_input.chain_info.clone(),
+        transaction: TransactionType::builder(builder),
+        ..Proto::SigningInput::default()
+    }
+}
+
+fn sign_checked<Context: UtxoContext>(
+    coin: &dyn CoinContext,
+    input: &Proto::SigningInput,
+) -> SigningResult<Proto::SigningOutput<'static>> {
+    let output = BitcoinSigner::<Context>::sign_impl(coin, input)?;
+    // The transaction can be rejected by the signing policy.
+    if output.error != SigningErrorType::OK {
+        return SigningError::err(output.error).context(output.error_message.to_string());
+    }
+    Ok(output)
+}
+
+fn find_out_point(
+    output: &Proto::SigningOutput,
+    script_pubkey: &Script,
+) -> SigningResult<Proto::OutPoint<'static>> {
+    let tx = output
+        .transaction
+        .as_ref()
+        .or_tw_err(SigningErrorType::Error_internal)
+        .context("No signed transaction")?;
+    let vout = tx
+        .outputs
+        .iter()
+        .position(|tx_output| tx_output.script_pubkey.as_ref() == script_pubkey.as_slice())
+        .or_tw_err(SigningErrorType::Error_internal)
+        .context("The deposit output is not found")?;
+
+    let txid = Txid::from_rpc_bytes(&output.txid)?;
+    Ok(Proto::OutPoint {
+        hash: Cow::from(txid.to_internal_bytes().to_vec()),
+        vout: vout as u32,
+    })
+}
+
+fn claiming_builder(variant: InputBuilderType) -> Proto::mod_Input::OneOfclaiming_script {
+    Proto::mod_Input::OneOfclaiming_script::script_builder(Proto::mod_Input::InputBuilder {
+        variant,
+    })
+}
+
+fn recipient_builder(variant: OutputBuilderType) -> RecipientType {
+    RecipientType::builder(Proto::mod_Output::OutputBuilder { variant })
+}
@@ -3,15 +3,57 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::address::Address;
+use tw_bitcoin::context::ChainParams;
+use tw_coin_entry::coin_context::CoinContext;
+use tw_coin_entry::derivation::Derivation;
 use tw_coin_entry::error::prelude::*;
+use tw_coin_entry::prefix::BitcoinBase58Prefix;
+use tw_keypair::tw::PublicKey;
+use tw_utxo::constants::MAX_MONEY;
 use tw_utxo::context::{AddressPrefixes, UtxoContext};
+use tw_utxo::dust::DustPolicy;
 use tw_utxo::script::Script;
+use tw_utxo::sighash::SighashAlgorithm;
+use tw_utxo::transaction::transaction_parts::Amount;
 
+/// The dust threshold of a P2PKH output enforced by the Bitcoin Cash nodes.
+const DUST_THRESHOLD: Amount = 546;
+
+#[derive(Default)]
 pub struct BitcoinCashContext;
 
+impl ChainParams for BitcoinCashContext {
+    // TODO `BitcoinCash` should probably have its own prefix enum with Cash and Base58 prefixes.
+    type AddressPrefix = BitcoinBase58Prefix;
+
+    fn parse_address(
+        coin: &dyn CoinContext,
+        address: &str,
+        prefix: Option<Self::AddressPrefix>,
+    ) -> AddressResult<Self::Address> {
+        Address::from_str_with_coin_and_prefix(coin, address, prefix)
+    }
+
+    fn derive_address(
+        coin: &dyn CoinContext,
+        public_key: PublicKey,
+        _derivation: Derivation,
+        prefix: Option<Self::AddressPrefix>,
+    ) -> AddressResult<Self::Address> {
+        let public_key = public_key
+            .to_secp256k1()
+            .ok_or(AddressError::PublicKeyTypeMismatch)?;
+        Address::p2pkh_with_coin_and_prefix(coin, public_key, prefix)
+    }
+}
+
 impl UtxoContext for BitcoinCashContext {
     type Address = Address;
 
+    const SIGHASH_ALGORITHM: SighashAlgorithm = SighashAlgorithm::ForkId;
+    const DEFAULT_DUST_POLICY: Option<DustPolicy> = Some(DustPolicy::FixedAmount(DUST_THRESHOLD));
+    const MAX_MONEY: Option<Amount> = Some(MAX_MONEY);
+
     fn addr_to_script_pubkey(
         addr: &Self::Address,
         prefixes: AddressPrefixes,
@@ -2,94 +2,7 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::address::Address;
 use crate::context::BitcoinCashContext;
-use std::str::FromStr;
-use tw_bitcoin::modules::compiler::BitcoinCompiler;
-use tw_bitcoin::modules::planner::BitcoinPlanner;
-use tw_bitcoin::modules::signer::BitcoinSigner;
-use tw_bitcoin::modules::transaction_util::BitcoinTransactionUtil;
-use tw_coin_entry::coin_context::CoinContext;
-use tw_coin_entry::coin_entry::{CoinEntry, PublicKeyBytes, SignatureBytes};
-use tw_coin_entry::derivation::Derivation;
-use tw_coin_entry::error::prelude::*;
-use tw_coin_entry::modules::json_signer::NoJsonSigner;
-use tw_coin_entry::modules::message_signer::NoMessageSigner;
-use tw_coin_entry::modules::transaction_decoder::NoTransactionDecoder;
-use tw_coin_entry::modules::wallet_connector::NoWalletConnector;
-use tw_coin_entry::prefix::BitcoinBase58Prefix;
-use tw_keypair::tw::PublicKey;
-use tw_proto::BitcoinV2::Proto;
+use tw_bitcoin::entry::UtxoEntry;
 
-pub struct BitcoinCashEntry;
-
-impl CoinEntry for BitcoinCashEntry {
-    // TODO `BitcoinCash` should probably have its own prefix enum with Cash and Base58 prefixes.
-    type AddressPrefix = BitcoinBase58Prefix;
-    type Address = Address;
-    type SigningInput<'a> = Proto::SigningInput<'a>;
-    type SigningOutput = Proto::SigningOutput<'static>;
-    type PreSigningOutput = Proto::PreSigningOutput<'static>;
-
-    // Optional modules:
-    type JsonSigner = NoJsonSigner;
-    type PlanBuilder = BitcoinPlanner<BitcoinCashContext>;
-    type MessageSigner = NoMessageSigner;
-    type WalletConnector = NoWalletConnector;
-    type TransactionDecoder = NoTransactionDecoder;
-    type TransactionUtil = BitcoinTransactionUtil;
-
-    #[inline]
-    fn parse_address(
-        &self,
-        coin: &dyn CoinContext,
-        address: &str,
-        prefix: Option<Self::AddressPrefix>,
-    ) -> AddressResult<Self::Address> {
-        Address::from_str_with_coin_and_prefix(coin, address, prefix)
-    }
-
-    #[inline]
-    fn parse_address_unchecked(&self, address: &str) -> AddressResult<Self::Address> {
-        Address::from_str(address)
-    }
-
-    #[inline]
-    fn derive_address(
-        &self,
-        coin: &dyn CoinContext,
-        public_key: PublicKey,
-        _derivation: Derivation,
-        prefix: Option<Self::AddressPrefix>,
-    ) -> AddressResult<Self::Address> {
-        let public_key = public_key
-            .to_secp256k1()
-            .ok_or(AddressError::PublicKeyTypeMismatch)?;
-        Address::p2pkh_with_coin_and_prefix(coin, public_key, prefix)
-    }
-
-    #[inline]
-    fn sign(&self, coin: &dyn CoinContext, input: Self::SigningInput<'_>) -> Self::SigningOutput {
-        BitcoinSigner::<BitcoinCashContext>::sign(coin, &input)
-    }
-
-    #[inline]
-    fn preimage_hashes(
-        &self,
-        coin: &dyn CoinContext,
-        input: Self::SigningInput<'_>,
-    ) -> Self::PreSigningOutput {
-        BitcoinCompiler::<BitcoinCashContext>::preimage_hashes(coin, input)
-    }
-
-    #[inline]
-    fn compile(
-        &self,
-        coin: &dyn CoinContext,
-        input: Self::SigningInput<'_>,
-        signatures: Vec<SignatureBytes>,
-        public_keys: Vec<PublicKeyBytes>,
-    ) -> Self::SigningOutput {
-        BitcoinCompiler::<BitcoinCashContext>::compile(coin, input, signatures, public_keys)
-    }
-}
+pub type BitcoinCashEntry = UtxoEntry<BitcoinCashContext>;
@@ -41,6 +41,16 @@ impl LegacyAddress {
         LegacyAddress::new(p2pkh_prefix, &public_key_hash)
     }
 
+    /// Derives a P2PKH address from the uncompressed public key (65 bytes),
+    /// as old paper wallets used to do.
+    pub fn p2pkh_with_uncompressed_public_key(
+        p2pkh_prefix: u8,
+        public_key: &ecdsa::secp256k1::PublicKey,
+    ) -> AddressResult<LegacyAddress> {
+        let public_key_hash = sha256_ripemd(public_key.uncompressed().as_slice());
+        LegacyAddress::new(p2pkh_prefix, &public_key_hash)
+    }
+
     /// Tries to parse a `LegacyAddress` and check if
     pub fn p2pkh_with_coin_and_prefix(
         coin: &dyn CoinContext,
@@ -5,6 +5,7 @@
 pub mod derivation;
 pub mod legacy;
 pub mod segwit;
+pub mod silent_payment;
 pub mod standard_bitcoin;
 pub mod taproot;
 pub mod witness_program;
@@ -79,6 +79,10 @@ impl SegwitAddress {
         SegwitAddress::from_str_checked(s, &hrp)
     }
 
+    pub fn hrp(&self) -> &str {
+        self.inner.hrp()
+    }
+
     pub fn witness_program(&self) -> &[u8] {
         self.inner.witness_program()
     }
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use bech32::{FromBase32, ToBase32};
+use core::fmt;
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H264;
+use tw_memory::Data;
+
+/// cbindgen:ignore
+pub const SILENT_PAYMENT_V0: u8 = 0;
+/// The version 31 is reserved for a backward incompatible change.
+/// cbindgen:ignore
+pub const SILENT_PAYMENT_INVALID_VERSION: u8 = 31;
+/// Silent Payment address HRP on the Bitcoin mainnet.
+/// cbindgen:ignore
+pub const SILENT_PAYMENT_MAINNET_HRP: &str = "sp";
+/// Silent Payment address HRP on the Bitcoin testnets.
+/// cbindgen:ignore
+pub const SILENT_PAYMENT_TESTNET_HRP: &str = "tsp";
+
+/// The payload is a scan public key followed by a spend public key.
+const PAYLOAD_LEN: usize = H264::LEN * 2;
+
+/// BIP-352 Silent Payment address.
+/// https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki#address-encoding
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SilentPaymentAddress {
+    hrp: String,
+    scan_pubkey: H264,
+    spend_pubkey: H264,
+}
+
+impl SilentPaymentAddress {
+    pub fn new(
+        hrp: String,
+        scan_pubkey: H264,
+        spend_pubkey: H264,
+    ) -> AddressResult<SilentPaymentAddress> {
+        for pubkey in [&scan_pubkey, &spend_pubkey] {
+            secp256k1::PublicKey::from_slice(pubkey.as_slice())
+                .map_err(|_| AddressError::InvalidInput)?;
+        }
+        Ok(SilentPaymentAddress {
+            hrp,
+            scan_pubkey,
+            spend_pubkey,
+        })
+    }
+
+    pub fn from_str_checked(s: &str, expected_hrp: &str) -> AddressResult<SilentPaymentAddress> {
+        let address = Self::from_str(s)?;
+        if address.hrp != expected_hrp {
+            return Err(AddressError::InvalidHrp);
+        }
+        Ok(address)
+    }
+
+    pub fn hrp(&self) -> &str {
+        &self.hrp
+    }
+
+    /// The public key the receiver scans the transactions with.
+    pub fn scan_pubkey(&self) -> &H264 {
+        &self.scan_pubkey
+    }
+
+    /// The public key the receiver spends the outputs with, possibly labeled.
+    pub fn spend_pubkey(&self) -> &H264 {
+        &self.spend_pubkey
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, payload_u5, checksum_variant) =
+            bech32::decode(s).map_err(|_| AddressError::FromBech32Error)?;
+        if checksum_variant != bech32::Variant::Bech32m {
+            return Err(AddressError::InvalidInput);
+        }
+
+        let Some((version, payload)) = payload_u5.split_first() else {
+            return Err(AddressError::InvalidInput);
+        };
+        let payload = Data::from_base32(payload).map_err(|_| AddressError::FromBech32Error)?;
+
+        // Future versions are expected to keep the V0 payload as a prefix.
+        let payload = match version.to_u8() {
+            SILENT_PAYMENT_V0 if payload.len() == PAYLOAD_LEN => payload.as_slice(),
+            SILENT_PAYMENT_V0 => return Err(AddressError::InvalidInput),
+            SILENT_PAYMENT_INVALID_VERSION => return Err(AddressError::Unsupported),
+            _ if payload.len() >= PAYLOAD_LEN => &payload[..PAYLOAD_LEN],
+            _ => return Err(AddressError::InvalidInput),
+        };
+
+        let (scan_pubkey, spend_pubkey) = payload.split_at(H264::LEN);
+        let scan_pubkey = H264::try_from(scan_pubkey).map_err(|_| AddressError::InvalidInput)?;
+        let spend_pubkey = H264::try_from(spend_pubkey).map_err(|_| AddressError::InvalidInput)?;
+        SilentPaymentAddress::new(hrp, scan_pubkey, spend_pubkey)
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version_u5 = bech32::u5::try_from_u8(SILENT_PAYMENT_V0).map_err(|_| fmt::Error)?;
+
+        let mut payload = Vec::with_capacity(PAYLOAD_LEN);
+        payload.extend_from_slice(self.scan_pubkey.as_slice());
+        payload.extend_from_slice(self.spend_pubkey.as_slice());
+
+        let mut data = vec![version_u5];
+        data.extend(payload.to_base32());
+
+        let address_str =
+            bech32::encode(&self.hrp, data, bech32::Variant::Bech32m).map_err(|_| fmt::Error)?;
+        write!(f, "{address_str}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+
+    #[test]
+    fn test_silent_payment_address_to_from_str() {
+        let address = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+        assert_eq!(address.hrp(), SILENT_PAYMENT_MAINNET_HRP);
+        assert_eq!(
+            address.scan_pubkey(),
+            &H264::from("0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4")
+        );
+        assert_eq!(
+            address.spend_pubkey(),
+            &H264::from("025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36")
+        );
+        assert_eq!(address.to_string(), ADDRESS);
+
+        SilentPaymentAddress::from_str_checked(ADDRESS, SILENT_PAYMENT_TESTNET_HRP).unwrap_err();
+    }
+
+    #[test]
+    fn test_silent_payment_address_invalid() {
+        // Invalid checksum.
+        let mut invalid = ADDRESS.to_string();
+        invalid.pop();
+        invalid.push('q');
+        SilentPaymentAddress::from_str(&invalid).unwrap_err();
+
+        // Taproot address.
+        SilentPaymentAddress::from_str(
+            "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf",
+        )
+        .unwrap_err();
+    }
+}
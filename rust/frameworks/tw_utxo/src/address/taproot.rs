@@ -117,6 +117,10 @@ impl TaprootAddress {
         TaprootAddress::from_str_checked(s, &hrp)
     }
 
+    pub fn hrp(&self) -> &str {
+        self.inner.hrp()
+    }
+
     pub fn witness_program(&self) -> &[u8] {
         self.inner.witness_program()
     }
@@ -2,6 +2,16 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::transaction::transaction_parts::Amount;
+
 /// A standard transaction is limited to 400k weight units (WU).
 /// https://bitcoin.stackexchange.com/questions/35570/what-is-the-maximum-number-of-inputs-outputs-a-transaction-can-have
 pub const MAX_TRANSACTION_WEIGHT: usize = 400_000;
+/// `nLockTime` values below the threshold are interpreted as block heights, otherwise as UNIX timestamps.
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+/// The max sequence that enables `nLockTime`, while not signaling replace-by-fee.
+pub const MAX_SEQUENCE_NONFINAL: u32 = 0xFFFF_FFFE;
+/// The number of satoshis in one BTC.
+pub const SATOSHIS_PER_BTC: Amount = 100_000_000;
+/// No amount larger than this (in satoshis) is valid, see `MAX_MONEY` in Bitcoin Core.
+pub const MAX_MONEY: Amount = 21_000_000 * SATOSHIS_PER_BTC;
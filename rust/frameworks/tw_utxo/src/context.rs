@@ -2,18 +2,35 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::dust::DustPolicy;
 use crate::script::Script;
+use crate::sighash::SighashAlgorithm;
+use crate::transaction::transaction_parts::Amount;
 use std::str::FromStr;
 use tw_coin_entry::error::prelude::{AddressError, SigningResult};
 
 pub struct AddressPrefixes {
     pub p2pkh_prefix: u8,
     pub p2sh_prefix: u8,
+    /// Segwit HRP. `None` if the chain doesn't support segwit addresses.
+    pub hrp: Option<String>,
 }
 
+/// Parameters of a Bitcoin-like chain, so a fork coin doesn't need to copy the signing code.
 pub trait UtxoContext {
     type Address: FromStr<Err = AddressError>;
 
+    /// Sighash algorithm the legacy inputs are signed with.
+    const SIGHASH_ALGORITHM: SighashAlgorithm = SighashAlgorithm::Standard;
+    /// Transaction version used if the signing request doesn't specify one.
+    const DEFAULT_TX_VERSION: u32 = 1;
+    /// Dust policy used if the signing request doesn't specify one.
+    /// `None` if the dust policy must be specified explicitly.
+    const DEFAULT_DUST_POLICY: Option<DustPolicy> = None;
+    /// The max amount in satoshis, see `MAX_MONEY` in Bitcoin Core.
+    /// `None` if the chain doesn't limit it, or the context is shared by chains with different limits.
+    const MAX_MONEY: Option<Amount> = None;
+
     fn addr_to_script_pubkey(
         addr: &Self::Address,
         prefixes: AddressPrefixes,
@@ -3,8 +3,10 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::dust::DustPolicy;
+use crate::modules::truc_policy::TRUC_VERSION;
 use crate::script::standard_script::conditions;
 use crate::transaction::transaction_interface::{TransactionInterface, TxOutputInterface};
+use crate::transaction::transaction_parts::Amount;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
@@ -23,6 +25,7 @@ impl<Transaction: TransactionInterface> DustFilter<Transaction> {
     }
 
     /// Filter dust UTXOs out.
+    /// Must-spend UTXOs are kept even if they are dust.
     /// Returns an error if there are no valid UTXOs.
     pub fn filter_inputs(
         &self,
@@ -30,29 +33,52 @@ impl<Transaction: TransactionInterface> DustFilter<Transaction> {
     ) -> SigningResult<UnsignedTransaction<Transaction>> {
         let dust_threshold = self.dust_policy.dust_threshold();
 
-        transaction.retain_inputs(|_utxo, utxo_args| utxo_args.amount >= dust_threshold)?;
+        transaction.retain_inputs(|_utxo, utxo_args| {
+            utxo_args.must_spend || utxo_args.amount >= dust_threshold
+        })?;
 
         Ok(transaction)
     }
 
     /// Checks if all transaction output amounts are greater or equal to a dust threshold.
+    /// A zero-fee TRUC (v3) transaction is allowed to have a single ephemeral dust output,
+    /// e.g. an anchor that is spent by a child transaction in the same package.
     pub fn check_outputs(
         &self,
         transaction: &UnsignedTransaction<Transaction>,
     ) -> SigningResult<()> {
-        let dust_threshold = self.dust_policy.dust_threshold();
-
-        let has_dust_output = transaction.transaction().outputs().iter().any(|output| {
-            if conditions::is_op_return(output.script_pubkey()) {
-                // Ignore the OP_RETURN output value. It can (or even should) be 0.
-                return false;
-            }
-            output.value() < dust_threshold
-        });
+        let dust_outputs =
+            dust_outputs(transaction.transaction(), self.dust_policy.dust_threshold());
 
-        if has_dust_output {
-            return SigningError::err(SigningErrorType::Error_dust_amount_requested);
+        match dust_outputs.len() {
+            0 => Ok(()),
+            1 if transaction.transaction().version() == TRUC_VERSION => {
+                if transaction.fee()? != 0 {
+                    return SigningError::err(SigningErrorType::Error_dust_amount_requested)
+                        .context(
+                            "Ephemeral dust output is allowed in zero-fee TRUC transactions only",
+                        );
+                }
+                Ok(())
+            },
+            _ => SigningError::err(SigningErrorType::Error_dust_amount_requested),
         }
-        Ok(())
     }
 }
+
+/// Returns indexes of the outputs which amounts are less than the dust threshold.
+pub fn dust_outputs<Transaction: TransactionInterface>(
+    transaction: &Transaction,
+    dust_threshold: Amount,
+) -> Vec<usize> {
+    transaction
+        .outputs()
+        .iter()
+        .enumerate()
+        .filter(|(_, output)| {
+            // Ignore the OP_RETURN output value. It can (or even should) be 0.
+            !conditions::is_op_return(output.script_pubkey()) && output.value() < dust_threshold
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
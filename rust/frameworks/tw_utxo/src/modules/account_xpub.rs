@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! scriptPubkeys derived from an account extended public key, e.g. `m/84'/0'/0'`.
+//! https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0049.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki
+
+use crate::script::standard_script::conditions;
+use crate::script::Script;
+use bitcoin::base58;
+use bitcoin::bip32::{ChildNumber, ExtendedPubKey};
+use secp256k1::SECP256K1;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::ripemd::bitcoin_hash_160;
+use tw_hash::{H160, H264};
+
+/// The external chain the receive addresses are derived at: `<account>/0/<index>`.
+pub const RECEIVE_CHAIN: u32 = 0;
+/// The internal chain the change addresses are derived at: `<account>/1/<index>`.
+pub const CHANGE_CHAIN: u32 = 1;
+
+/// BIP-32 serialized extended key length.
+const EXTENDED_KEY_LEN: usize = 78;
+
+/// BIP-32 mainnet and testnet public key versions.
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xCF];
+/// SLIP-132 `ypub`, `zpub`, `upub` and `vpub` versions.
+const YPUB_VERSION: [u8; 4] = [0x04, 0x9D, 0x7C, 0xB2];
+const ZPUB_VERSION: [u8; 4] = [0x04, 0xB2, 0x47, 0x46];
+const UPUB_VERSION: [u8; 4] = [0x04, 0x4A, 0x52, 0x62];
+const VPUB_VERSION: [u8; 4] = [0x04, 0x5F, 0x1C, 0xF6];
+
+/// The derivation scheme the account belongs to, defines the default type of the derived scriptPubkeys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DerivationScheme {
+    /// P2PKH.
+    Bip44,
+    /// P2SH-P2WPKH.
+    Bip49,
+    /// P2WPKH.
+    Bip84,
+    /// P2TR key-path.
+    Bip86,
+}
+
+impl DerivationScheme {
+    pub fn script_type(self) -> ScriptType {
+        match self {
+            DerivationScheme::Bip44 => ScriptType::P2pkh,
+            DerivationScheme::Bip49 => ScriptType::P2shP2wpkh,
+            DerivationScheme::Bip84 => ScriptType::P2wpkh,
+            DerivationScheme::Bip86 => ScriptType::P2tr,
+        }
+    }
+}
+
+/// The type of the scriptPubkeys derived from a single public key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScriptType {
+    P2pkh,
+    P2shP2wpkh,
+    P2wpkh,
+    /// P2TR key-path.
+    P2tr,
+}
+
+pub struct AccountXpub {
+    xpub: ExtendedPubKey,
+    scheme: DerivationScheme,
+    script_type: ScriptType,
+}
+
+impl AccountXpub {
+    /// Parses the account extended public key.
+    /// SLIP-132 `ypub`, `zpub`, `upub` and `vpub` keys are accepted as well,
+    /// although the scriptPubkey type is always defined by the `scheme`.
+    pub fn new(xpub: &str, scheme: DerivationScheme) -> SigningResult<AccountXpub> {
+        let mut data = base58::decode_check(xpub)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid extended public key encoding")?;
+        if data.len() != EXTENDED_KEY_LEN {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Invalid extended public key length");
+        }
+
+        let version: [u8; 4] = data[..4].try_into().expect("Expected 4 byte version");
+        let normalized_version = match version {
+            XPUB_VERSION | YPUB_VERSION | ZPUB_VERSION => XPUB_VERSION,
+            TPUB_VERSION | UPUB_VERSION | VPUB_VERSION => TPUB_VERSION,
+            _ => {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("Unsupported extended public key version")
+            },
+        };
+        data[..4].copy_from_slice(&normalized_version);
+
+        let xpub = ExtendedPubKey::decode(&data)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid extended public key")?;
+        Ok(AccountXpub {
+            xpub,
+            scheme,
+            script_type: scheme.script_type(),
+        })
+    }
+
+    /// Overrides the type of the derived scriptPubkeys, which is defined by the derivation scheme by default.
+    /// Please note that other wallets will likely not find the scriptPubkeys of a non-default type.
+    pub fn with_script_type(mut self, script_type: ScriptType) -> AccountXpub {
+        self.script_type = script_type;
+        self
+    }
+
+    pub fn scheme(&self) -> DerivationScheme {
+        self.scheme
+    }
+
+    pub fn script_type(&self) -> ScriptType {
+        self.script_type
+    }
+
+    /// Derives the compressed public key at `<account>/<chain>/<index>`.
+    pub fn derive_public_key(&self, chain: u32, index: u32) -> SigningResult<H264> {
+        let path = [
+            ChildNumber::from_normal_idx(chain),
+            ChildNumber::from_normal_idx(index),
+        ]
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Hardened derivation is not possible from an extended public key")?;
+
+        let child = self
+            .xpub
+            .derive_pub(SECP256K1, &path)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error deriving a child public key")?;
+        Ok(H264::from(child.public_key.serialize()))
+    }
+
+    /// Derives the scriptPubkey at `<account>/<chain>/<index>`.
+    pub fn derive_script_pubkey(&self, chain: u32, index: u32) -> SigningResult<Script> {
+        let pubkey = self.derive_public_key(chain, index)?;
+        let pubkey_hash = hash_160(pubkey.as_slice());
+
+        let script = match self.script_type {
+            ScriptType::P2pkh => conditions::new_p2pkh(&pubkey_hash),
+            ScriptType::P2shP2wpkh => {
+                let redeem_script = conditions::new_p2wpkh(&pubkey_hash);
+                conditions::new_p2sh(&hash_160(redeem_script.as_slice()))
+            },
+            ScriptType::P2wpkh => conditions::new_p2wpkh(&pubkey_hash),
+            ScriptType::P2tr => conditions::new_p2tr_key_path(&pubkey),
+        };
+        Ok(script)
+    }
+
+    /// Derives the change scriptPubkey at `<account>/1/<index>`.
+    pub fn derive_change_script_pubkey(&self, index: u32) -> SigningResult<Script> {
+        self.derive_script_pubkey(CHANGE_CHAIN, index)
+    }
+}
+
+fn hash_160(data: &[u8]) -> H160 {
+    H160::try_from(bitcoin_hash_160(data).as_slice()).expect("Expected 20 byte array hash")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::ToHex;
+
+    /// BIP-84 test vector account `m/84'/0'/0'`.
+    const BIP84_ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+    /// The same key with the testnet `vpub` version.
+    const BIP84_VPUB: &str = "vpub5YvMuJNjRSYon44z9QmCfdf8SqJRVNvz6m55Qy5iVjZQxDfUgtiQjnc7CC1fAbED2tAGCZRERUfvtn2DstZGU6HMns6dXXH2wujSc2wfi2x";
+    /// BIP-86 test vector account `m/86'/0'/0'`.
+    const BIP86_XPUB: &str = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+    fn script_hex(xpub: &str, scheme: DerivationScheme, chain: u32, index: u32) -> String {
+        AccountXpub::new(xpub, scheme)
+            .unwrap()
+            .derive_script_pubkey(chain, index)
+            .unwrap()
+            .as_slice()
+            .to_hex()
+    }
+
+    #[test]
+    fn test_account_xpub_bip84() {
+        // bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu
+        assert_eq!(
+            script_hex(BIP84_ZPUB, DerivationScheme::Bip84, RECEIVE_CHAIN, 0),
+            "0014c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2"
+        );
+        // bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el
+        let account = AccountXpub::new(BIP84_ZPUB, DerivationScheme::Bip84).unwrap();
+        assert_eq!(
+            account
+                .derive_change_script_pubkey(0)
+                .unwrap()
+                .as_slice()
+                .to_hex(),
+            "00143e34985dca6fddc9fb369940e4c7d8e2873f529c"
+        );
+        // The version doesn't affect the derived keys.
+        assert_eq!(
+            script_hex(BIP84_VPUB, DerivationScheme::Bip84, CHANGE_CHAIN, 0),
+            "00143e34985dca6fddc9fb369940e4c7d8e2873f529c"
+        );
+    }
+
+    #[test]
+    fn test_account_xpub_bip86() {
+        // bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr
+        assert_eq!(
+            script_hex(BIP86_XPUB, DerivationScheme::Bip86, RECEIVE_CHAIN, 0),
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+        // bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7
+        assert_eq!(
+            script_hex(BIP86_XPUB, DerivationScheme::Bip86, CHANGE_CHAIN, 0),
+            "5120882d74e5d0572d5a816cef0041a96b6c1de832f6f9676d9605c44d5e9a97d3dc"
+        );
+    }
+
+    #[test]
+    fn test_account_xpub_legacy_schemes() {
+        assert_eq!(
+            script_hex(BIP84_ZPUB, DerivationScheme::Bip44, CHANGE_CHAIN, 0),
+            "76a9143e34985dca6fddc9fb369940e4c7d8e2873f529c88ac"
+        );
+        assert_eq!(
+            script_hex(BIP84_ZPUB, DerivationScheme::Bip49, CHANGE_CHAIN, 0),
+            "a9148f0a7ab7113215b41b9381ff71df5296b0f0864f87"
+        );
+    }
+
+    #[test]
+    fn test_account_xpub_script_type_override() {
+        let account = AccountXpub::new(BIP84_ZPUB, DerivationScheme::Bip84)
+            .unwrap()
+            .with_script_type(ScriptType::P2tr);
+        assert_eq!(account.scheme(), DerivationScheme::Bip84);
+        assert_eq!(
+            account
+                .derive_change_script_pubkey(0)
+                .unwrap()
+                .as_slice()
+                .to_hex(),
+            "512066313993cf680f7bb7870666f65b199bbf73240c4045c05fc557eff26251b0b9"
+        );
+    }
+
+    #[test]
+    fn test_account_xpub_invalid() {
+        // Invalid checksum.
+        let mut invalid = BIP84_ZPUB.to_string();
+        invalid.pop();
+        invalid.push('t');
+        let err = AccountXpub::new(&invalid, DerivationScheme::Bip84).unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+        // Hardened index.
+        let account = AccountXpub::new(BIP84_ZPUB, DerivationScheme::Bip84).unwrap();
+        let err = account
+            .derive_change_script_pubkey(0x8000_0000)
+            .unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    }
+}
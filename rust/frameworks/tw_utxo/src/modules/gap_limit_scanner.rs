@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Generates the account scriptPubkeys to look for during the wallet scanning.
+//! The receive and change chains are scanned until `gap_limit` consecutive scriptPubkeys are unused.
+//! https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki#address-gap-limit
+
+use crate::modules::account_xpub::{AccountXpub, CHANGE_CHAIN, RECEIVE_CHAIN};
+use crate::script::Script;
+use std::collections::HashMap;
+use tw_coin_entry::error::prelude::*;
+use tw_memory::Data;
+
+/// The gap limit recommended by BIP-44.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+/// The max number of indexes a used scriptPubkey can be ahead of the derived ones.
+/// Limits the number of scriptPubkeys derived in the next batch.
+pub const MAX_USED_INDEX_AHEAD: u32 = 1_000;
+/// Indexes starting from `2^31` are hardened and cannot be derived from the account xpub.
+const HARDENED_INDEX: u32 = 0x8000_0000;
+
+/// A scriptPubkey derived at `<account>/<chain>/<index>`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivedScriptPubkey {
+    pub chain: u32,
+    pub index: u32,
+    /// Raw scriptPubkey as it appears in the transaction outputs.
+    pub script_pubkey: Script,
+}
+
+#[derive(Clone, Copy, Default)]
+struct ChainState {
+    /// The index of the next scriptPubkey to derive.
+    next_index: u32,
+    /// The number of scriptPubkeys up to the last used one, i.e. `last_used_index + 1`.
+    used_count: u32,
+}
+
+/// Usage:
+/// 1. Get the scriptPubkeys to look for via [`GapLimitScanner::next_batch`].
+/// 2. Mark the scriptPubkeys found in the transactions or the node scan results
+///    via [`GapLimitScanner::mark_used_script`].
+/// 3. Repeat until the batch is empty.
+pub struct GapLimitScanner {
+    account: AccountXpub,
+    gap_limit: u32,
+    chains: [ChainState; 2],
+    /// Derived scriptPubkeys mapped to their `(chain, index)`.
+    derived: HashMap<Data, (u32, u32)>,
+}
+
+impl GapLimitScanner {
+    pub fn new(account: AccountXpub, gap_limit: u32) -> SigningResult<GapLimitScanner> {
+        if gap_limit == 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Gap limit must be at least 1");
+        }
+        Ok(GapLimitScanner {
+            account,
+            gap_limit,
+            chains: [ChainState::default(); 2],
+            derived: HashMap::default(),
+        })
+    }
+
+    /// Derives the receive and the change scriptPubkeys that are not derived yet
+    /// and are within the gap limit from the last used ones.
+    /// Returns an empty batch once both chains are scanned.
+    pub fn next_batch(&mut self) -> SigningResult<Vec<DerivedScriptPubkey>> {
+        let mut batch = Vec::new();
+        for chain in [RECEIVE_CHAIN, CHANGE_CHAIN] {
+            let state = &mut self.chains[chain as usize];
+            let end_index = state
+                .used_count
+                .saturating_add(self.gap_limit)
+                .min(HARDENED_INDEX);
+
+            while state.next_index < end_index {
+                let index = state.next_index;
+                let script_pubkey = self.account.derive_script_pubkey(chain, index)?;
+
+                self.derived.insert(script_pubkey.to_vec(), (chain, index));
+                batch.push(DerivedScriptPubkey {
+                    chain,
+                    index,
+                    script_pubkey,
+                });
+                state.next_index += 1;
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Marks the scriptPubkey at `<account>/<chain>/<index>` as used,
+    /// so the next batch is extended up to the gap limit from this index.
+    /// The index must not be more than [`MAX_USED_INDEX_AHEAD`] ahead of the derived scriptPubkeys.
+    pub fn mark_used(&mut self, chain: u32, index: u32) -> SigningResult<()> {
+        if chain != RECEIVE_CHAIN && chain != CHANGE_CHAIN {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Unexpected chain '{chain}'"));
+        }
+        if index >= HARDENED_INDEX {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context(format!("Index '{index}' is hardened and cannot be derived"));
+        }
+
+        let state = &mut self.chains[chain as usize];
+        if index.saturating_sub(state.next_index) > MAX_USED_INDEX_AHEAD {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Index '{index}' is more than {MAX_USED_INDEX_AHEAD} ahead of the derived ones"
+            ));
+        }
+        state.used_count = state.used_count.max(index + 1);
+        Ok(())
+    }
+
+    /// Marks the `script_pubkey` as used if it was derived by the scanner.
+    /// Returns the `(chain, index)` of the script, or `None` if it doesn't belong to the account.
+    pub fn mark_used_script(&mut self, script_pubkey: &[u8]) -> Option<(u32, u32)> {
+        let (chain, index) = *self.derived.get(script_pubkey)?;
+        self.mark_used(chain, index)
+            .expect("Derived scripts belong to the receive or change chain");
+        Some((chain, index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::account_xpub::DerivationScheme;
+    use tw_encoding::hex::ToHex;
+
+    /// BIP-84 test vector account `m/84'/0'/0'`.
+    const BIP84_ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    fn scanner(gap_limit: u32) -> GapLimitScanner {
+        let account = AccountXpub::new(BIP84_ZPUB, DerivationScheme::Bip84).unwrap();
+        GapLimitScanner::new(account, gap_limit).unwrap()
+    }
+
+    fn paths(batch: &[DerivedScriptPubkey]) -> Vec<(u32, u32)> {
+        batch.iter().map(|s| (s.chain, s.index)).collect()
+    }
+
+    #[test]
+    fn test_gap_limit_scanner() {
+        let mut scanner = scanner(2);
+
+        let batch = scanner.next_batch().unwrap();
+        assert_eq!(paths(&batch), [(0, 0), (0, 1), (1, 0), (1, 1)]);
+        // bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu
+        assert_eq!(
+            batch[0].script_pubkey.as_slice().to_hex(),
+            "0014c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2"
+        );
+        // bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el
+        assert_eq!(
+            batch[2].script_pubkey.as_slice().to_hex(),
+            "00143e34985dca6fddc9fb369940e4c7d8e2873f529c"
+        );
+
+        // The second receive address is found in a transaction.
+        let found = batch[1].script_pubkey.clone();
+        assert_eq!(scanner.mark_used_script(found.as_slice()), Some((0, 1)));
+
+        // The receive chain is extended up to the gap limit from the used address.
+        let batch = scanner.next_batch().unwrap();
+        assert_eq!(paths(&batch), [(0, 2), (0, 3)]);
+
+        // Nothing else is used.
+        assert!(scanner.next_batch().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gap_limit_scanner_mark_used() {
+        let mut scanner = scanner(1);
+        assert_eq!(paths(&scanner.next_batch().unwrap()), [(0, 0), (1, 0)]);
+
+        // The used index can be known before the scriptPubkey is derived.
+        scanner.mark_used(CHANGE_CHAIN, 2).unwrap();
+        assert_eq!(
+            paths(&scanner.next_batch().unwrap()),
+            [(1, 1), (1, 2), (1, 3)]
+        );
+
+        // Foreign scripts are ignored.
+        assert_eq!(scanner.mark_used_script(&[0x51]), None);
+        assert!(scanner.next_batch().unwrap().is_empty());
+
+        assert!(scanner.mark_used(2, 0).is_err());
+    }
+
+    #[test]
+    fn test_gap_limit_scanner_mark_used_too_far() {
+        let mut scanner = scanner(1);
+        assert_eq!(paths(&scanner.next_batch().unwrap()), [(0, 0), (1, 0)]);
+
+        // Hardened indexes cannot be derived from the account xpub.
+        assert!(scanner.mark_used(RECEIVE_CHAIN, HARDENED_INDEX).is_err());
+        assert!(scanner.mark_used(RECEIVE_CHAIN, u32::MAX).is_err());
+
+        // The next index to derive is 1.
+        assert!(scanner
+            .mark_used(RECEIVE_CHAIN, MAX_USED_INDEX_AHEAD + 2)
+            .is_err());
+        scanner
+            .mark_used(RECEIVE_CHAIN, MAX_USED_INDEX_AHEAD + 1)
+            .unwrap();
+        // The receive indexes from 1 up to the used one, and one more within the gap limit.
+        assert_eq!(
+            scanner.next_batch().unwrap().len(),
+            MAX_USED_INDEX_AHEAD as usize + 2
+        );
+    }
+
+    #[test]
+    fn test_gap_limit_scanner_invalid_gap_limit() {
+        let account = AccountXpub::new(BIP84_ZPUB, DerivationScheme::Bip84).unwrap();
+        assert!(GapLimitScanner::new(account, 0).is_err());
+    }
+}
@@ -3,9 +3,10 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::modules::sighash_computer::TaprootTweak;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::H264;
+use tw_hash::{H256, H264};
 use tw_keypair::{ecdsa, schnorr};
 
 /// Standard Bitcoin keys manager.
@@ -16,6 +17,12 @@ pub struct KeysManager {
     ecdsa_public_private_map: HashMap<H264, ecdsa::secp256k1::PrivateKey>,
     /// Schnorr private keys.
     schnorr_private_keys: Vec<schnorr::PrivateKey>,
+    /// Tweaked schnorr keys grouped by a Taproot tweak (a merkle root and a contract commitment) they were tweaked with.
+    /// Each group maps tweaked x-only public keys to the tweaked private keys.
+    ///
+    /// The cache lives as long as the keys manager does, i.e. for the duration of one signing call,
+    /// so many Taproot inputs spending the same key are tweaked only once.
+    tweaked_schnorr_cache: RefCell<HashMap<TaprootTweak, HashMap<H256, schnorr::PrivateKey>>>,
 }
 
 impl KeysManager {
@@ -27,6 +34,8 @@ impl KeysManager {
 
     pub fn add_schnorr_private(&mut self, private: schnorr::PrivateKey) -> &mut Self {
         self.schnorr_private_keys.push(private);
+        // Tweaked keys must be recomputed to include the new private key.
+        self.tweaked_schnorr_cache.get_mut().clear();
         self
     }
 
@@ -45,6 +54,8 @@ impl KeysManager {
     /// Gets a schnorr private key by an either tweaked or untweaked x-only public key.
     /// The function iterates over the private keys, tweaks them if specified in `taproot_tweak`,
     /// and returns `Ok(schnorr::PrivateKey)` if found.
+    /// If there is no tweaked key matching a key-path public key, the untweaked keys are checked as well.
+    /// Tweaked private keys are cached by the Taproot tweak.
     pub fn get_schnorr_private(
         &self,
         public: &schnorr::XOnlyPublicKey,
@@ -52,21 +63,43 @@ impl KeysManager {
     ) -> SigningResult<schnorr::PrivateKey> {
         let pubkey_bytes = public.bytes();
 
-        for private_key in self.schnorr_private_keys.iter() {
-            match taproot_tweak {
-                Some(ref tweak) => {
-                    let tweaked_private = private_key.clone().tweak(tweak.merkle_root);
-                    if tweaked_private.public().x_only().bytes() == pubkey_bytes {
-                        return Ok(tweaked_private);
-                    }
-                    // Otherwise, continue searching for a private key.
-                },
-                None => {
-                    if private_key.public().x_only().bytes() == pubkey_bytes {
-                        return Ok(private_key.clone());
-                    }
-                    // Otherwise, continue searching for a private key.
-                },
+        if let Some(ref tweak) = taproot_tweak {
+            let mut cache = self.tweaked_schnorr_cache.borrow_mut();
+            let tweaked_keys = cache.entry(tweak.clone()).or_insert_with(|| {
+                self.schnorr_private_keys
+                    .iter()
+                    .filter_map(|private_key| {
+                        let internal_private = match tweak.contract_commitment {
+                            Some(ref commitment) => {
+                                private_key.clone().pay_to_contract(commitment).ok()?
+                            },
+                            None => private_key.clone(),
+                        };
+                        let tweaked_private = internal_private.tweak(tweak.merkle_root);
+                        Some((tweaked_private.public().x_only().bytes(), tweaked_private))
+                    })
+                    .collect()
+            });
+
+            if let Some(tweaked_private) = tweaked_keys.get(&pubkey_bytes) {
+                return Ok(tweaked_private.clone());
+            }
+        }
+
+        // Key-path outputs can also be locked to an untweaked key, e.g. BIP-352 Silent Payment outputs.
+        let is_script_tree_tweak = matches!(
+            taproot_tweak,
+            Some(TaprootTweak {
+                merkle_root: Some(_),
+                ..
+            })
+        );
+        if !is_script_tree_tweak {
+            for private_key in self.schnorr_private_keys.iter() {
+                if private_key.public().x_only().bytes() == pubkey_bytes {
+                    return Ok(private_key.clone());
+                }
+                // Otherwise, continue searching for a private key.
             }
         }
 
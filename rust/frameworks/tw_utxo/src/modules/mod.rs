@@ -2,10 +2,17 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+pub mod account_xpub;
 pub mod fee_estimator;
+pub mod gap_limit_scanner;
 pub mod keys_manager;
+pub mod ownership_proof;
+pub mod script_ownership;
 pub mod sighash_computer;
 pub mod sighash_verifier;
+pub mod silent_payments;
+pub mod standardness;
+pub mod truc_policy;
 pub mod tx_compiler;
 pub mod tx_planner;
 pub mod tx_signer;
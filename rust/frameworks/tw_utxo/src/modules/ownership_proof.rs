@@ -0,0 +1,467 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! SLIP-19 proofs of ownership.
+//! https://github.com/satoshilabs/slips/blob/master/slip-0019.md
+
+use crate::encode::compact_integer::CompactInteger;
+use crate::encode::stream::Stream;
+use crate::script::standard_script::conditions;
+use crate::script::{Script, Witness};
+use crate::sighash::SighashType;
+use crate::signature::{BitcoinEcdsaSignature, FromRawOrDerBytes};
+use bitcoin::hashes::{hmac, sha512, Hash, HashEngine};
+use tw_coin_entry::error::prelude::*;
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::hmac::hmac_sha256;
+use tw_hash::sha2::sha256;
+use tw_hash::H256;
+use tw_keypair::traits::{SigningKeyTrait, VerifyingKeyTrait};
+use tw_keypair::{ecdsa, schnorr};
+use tw_memory::Data;
+use tw_misc::traits::ToBytesZeroizing;
+
+/// `SL\x00\x19` version magic of the proof.
+pub const VERSION_MAGIC: [u8; 4] = [0x53, 0x4c, 0x00, 0x19];
+/// The flag set if the user confirmed the proof generation.
+const USER_CONFIRMATION_FLAG: u8 = 0x01;
+
+const SLIP21_SEED_KEY: &[u8] = b"Symmetric key seed";
+/// SLIP-21 path of the ownership identification key: `m/"SLIP-0019"/"Ownership identification key"`.
+const OWNERSHIP_KEY_PATH: [&[u8]; 2] = [b"SLIP-0019", b"Ownership identification key"];
+
+const SIGHASH_ALL: u32 = 1;
+
+/// Derives the ownership identification key from the wallet seed with SLIP-21.
+pub fn ownership_identification_key(seed: &[u8]) -> H256 {
+    let mut node = hmac_sha512(SLIP21_SEED_KEY, seed);
+    for label in OWNERSHIP_KEY_PATH {
+        let mut message = vec![0];
+        message.extend_from_slice(label);
+        node = hmac_sha512(&node[..32], &message);
+    }
+    H256::try_from(&node[32..]).expect("HMAC-SHA512 output is 64 bytes")
+}
+
+/// Computes the ownership ID of a `script_pubkey`, so the wallet can recognize its own UTXOs in a proof.
+pub fn ownership_id(ownership_key: &H256, script_pubkey: &Script) -> H256 {
+    let id = hmac_sha256(ownership_key.as_slice(), script_pubkey.as_slice());
+    H256::try_from(id.as_slice()).expect("HMAC-SHA256 output is 32 bytes")
+}
+
+/// A proof that the signer can spend a UTXO.
+/// Only P2WPKH and P2TR (key-path) UTXOs are supported.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OwnershipProof {
+    /// Whether the user confirmed the proof generation.
+    pub user_confirmation: bool,
+    pub ownership_ids: Vec<H256>,
+    pub script_sig: Script,
+    pub witness: Witness,
+}
+
+impl OwnershipProof {
+    /// Signs a proof of ownership of the UTXO locked to the `script_pubkey`.
+    /// `commitment_data` is an arbitrary data the proof commits to, e.g. a withdrawal request ID.
+    pub fn sign(
+        private_key: &ecdsa::secp256k1::PrivateKey,
+        script_pubkey: &Script,
+        ownership_ids: Vec<H256>,
+        user_confirmation: bool,
+        commitment_data: &[u8],
+    ) -> SigningResult<OwnershipProof> {
+        let mut proof = OwnershipProof {
+            user_confirmation,
+            ownership_ids,
+            ..OwnershipProof::default()
+        };
+        let sighash = proof.sighash(script_pubkey, commitment_data);
+
+        if let Some(pubkey_hash) = conditions::match_p2wpkh(script_pubkey) {
+            let public_key = private_key.public().compressed();
+            if pubkey_hash.as_slice() != sha256_ripemd(public_key.as_slice()) {
+                return SigningError::err(SigningErrorType::Error_missing_private_key)
+                    .context("The private key doesn't match the P2WPKH scriptPubkey");
+            }
+
+            let der_sig = private_key
+                .sign(sighash)
+                .and_then(|sig| sig.to_der())
+                .into_tw()
+                .context("Error signing the ownership proof")?;
+            let signature =
+                BitcoinEcdsaSignature::new(der_sig, SighashType::from_u32(SIGHASH_ALL)?)?;
+
+            proof.witness.push_item(Script::from(signature.serialize()));
+            proof
+                .witness
+                .push_item(Script::from(public_key.as_slice().to_vec()));
+            return Ok(proof);
+        }
+
+        if let Some(output_key) = conditions::match_p2tr(script_pubkey) {
+            // The UTXO is expected to be locked to the BIP-86 tweaked public key.
+            let private_key =
+                schnorr::PrivateKey::try_from(private_key.to_zeroizing_vec().as_slice())
+                    .into_tw()
+                    .context("Invalid private key")?
+                    .tweak(None);
+            if private_key.public().x_only().bytes() != output_key {
+                return SigningError::err(SigningErrorType::Error_missing_private_key)
+                    .context("The private key doesn't match the P2TR scriptPubkey");
+            }
+
+            let signature = private_key
+                .sign(sighash)
+                .into_tw()
+                .context("Error signing the ownership proof")?;
+            // SIGHASH_DEFAULT is implied.
+            proof
+                .witness
+                .push_item(Script::from(signature.bytes().to_vec()));
+            return Ok(proof);
+        }
+
+        SigningError::err(SigningErrorType::Error_not_supported)
+            .context("Only P2WPKH and P2TR ownership proofs are supported")
+    }
+
+    /// Verifies the proof of ownership of the UTXO locked to the `script_pubkey`.
+    pub fn verify(&self, script_pubkey: &Script, commitment_data: &[u8]) -> SigningResult<()> {
+        let sighash = self.sighash(script_pubkey, commitment_data);
+        if !self.script_sig.is_empty() {
+            return SigningError::err(SigningErrorType::Error_not_supported)
+                .context("Only P2WPKH and P2TR ownership proofs are supported");
+        }
+
+        if let Some(pubkey_hash) = conditions::match_p2wpkh(script_pubkey) {
+            let [signature, public_key] = self.witness.as_items() else {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("P2WPKH ownership proof must have a signature and a public key");
+            };
+            if pubkey_hash.as_slice() != sha256_ripemd(public_key.as_slice()) {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("The public key doesn't match the P2WPKH scriptPubkey");
+            }
+
+            let public_key = ecdsa::secp256k1::PublicKey::try_from(public_key.as_slice())
+                .into_tw()
+                .context("Invalid public key")?;
+            let (sighash_ty, der_sig) = signature
+                .as_slice()
+                .split_last()
+                .or_tw_err(SigningErrorType::Error_invalid_params)
+                .context("Empty signature")?;
+            if u32::from(*sighash_ty) != SIGHASH_ALL {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("Ownership proof must be signed with SIGHASH_ALL");
+            }
+            let signature = ecdsa::secp256k1::VerifySignature::from_raw_or_der_bytes(der_sig)?;
+            return verify_signature(public_key.verify(signature, sighash));
+        }
+
+        if let Some(output_key) = conditions::match_p2tr(script_pubkey) {
+            let [signature] = self.witness.as_items() else {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("P2TR ownership proof must have a single signature");
+            };
+            let public_key = schnorr::XOnlyPublicKey::try_from(output_key.as_slice())
+                .into_tw()
+                .context("Invalid Taproot output key")?;
+            let signature = schnorr::Signature::try_from(signature.as_slice())
+                .into_tw()
+                .context("Invalid schnorr signature, SIGHASH_DEFAULT is expected")?;
+            return verify_signature(public_key.verify(signature, sighash));
+        }
+
+        SigningError::err(SigningErrorType::Error_not_supported)
+            .context("Only P2WPKH and P2TR ownership proofs are supported")
+    }
+
+    pub fn encode(&self) -> Data {
+        let mut stream = Stream::default();
+        self.encode_body(&mut stream);
+        stream.append(&self.script_sig).append(&self.witness);
+        stream.out()
+    }
+
+    pub fn decode(bytes: &[u8]) -> SigningResult<OwnershipProof> {
+        let mut reader = Reader { bytes };
+        if reader.read_slice(VERSION_MAGIC.len())? != VERSION_MAGIC {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Invalid ownership proof version magic");
+        }
+
+        let flags = reader.read_slice(1)?[0];
+        if flags & !USER_CONFIRMATION_FLAG != 0 {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Unknown ownership proof flags");
+        }
+
+        let ids_count = reader.read_compact_size()?;
+        let ownership_ids = (0..ids_count)
+            .map(|_| Ok(H256::try_from(reader.read_slice(H256::LEN)?).expect("32 bytes read")))
+            .collect::<SigningResult<Vec<_>>>()?;
+
+        let script_sig_len = reader.read_compact_size()?;
+        let script_sig = Script::from(reader.read_slice(script_sig_len)?.to_vec());
+
+        let mut witness = Witness::default();
+        for _ in 0..reader.read_compact_size()? {
+            let item_len = reader.read_compact_size()?;
+            witness.push_item(Script::from(reader.read_slice(item_len)?.to_vec()));
+        }
+
+        if !reader.bytes.is_empty() {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Unexpected bytes after the ownership proof");
+        }
+        Ok(OwnershipProof {
+            user_confirmation: flags & USER_CONFIRMATION_FLAG != 0,
+            ownership_ids,
+            script_sig,
+            witness,
+        })
+    }
+
+    /// `SHA256(proofBody || proofFooter)`, where the footer consists of the `script_pubkey` and the `commitment_data`.
+    fn sighash(&self, script_pubkey: &Script, commitment_data: &[u8]) -> H256 {
+        let mut stream = Stream::default();
+        self.encode_body(&mut stream);
+        stream
+            .append(script_pubkey)
+            .append(&CompactInteger::from(commitment_data.len()))
+            .append_raw_slice(commitment_data);
+        H256::try_from(sha256(&stream.out()).as_slice()).expect("SHA256 output is 32 bytes")
+    }
+
+    fn encode_body(&self, stream: &mut Stream) {
+        let flags = if self.user_confirmation {
+            USER_CONFIRMATION_FLAG
+        } else {
+            0
+        };
+        stream
+            .append_raw_slice(&VERSION_MAGIC)
+            .append(&flags)
+            .append_list(&self.ownership_ids);
+    }
+}
+
+fn verify_signature(is_valid: bool) -> SigningResult<()> {
+    if !is_valid {
+        return SigningError::err(SigningErrorType::Error_signing)
+            .context("Invalid ownership proof signature");
+    }
+    Ok(())
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(key);
+    engine.input(data);
+    hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_slice(&mut self, len: usize) -> SigningResult<&'a [u8]> {
+        if len > self.bytes.len() {
+            return SigningError::err(SigningErrorType::Error_input_parse)
+                .context("Unexpected end of the ownership proof");
+        }
+        let (slice, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(slice)
+    }
+
+    fn read_compact_size(&mut self) -> SigningResult<usize> {
+        let len = match self.read_slice(1)?[0] {
+            0xfd => 2,
+            0xfe => 4,
+            0xff => 8,
+            value => return Ok(usize::from(value)),
+        };
+        let mut bytes = [0; 8];
+        bytes[..len].copy_from_slice(self.read_slice(len)?);
+        usize::try_from(u64::from_le_bytes(bytes))
+            .tw_err(|_| SigningErrorType::Error_input_parse)
+            .context("Compact size is too large")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::{DecodeHex, ToHex};
+
+    /// BIP-39 seed of the "abandon abandon ... about" mnemonic.
+    const SEED: &str = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4";
+    /// The private key at `m/84'/0'/0'/1/0`.
+    const PRIVATE_KEY: &str = "3277578a56b721e4c9f071f1e24aa0f94c4ff72e7967fea03b134f605f07c8fd";
+    /// bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el
+    const P2WPKH_SCRIPT: &str = "00143e34985dca6fddc9fb369940e4c7d8e2873f529c";
+
+    fn script(hex: &str) -> Script {
+        Script::from(hex.decode_hex().unwrap())
+    }
+
+    fn private_key() -> ecdsa::secp256k1::PrivateKey {
+        ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap()
+    }
+
+    #[test]
+    fn test_ownership_id() {
+        let ownership_key = ownership_identification_key(&SEED.decode_hex().unwrap());
+        assert_eq!(
+            ownership_key.to_hex(),
+            "cd50559c65666fd381e823b82fff04763465062c1ff4c93d3e147a306f884130"
+        );
+        assert_eq!(
+            ownership_id(&ownership_key, &script(P2WPKH_SCRIPT)).to_hex(),
+            "dc798b0eef7ead2c66cf276f1428cb2d92afee7b8e646d3db69cd697619aec4b"
+        );
+    }
+
+    #[test]
+    fn test_ownership_proof_p2wpkh() {
+        let script_pubkey = script(P2WPKH_SCRIPT);
+        let ownership_key = ownership_identification_key(&SEED.decode_hex().unwrap());
+        let ownership_ids = vec![ownership_id(&ownership_key, &script_pubkey)];
+        let commitment_data = b"withdrawal-42";
+
+        let proof = OwnershipProof::sign(
+            &private_key(),
+            &script_pubkey,
+            ownership_ids,
+            false,
+            commitment_data,
+        )
+        .unwrap();
+        let encoded = proof.encode();
+        // Version magic, flags, one ownership ID, empty scriptSig and two witness items.
+        assert!(encoded.to_hex().starts_with(
+            "534c00190001dc798b0eef7ead2c66cf276f1428cb2d92afee7b8e646d3db69cd697619aec4b0002"
+        ));
+
+        let decoded = OwnershipProof::decode(&encoded).unwrap();
+        assert_eq!(decoded, proof);
+        decoded.verify(&script_pubkey, commitment_data).unwrap();
+
+        // The proof commits to the data and the scriptPubkey.
+        decoded
+            .verify(&script_pubkey, b"withdrawal-43")
+            .unwrap_err();
+        decoded
+            .verify(
+                &script("0014c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2"),
+                commitment_data,
+            )
+            .unwrap_err();
+        // The proof commits to the user confirmation flag.
+        let confirmed = OwnershipProof {
+            user_confirmation: true,
+            ..decoded
+        };
+        confirmed
+            .verify(&script_pubkey, commitment_data)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_ownership_proof_p2tr() {
+        let public_key = private_key().public().compressed();
+        let script_pubkey = conditions::new_p2tr_key_path(&public_key);
+
+        let proof = OwnershipProof::sign(&private_key(), &script_pubkey, Vec::default(), true, &[])
+            .unwrap();
+        assert_eq!(proof.witness.as_items().len(), 1);
+        assert_eq!(proof.witness.as_items()[0].len(), 64);
+
+        let decoded = OwnershipProof::decode(&proof.encode()).unwrap();
+        assert!(decoded.user_confirmation);
+        decoded.verify(&script_pubkey, &[]).unwrap();
+        decoded.verify(&script_pubkey, b"data").unwrap_err();
+    }
+
+    /// SLIP-19 test vectors of the "all all all all all all all all all all all all" mnemonic.
+    mod slip19_vectors {
+        use super::*;
+
+        /// BIP-39 seed of the mnemonic.
+        const SEED: &str = "c76c4ac4f4e4a00d6b274d5c39c700bb4a7ddc04fbc6f78e85ca75007b5b495f74a9043eeb77bdd53aa6fc3a0e31462270316fa04b8c19114c8798706cd02ac8";
+        const OWNERSHIP_KEY: &str =
+            "0a115a171e30f8a740bae6c4144bec5dc1099ffa79b83dfb8aa3501d094de585";
+
+        #[test]
+        fn test_slip19_ownership_key() {
+            let ownership_key = ownership_identification_key(&SEED.decode_hex().unwrap());
+            assert_eq!(ownership_key.to_hex(), OWNERSHIP_KEY);
+        }
+
+        /// Test vector 1 (P2WPKH) at `m/84'/0'/0'/1/0`.
+        #[test]
+        fn test_slip19_p2wpkh() {
+            let private_key = ecdsa::secp256k1::PrivateKey::try_from(
+                "3460814214450e864ec722ff1f84f96c41746cd6bbe2f1c09b33972761032e9f",
+            )
+            .unwrap();
+            let script_pubkey = script("0014b2f771c370ccf219cd3059cda92bdf7f00cf2103");
+            let expected_proof = "534c00190001a122407efc198211c81af4450f40b235d54775efd934d16b9e31c6ce9bad57070002483045022100c0dc28bb563fc5fea76cacff75dba9cb4122412faae01937cdebccfb065f9a7002202e980bfbd8a434a7fc4cd2ca49da476ce98ca097437f8159b1a386b41fcdfac50121032ef68318c8f6aaa0adec0199c69901f0db7d3485eb38d9ad235221dc3d61154b";
+
+            let ownership_key = H256::from(OWNERSHIP_KEY);
+            let id = ownership_id(&ownership_key, &script_pubkey);
+            assert_eq!(
+                id.to_hex(),
+                "a122407efc198211c81af4450f40b235d54775efd934d16b9e31c6ce9bad5707"
+            );
+
+            // RFC6979 signatures are deterministic.
+            let proof =
+                OwnershipProof::sign(&private_key, &script_pubkey, vec![id], false, &[]).unwrap();
+            assert_eq!(proof.encode().to_hex(), expected_proof);
+
+            let decoded = OwnershipProof::decode(&expected_proof.decode_hex().unwrap()).unwrap();
+            decoded.verify(&script_pubkey, &[]).unwrap();
+        }
+
+        /// Test vector 3 (P2TR) at `m/86'/0'/0'/1/0`.
+        /// Schnorr signatures use auxiliary randomness, so the proof is verified only.
+        #[test]
+        fn test_slip19_p2tr() {
+            let script_pubkey =
+                script("51204102897557de0cafea0a8401ea5b59668eccb753e4b100aebe6a19609f3cc79f");
+            let proof = "534c00190001dc18066224b9e30e306303436dc18ab881c7266c13790350a3fe415e438135ec000140647d6af883107a870417e808abe424882bd28ee04a28ba85a7e99400e1b9485075733695964c2a0fa02d4439ab80830e9566ccbd10f2597f5513eff9f03a0497";
+
+            let ownership_key = H256::from(OWNERSHIP_KEY);
+            assert_eq!(
+                ownership_id(&ownership_key, &script_pubkey).to_hex(),
+                "dc18066224b9e30e306303436dc18ab881c7266c13790350a3fe415e438135ec"
+            );
+
+            let decoded = OwnershipProof::decode(&proof.decode_hex().unwrap()).unwrap();
+            assert!(!decoded.user_confirmation);
+            decoded.verify(&script_pubkey, &[]).unwrap();
+            assert_eq!(decoded.encode().to_hex(), proof);
+        }
+    }
+
+    #[test]
+    fn test_ownership_proof_invalid() {
+        // The private key doesn't match the scriptPubkey.
+        let other_script = script("0014c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2");
+        OwnershipProof::sign(&private_key(), &other_script, Vec::default(), false, &[])
+            .unwrap_err();
+        // P2PKH is not supported.
+        let p2pkh = script("76a9143e34985dca6fddc9fb369940e4c7d8e2873f529c88ac");
+        OwnershipProof::sign(&private_key(), &p2pkh, Vec::default(), false, &[]).unwrap_err();
+
+        // Invalid version magic.
+        OwnershipProof::decode(&"534c00180000000100".decode_hex().unwrap()).unwrap_err();
+        // Trailing bytes.
+        OwnershipProof::decode(&"534c0019000000000000".decode_hex().unwrap()).unwrap_err();
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Finds the transaction outputs that pay to the account scriptPubkeys,
+//! e.g. to detect incoming payments or to identify the change outputs.
+
+use crate::modules::account_xpub::AccountXpub;
+use crate::modules::gap_limit_scanner::GapLimitScanner;
+use crate::transaction::transaction_interface::{TransactionInterface, TxOutputInterface};
+use tw_coin_entry::error::prelude::*;
+
+/// Usage:
+/// 1. Mark the indexes known to be used, if any, via [`ScriptOwnershipChecker::mark_used`].
+/// 2. Check the transactions via [`ScriptOwnershipChecker::check_transaction`]
+///    or [`ScriptOwnershipChecker::check_encoded_transaction`].
+///
+/// The found scriptPubkeys are marked as used, so the next checks look further
+/// on the receive and change chains.
+pub struct ScriptOwnershipChecker {
+    scanner: GapLimitScanner,
+}
+
+impl ScriptOwnershipChecker {
+    pub fn new(account: AccountXpub, gap_limit: u32) -> SigningResult<ScriptOwnershipChecker> {
+        Ok(ScriptOwnershipChecker {
+            scanner: GapLimitScanner::new(account, gap_limit)?,
+        })
+    }
+
+    /// Marks the scriptPubkey at `<account>/<chain>/<index>` as used,
+    /// so the scriptPubkeys up to the gap limit from this index are checked.
+    pub fn mark_used(&mut self, chain: u32, index: u32) -> SigningResult<()> {
+        self.scanner.mark_used(chain, index)
+    }
+
+    /// Returns the `(chain, index)` of each scriptPubkey, or `None` if it doesn't belong to the account.
+    pub fn check_scripts(
+        &mut self,
+        script_pubkeys: &[&[u8]],
+    ) -> SigningResult<Vec<Option<(u32, u32)>>> {
+        let mut paths = vec![None; script_pubkeys.len()];
+        loop {
+            // A found scriptPubkey extends the chain, so the rest are checked against the next batch as well.
+            for (path, script_pubkey) in paths.iter_mut().zip(script_pubkeys) {
+                if path.is_none() {
+                    *path = self.scanner.mark_used_script(script_pubkey);
+                }
+            }
+            if paths.iter().all(Option::is_some) || self.scanner.next_batch()?.is_empty() {
+                return Ok(paths);
+            }
+        }
+    }
+
+    /// Returns the `(chain, index)` of each transaction output, or `None` if it doesn't belong to the account.
+    pub fn check_transaction<Transaction: TransactionInterface>(
+        &mut self,
+        tx: &Transaction,
+    ) -> SigningResult<Vec<Option<(u32, u32)>>> {
+        let script_pubkeys: Vec<_> = tx
+            .outputs()
+            .iter()
+            .map(|output| output.script_pubkey().as_slice())
+            .collect();
+        self.check_scripts(&script_pubkeys)
+    }
+
+    /// Same as [`ScriptOwnershipChecker::check_transaction`], but takes a consensus encoded transaction.
+    pub fn check_encoded_transaction(
+        &mut self,
+        encoded_tx: &[u8],
+    ) -> SigningResult<Vec<Option<(u32, u32)>>> {
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(encoded_tx)
+            .tw_err(|_| SigningErrorType::Error_input_parse)
+            .context("Invalid transaction encoding")?;
+        let script_pubkeys: Vec<_> = tx
+            .output
+            .iter()
+            .map(|output| output.script_pubkey.as_bytes())
+            .collect();
+        self.check_scripts(&script_pubkeys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::account_xpub::{DerivationScheme, CHANGE_CHAIN, RECEIVE_CHAIN};
+    use tw_encoding::hex::DecodeHex;
+
+    /// BIP-84 test vector account `m/84'/0'/0'`.
+    const BIP84_ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    fn account() -> AccountXpub {
+        AccountXpub::new(BIP84_ZPUB, DerivationScheme::Bip84).unwrap()
+    }
+
+    fn script(chain: u32, index: u32) -> Vec<u8> {
+        account()
+            .derive_script_pubkey(chain, index)
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_script_ownership_checker() {
+        let mut checker = ScriptOwnershipChecker::new(account(), 2).unwrap();
+        let foreign = vec![0x51];
+        let receive_1 = script(RECEIVE_CHAIN, 1);
+        let change_0 = script(CHANGE_CHAIN, 0);
+
+        let paths = checker
+            .check_scripts(&[&foreign, &receive_1, &change_0])
+            .unwrap();
+        assert_eq!(
+            paths,
+            [None, Some((RECEIVE_CHAIN, 1)), Some((CHANGE_CHAIN, 0))]
+        );
+
+        // The receive chain is extended up to the gap limit from the found index.
+        let receive_3 = script(RECEIVE_CHAIN, 3);
+        let receive_4 = script(RECEIVE_CHAIN, 4);
+        assert_eq!(
+            checker.check_scripts(&[&receive_3]).unwrap(),
+            [Some((RECEIVE_CHAIN, 3))]
+        );
+        // Index 7 is beyond the gap limit even after index 4 is found.
+        let receive_7 = script(RECEIVE_CHAIN, 7);
+        assert_eq!(
+            checker.check_scripts(&[&receive_7, &receive_4]).unwrap(),
+            [None, Some((RECEIVE_CHAIN, 4))]
+        );
+    }
+
+    #[test]
+    fn test_script_ownership_checker_mark_used() {
+        let mut checker = ScriptOwnershipChecker::new(account(), 1).unwrap();
+        let change_5 = script(CHANGE_CHAIN, 5);
+        assert_eq!(checker.check_scripts(&[&change_5]).unwrap(), [None]);
+
+        // The change index is known from the wallet history.
+        checker.mark_used(CHANGE_CHAIN, 4).unwrap();
+        assert_eq!(
+            checker.check_scripts(&[&change_5]).unwrap(),
+            [Some((CHANGE_CHAIN, 5))]
+        );
+    }
+
+    #[test]
+    fn test_script_ownership_checker_encoded_transaction() {
+        let mut checker = ScriptOwnershipChecker::new(account(), 20).unwrap();
+
+        // A transaction paying to a foreign P2WPKH and to the change `m/84'/0'/0'/1/0`.
+        let encoded_tx = "020000000100000000000000000000000000000000000000000000000000000000000000000000000000ffffffff02e8030000000000001600141111111111111111111111111111111111111111d0070000000000001600143e34985dca6fddc9fb369940e4c7d8e2873f529c00000000"
+            .decode_hex()
+            .unwrap();
+        assert_eq!(
+            checker.check_encoded_transaction(&encoded_tx).unwrap(),
+            [None, Some((CHANGE_CHAIN, 0))]
+        );
+
+        let err = checker
+            .check_encoded_transaction(&encoded_tx[..10])
+            .unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_input_parse);
+    }
+}
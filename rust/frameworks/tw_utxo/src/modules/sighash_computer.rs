@@ -14,6 +14,7 @@ use std::marker::PhantomData;
 use tw_coin_entry::coin_entry::PublicKeyBytes;
 use tw_coin_entry::error::prelude::SigningResult;
 use tw_hash::H256;
+use tw_memory::Data;
 
 #[derive(Debug, Clone)]
 pub struct TxPreimage {
@@ -26,17 +27,22 @@ pub struct UtxoSighash {
     /// The signing method needs to be used for this sighash.
     pub signing_method: SigningMethod,
     pub sighash: H256,
+    /// The signature message the sighash is computed from.
+    /// Allows external signers to recompute and verify the sighash.
+    pub preimage: Data,
     pub signer_pubkey: PublicKeyBytes,
     /// Taproot tweak if [`SigningMethod::Taproot`] signing method is used.
     /// Empty if there is no need to tweak the private to sign the sighash.
     pub taproot_tweak: Option<TaprootTweak>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub struct TaprootTweak {
     /// 32 bytes merkle root of the script tree.
     /// `None` if there are no scripts, and the private key should be tweaked without a merkle root.
     pub merkle_root: Option<H256>,
+    /// Pay-to-contract commitment the private key should be tweaked with before the merkle root tweak.
+    pub contract_commitment: Option<H256>,
 }
 
 /// Sighash Computer with a standard Bitcoin behaviour.
@@ -74,21 +80,24 @@ where
                     signing_method,
                 };
 
-                let (sighash, taproot_tweak) = match signing_method {
+                let (preimage, taproot_tweak) = match signing_method {
                     SigningMethod::Legacy | SigningMethod::Segwit => {
-                        let sighash = unsigned_tx.transaction().preimage_tx(&utxo_args)?;
-                        (sighash, None)
+                        let preimage = unsigned_tx.transaction().preimage_tx(&utxo_args)?;
+                        (preimage, None)
                     },
                     SigningMethod::Taproot => {
                         // TODO Move `tr_spent_amounts` and `tr_spent_script_pubkeys` logic to `Transaction::preimage_taproot_tx()`.
-                        let tr_spent_amounts: Vec<Amount> = unsigned_tx
-                            .input_args()
-                            .iter()
-                            .map(|utxo| utxo.amount)
-                            .collect();
+                        // The UTXO may be signed knowing its own spent output only.
+                        let spent_utxos = if utxo.one_prevout {
+                            std::slice::from_ref(utxo)
+                        } else {
+                            unsigned_tx.input_args()
+                        };
+
+                        let tr_spent_amounts: Vec<Amount> =
+                            spent_utxos.iter().map(|utxo| utxo.amount).collect();
 
-                        let tr_spent_script_pubkeys: Vec<Script> = unsigned_tx
-                            .input_args()
+                        let tr_spent_script_pubkeys: Vec<Script> = spent_utxos
                             .iter()
                             .map(|utxo| {
                                 if utxo.signing_method == SigningMethod::Taproot {
@@ -104,19 +113,21 @@ where
                         let tr = UtxoTaprootPreimageArgs {
                             args: utxo_args,
                             spent_amounts: tr_spent_amounts,
-                            spent_script_pubkeys: tr_spent_script_pubkeys.clone(),
+                            spent_script_pubkeys: tr_spent_script_pubkeys,
+                            one_prevout: utxo.one_prevout,
                         };
 
-                        let sighash = unsigned_tx.transaction().preimage_taproot_tx(&tr)?;
+                        let preimage = unsigned_tx.transaction().preimage_taproot_tx(&tr)?;
                         let taproot_tweak = Self::get_taproot_tweak(utxo);
 
-                        (sighash, taproot_tweak)
+                        (preimage, taproot_tweak)
                     },
                 };
 
                 Ok(UtxoSighash {
                     signing_method,
-                    sighash,
+                    sighash: preimage.sighash,
+                    preimage: preimage.preimage,
                     signer_pubkey: utxo.spender_public_key.clone(),
                     taproot_tweak,
                 })
@@ -131,7 +142,10 @@ where
         if utxo.leaf_hash_code_separator.is_none() {
             // Tweak keypair for P2TR key-path (ie. zeroed Merkle root).
             let merkle_root = None;
-            Some(TaprootTweak { merkle_root })
+            Some(TaprootTweak {
+                merkle_root,
+                contract_commitment: utxo.contract_commitment,
+            })
         } else {
             None
         }
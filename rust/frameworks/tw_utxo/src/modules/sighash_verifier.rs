@@ -8,6 +8,8 @@ use crate::signing_mode::SigningMethod;
 use crate::transaction::transaction_interface::TransactionInterface;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use crate::transaction::{TransactionPreimage, UtxoToSign};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use tw_coin_entry::coin_entry::SignatureBytes;
 use tw_coin_entry::error::prelude::*;
@@ -44,16 +46,24 @@ where
         // Compute transaction preimage and verify if all given signatures correspond to the result sighashes.
         let tx_preimage = SighashComputer::preimage_tx(unsigned_tx)?;
 
-        for ((sighash, utxo_args), signature) in tx_preimage
+        let mut decoded = DecodedSignatures::default();
+        for (i, ((sighash, utxo_args), signature)) in tx_preimage
             .sighashes
             .iter()
             .zip(unsigned_tx.input_args())
             .zip(signatures)
+            .enumerate()
         {
-            Self::verify_signature(sighash, utxo_args, signature)?;
+            // Foreign UTXOs are not signed by us.
+            if utxo_args.foreign {
+                continue;
+            }
+            decoded
+                .push(i, sighash, utxo_args, signature)
+                .with_context(|| format!("Input '{i}' has an invalid signature or public key"))?;
         }
 
-        Ok(())
+        decoded.verify()
     }
 
     pub fn verify_signature(
@@ -76,22 +86,9 @@ where
         public_key: &[u8],
         signature: &[u8],
     ) -> SigningResult<()> {
-        let public_key = ecdsa::secp256k1::PublicKey::try_from(public_key)
-            .into_tw()
-            .with_context(|| format!("Error invalid ecdsa public key: {}", public_key.to_hex()))?;
-
+        let public_key = parse_ecdsa_public_key(public_key)?;
         let sign = ecdsa::secp256k1::VerifySignature::from_raw_or_der_bytes(signature)?;
-
-        if !public_key.verify(sign, sighash.sighash) {
-            return SigningError::err(SigningErrorType::Error_signing).context(format!(
-                "Error verifying ecdsa signature: sighash='{}', pubkey='{}', signature='{}'",
-                sighash.sighash,
-                public_key.compressed(),
-                signature.to_hex()
-            ));
-        }
-
-        Ok(())
+        verify_ecdsa(sighash, &public_key, sign, signature)
     }
 
     /// Please note that the public key must be tweaked already if needed.
@@ -100,29 +97,9 @@ where
         public_key: &[u8],
         signature: &[u8],
     ) -> SigningResult<()> {
-        let public_key = schnorr::XOnlyPublicKey::try_from(public_key)
-            .into_tw()
-            .with_context(|| {
-                format!(
-                    "Error invalid schnorr x-only public key: {}",
-                    public_key.to_hex()
-                )
-            })?;
-
-        let sign = schnorr::Signature::try_from(signature)
-            .into_tw()
-            .context("Given an invalid schnorr signature")?;
-
-        if !public_key.verify(sign, sighash.sighash) {
-            return SigningError::err(SigningErrorType::Error_signing).context(format!(
-                "Error verifying schnorr signature: sighash='{}', pubkey='{}', signature='{}'",
-                sighash.sighash,
-                public_key.bytes(),
-                signature.to_hex()
-            ));
-        }
-
-        Ok(())
+        let public_key = parse_schnorr_public_key(public_key)?;
+        let sign = parse_schnorr_signature(signature)?;
+        verify_schnorr(sighash, &public_key, sign, signature)
     }
 
     pub(crate) fn check_signatures_number(
@@ -137,3 +114,153 @@ where
         Ok(())
     }
 }
+
+/// Decodes all signatures of a transaction before verifying them.
+///
+/// Public keys and signatures are decoded upfront, so a malformed input is rejected before any
+/// elliptic curve operation is performed.
+/// Public keys that are shared by several inputs are decoded only once.
+/// The signatures are then verified one by one in the inputs order.
+///
+/// TODO batch Schnorr verification is not implemented, as `secp256k1` 0.27 doesn't provide it.
+/// Each signature is still verified individually, so the verification cost is linear in the number of inputs.
+#[derive(Default)]
+struct DecodedSignatures<'a> {
+    ecdsa_keys: HashMap<&'a [u8], ecdsa::secp256k1::PublicKey>,
+    schnorr_keys: HashMap<&'a [u8], schnorr::XOnlyPublicKey>,
+    items: Vec<DecodedItem<'a>>,
+}
+
+struct DecodedItem<'a> {
+    input_index: usize,
+    sighash: &'a UtxoSighash,
+    public_key: &'a [u8],
+    signature: DecodedSignature,
+    signature_bytes: &'a [u8],
+}
+
+enum DecodedSignature {
+    Ecdsa(ecdsa::secp256k1::VerifySignature),
+    Schnorr(schnorr::Signature),
+}
+
+impl<'a> DecodedSignatures<'a> {
+    fn push(
+        &mut self,
+        input_index: usize,
+        sighash: &'a UtxoSighash,
+        utxo_args: &'a UtxoToSign,
+        signature_bytes: &'a [u8],
+    ) -> SigningResult<()> {
+        let public_key = utxo_args.spender_public_key.as_slice();
+
+        let signature = match utxo_args.signing_method {
+            SigningMethod::Legacy | SigningMethod::Segwit => {
+                if let Entry::Vacant(entry) = self.ecdsa_keys.entry(public_key) {
+                    entry.insert(parse_ecdsa_public_key(public_key)?);
+                }
+                DecodedSignature::Ecdsa(ecdsa::secp256k1::VerifySignature::from_raw_or_der_bytes(
+                    signature_bytes,
+                )?)
+            },
+            SigningMethod::Taproot => {
+                if let Entry::Vacant(entry) = self.schnorr_keys.entry(public_key) {
+                    entry.insert(parse_schnorr_public_key(public_key)?);
+                }
+                DecodedSignature::Schnorr(parse_schnorr_signature(signature_bytes)?)
+            },
+        };
+
+        self.items.push(DecodedItem {
+            input_index,
+            sighash,
+            public_key,
+            signature,
+            signature_bytes,
+        });
+        Ok(())
+    }
+
+    fn verify(self) -> SigningResult<()> {
+        for item in self.items {
+            let input_index = item.input_index;
+            match item.signature {
+                DecodedSignature::Ecdsa(signature) => {
+                    let public_key = self
+                        .ecdsa_keys
+                        .get(item.public_key)
+                        .or_tw_err(SigningErrorType::Error_internal)
+                        .context("Expected an ecdsa public key to be decoded")?;
+                    verify_ecdsa(item.sighash, public_key, signature, item.signature_bytes)
+                },
+                DecodedSignature::Schnorr(signature) => {
+                    let public_key = self
+                        .schnorr_keys
+                        .get(item.public_key)
+                        .or_tw_err(SigningErrorType::Error_internal)
+                        .context("Expected a schnorr public key to be decoded")?;
+                    verify_schnorr(item.sighash, public_key, signature, item.signature_bytes)
+                },
+            }
+            .with_context(|| format!("Input '{input_index}' has an invalid signature"))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_ecdsa_public_key(public_key: &[u8]) -> SigningResult<ecdsa::secp256k1::PublicKey> {
+    ecdsa::secp256k1::PublicKey::try_from(public_key)
+        .into_tw()
+        .with_context(|| format!("Error invalid ecdsa public key: {}", public_key.to_hex()))
+}
+
+fn parse_schnorr_public_key(public_key: &[u8]) -> SigningResult<schnorr::XOnlyPublicKey> {
+    schnorr::XOnlyPublicKey::try_from(public_key)
+        .into_tw()
+        .with_context(|| {
+            format!(
+                "Error invalid schnorr x-only public key: {}",
+                public_key.to_hex()
+            )
+        })
+}
+
+fn parse_schnorr_signature(signature: &[u8]) -> SigningResult<schnorr::Signature> {
+    schnorr::Signature::try_from(signature)
+        .into_tw()
+        .context("Given an invalid schnorr signature")
+}
+
+fn verify_ecdsa(
+    sighash: &UtxoSighash,
+    public_key: &ecdsa::secp256k1::PublicKey,
+    sign: ecdsa::secp256k1::VerifySignature,
+    signature: &[u8],
+) -> SigningResult<()> {
+    if !public_key.verify(sign, sighash.sighash) {
+        return SigningError::err(SigningErrorType::Error_signing).context(format!(
+            "Error verifying ecdsa signature: sighash='{}', pubkey='{}', signature='{}'",
+            sighash.sighash,
+            public_key.compressed(),
+            signature.to_hex()
+        ));
+    }
+    Ok(())
+}
+
+fn verify_schnorr(
+    sighash: &UtxoSighash,
+    public_key: &schnorr::XOnlyPublicKey,
+    sign: schnorr::Signature,
+    signature: &[u8],
+) -> SigningResult<()> {
+    if !public_key.verify(sign, sighash.sighash) {
+        return SigningError::err(SigningErrorType::Error_signing).context(format!(
+            "Error verifying schnorr signature: sighash='{}', pubkey='{}', signature='{}'",
+            sighash.sighash,
+            public_key.bytes(),
+            signature.to_hex()
+        ));
+    }
+    Ok(())
+}
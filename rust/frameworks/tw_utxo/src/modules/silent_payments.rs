@@ -0,0 +1,542 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! BIP-352 Silent Payments.
+//! https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki
+
+use crate::address::silent_payment::SilentPaymentAddress;
+use crate::encode::stream::Stream;
+use crate::transaction::transaction_parts::OutPoint;
+use bitcoin::bip32::{ChildNumber, ExtendedPrivKey};
+use secp256k1::{Parity, PublicKey, Scalar, SecretKey, XOnlyPublicKey, SECP256K1};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::hasher::tagged_hash;
+use tw_hash::{H256, H264};
+
+const INPUTS_TAG: &[u8] = b"BIP0352/Inputs";
+const SHARED_SECRET_TAG: &[u8] = b"BIP0352/SharedSecret";
+const LABEL_TAG: &[u8] = b"BIP0352/Label";
+
+/// BIP-352 key derivation purpose: `m/352'/coin_type'/account'/{0',1'}/0`.
+pub const SILENT_PAYMENT_PURPOSE: u32 = 352;
+const SPEND_KEY_BRANCH: u32 = 0;
+const SCAN_KEY_BRANCH: u32 = 1;
+
+/// A private key of an input eligible for the shared secret derivation.
+#[derive(Clone, Copy)]
+pub enum InputPrivateKey {
+    /// P2PKH, P2WPKH or P2SH-P2WPKH input private key.
+    Ecdsa(SecretKey),
+    /// P2TR key-path input private key, tweaked with the Taproot tweak already.
+    Taproot(SecretKey),
+}
+
+impl InputPrivateKey {
+    /// Taproot output keys are x-only, so the private key is negated if the public key has an odd Y.
+    fn normalized(&self) -> SecretKey {
+        match *self {
+            InputPrivateKey::Ecdsa(key) => key,
+            InputPrivateKey::Taproot(key) => {
+                match PublicKey::from_secret_key(SECP256K1, &key).x_only_public_key() {
+                    (_, Parity::Odd) => key.negate(),
+                    (_, Parity::Even) => key,
+                }
+            },
+        }
+    }
+}
+
+/// Derives the one-time P2TR output keys paying to Silent Payment addresses.
+pub struct SilentPaymentSender {
+    /// `input_hash·a`, where `a` is the sum of the eligible input private keys.
+    tweaked_input_key: SecretKey,
+}
+
+impl SilentPaymentSender {
+    /// `input_keys` are private keys of the eligible inputs,
+    /// `out_points` are out-points of all the transaction inputs.
+    pub fn new(
+        input_keys: &[InputPrivateKey],
+        out_points: &[OutPoint],
+    ) -> SigningResult<SilentPaymentSender> {
+        let mut keys = input_keys.iter().map(InputPrivateKey::normalized);
+        let first = keys
+            .next()
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("Silent Payments require at least one eligible input")?;
+        let input_key_sum = keys.try_fold(first, |sum, key| {
+            sum.add_tweak(&Scalar::from(key))
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Sum of the input private keys is zero")
+        })?;
+
+        let input_pubkey_sum = PublicKey::from_secret_key(SECP256K1, &input_key_sum);
+        let input_hash = input_hash(out_points, &input_pubkey_sum)?;
+        let tweaked_input_key = input_key_sum
+            .mul_tweak(&input_hash)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Invalid input hash")?;
+
+        Ok(SilentPaymentSender { tweaked_input_key })
+    }
+
+    /// Returns x-only output keys for the given `recipients` in the same order.
+    /// Outputs paying to the same scan key get an incrementing counter.
+    pub fn derive_outputs(&self, recipients: &[SilentPaymentAddress]) -> SigningResult<Vec<H256>> {
+        let mut shared_secrets: HashMap<H264, (PublicKey, u32)> = HashMap::default();
+
+        recipients
+            .iter()
+            .map(|recipient| {
+                let (shared_secret, k) = match shared_secrets.entry(*recipient.scan_pubkey()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let shared_secret = parse_public_key(recipient.scan_pubkey())?
+                            .mul_tweak(SECP256K1, &Scalar::from(self.tweaked_input_key))
+                            .tw_err(|_| SigningErrorType::Error_internal)
+                            .context("Error computing the ECDH shared secret")?;
+                        entry.insert((shared_secret, 0))
+                    },
+                };
+
+                let tweak = shared_secret_tweak(shared_secret, *k)?;
+                *k += 1;
+
+                let output_key = parse_public_key(recipient.spend_pubkey())?
+                    .add_exp_tweak(SECP256K1, &tweak)
+                    .tw_err(|_| SigningErrorType::Error_internal)
+                    .context("Error tweaking the spend public key")?;
+                Ok(H256::from(output_key.x_only_public_key().0.serialize()))
+            })
+            .collect()
+    }
+}
+
+/// A public key of an input eligible for the shared secret derivation,
+/// extracted from the scriptSig, witness or scriptPubkey of the spent output.
+#[derive(Clone, Copy)]
+pub enum InputPublicKey {
+    /// P2PKH, P2WPKH or P2SH-P2WPKH input compressed public key.
+    Ecdsa(H264),
+    /// P2TR key-path input x-only output key.
+    Taproot(H256),
+}
+
+impl InputPublicKey {
+    fn to_public_key(self) -> SigningResult<PublicKey> {
+        match self {
+            InputPublicKey::Ecdsa(pubkey) => parse_public_key(&pubkey),
+            // Taproot output keys are lifted to a point with an even Y.
+            InputPublicKey::Taproot(x_only) => XOnlyPublicKey::from_slice(x_only.as_slice())
+                .map(|x_only| x_only.public_key(Parity::Even))
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Invalid Taproot input public key"),
+        }
+    }
+}
+
+/// An output paying to the receiver found by [`SilentPaymentReceiver::scan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DetectedOutput {
+    /// Index of the output in the scanned x-only output keys.
+    pub output_index: usize,
+    /// The tweak to be added to the spend private key, including the label tweak if any.
+    pub tweak: H256,
+    /// The label the output was sent to, if any.
+    pub label: Option<u32>,
+}
+
+/// Detects the outputs paying to a Silent Payment address and derives the private keys to spend them.
+pub struct SilentPaymentReceiver {
+    scan_key: SecretKey,
+    spend_key: SecretKey,
+}
+
+impl SilentPaymentReceiver {
+    pub fn new(scan_key: SecretKey, spend_key: SecretKey) -> SilentPaymentReceiver {
+        SilentPaymentReceiver {
+            scan_key,
+            spend_key,
+        }
+    }
+
+    /// Derives the scan key at `m/352'/coin_type'/account'/1'/0`
+    /// and the spend key at `m/352'/coin_type'/account'/0'/0`.
+    pub fn from_master_key(
+        master_key: &ExtendedPrivKey,
+        coin_type: u32,
+        account: u32,
+    ) -> SigningResult<SilentPaymentReceiver> {
+        let derive = |branch: u32| -> SigningResult<SecretKey> {
+            let path = [
+                ChildNumber::from_hardened_idx(SILENT_PAYMENT_PURPOSE),
+                ChildNumber::from_hardened_idx(coin_type),
+                ChildNumber::from_hardened_idx(account),
+                ChildNumber::from_hardened_idx(branch),
+                ChildNumber::from_normal_idx(0),
+            ]
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid derivation path index")?;
+
+            master_key
+                .derive_priv(SECP256K1, &path)
+                .map(|child| child.private_key)
+                .tw_err(|_| SigningErrorType::Error_internal)
+                .context("Error deriving a Silent Payment key")
+        };
+
+        Ok(SilentPaymentReceiver::new(
+            derive(SCAN_KEY_BRANCH)?,
+            derive(SPEND_KEY_BRANCH)?,
+        ))
+    }
+
+    /// Returns the receiver address with the given `hrp`.
+    pub fn address(&self, hrp: &str) -> SigningResult<SilentPaymentAddress> {
+        self.address_with_spend_pubkey(hrp, PublicKey::from_secret_key(SECP256K1, &self.spend_key))
+    }
+
+    /// Returns the receiver address with the given `label`.
+    /// Please note the label `0` is reserved for change outputs.
+    pub fn labeled_address(&self, hrp: &str, label: u32) -> SigningResult<SilentPaymentAddress> {
+        let labeled_spend_pubkey = PublicKey::from_secret_key(SECP256K1, &self.spend_key)
+            .add_exp_tweak(SECP256K1, &self.label_tweak(label)?)
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error tweaking the spend public key with the label")?;
+        self.address_with_spend_pubkey(hrp, labeled_spend_pubkey)
+    }
+
+    /// Finds the outputs paying to the receiver in a candidate transaction.
+    ///
+    /// `input_pubkeys` are public keys of the eligible inputs,
+    /// `out_points` are out-points of all the transaction inputs,
+    /// `outputs` are x-only output keys of the transaction P2TR outputs,
+    /// `labels` are the labels to look for in addition to the unlabeled address.
+    pub fn scan(
+        &self,
+        input_pubkeys: &[InputPublicKey],
+        out_points: &[OutPoint],
+        outputs: &[H256],
+        labels: &[u32],
+    ) -> SigningResult<Vec<DetectedOutput>> {
+        let input_pubkeys = input_pubkeys
+            .iter()
+            .map(|pubkey| pubkey.to_public_key())
+            .collect::<SigningResult<Vec<_>>>()?;
+        let input_pubkey_refs: Vec<&PublicKey> = input_pubkeys.iter().collect();
+        let input_pubkey_sum = PublicKey::combine_keys(&input_pubkey_refs)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("No eligible inputs or the sum of the input public keys is infinity")?;
+
+        let input_hash = input_hash(out_points, &input_pubkey_sum)?;
+        let shared_secret = input_pubkey_sum
+            .mul_tweak(SECP256K1, &input_hash)
+            .and_then(|point| point.mul_tweak(SECP256K1, &Scalar::from(self.scan_key)))
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Error computing the ECDH shared secret")?;
+
+        let label_tweaks = labels
+            .iter()
+            .map(|label| Ok((*label, self.label_tweak(*label)?)))
+            .collect::<SigningResult<Vec<_>>>()?;
+        let spend_pubkey = PublicKey::from_secret_key(SECP256K1, &self.spend_key);
+
+        let mut detected = Vec::default();
+        // Outputs to the same receiver use consecutive counters, so scanning stops at the first missing one.
+        for k in 0.. {
+            let tweak = shared_secret_tweak(&shared_secret, k)?;
+            match Self::find_output(outputs, &spend_pubkey, &tweak, &label_tweaks)? {
+                Some(output) => detected.push(output),
+                None => break,
+            }
+        }
+        Ok(detected)
+    }
+
+    /// Returns the private key to spend the detected output as a P2TR key-path input.
+    /// Please note the output key is not tweaked with the Taproot tweak.
+    pub fn spend_private_key(&self, output: &DetectedOutput) -> SigningResult<SecretKey> {
+        let tweak = Scalar::from_be_bytes(output.tweak.take())
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid output tweak")?;
+        self.spend_key
+            .add_tweak(&tweak)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Error tweaking the spend private key")
+    }
+
+    /// Checks whether any of the `outputs` pays to `B_spend + t_k·G` or to a labeled `B_spend + (t_k + label)·G`.
+    fn find_output(
+        outputs: &[H256],
+        spend_pubkey: &PublicKey,
+        tweak: &Scalar,
+        label_tweaks: &[(u32, Scalar)],
+    ) -> SigningResult<Option<DetectedOutput>> {
+        let unlabeled = std::iter::once((None, SecretKey::from_slice(&tweak.to_be_bytes())));
+        let labeled = label_tweaks.iter().map(|(label, label_tweak)| {
+            let total_tweak =
+                SecretKey::from_slice(&tweak.to_be_bytes()).and_then(|t| t.add_tweak(label_tweak));
+            (Some(*label), total_tweak)
+        });
+
+        for (label, total_tweak) in unlabeled.chain(labeled) {
+            let total_tweak = total_tweak
+                .tw_err(|_| SigningErrorType::Error_internal)
+                .context("Invalid Silent Payment tweak")?;
+            let output_key = spend_pubkey
+                .add_exp_tweak(SECP256K1, &Scalar::from(total_tweak))
+                .tw_err(|_| SigningErrorType::Error_internal)
+                .context("Error tweaking the spend public key")?;
+            let output_x_only = H256::from(output_key.x_only_public_key().0.serialize());
+
+            if let Some(output_index) = outputs.iter().position(|output| *output == output_x_only) {
+                return Ok(Some(DetectedOutput {
+                    output_index,
+                    tweak: H256::from(total_tweak.secret_bytes()),
+                    label,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Computes `hash_BIP0352/Label(ser256(b_scan) || ser32(m))`.
+    fn label_tweak(&self, label: u32) -> SigningResult<Scalar> {
+        let mut preimage = self.scan_key.secret_bytes().to_vec();
+        preimage.extend_from_slice(&label.to_be_bytes());
+        hash_to_scalar(LABEL_TAG, &preimage)
+    }
+
+    fn address_with_spend_pubkey(
+        &self,
+        hrp: &str,
+        spend_pubkey: PublicKey,
+    ) -> SigningResult<SilentPaymentAddress> {
+        let scan_pubkey = PublicKey::from_secret_key(SECP256K1, &self.scan_key);
+        SilentPaymentAddress::new(
+            hrp.to_string(),
+            H264::from(scan_pubkey.serialize()),
+            H264::from(spend_pubkey.serialize()),
+        )
+        .into_tw()
+        .context("Invalid Silent Payment address")
+    }
+}
+
+/// Computes `hash_BIP0352/Inputs(outpoint_L || A)`, where `outpoint_L` is the lexicographically smallest out-point
+/// and `A` is the sum of the eligible input public keys.
+pub fn input_hash(out_points: &[OutPoint], input_pubkey_sum: &PublicKey) -> SigningResult<Scalar> {
+    let smallest_out_point = out_points
+        .iter()
+        .map(|out_point| {
+            let mut stream = Stream::default();
+            stream.append(out_point);
+            stream.out()
+        })
+        .min()
+        .or_tw_err(SigningErrorType::Error_invalid_params)
+        .context("No transaction inputs")?;
+
+    let mut preimage = smallest_out_point;
+    preimage.extend_from_slice(&input_pubkey_sum.serialize());
+    hash_to_scalar(INPUTS_TAG, &preimage)
+}
+
+/// Computes `t_k = hash_BIP0352/SharedSecret(ecdh_shared_secret || ser32(k))`.
+pub fn shared_secret_tweak(shared_secret: &PublicKey, k: u32) -> SigningResult<Scalar> {
+    let mut preimage = shared_secret.serialize().to_vec();
+    preimage.extend_from_slice(&k.to_be_bytes());
+    hash_to_scalar(SHARED_SECRET_TAG, &preimage)
+}
+
+fn hash_to_scalar(tag: &[u8], data: &[u8]) -> SigningResult<Scalar> {
+    let hash = H256::try_from(tagged_hash(tag, data).as_slice())
+        .tw_err(|_| SigningErrorType::Error_internal)
+        .context("Tagged hash must be H256")?;
+    Scalar::from_be_bytes(hash.take())
+        .tw_err(|_| SigningErrorType::Error_internal)
+        .context("Tagged hash is not a valid scalar")
+}
+
+fn parse_public_key(pubkey: &H264) -> SigningResult<PublicKey> {
+    PublicKey::from_slice(pubkey.as_slice())
+        .tw_err(|_| SigningErrorType::Error_invalid_params)
+        .context("Invalid Silent Payment public key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use tw_encoding::hex::{DecodeHex, ToHex};
+
+    const ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+
+    fn secret_key(hex: &str) -> SecretKey {
+        SecretKey::from_str(hex).unwrap()
+    }
+
+    fn out_points() -> Vec<OutPoint> {
+        vec![
+            OutPoint {
+                hash: H256::from(
+                    "169e1e83e930853391bc6f35f605c6754cfead57cf8387639d3b4096c54f18f4",
+                ),
+                index: 0,
+            },
+            OutPoint {
+                hash: H256::from(
+                    "8dd4f5fbd5e980fc02f35c6ce145935b11e284605bf599a13c6d415db55d07a1",
+                ),
+                index: 1,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_silent_payment_sender() {
+        let ecdsa_key = InputPrivateKey::Ecdsa(secret_key(
+            "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1",
+        ));
+        // The public key has an odd Y, so the private key must be negated.
+        let taproot_key = InputPrivateKey::Taproot(secret_key(
+            "03bfe77a3c6d37bf8fb39a9ced53970356d91d3ecdefa2a689c82bd00f6a86ee",
+        ));
+        let recipient = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+
+        let sender = SilentPaymentSender::new(&[ecdsa_key, taproot_key], &out_points()).unwrap();
+        let outputs = sender
+            .derive_outputs(&[recipient.clone(), recipient.clone()])
+            .unwrap();
+        assert_eq!(
+            outputs[0].to_hex(),
+            "080e7131209998d0600ed3151cf3be4224e3e7d84cd2f4cfa52df141ff5049c8"
+        );
+        // The second output to the same recipient uses the next counter.
+        assert_eq!(
+            outputs[1].to_hex(),
+            "60dcb2d5f02f79501c1f02ddad383436936a3401d16772b2f83a4418f9afdb74"
+        );
+
+        // A single input.
+        let sender = SilentPaymentSender::new(&[ecdsa_key], &out_points()[..1]).unwrap();
+        let outputs = sender.derive_outputs(&[recipient]).unwrap();
+        assert_eq!(
+            outputs[0].to_hex(),
+            "67fee277da9e8542b5d2e6f32d660a9bbd3f0e107c2d53638ab1d869088882d6"
+        );
+    }
+
+    #[test]
+    fn test_silent_payment_sender_no_inputs() {
+        SilentPaymentSender::new(&[], &out_points()).unwrap_err();
+    }
+
+    fn receiver() -> SilentPaymentReceiver {
+        let seed = "000102030405060708090a0b0c0d0e0f".decode_hex().unwrap();
+        let master_key = ExtendedPrivKey::new_master(bitcoin::Network::Bitcoin, &seed).unwrap();
+        SilentPaymentReceiver::from_master_key(&master_key, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_silent_payment_receiver_address() {
+        let receiver = receiver();
+        assert_eq!(
+            receiver.address("sp").unwrap().to_string(),
+            "sp1qqwjnwdugzyeqe6xhj7unvjy7aw984l2v08kgcn8aypqthf00tckd6qu3hlqqjy9y523c4vyll6hz9svyjmdlppfwsxh7hdzj99kh859z9vc56rgj"
+        );
+        assert_eq!(
+            receiver.labeled_address("sp", 1).unwrap().to_string(),
+            "sp1qqwjnwdugzyeqe6xhj7unvjy7aw984l2v08kgcn8aypqthf00tckd6qlx4nf6p0pn4lnfzk906ahje3n0rrrd3nvnrzznl9k3sel4sppkdsmdwe86"
+        );
+    }
+
+    #[test]
+    fn test_silent_payment_receiver_scan() {
+        let receiver = receiver();
+        let ecdsa_key =
+            secret_key("eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1");
+        let taproot_key =
+            secret_key("03bfe77a3c6d37bf8fb39a9ced53970356d91d3ecdefa2a689c82bd00f6a86ee");
+
+        let sender = SilentPaymentSender::new(
+            &[
+                InputPrivateKey::Ecdsa(ecdsa_key),
+                InputPrivateKey::Taproot(taproot_key),
+            ],
+            &out_points(),
+        )
+        .unwrap();
+        let outputs = sender
+            .derive_outputs(&[
+                receiver.address("sp").unwrap(),
+                receiver.labeled_address("sp", 1).unwrap(),
+            ])
+            .unwrap();
+        assert_eq!(
+            outputs[0].to_hex(),
+            "3e523957d4f2dbbd59ebdbedc46146abf8a1010f7e38950d709e4ccd91b852f6"
+        );
+        assert_eq!(
+            outputs[1].to_hex(),
+            "39e49879d296f025be8359d75e15f3a7bfddcb18b4238f7108c9a305203e8f23"
+        );
+
+        let input_pubkeys = [
+            InputPublicKey::Ecdsa(H264::from(
+                "025a1e61f898173040e20616d43e9f496fba90338a39faa1ed98fcbaeee4dd9be5",
+            )),
+            InputPublicKey::Taproot(H256::from(
+                "a1e5131a02609d6362a568e802103d50b0d243079426fa4e178ceab064ae1279",
+            )),
+        ];
+        // The outputs order in a transaction is arbitrary.
+        let tx_outputs = [outputs[1], outputs[0]];
+
+        // The labeled output is not detected if the label is unknown.
+        let detected = receiver
+            .scan(&input_pubkeys, &out_points(), &tx_outputs, &[])
+            .unwrap();
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].output_index, 1);
+
+        let detected = receiver
+            .scan(&input_pubkeys, &out_points(), &tx_outputs, &[1])
+            .unwrap();
+        assert_eq!(detected.len(), 2);
+        assert_eq!(detected[0].output_index, 1);
+        assert_eq!(detected[0].label, None);
+        assert_eq!(detected[1].output_index, 0);
+        assert_eq!(detected[1].label, Some(1));
+
+        let spend_key_0 = receiver.spend_private_key(&detected[0]).unwrap();
+        assert_eq!(
+            spend_key_0.display_secret().to_string(),
+            "4d0b692d793155d10f96708cfe31743eaddde4c1cd647ff4d7fecf1c46d1704f"
+        );
+        let spend_key_1 = receiver.spend_private_key(&detected[1]).unwrap();
+        assert_eq!(
+            spend_key_1.display_secret().to_string(),
+            "f63736fed947f3bcf7ecf5d36e9220543c895f8ab98da4d485696a4f7a25eaa9"
+        );
+    }
+
+    #[test]
+    fn test_silent_payment_receiver_scan_no_outputs() {
+        let input_pubkeys = [InputPublicKey::Ecdsa(H264::from(
+            "025a1e61f898173040e20616d43e9f496fba90338a39faa1ed98fcbaeee4dd9be5",
+        ))];
+        let outputs = [H256::from(
+            "3e523957d4f2dbbd59ebdbedc46146abf8a1010f7e38950d709e4ccd91b852f6",
+        )];
+        let detected = receiver()
+            .scan(&input_pubkeys, &out_points(), &outputs, &[1])
+            .unwrap();
+        assert!(detected.is_empty());
+    }
+}
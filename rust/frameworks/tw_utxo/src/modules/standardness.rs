@@ -0,0 +1,316 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Standardness limits of the input claiming scripts.
+//! Non-standard transactions are consensus-valid, but rejected by the nodes with the default relay policy.
+
+use crate::script::standard_script::conditions;
+use crate::script::standard_script::opcodes::*;
+use crate::script::{Script, Witness};
+use crate::spending_data::SpendingData;
+use crate::transaction::UtxoToSign;
+use tw_coin_entry::error::prelude::*;
+
+/// The max size of a scriptSig.
+pub const MAX_STANDARD_SCRIPTSIG_SIZE: usize = 1650;
+/// The max size of a single data push, including a P2SH redeem script.
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+/// The max size of a P2WSH witness script.
+pub const MAX_STANDARD_P2WSH_SCRIPT_SIZE: usize = 3600;
+/// The max number of the P2WSH witness stack items, excluding the witness script.
+pub const MAX_STANDARD_P2WSH_STACK_ITEMS: usize = 100;
+/// The max size of a P2WSH witness stack item, excluding the witness script.
+pub const MAX_STANDARD_P2WSH_STACK_ITEM_SIZE: usize = 80;
+/// The max size of a Tapscript witness stack item, excluding the leaf script and the control block.
+pub const MAX_STANDARD_TAPSCRIPT_STACK_ITEM_SIZE: usize = 80;
+
+/// The first byte of the Taproot annex witness item.
+const TAPROOT_ANNEX_TAG: u8 = 0x50;
+/// The leaf version mask of the Taproot control block first byte.
+const TAPROOT_LEAF_MASK: u8 = 0xfe;
+/// The BIP-342 Tapscript leaf version.
+const TAPROOT_LEAF_TAPSCRIPT: u8 = 0xc0;
+
+pub struct StandardnessChecker;
+
+impl StandardnessChecker {
+    /// Checks the claiming script (scriptSig and witness) of the UTXO against the standardness limits.
+    pub fn check_spending_data(utxo_args: &UtxoToSign, claim: &SpendingData) -> SigningResult<()> {
+        Self::check_script_sig(&claim.script_sig)?;
+        Self::check_witness(&utxo_args.prevout_script_pubkey, &claim.witness)
+    }
+
+    fn check_script_sig(script_sig: &Script) -> SigningResult<()> {
+        if script_sig.len() > MAX_STANDARD_SCRIPTSIG_SIZE {
+            return SigningError::err(SigningErrorType::Error_script_redeem).context(format!(
+                "scriptSig exceeds {MAX_STANDARD_SCRIPTSIG_SIZE} bytes"
+            ));
+        }
+
+        let pushes = parse_pushes(script_sig.as_slice())?;
+        if pushes
+            .iter()
+            .any(|push| push.len() > MAX_SCRIPT_ELEMENT_SIZE)
+        {
+            return SigningError::err(SigningErrorType::Error_script_redeem).context(format!(
+                "scriptSig data push exceeds {MAX_SCRIPT_ELEMENT_SIZE} bytes"
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_witness(prevout_script_pubkey: &Script, witness: &Witness) -> SigningResult<()> {
+        let items = witness.as_items();
+
+        if conditions::is_p2wsh(prevout_script_pubkey) {
+            let Some((witness_script, stack)) = items.split_last() else {
+                return Ok(());
+            };
+            if witness_script.len() > MAX_STANDARD_P2WSH_SCRIPT_SIZE {
+                return SigningError::err(SigningErrorType::Error_script_witness_program).context(
+                    format!("Witness script exceeds {MAX_STANDARD_P2WSH_SCRIPT_SIZE} bytes"),
+                );
+            }
+            if stack.len() > MAX_STANDARD_P2WSH_STACK_ITEMS {
+                return SigningError::err(SigningErrorType::Error_script_witness_program).context(
+                    format!("Witness has more than {MAX_STANDARD_P2WSH_STACK_ITEMS} stack items"),
+                );
+            }
+            return check_stack_items(stack, MAX_STANDARD_P2WSH_STACK_ITEM_SIZE);
+        }
+
+        if conditions::is_p2tr(prevout_script_pubkey) {
+            let mut items = items;
+            if items.len() >= 2 {
+                if let Some(annex) = items.last() {
+                    if annex.as_slice().first() == Some(&TAPROOT_ANNEX_TAG) {
+                        items = &items[..items.len() - 1];
+                    }
+                }
+            }
+            // Key-path spend, there is only a signature.
+            if items.len() < 2 {
+                return Ok(());
+            }
+            // The stack item limit applies to the Tapscript leaf version only.
+            let control_block = &items[items.len() - 1];
+            let leaf_version = control_block
+                .as_slice()
+                .first()
+                .map(|byte| byte & TAPROOT_LEAF_MASK);
+            if leaf_version != Some(TAPROOT_LEAF_TAPSCRIPT) {
+                return Ok(());
+            }
+            // Skip the leaf script and the control block.
+            let stack = &items[..items.len() - 2];
+            return check_stack_items(stack, MAX_STANDARD_TAPSCRIPT_STACK_ITEM_SIZE);
+        }
+
+        Ok(())
+    }
+}
+
+fn check_stack_items(stack: &[Script], max_item_size: usize) -> SigningResult<()> {
+    if stack.iter().any(|item| item.len() > max_item_size) {
+        return SigningError::err(SigningErrorType::Error_script_witness_program)
+            .context(format!("Witness stack item exceeds {max_item_size} bytes"));
+    }
+    Ok(())
+}
+
+/// Returns the data pushed by the script, or an error if the script is not push-only.
+fn parse_pushes(script: &[u8]) -> SigningResult<Vec<&[u8]>> {
+    let read_len = |at: usize, bytes: usize| -> SigningResult<usize> {
+        let len_bytes = script
+            .get(at..at + bytes)
+            .or_tw_err(SigningErrorType::Error_script_redeem)
+            .context("scriptSig push is truncated")?;
+        Ok(len_bytes
+            .iter()
+            .rev()
+            .fold(0, |len, byte| (len << 8) | *byte as usize))
+    };
+
+    let mut pushes = Vec::default();
+    let mut i = 0;
+    while i < script.len() {
+        let opcode = script[i];
+        i += 1;
+
+        let data_len = match opcode {
+            OP_PUSHDATA1 => {
+                i += 1;
+                read_len(i - 1, 1)?
+            },
+            OP_PUSHDATA2 => {
+                i += 2;
+                read_len(i - 2, 2)?
+            },
+            OP_PUSHDATA4 => {
+                i += 4;
+                read_len(i - 4, 4)?
+            },
+            // OP_PUSHBYTES_0..=OP_PUSHBYTES_75
+            n if n < OP_PUSHDATA1 => n as usize,
+            // OP_1NEGATE, OP_RESERVED and OP_1..=OP_16 are considered push-only.
+            n if n <= OP_PUSHNUM_16 => continue,
+            _ => {
+                return SigningError::err(SigningErrorType::Error_script_redeem)
+                    .context("scriptSig must be push-only")
+            },
+        };
+
+        let data_end = i
+            .checked_add(data_len)
+            .or_tw_err(SigningErrorType::Error_script_redeem)
+            .context("scriptSig push length overflow")?;
+        let data = script
+            .get(i..data_end)
+            .or_tw_err(SigningErrorType::Error_script_redeem)
+            .context("scriptSig push is truncated")?;
+        pushes.push(data);
+        i = data_end;
+    }
+    Ok(pushes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spending_data::{standard_constructor, SpendingDataConstructor};
+    use crate::transaction::standard_transaction::builder::UtxoBuilder;
+    use tw_hash::H256;
+
+    fn utxo_args(prevout_script_pubkey: Script) -> UtxoToSign {
+        let (_, utxo_args) = UtxoBuilder::default()
+            .prev_txid(H256::default())
+            .prev_index(0)
+            .amount(1_000)
+            .sighash_type(crate::sighash::SighashType::default())
+            .foreign(prevout_script_pubkey, Script::default(), Witness::default())
+            .unwrap();
+        utxo_args
+    }
+
+    fn claim(script_sig: Vec<u8>, witness_items: Vec<Vec<u8>>) -> SpendingData {
+        let mut witness = Witness::default();
+        for item in witness_items {
+            witness.push_item(Script::from(item));
+        }
+        SpendingData {
+            script_sig: Script::from(script_sig),
+            witness,
+        }
+    }
+
+    fn error_type(utxo: &UtxoToSign, claim: &SpendingData) -> Option<SigningErrorType> {
+        StandardnessChecker::check_spending_data(utxo, claim)
+            .err()
+            .map(|e| *e.error_type())
+    }
+
+    #[test]
+    fn test_check_script_sig() {
+        let p2sh = utxo_args(conditions::new_p2sh(&Default::default()));
+
+        // `OP_0 <72 bytes> <71 bytes>`
+        let mut script_sig = vec![0x00, 0x48];
+        script_sig.extend_from_slice(&[1; 72]);
+        script_sig.push(0x47);
+        script_sig.extend_from_slice(&[2; 71]);
+        assert_eq!(error_type(&p2sh, &claim(script_sig.clone(), vec![])), None);
+
+        // `OP_DUP` is not a push opcode.
+        let mut non_push = script_sig.clone();
+        non_push.push(OP_DUP);
+        assert_eq!(
+            error_type(&p2sh, &claim(non_push, vec![])),
+            Some(SigningErrorType::Error_script_redeem)
+        );
+
+        // Truncated push.
+        assert_eq!(
+            error_type(&p2sh, &claim(script_sig[..50].to_vec(), vec![])),
+            Some(SigningErrorType::Error_script_redeem)
+        );
+
+        // The redeem script exceeds 520 bytes.
+        let mut large_push = vec![OP_PUSHDATA2, 0x09, 0x02];
+        large_push.extend_from_slice(&[0; 521]);
+        assert_eq!(
+            error_type(&p2sh, &claim(large_push, vec![])),
+            Some(SigningErrorType::Error_script_redeem)
+        );
+
+        // The scriptSig exceeds 1650 bytes.
+        let mut large_script_sig = Vec::default();
+        for _ in 0..4 {
+            large_script_sig.extend_from_slice(&[OP_PUSHDATA2, 0x08, 0x02]);
+            large_script_sig.extend_from_slice(&[0; 520]);
+        }
+        assert_eq!(
+            error_type(&p2sh, &claim(large_script_sig, vec![])),
+            Some(SigningErrorType::Error_script_redeem)
+        );
+    }
+
+    #[test]
+    fn test_check_p2wsh_witness() {
+        let p2wsh = utxo_args(conditions::new_p2wsh(&Default::default()));
+        let witness_script = vec![OP_CHECKSIG; 100];
+
+        let valid = claim(vec![], vec![vec![1; 72], witness_script.clone()]);
+        assert_eq!(error_type(&p2wsh, &valid), None);
+
+        let large_item = claim(vec![], vec![vec![1; 81], witness_script.clone()]);
+        assert_eq!(
+            error_type(&p2wsh, &large_item),
+            Some(SigningErrorType::Error_script_witness_program)
+        );
+
+        let mut too_many_items = vec![vec![1]; 101];
+        too_many_items.push(witness_script);
+        assert_eq!(
+            error_type(&p2wsh, &claim(vec![], too_many_items)),
+            Some(SigningErrorType::Error_script_witness_program)
+        );
+
+        let large_script = claim(vec![], vec![vec![1; 72], vec![OP_CHECKSIG; 3601]]);
+        assert_eq!(
+            error_type(&p2wsh, &large_script),
+            Some(SigningErrorType::Error_script_witness_program)
+        );
+    }
+
+    #[test]
+    fn test_check_p2tr_witness() {
+        let p2tr = utxo_args(conditions::new_p2tr_dangerous_assume_tweaked(
+            &Default::default(),
+        ));
+
+        // Key-path spend.
+        assert_eq!(error_type(&p2tr, &claim(vec![], vec![vec![1; 65]])), None);
+
+        // Script-path spend, the leaf script and the control block are not limited.
+        let script_path = claim(vec![], vec![vec![1; 65], vec![2; 1000], vec![0xc0; 33]]);
+        assert_eq!(error_type(&p2tr, &script_path), None);
+
+        let large_item = claim(vec![], vec![vec![1; 81], vec![2; 1000], vec![0xc0; 33]]);
+        assert_eq!(
+            error_type(&p2tr, &large_item),
+            Some(SigningErrorType::Error_script_witness_program)
+        );
+
+        // The control block parity bit doesn't affect the leaf version.
+        let odd_parity = claim(vec![], vec![vec![1; 81], vec![2; 1000], vec![0xc1; 33]]);
+        assert_eq!(
+            error_type(&p2tr, &odd_parity),
+            Some(SigningErrorType::Error_script_witness_program)
+        );
+
+        // The stack items of unknown leaf versions are not limited.
+        let unknown_leaf = claim(vec![], vec![vec![1; 81], vec![2; 1000], vec![0xc2; 33]]);
+        assert_eq!(error_type(&p2tr, &unknown_leaf), None);
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Topologically Restricted Until Confirmation (TRUC) transactions policy.
+//! https://github.com/bitcoin/bips/blob/master/bip-0431.mediawiki
+
+use crate::dust::dust_filter::dust_outputs;
+use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
+use crate::transaction::transaction_parts::Amount;
+use std::collections::HashSet;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+
+/// The transaction version that signals TRUC.
+pub const TRUC_VERSION: i32 = 3;
+/// The max `vsize` of a TRUC transaction.
+pub const TRUC_MAX_VSIZE: usize = 10_000;
+/// The max `vsize` of a TRUC transaction that spends an unconfirmed TRUC parent.
+pub const TRUC_CHILD_MAX_VSIZE: usize = 1_000;
+
+pub struct TrucPolicy;
+
+impl TrucPolicy {
+    /// Checks whether the transaction conforms the TRUC size limits.
+    /// Returns warnings on the rules that cannot be verified without the mempool state,
+    /// or an empty list if the transaction is not TRUC.
+    ///
+    /// `unconfirmed_parents` - hashes of the unconfirmed transactions the inputs may spend.
+    /// `dust_threshold` - outputs below the threshold are considered ephemeral dust.
+    pub fn check<Transaction: TransactionInterface>(
+        tx: &Transaction,
+        vsize: usize,
+        unconfirmed_parents: &HashSet<H256>,
+        dust_threshold: Amount,
+    ) -> SigningResult<Vec<String>> {
+        if tx.version() != TRUC_VERSION {
+            return Ok(Vec::default());
+        }
+
+        if vsize > TRUC_MAX_VSIZE {
+            return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+                "TRUC transaction vsize '{vsize}' exceeds {TRUC_MAX_VSIZE} vbytes"
+            ));
+        }
+
+        let mut warnings: Vec<_> = dust_outputs(tx, dust_threshold)
+            .into_iter()
+            .map(|i| {
+                format!("Output '{i}' is ephemeral dust and must be spent by a child transaction in the same package")
+            })
+            .collect();
+
+        let spent_parents: HashSet<_> = tx
+            .inputs()
+            .iter()
+            .map(|txin| txin.previous_output().hash)
+            .filter(|hash| unconfirmed_parents.contains(hash))
+            .collect();
+        if spent_parents.is_empty() {
+            return Ok(warnings);
+        }
+
+        if vsize > TRUC_CHILD_MAX_VSIZE {
+            return SigningError::err(SigningErrorType::Error_tx_too_big).context(format!(
+                "TRUC transaction spending an unconfirmed parent has vsize '{vsize}' exceeding {TRUC_CHILD_MAX_VSIZE} vbytes"
+            ));
+        }
+
+        warnings.push(
+            "The unconfirmed parent must be a TRUC transaction without unconfirmed ancestors"
+                .to_string(),
+        );
+        if spent_parents.len() > 1 {
+            warnings.push(format!(
+                "TRUC transaction spends outputs of {} unconfirmed transactions, but at most one is allowed",
+                spent_parents.len()
+            ));
+        }
+        Ok(warnings)
+    }
+}
@@ -2,7 +2,9 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::modules::sighash_computer::{SighashComputer, TxPreimage};
 use crate::modules::sighash_verifier::SighashVerifier;
+use crate::modules::standardness::StandardnessChecker;
 use crate::sighash::SighashType;
 use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature, FromRawOrDerBytes};
 use crate::spending_data::{
@@ -11,7 +13,7 @@ use crate::spending_data::{
 };
 use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
-use crate::transaction::TransactionPreimage;
+use crate::transaction::{TransactionPreimage, UtxoToSign};
 use std::marker::PhantomData;
 use tw_coin_entry::coin_entry::SignatureBytes;
 use tw_coin_entry::error::prelude::*;
@@ -39,31 +41,54 @@ where
     pub fn compile(
         unsigned_tx: UnsignedTransaction<Transaction>,
         signatures: &[SignatureBytes],
+    ) -> SigningResult<Transaction> {
+        Self::compile_impl(unsigned_tx, signatures, false)
+    }
+
+    /// Compiles an unsigned transaction with the given signatures,
+    /// leaving the inputs with empty signatures unsigned, i.e. with empty claiming scripts.
+    pub fn compile_partially(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        signatures: &[SignatureBytes],
+    ) -> SigningResult<Transaction> {
+        Self::compile_impl(unsigned_tx, signatures, true)
+    }
+
+    fn compile_impl(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        signatures: &[SignatureBytes],
+        allow_unsigned: bool,
     ) -> SigningResult<Transaction> {
         SighashVerifier::<Transaction>::check_signatures_number(
             unsigned_tx.inputs().len(),
             signatures.len(),
         )?;
 
-        let claims = unsigned_tx
-            .input_args()
-            .iter()
+        let (mut signed_tx, unsigned_tx_args) = unsigned_tx.into_parts();
+
+        // Build the claiming script (scriptSig or Witness) and put it into the transaction input right away,
+        // so no intermediate `SpendingData` list is allocated.
+        for (i, ((utxo, utxo_args), sign)) in signed_tx
+            .inputs_mut()
+            .iter_mut()
+            .zip(unsigned_tx_args.iter())
             .zip(signatures)
-            .map(|(utxo, sign)| match utxo.spending_data_constructor {
-                SpendingDataConstructor::Ecdsa(ref ecdsa_constructor) => {
-                    Self::get_ecdsa_spending_data(ecdsa_constructor.as_ref(), sign, utxo.sighash_ty)
-                },
-                SpendingDataConstructor::Schnorr(ref schnorr_constructor) => {
-                    Self::get_schnorr_spending_data(
-                        schnorr_constructor.as_ref(),
-                        sign,
-                        utxo.sighash_ty,
-                    )
-                },
-            })
-            .collect::<SigningResult<_>>()?;
-
-        Self::compile_spending_data(unsigned_tx, claims)
+            .enumerate()
+        {
+            // Foreign UTXOs keep the claiming script provided by another party.
+            if utxo_args.foreign {
+                continue;
+            }
+            if allow_unsigned && sign.is_empty() {
+                continue;
+            }
+
+            let claim = Self::get_spending_data(i, utxo_args, sign)?;
+            Self::check_standardness(i, utxo_args, &claim)?;
+            Self::set_spending_data(utxo, claim)?;
+        }
+
+        Ok(signed_tx)
     }
 
     /// Compiles the transaction with the given spending data.
@@ -76,23 +101,127 @@ where
             claims.len(),
         )?;
 
+        for (i, (utxo_args, claim)) in unsigned_tx
+            .input_args()
+            .iter()
+            .zip(claims.iter())
+            .enumerate()
+        {
+            Self::check_standardness(i, utxo_args, claim)?;
+        }
+
         let mut signed_tx = unsigned_tx.into_transaction();
 
         // Add the claiming script (scriptSig or Witness) to the transaction inputs.
         for (utxo, claim) in signed_tx.inputs_mut().iter_mut().zip(claims.into_iter()) {
-            if claim.script_sig.is_empty() && claim.witness.is_empty() {
-                return SigningError::err(SigningErrorType::Error_invalid_params).context(
-                    "Either scriptSig or witness must be provided to compile a transaction",
-                );
-            }
+            Self::set_spending_data(utxo, claim)?;
+        }
+
+        Ok(signed_tx)
+    }
 
-            utxo.set_script_sig(claim.script_sig);
-            utxo.set_witness(claim.witness);
+    /// Compiles the transaction with the given claims of all inputs, replacing the claim of `input_index`
+    /// with one built from the given `signature`, e.g. after a cosigner re-signed the input.
+    /// Other claims may be empty if the inputs are not signed yet.
+    ///
+    /// The signature is verified against the input's sighash.
+    pub fn replace_signature(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        mut claims: Vec<SpendingData>,
+        input_index: usize,
+        signature: &[u8],
+    ) -> SigningResult<Transaction> {
+        SighashVerifier::<Transaction>::check_signatures_number(
+            unsigned_tx.inputs().len(),
+            claims.len(),
+        )?;
+
+        let utxo_args = unsigned_tx
+            .input_args()
+            .get(input_index)
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("There is no '{input_index}' input"))?;
+        if utxo_args.foreign {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .with_context(|| format!("Input '{input_index}' is foreign and cannot be signed"));
+        }
+
+        let TxPreimage { sighashes } = SighashComputer::preimage_tx(&unsigned_tx)?;
+        let sighash = sighashes
+            .get(input_index)
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Expected a sighash for every input")?;
+        SighashVerifier::<Transaction>::verify_signature(sighash, utxo_args, signature)?;
+
+        let new_claim = Self::get_spending_data(input_index, utxo_args, signature)?;
+
+        Self::check_standardness(input_index, utxo_args, &new_claim)?;
+        claims[input_index] = new_claim;
+
+        let mut signed_tx = unsigned_tx.into_transaction();
+        for (i, (utxo, claim)) in signed_tx
+            .inputs_mut()
+            .iter_mut()
+            .zip(claims.into_iter())
+            .enumerate()
+        {
+            if i == input_index {
+                Self::set_spending_data(utxo, claim)?;
+            } else {
+                utxo.set_script_sig(claim.script_sig);
+                utxo.set_witness(claim.witness);
+            }
         }
 
         Ok(signed_tx)
     }
 
+    /// Checks the claiming script against the standardness limits,
+    /// so the transaction is not rejected by the nodes on broadcast.
+    fn check_standardness(
+        input_index: usize,
+        utxo_args: &UtxoToSign,
+        claim: &SpendingData,
+    ) -> SigningResult<()> {
+        StandardnessChecker::check_spending_data(utxo_args, claim)
+            .with_context(|| format!("Input '{input_index}' is non-standard"))
+    }
+
+    fn set_spending_data(utxo: &mut Transaction::Input, claim: SpendingData) -> SigningResult<()> {
+        if claim.script_sig.is_empty() && claim.witness.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Either scriptSig or witness must be provided to compile a transaction");
+        }
+
+        utxo.set_script_sig(claim.script_sig);
+        utxo.set_witness(claim.witness);
+        Ok(())
+    }
+
+    /// Builds the claiming script from the signature provided by the caller,
+    /// which is expected to be invalid in the worst case.
+    fn get_spending_data(
+        input_index: usize,
+        utxo_args: &UtxoToSign,
+        sign: &[u8],
+    ) -> SigningResult<SpendingData> {
+        match utxo_args.spending_data_constructor {
+            SpendingDataConstructor::Ecdsa(ref ecdsa_constructor) => Self::get_ecdsa_spending_data(
+                ecdsa_constructor.as_ref(),
+                sign,
+                utxo_args.sighash_ty,
+            ),
+            SpendingDataConstructor::Schnorr(ref schnorr_constructor) => {
+                Self::get_schnorr_spending_data(
+                    schnorr_constructor.as_ref(),
+                    sign,
+                    utxo_args.sighash_ty,
+                )
+            },
+        }
+        .with_context(|| format!("Input '{input_index}' has an invalid signature"))
+    }
+
     fn get_ecdsa_spending_data(
         ecdsa_constructor: &dyn EcdsaSpendingDataConstructor,
         sign: &[u8],
@@ -4,6 +4,7 @@
 
 use crate::dust::dust_filter::DustFilter;
 use crate::dust::DustPolicy;
+use crate::modules::utxo_selector::change_split::ChangeSplit;
 use crate::modules::utxo_selector::exact_selector::ExactInputSelector;
 use crate::modules::utxo_selector::max_selector::MaxInputSelector;
 use crate::modules::utxo_selector::{InputSelector, SelectResult};
@@ -33,8 +34,12 @@ pub enum RequestType<Transaction: TransactionInterface> {
     },
     SendExact {
         unsigned_tx: UnsignedTransaction<Transaction>,
-        change_output: Option<Transaction::Output>,
+        /// The change outputs, see [`ExactInputSelector::change_outputs`].
+        change_outputs: Vec<Transaction::Output>,
+        change_split: Option<ChangeSplit>,
         input_selector: InputSelector,
+        /// The output indexes the transaction fee is deducted from.
+        subtract_fee_from: Vec<usize>,
     },
 }
 
@@ -68,15 +73,19 @@ where
             },
             RequestType::SendExact {
                 unsigned_tx,
-                change_output,
+                change_outputs,
+                change_split,
                 input_selector,
+                subtract_fee_from,
             } => {
                 let unsigned_tx = dust_filter
                     .filter_inputs(unsigned_tx)
                     .context("Error filtering dust UTXOs")?;
 
                 ExactInputSelector::new(unsigned_tx)
-                    .maybe_change_output(change_output)
+                    .change_outputs(change_outputs)
+                    .maybe_change_split(change_split)
+                    .subtract_fee_from(subtract_fee_from)
                     .select_inputs(request.dust_policy, input_selector, request.fee_per_vbyte)
             },
         }
@@ -6,7 +6,7 @@ use crate::modules::keys_manager::KeysManager;
 use crate::modules::sighash_computer::{SighashComputer, TxPreimage, UtxoSighash};
 use crate::modules::tx_compiler::TxCompiler;
 use crate::signing_mode::SigningMethod;
-use crate::transaction::transaction_interface::TransactionInterface;
+use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use crate::transaction::TransactionPreimage;
 use std::marker::PhantomData;
@@ -33,20 +33,68 @@ where
         unsigned_tx: UnsignedTransaction<Transaction>,
         keys_manager: &KeysManager,
     ) -> SigningResult<Transaction> {
+        let signatures = Self::sign_sighashes(&unsigned_tx, keys_manager, false)?;
+        TxCompiler::compile(unsigned_tx, &signatures)
+    }
+
+    /// Signs the inputs which private keys are known to the `keys_manager`,
+    /// and leaves the rest unsigned, i.e. with empty claiming scripts.
+    ///
+    /// Returns the partially signed transaction and the indexes of the inputs left unsigned.
+    pub fn sign_tx_partially(
+        unsigned_tx: UnsignedTransaction<Transaction>,
+        keys_manager: &KeysManager,
+    ) -> SigningResult<(Transaction, Vec<usize>)> {
+        let signatures = Self::sign_sighashes(&unsigned_tx, keys_manager, true)?;
+        let signed_tx = TxCompiler::compile_partially(unsigned_tx, &signatures)?;
+
+        // Please note that foreign inputs may also be left without a claiming script.
+        let unsigned_inputs = signed_tx
+            .inputs()
+            .iter()
+            .enumerate()
+            .filter(|(_, txin)| !txin.has_script_sig() && !txin.has_witness())
+            .map(|(i, _)| i)
+            .collect();
+        Ok((signed_tx, unsigned_inputs))
+    }
+
+    /// Signs the sighashes of the transaction inputs.
+    /// If `skip_missing_keys` is set, returns empty signatures for the inputs
+    /// whose private keys are not found instead of an error.
+    fn sign_sighashes(
+        unsigned_tx: &UnsignedTransaction<Transaction>,
+        keys_manager: &KeysManager,
+        skip_missing_keys: bool,
+    ) -> SigningResult<Vec<SignatureBytes>> {
         let TxPreimage { sighashes } =
-            SighashComputer::preimage_tx(&unsigned_tx).context("Error sighash pre-imaging")?;
+            SighashComputer::preimage_tx(unsigned_tx).context("Error sighash pre-imaging")?;
 
-        let signatures: Vec<_> = sighashes
+        sighashes
             .iter()
-            .map(|sighash| match sighash.signing_method {
-                SigningMethod::Legacy | SigningMethod::Segwit => {
-                    Self::sign_legacy_sighash(keys_manager, sighash)
-                },
-                SigningMethod::Taproot => Self::sign_taproot_sighash(keys_manager, sighash),
+            .zip(unsigned_tx.input_args())
+            .map(|(sighash, utxo_args)| {
+                if utxo_args.foreign {
+                    // Foreign UTXOs are claimed by another party.
+                    return Ok(SignatureBytes::default());
+                }
+                let signature = match sighash.signing_method {
+                    SigningMethod::Legacy | SigningMethod::Segwit => {
+                        Self::sign_legacy_sighash(keys_manager, sighash)
+                    },
+                    SigningMethod::Taproot => Self::sign_taproot_sighash(keys_manager, sighash),
+                };
+                match signature {
+                    Err(e)
+                        if skip_missing_keys
+                            && *e.error_type() == SigningErrorType::Error_missing_private_key =>
+                    {
+                        Ok(SignatureBytes::default())
+                    },
+                    other => other,
+                }
             })
-            .collect::<SigningResult<_>>()?;
-
-        TxCompiler::compile(unsigned_tx, &signatures)
+            .collect()
     }
 
     pub fn sign_legacy_sighash(
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::transaction::transaction_parts::Amount;
+use tw_hash::sha2::sha256;
+use tw_memory::Data;
+
+/// The max number of change outputs the change can be split into.
+pub const MAX_CHANGE_SPLIT_OUTPUTS: usize = 10;
+
+/// Splits the change into several outputs of pseudo-random sizes,
+/// making the change output harder to fingerprint.
+/// Each change output must have a distinct scriptPubkey.
+#[derive(Clone, Debug)]
+pub struct ChangeSplit {
+    /// The max number of change outputs, up to [`MAX_CHANGE_SPLIT_OUTPUTS`].
+    pub outputs_count: usize,
+    /// The min amount of each change output.
+    /// Please note the dust threshold is used if it's greater.
+    pub min_amount: Amount,
+    /// The seed the output sizes are derived from.
+    /// The same seed leads to the same split, so `plan` and `sign` produce the same transaction.
+    pub seed: Data,
+}
+
+impl ChangeSplit {
+    /// Splits the `change` amount into at most [`ChangeSplit::outputs_count`] amounts.
+    /// Reduces the number of outputs if the change is not enough to cover the min amounts.
+    pub fn split(&self, change: Amount, dust_threshold: Amount) -> Vec<Amount> {
+        let min_amount = self.min_amount.max(dust_threshold).max(1);
+        let outputs_count = (change / min_amount).clamp(1, self.outputs_count.max(1) as Amount);
+        if outputs_count == 1 {
+            return vec![change];
+        }
+
+        // Each output gets the min amount and a random share of the remaining amount.
+        let remaining = (change - min_amount * outputs_count) as u128;
+        let weights: Vec<u128> = (0..outputs_count as u32)
+            .map(|i| self.random_weight(i))
+            .collect();
+        let total_weight: u128 = weights.iter().sum();
+
+        let mut amounts: Vec<Amount> = weights
+            .iter()
+            .map(|weight| min_amount + (remaining * weight / total_weight) as Amount)
+            .collect();
+
+        // Give the rounding remainder to the last output.
+        let distributed: Amount = amounts.iter().sum();
+        if let Some(last) = amounts.last_mut() {
+            *last += change - distributed;
+        }
+        amounts
+    }
+
+    /// Returns a pseudo-random weight in the `[1, 2^32]` range.
+    fn random_weight(&self, index: u32) -> u128 {
+        let mut preimage = self.seed.clone();
+        preimage.extend_from_slice(&index.to_le_bytes());
+        let hash = sha256(&preimage);
+
+        let mut bytes = [0_u8; 4];
+        bytes.copy_from_slice(&hash[..4]);
+        u32::from_le_bytes(bytes) as u128 + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change_split(outputs_count: usize, min_amount: Amount) -> ChangeSplit {
+        ChangeSplit {
+            outputs_count,
+            min_amount,
+            seed: b"seed".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_change_split() {
+        let split = change_split(3, 1_000);
+        let amounts = split.split(100_000, 546);
+        assert_eq!(amounts.len(), 3);
+        assert_eq!(amounts.iter().sum::<Amount>(), 100_000);
+        assert!(amounts.iter().all(|amount| *amount >= 1_000));
+
+        // The same seed leads to the same split.
+        assert_eq!(split.split(100_000, 546), amounts);
+
+        let other = ChangeSplit {
+            seed: b"other seed".to_vec(),
+            ..split
+        };
+        assert_ne!(other.split(100_000, 546), amounts);
+    }
+
+    #[test]
+    fn test_change_split_reduce_outputs() {
+        // The change is enough for two outputs only.
+        let amounts = change_split(5, 1_000).split(2_500, 546);
+        assert_eq!(amounts.len(), 2);
+        assert_eq!(amounts.iter().sum::<Amount>(), 2_500);
+        assert!(amounts.iter().all(|amount| *amount >= 1_000));
+
+        // The dust threshold is greater than the min amount.
+        let amounts = change_split(5, 100).split(1_000, 546);
+        assert_eq!(amounts, vec![1_000]);
+    }
+}
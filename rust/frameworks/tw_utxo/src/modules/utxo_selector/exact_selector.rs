@@ -5,6 +5,8 @@
 use crate::constants::MAX_TRANSACTION_WEIGHT;
 use crate::dust::DustPolicy;
 use crate::modules::fee_estimator::FeeEstimator;
+use crate::modules::utxo_selector::change_split::{ChangeSplit, MAX_CHANGE_SPLIT_OUTPUTS};
+use crate::modules::utxo_selector::fee_split::split_fee;
 use crate::modules::utxo_selector::{InputSelector, SelectPlan, SelectResult};
 use crate::script::{Script, Witness};
 use crate::transaction::transaction_interface::{
@@ -13,12 +15,15 @@ use crate::transaction::transaction_interface::{
 use crate::transaction::transaction_parts::Amount;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use crate::transaction::UtxoToSign;
+use std::collections::HashSet;
 use tw_coin_entry::error::prelude::*;
 
 /// UTXO selector used to send the exact amounts specified in outputs.
 pub struct ExactInputSelector<Transaction: TransactionInterface> {
     unsigned_tx: UnsignedTransaction<Transaction>,
-    change_output: Option<Transaction::Output>,
+    change_outputs: Vec<Transaction::Output>,
+    change_split: Option<ChangeSplit>,
+    subtract_fee_from: Vec<usize>,
 }
 
 impl<Transaction> ExactInputSelector<Transaction>
@@ -28,12 +33,29 @@ where
     pub fn new(unsigned_tx: UnsignedTransaction<Transaction>) -> Self {
         ExactInputSelector {
             unsigned_tx,
-            change_output: None,
+            change_outputs: Vec::default(),
+            change_split: None,
+            subtract_fee_from: Vec::default(),
         }
     }
 
-    pub fn maybe_change_output(mut self, output: Option<Transaction::Output>) -> Self {
-        self.change_output = output;
+    /// Sets the change outputs. The change goes to the first output,
+    /// unless it's split between the first [`ChangeSplit::outputs_count`] outputs.
+    pub fn change_outputs(mut self, outputs: Vec<Transaction::Output>) -> Self {
+        self.change_outputs = outputs;
+        self
+    }
+
+    /// Splits the change into several outputs if specified.
+    pub fn maybe_change_split(mut self, change_split: Option<ChangeSplit>) -> Self {
+        self.change_split = change_split;
+        self
+    }
+
+    /// Deducts the transaction fee from the outputs at the given indexes instead of adding it on top of the outputs.
+    /// The fee is split between the outputs proportionally to their amounts.
+    pub fn subtract_fee_from(mut self, output_indexes: Vec<usize>) -> Self {
+        self.subtract_fee_from = output_indexes;
         self
     }
 
@@ -48,10 +70,21 @@ where
         // Calculate the total output amount.
         let mut total_out = self.unsigned_tx.total_output()?;
 
-        if let Some(ref change_output) = self.change_output {
-            // Push the change output to the temporary "estimated" transaction only to get the most max possible fee.
-            // Later, if the result change amount is not dust (> dustThreshold),
-            // the change output will be pushed to the result unsigned transaction.
+        for output_idx in self.subtract_fee_from.iter().copied() {
+            if output_idx >= self.unsigned_tx.outputs().len() {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                    "Cannot subtract the fee from a non-existent output '{output_idx}'"
+                ));
+            }
+        }
+
+        let change_outputs_count = self.change_outputs_count()?;
+        self.change_outputs.truncate(change_outputs_count);
+
+        // Push the change output(s) to the temporary "estimated" transaction only to get the most max possible fee.
+        // Later, if the result change amount is not dust (> dustThreshold),
+        // the change output(s) will be pushed to the result unsigned transaction.
+        for change_output in self.change_outputs.iter() {
             estimated_tx.push_output(change_output.clone());
         }
 
@@ -76,6 +109,11 @@ where
             },
         }
 
+        // Move the must-spend UTXOs to the front, so they are always selected.
+        // Please note the sort is stable, so the order within the groups is preserved.
+        utxos.sort_by_key(|(_, arg)| !arg.must_spend);
+        let must_spend_count = utxos.iter().filter(|(_, arg)| arg.must_spend).count();
+
         // Select the UTXOs to cover all the outputs and the fee.
         let mut total_in = 0;
         let mut tx_fee = 0;
@@ -107,14 +145,19 @@ where
             tx_fee = FeeEstimator::estimate_fee(&estimated_tx, fee_rate)?;
 
             // Check if the total input amount covers the total output amount
-            // and the fee.
-            if total_in >= total_out + tx_fee {
-                total_covered = true;
+            // and the fee, unless the fee is deducted from an output.
+            // Please note a must-spend UTXO can increase the fee more than its amount.
+            total_covered = if self.subtract_fee_from.is_empty() {
+                total_in >= total_out + tx_fee
+            } else {
+                total_in >= total_out
+            };
 
-                // Unless we're told to use all inputs, we can stop here.
-                if InputSelector::UseAll != selector {
-                    break;
-                }
+            // Unless we're told to use all inputs, or there are must-spend UTXOs left,
+            // we can stop here.
+            let must_spend_selected = selected_utxos.len() >= must_spend_count;
+            if total_covered && must_spend_selected && InputSelector::UseAll != selector {
+                break;
             }
         }
 
@@ -124,6 +167,32 @@ where
                 .context(format!("Insufficient funds to generate a transaction. Available '{total_in}', required '{total_out}' + fee '{tx_fee}'"));
         }
 
+        // Deduct the fee from the output amounts.
+        if !self.subtract_fee_from.is_empty() {
+            let outputs = self.unsigned_tx.outputs_mut();
+            let amounts: Vec<Amount> = self
+                .subtract_fee_from
+                .iter()
+                .map(|output_idx| outputs[*output_idx].value())
+                .collect();
+            let fee_shares = split_fee(tx_fee, &amounts);
+
+            for (output_idx, fee_share) in self.subtract_fee_from.iter().zip(fee_shares) {
+                let output = &mut outputs[*output_idx];
+                let amount_after_fee = output.value() - fee_share;
+                if amount_after_fee <= 0 {
+                    return SigningError::err(SigningErrorType::Error_not_enough_utxos).context(
+                        format!(
+                            "Output '{output_idx}' amount '{}' doesn't cover its fee share '{fee_share}'",
+                            output.value()
+                        ),
+                    );
+                }
+                output.set_value(amount_after_fee);
+            }
+            total_out -= tx_fee;
+        }
+
         // Calculate the change amount.
         debug_assert!(
             total_in >= total_out + tx_fee,
@@ -132,18 +201,31 @@ where
         let mut change = total_in - total_out - tx_fee;
 
         let dust_threshold = dust_policy.dust_threshold();
-        match self.change_output {
-            Some(mut change_output) if change >= dust_threshold => {
-                change_output.set_value(change);
+        if !self.change_outputs.is_empty() && change >= dust_threshold {
+            let change_amounts = match self.change_split {
+                Some(ref split) => split.split(change, dust_threshold),
+                None => vec![change],
+            };
+            // The split may reduce the number of outputs, so the trailing change outputs are dropped.
+            for (mut change_output, change_amount) in
+                self.change_outputs.into_iter().zip(change_amounts)
+            {
+                change_output.set_value(change_amount);
                 self.unsigned_tx.push_output(change_output);
-                total_out += change;
-            },
+            }
+            total_out += change;
+        } else {
             // Either not specified or the change amount is dust.
-            _ => change = 0,
+            change = 0;
         }
 
         // Clear script_sig's and witnesses before updating the result transaction.
-        for utxo in selected_utxos.iter_mut() {
+        // Foreign UTXOs keep their claiming scripts as they are not signed.
+        for (utxo, _) in selected_utxos
+            .iter_mut()
+            .zip(selected_utxo_args.iter())
+            .filter(|(_, arg)| !arg.foreign)
+        {
             utxo.set_script_sig(Script::default());
             utxo.set_witness(Witness::default());
         }
@@ -168,4 +250,37 @@ where
             plan,
         })
     }
+
+    /// Returns the number of change outputs the change can be sent to.
+    /// Checks there is a distinct change output for each split change amount.
+    fn change_outputs_count(&self) -> SigningResult<usize> {
+        if self.change_outputs.is_empty() {
+            return Ok(0);
+        }
+        let Some(ref split) = self.change_split else {
+            return Ok(1);
+        };
+
+        let outputs_count = split.outputs_count.max(1);
+        if outputs_count > MAX_CHANGE_SPLIT_OUTPUTS {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "The change cannot be split into more than {MAX_CHANGE_SPLIT_OUTPUTS} outputs"
+            ));
+        }
+        if self.change_outputs.len() < outputs_count {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+                "Splitting the change requires {outputs_count} change outputs, provided {}",
+                self.change_outputs.len()
+            ));
+        }
+
+        let mut script_pubkeys = HashSet::with_capacity(outputs_count);
+        for change_output in self.change_outputs.iter().take(outputs_count) {
+            if !script_pubkeys.insert(change_output.script_pubkey().as_slice()) {
+                return SigningError::err(SigningErrorType::Error_invalid_params)
+                    .context("Split change outputs must have distinct scriptPubkeys");
+            }
+        }
+        Ok(outputs_count)
+    }
 }
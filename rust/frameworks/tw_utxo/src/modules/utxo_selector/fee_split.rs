@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::transaction::transaction_parts::Amount;
+
+/// Splits the `fee` between the outputs proportionally to their `amounts`.
+/// Please note Bitcoin Core splits the fee equally between the outputs instead.
+/// The rounding remainder is deducted from the first output, so the shares always sum up to the `fee`.
+pub fn split_fee(fee: Amount, amounts: &[Amount]) -> Vec<Amount> {
+    let total_amount: i128 = amounts.iter().map(|amount| *amount as i128).sum();
+
+    let mut shares: Vec<Amount> = amounts
+        .iter()
+        .map(|amount| match total_amount {
+            0 => 0,
+            _ => (fee as i128 * *amount as i128 / total_amount) as Amount,
+        })
+        .collect();
+
+    let distributed: Amount = shares.iter().sum();
+    if let Some(first) = shares.first_mut() {
+        *first += fee - distributed;
+    }
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_fee() {
+        assert_eq!(split_fee(1_000, &[10_000, 30_000]), vec![250, 750]);
+        // 1_000 / 3 = 333, and the remainder goes to the first output.
+        assert_eq!(
+            split_fee(1_000, &[5_000, 5_000, 5_000]),
+            vec![334, 333, 333]
+        );
+        assert_eq!(split_fee(1_000, &[7_000]), vec![1_000]);
+
+        let shares = split_fee(997, &[1_234, 5_678, 9_101]);
+        assert_eq!(shares, vec![78, 353, 566]);
+        assert_eq!(shares.iter().sum::<Amount>(), 997);
+    }
+
+    #[test]
+    fn test_split_fee_large_amounts() {
+        // The intermediate product doesn't fit `i64`.
+        let amounts = [2_100_000_000_000_000, 2_100_000_000_000_000];
+        assert_eq!(
+            split_fee(100_000_001, &amounts),
+            vec![50_000_001, 50_000_000]
+        );
+    }
+}
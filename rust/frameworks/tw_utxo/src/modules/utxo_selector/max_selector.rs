@@ -51,8 +51,13 @@ where
             .iter()
             .cloned()
             .zip(self.unsigned_tx.input_args().iter().cloned())
-            // Sort the UTXOs in the descending order (largest first).
-            .sorted_by(|(_, a), (_, b)| b.amount.cmp(&a.amount))
+            // Sort the UTXOs in the descending order (largest first),
+            // but keep the must-spend UTXOs at the front, so they are removed last.
+            .sorted_by(|(_, a), (_, b)| {
+                b.must_spend
+                    .cmp(&a.must_spend)
+                    .then(b.amount.cmp(&a.amount))
+            })
             .unzip();
 
         // Remove transaction inputs from lower to higher until we have a transaction
@@ -71,6 +76,10 @@ where
             let removed_arg = selected_utxo_args
                 .pop()
                 .expect("!selected_utxos.is_empty() must be checked already");
+            if removed_arg.must_spend {
+                return SigningError::err(SigningErrorType::Error_tx_too_big)
+                    .context("Too many must-spend UTXOs to fit into a transaction");
+            }
             debug_assert!(total_in >= removed_arg.amount);
             total_in -= removed_arg.amount;
         }
@@ -79,7 +88,12 @@ where
         // If the list of UTXOs is empty, [`UnsignedTransaction::set_inputs`] will return an error.
 
         // Clear script_sig's and witnesses before updating the result transaction.
-        for utxo in selected_utxos.iter_mut() {
+        // Foreign UTXOs keep their claiming scripts as they are not signed.
+        for (utxo, _) in selected_utxos
+            .iter_mut()
+            .zip(selected_utxo_args.iter())
+            .filter(|(_, arg)| !arg.foreign)
+        {
             utxo.set_script_sig(Script::default());
             utxo.set_witness(Witness::default());
         }
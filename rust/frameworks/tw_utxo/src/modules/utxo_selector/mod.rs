@@ -5,7 +5,9 @@
 use crate::transaction::transaction_parts::Amount;
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 
+pub mod change_split;
 pub mod exact_selector;
+pub mod fee_split;
 pub mod max_selector;
 
 #[non_exhaustive]
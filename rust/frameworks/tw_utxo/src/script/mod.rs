@@ -9,6 +9,7 @@ use standard_script::opcodes::*;
 use tw_memory::Data;
 
 pub mod standard_script;
+pub mod taproot_tree;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Script {
@@ -89,6 +90,33 @@ impl Script {
         self.bytes.extend_from_slice(data);
     }
 
+    /// Pushes the given number with the minimal encoding:
+    /// `OP_0`, `OP_1NEGATE`, `OP_1..=OP_16` or a little-endian script number.
+    pub fn push_number(&mut self, n: i64) {
+        match n {
+            0 => self.push(OP_PUSHBYTES_0),
+            -1 => self.push(OP_PUSHNUM_NEG1),
+            1..=16 => self.push(OP_PUSHNUM_1 + (n as u8) - 1),
+            _ => {
+                let negative = n < 0;
+                let mut abs = n.unsigned_abs();
+                let mut number = Vec::with_capacity(9);
+                while abs > 0 {
+                    number.push((abs & 0xff) as u8);
+                    abs >>= 8;
+                }
+                // The most significant bit is the sign bit, so add an extra byte if it's already used.
+                let last = number.len() - 1;
+                if number[last] & 0x80 != 0 {
+                    number.push(if negative { 0x80 } else { 0x00 });
+                } else if negative {
+                    number[last] |= 0x80;
+                }
+                self.push_slice(&number);
+            },
+        }
+    }
+
     /// Appends the given data to the end of the script as-is.
     pub fn append(&mut self, data: &[u8]) {
         self.bytes.extend_from_slice(data);
@@ -136,6 +164,12 @@ impl Witness {
         Self::default()
     }
 
+    pub fn with_capacity(capacity: usize) -> Self {
+        Witness {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
     pub fn push_item(&mut self, item: Script) {
         self.items.push(item);
     }
@@ -208,4 +242,30 @@ mod tests {
 
         assert_eq!(script.bytes.to_hex(), bitcoin_script.to_hex());
     }
+
+    #[test]
+    fn test_script_push_number() {
+        for n in [
+            0,
+            -1,
+            1,
+            16,
+            17,
+            127,
+            128,
+            255,
+            256,
+            -128,
+            144,
+            65_535,
+            -65_536,
+            i32::MAX as i64,
+        ] {
+            let mut script = Script::new();
+            script.push_number(n);
+
+            let bitcoin_script = bitcoin::script::Script::builder().push_int(n).into_bytes();
+            assert_eq!(script.bytes.to_hex(), bitcoin_script.to_hex(), "{n}");
+        }
+    }
 }
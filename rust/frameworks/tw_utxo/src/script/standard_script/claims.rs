@@ -5,13 +5,16 @@ use crate::signature::{BitcoinEcdsaSignature, BitcoinSchnorrSignature};
 
 use super::Script;
 
+/// The length of an `OP_PUSHBYTES_n` prefix.
+const PUSH_PREFIX_LEN: usize = 1;
+
 /// Creates a script to claim a P2PK spending condition (_scriptSig_).
 ///
 /// ```txt
 /// <sig>
 /// ```
 pub fn new_p2pk(sig: &BitcoinEcdsaSignature) -> Script {
-    let mut s = Script::with_capacity(35);
+    let mut s = Script::with_capacity(PUSH_PREFIX_LEN + BitcoinEcdsaSignature::SER_SIZE);
     s.push_slice(&sig.serialize());
     s
 }
@@ -33,15 +36,18 @@ pub fn new_p2pk(sig: &BitcoinEcdsaSignature) -> Script {
 // }
 
 /// Creates a script to claim a P2PKH spending condition (_scriptSig_).
+/// The `pubkey` can be either compressed or uncompressed.
 /// See [`tw_keypair::ecdsa::der::Signature::der_bytes`].
 ///
 /// ```txt
 /// <push><sig><push><pubkey>
 /// ```
-pub fn new_p2pkh(sig: &BitcoinEcdsaSignature, pubkey: &H264) -> Script {
-    let mut s = Script::with_capacity(100);
+pub fn new_p2pkh(sig: &BitcoinEcdsaSignature, pubkey: &[u8]) -> Script {
+    let mut s = Script::with_capacity(
+        PUSH_PREFIX_LEN + BitcoinEcdsaSignature::SER_SIZE + PUSH_PREFIX_LEN + pubkey.len(),
+    );
     s.push_slice(&sig.serialize());
-    s.push_slice(pubkey.as_slice());
+    s.push_slice(pubkey);
     s
 }
 
@@ -72,7 +78,7 @@ pub fn new_p2pkh(sig: &BitcoinEcdsaSignature, pubkey: &H264) -> Script {
 /// <pubkey>
 /// ```
 pub fn new_p2wpkh(sig: &BitcoinEcdsaSignature, pubkey: H264) -> Witness {
-    let mut w = Witness::new();
+    let mut w = Witness::with_capacity(2);
     w.push_item(Script::from(sig.serialize()));
     w.push_item(Script::from(pubkey.into_vec()));
     w
@@ -85,7 +91,7 @@ pub fn new_p2wpkh(sig: &BitcoinEcdsaSignature, pubkey: H264) -> Witness {
 /// <sig>
 /// ```
 pub fn new_p2tr_key_path(sig: Vec<u8>) -> Witness {
-    let mut w = Witness::new();
+    let mut w = Witness::with_capacity(1);
     w.push_item(Script::from(sig));
     w
 }
@@ -103,7 +109,7 @@ pub fn new_p2tr_script_path(
     payload: Script,
     control_block: Vec<u8>,
 ) -> Witness {
-    let mut w = Witness::new();
+    let mut w = Witness::with_capacity(3);
     w.push_item(Script::from(sig.serialize()));
     w.push_item(payload);
     w.push_item(Script::from(control_block));
@@ -1,9 +1,10 @@
 use bitcoin::hashes::Hash;
-use bitcoin::key::TweakedPublicKey;
 use secp256k1::XOnlyPublicKey;
+use tw_coin_entry::error::prelude::*;
 use tw_hash::H160;
 use tw_hash::H256;
 use tw_hash::H264;
+use tw_hash::H520;
 use tw_memory::Data;
 use tw_misc::traits::ToBytesVec;
 
@@ -11,6 +12,12 @@ use super::opcodes::*;
 use super::Script;
 use super::SEGWIT_VERSION;
 
+/// Maximum number of public keys in a multisig script.
+pub const MAX_MULTISIG_PUBKEYS: usize = 20;
+
+/// The witness v1 program of the Pay-to-Anchor output.
+pub const P2A_PROGRAM: [u8; 2] = [0x4e, 0x73];
+
 /// Creates a P2SH spending condition (_scriptPubkey_).
 ///
 /// ```txt
@@ -36,6 +43,19 @@ pub fn new_p2pk(pubkey: &H264) -> Script {
     s
 }
 
+/// Creates a P2PK spending condition (_scriptPubkey_) with an uncompressed public key,
+/// as used by the early coinbase outputs.
+///
+/// ```txt
+/// <push><uncompressed_pubkey> OP_CHECKSIG
+/// ```
+pub fn new_p2pk_uncompressed(pubkey: &H520) -> Script {
+    let mut s = Script::with_capacity(67);
+    s.push_slice(pubkey.as_slice());
+    s.push(OP_CHECKSIG);
+    s
+}
+
 /// Creates a P2PKH spending condition (_scriptPubkey_).
 ///
 /// ```txt
@@ -85,12 +105,11 @@ pub fn new_p2tr_key_path(pubkey: &H264) -> Script {
 }
 
 pub fn new_p2tr_dangerous_assume_tweaked(xonly: &H256) -> Script {
-    // We're relying on the `bitcoin` crate to generate anything Taproot related.
-    let internal_key = XOnlyPublicKey::from_slice(xonly.as_slice()).unwrap();
-    let output_key = TweakedPublicKey::dangerous_assume_tweaked(internal_key);
-
-    let script = bitcoin::ScriptBuf::new_v1_p2tr_tweaked(output_key);
-    Script::from(script.to_vec())
+    // The output key is pushed as is, so invalid x-only keys don't panic.
+    let mut s = Script::with_capacity(34);
+    s.push(OP_PUSHNUM_1);
+    s.push_slice(xonly.as_slice());
+    s
 }
 
 pub fn new_p2tr_script_path(pubkey: &H264, merkle_root: &H256) -> Script {
@@ -109,6 +128,96 @@ pub fn new_p2tr_script_path(pubkey: &H264, merkle_root: &H256) -> Script {
     Script::from(script.to_vec())
 }
 
+/// Creates a Pay-to-Anchor (P2A) spending condition (_scriptPubkey_).
+///
+/// ```txt
+/// OP_1 <push><0x4e73>
+/// ```
+pub fn new_p2a() -> Script {
+    let mut s = Script::with_capacity(4);
+    s.push(OP_PUSHNUM_1);
+    s.push_slice(&P2A_PROGRAM);
+    s
+}
+
+/// Creates a bare BIP-119 `OP_CHECKTEMPLATEVERIFY` spending condition.
+/// Use [`crate::transaction::template_hash::TransactionTemplate`] to compute the template hash.
+///
+/// ```txt
+/// <push><template_hash> OP_CHECKTEMPLATEVERIFY
+/// ```
+#[cfg(feature = "ctv")]
+pub fn new_ctv(template_hash: &H256) -> Script {
+    let mut s = Script::with_capacity(34);
+    s.push_slice(template_hash.as_slice());
+    s.push(OP_CTV);
+    s
+}
+
+/// Creates an M-of-N multisig redeem script. The public keys are used in the given order.
+///
+/// ```txt
+/// <threshold> <push><pubkey_1> ... <push><pubkey_N> <N> OP_CHECKMULTISIG
+/// ```
+pub fn new_multisig(threshold: u8, pubkeys: &[H264]) -> SigningResult<Script> {
+    if pubkeys.is_empty() || pubkeys.len() > MAX_MULTISIG_PUBKEYS {
+        return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+            "Multisig must have from 1 to {MAX_MULTISIG_PUBKEYS} public keys"
+        ));
+    }
+    if threshold == 0 || threshold as usize > pubkeys.len() {
+        return SigningError::err(SigningErrorType::Error_invalid_params).context(format!(
+            "Multisig threshold must be from 1 to the number of public keys ({})",
+            pubkeys.len()
+        ));
+    }
+
+    let mut s = Script::with_capacity(3 + pubkeys.len() * (H264::LEN + 1));
+    s.push_number(threshold as i64);
+    for pubkey in pubkeys {
+        s.push_slice(pubkey.as_slice());
+    }
+    s.push_number(pubkeys.len() as i64);
+    s.push(OP_CHECKMULTISIG);
+    Ok(s)
+}
+
+/// Creates an M-of-N multisig redeem script with the public keys sorted lexicographically (BIP-67),
+/// i.e. the `sortedmulti()` output descriptor semantics.
+pub fn new_sorted_multisig(threshold: u8, pubkeys: &[H264]) -> SigningResult<Script> {
+    let mut sorted = pubkeys.to_vec();
+    sorted.sort();
+    new_multisig(threshold, &sorted)
+}
+
+/// Creates a Tapscript leaf spendable with a signature of the `xonly` key.
+///
+/// ```txt
+/// <push><xonly> OP_CHECKSIG
+/// ```
+pub fn new_tapscript_checksig(xonly: &H256) -> Script {
+    let mut s = Script::with_capacity(34);
+    s.push_slice(xonly.as_slice());
+    s.push(OP_CHECKSIG);
+    s
+}
+
+/// Creates a Tapscript leaf spendable with a signature of the `xonly` key
+/// once the output is `delay` blocks old (BIP-68 relative timelock).
+///
+/// ```txt
+/// <delay> OP_CHECKSEQUENCEVERIFY OP_DROP <push><xonly> OP_CHECKSIG
+/// ```
+pub fn new_tapscript_csv_checksig(delay: u16, xonly: &H256) -> Script {
+    let mut s = Script::with_capacity(40);
+    s.push_number(delay as i64);
+    s.push(OP_CSV);
+    s.push(OP_DROP);
+    s.push_slice(xonly.as_slice());
+    s.push(OP_CHECKSIG);
+    s
+}
+
 pub fn new_op_return(data: &[u8]) -> Script {
     let mut s = Script::with_capacity(83);
     s.push(OP_RETURN);
@@ -116,6 +225,17 @@ pub fn new_op_return(data: &[u8]) -> Script {
     s
 }
 
+/// Creates an OP_RETURN script with each of the `pushes` pushed separately:
+/// `OP_RETURN <push_1> ... <push_N>`.
+pub fn new_op_return_pushes<T: AsRef<[u8]>>(pushes: &[T]) -> Script {
+    let mut s = Script::with_capacity(83);
+    s.push(OP_RETURN);
+    for data in pushes {
+        s.push_slice(data.as_ref());
+    }
+    s
+}
+
 pub fn is_p2sh(s: &Script) -> bool {
     let b = s.as_slice();
     b.len() == 23 && b[0] == OP_HASH160 && b[1] == OP_PUSHBYTES_20 && b[22] == OP_EQUAL
@@ -147,6 +267,11 @@ pub fn is_p2tr(s: &Script) -> bool {
     b.len() == 34 && b[0] == taproot_version_op_code && b[1] == OP_PUSHBYTES_32
 }
 
+pub fn is_p2a(s: &Script) -> bool {
+    let b = s.as_slice();
+    b.len() == 4 && b[0] == OP_PUSHNUM_1 && b[1] == OP_PUSHBYTES_2 && b[2..] == P2A_PROGRAM
+}
+
 pub fn is_op_return(s: &Script) -> bool {
     let b = s.as_slice();
     !b.is_empty() && b[0] == OP_RETURN
@@ -178,6 +178,8 @@ pub const OP_NOP1: u8 = 0xb0;
 pub const OP_CLTV: u8 = 0xb1;
 pub const OP_CSV: u8 = 0xb2;
 pub const OP_NOP4: u8 = 0xb3;
+/// BIP-119 redefines `OP_NOP4`.
+pub const OP_CTV: u8 = OP_NOP4;
 pub const OP_NOP5: u8 = 0xb4;
 pub const OP_NOP6: u8 = 0xb5;
 pub const OP_NOP7: u8 = 0xb6;
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::script::Script;
+use bitcoin::hashes::Hash;
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo};
+use secp256k1::{XOnlyPublicKey, SECP256K1};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::H256;
+use tw_keypair::schnorr;
+use tw_memory::Data;
+
+/// BIP-342 Tapscript leaf version.
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// A leaf of a Taproot script tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaprootLeaf {
+    pub script: Script,
+    /// Leaf version, must be even and not `0x50`.
+    pub version: u8,
+}
+
+impl TaprootLeaf {
+    pub fn new(script: Script, version: u8) -> TaprootLeaf {
+        TaprootLeaf { script, version }
+    }
+
+    /// Creates a leaf with the [`TAPSCRIPT_LEAF_VERSION`] version.
+    pub fn tapscript(script: Script) -> TaprootLeaf {
+        TaprootLeaf::new(script, TAPSCRIPT_LEAF_VERSION)
+    }
+
+    /// Computes the leaf hash committed to in the tree and in the script-path sighash.
+    pub fn leaf_hash(&self) -> SigningResult<H256> {
+        let (script, version) = self.to_bitcoin()?;
+        let leaf_hash = TapLeafHash::from_script(&script, version);
+        Ok(H256::from(leaf_hash.to_byte_array()))
+    }
+
+    /// Checks if the `control_block` proves that the leaf is committed to the Taproot `output_key`,
+    /// so the leaf can be revealed at the script-path spend.
+    pub fn verify_control_block(
+        &self,
+        control_block: &[u8],
+        output_key: &H256,
+    ) -> SigningResult<()> {
+        let (script, version) = self.to_bitcoin()?;
+        let control_block = ControlBlock::decode(control_block)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid Taproot control block")?;
+        if control_block.leaf_version != version {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The control block leaf version doesn't match the leaf");
+        }
+
+        let output_key = XOnlyPublicKey::from_slice(output_key.as_slice())
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Invalid Taproot output key")?;
+        if !control_block.verify_taproot_commitment(SECP256K1, output_key, &script) {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("The control block doesn't commit the leaf to the Taproot output");
+        }
+        Ok(())
+    }
+
+    fn to_bitcoin(&self) -> SigningResult<(bitcoin::ScriptBuf, LeafVersion)> {
+        let version = LeafVersion::from_consensus(self.version)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .with_context(|| format!("Invalid Taproot leaf version: {}", self.version))?;
+        Ok((
+            bitcoin::ScriptBuf::from_bytes(self.script.to_vec()),
+            version,
+        ))
+    }
+}
+
+/// Taproot script tree.
+/// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs
+#[derive(Clone, Debug)]
+pub struct TaprootTree {
+    builder: TaprootBuilder,
+}
+
+impl TaprootTree {
+    /// Builds a tree from the leaves with the given depths, where `0` is the root.
+    /// The leaves must be in the depth-first order.
+    pub fn with_depths<I>(leaves: I) -> SigningResult<TaprootTree>
+    where
+        I: IntoIterator<Item = (u8, TaprootLeaf)>,
+    {
+        let mut builder = TaprootBuilder::new();
+        let mut leaves_count = 0;
+        for (depth, leaf) in leaves {
+            let (script, version) = leaf.to_bitcoin()?;
+            builder = builder
+                .add_leaf_with_ver(depth, script, version)
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .with_context(|| format!("Cannot add a leaf at depth '{depth}'"))?;
+            leaves_count += 1;
+        }
+
+        if leaves_count == 0 {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Taproot tree must have at least one leaf");
+        }
+        if !builder.is_finalizable() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("Taproot tree is incomplete. Check the leaves depths and order");
+        }
+        Ok(TaprootTree { builder })
+    }
+
+    /// Builds a Huffman tree from the leaves with the given `weight`s (relative spend probabilities),
+    /// so the leaves that are more likely to be spent get shorter merkle proofs and cheaper witnesses.
+    pub fn with_huffman_weights<I>(leaves: I) -> SigningResult<TaprootTree>
+    where
+        I: IntoIterator<Item = (u32, TaprootLeaf)>,
+    {
+        let (weights, leaves): (Vec<u32>, Vec<TaprootLeaf>) = leaves.into_iter().unzip();
+        let leaves_with_depths = huffman_depths(&weights)
+            .into_iter()
+            .map(|(depth, leaf_index)| (depth, leaves[leaf_index].clone()));
+        TaprootTree::with_depths(leaves_with_depths).context("Cannot build a Huffman Taproot tree")
+    }
+
+    /// Computes the spend info of the tree committed to the given `internal_pubkey`.
+    pub fn spend_info(
+        &self,
+        internal_pubkey: &schnorr::PublicKey,
+    ) -> SigningResult<TaprootSpendInfo> {
+        let internal_key =
+            secp256k1::XOnlyPublicKey::from_slice(internal_pubkey.x_only().bytes().as_slice())
+                .tw_err(|_| SigningErrorType::Error_invalid_params)
+                .context("Invalid Taproot internal public key")?;
+
+        self.builder
+            .clone()
+            .finalize(&secp256k1::Secp256k1::verification_only(), internal_key)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .context("Taproot tree is incomplete")
+    }
+
+    /// Computes the merkle root of the tree.
+    pub fn merkle_root(&self, internal_pubkey: &schnorr::PublicKey) -> SigningResult<H256> {
+        self.spend_info(internal_pubkey)?
+            .merkle_root()
+            .map(|root| H256::from(root.to_byte_array()))
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("No merkle root of the Taproot tree")
+    }
+
+    /// Computes the serialized control block required to spend the given `leaf`.
+    /// The control block commits to the leaf version.
+    pub fn control_block(
+        &self,
+        internal_pubkey: &schnorr::PublicKey,
+        leaf: &TaprootLeaf,
+    ) -> SigningResult<Data> {
+        let leaf = leaf.to_bitcoin()?;
+        self.spend_info(internal_pubkey)?
+            .control_block(&leaf)
+            .map(|control_block| control_block.serialize())
+            .or_tw_err(SigningErrorType::Error_invalid_params)
+            .context("The script is not a leaf of the Taproot tree")
+    }
+}
+
+enum HuffmanNode {
+    Leaf(usize),
+    Branch(usize, usize),
+}
+
+/// Returns the leaves depths and indexes in the depth-first order of the Huffman tree.
+fn huffman_depths(weights: &[u32]) -> Vec<(u8, usize)> {
+    let mut nodes: Vec<HuffmanNode> = (0..weights.len()).map(HuffmanNode::Leaf).collect();
+    // Min-heap of `(weight, node_index)`. The node index breaks ties, so the tree is deterministic.
+    let mut heap: BinaryHeap<Reverse<(u64, usize)>> = weights
+        .iter()
+        .enumerate()
+        .map(|(leaf_index, weight)| Reverse((u64::from(*weight), leaf_index)))
+        .collect();
+
+    let root = loop {
+        let Some(Reverse((weight_a, a))) = heap.pop() else {
+            // No leaves.
+            return Vec::default();
+        };
+        let Some(Reverse((weight_b, b))) = heap.pop() else {
+            break a;
+        };
+        nodes.push(HuffmanNode::Branch(a, b));
+        heap.push(Reverse((weight_a + weight_b, nodes.len() - 1)));
+    };
+
+    let mut depths = Vec::with_capacity(weights.len());
+    let mut stack = vec![(root, 0_usize)];
+    while let Some((node_index, depth)) = stack.pop() {
+        match nodes[node_index] {
+            // The max depth is checked by the tree builder.
+            HuffmanNode::Leaf(leaf_index) => {
+                depths.push((u8::try_from(depth).unwrap_or(u8::MAX), leaf_index))
+            },
+            HuffmanNode::Branch(left, right) => {
+                // Push the right child first to visit the left one first.
+                stack.push((right, depth + 1));
+                stack.push((left, depth + 1));
+            },
+        }
+    }
+    depths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_encoding::hex::{DecodeHex, ToHex};
+
+    fn leaf(hex: &str) -> TaprootLeaf {
+        TaprootLeaf::tapscript(Script::from(hex.decode_hex().unwrap()))
+    }
+
+    fn internal_pubkey() -> schnorr::PublicKey {
+        let internal_pubkey = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            .decode_hex()
+            .unwrap();
+        schnorr::PublicKey::try_from(internal_pubkey.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_taproot_tree_huffman() {
+        let internal_pubkey = internal_pubkey();
+        // OP_1, OP_2, OP_3
+        let (cold, warm, hot) = (leaf("51"), leaf("52"), leaf("53"));
+
+        let huffman = TaprootTree::with_huffman_weights([
+            (1, cold.clone()),
+            (1, warm.clone()),
+            (8, hot.clone()),
+        ])
+        .unwrap();
+        // The most likely leaf gets the shortest merkle proof: 33 bytes header and one 32 bytes node.
+        let control_block_size =
+            |leaf: &TaprootLeaf| huffman.control_block(&internal_pubkey, leaf).unwrap().len();
+        assert_eq!(control_block_size(&hot), 65);
+        assert_eq!(control_block_size(&cold), 97);
+        assert_eq!(control_block_size(&warm), 97);
+
+        // The same tree built by the depths.
+        let by_depths = TaprootTree::with_depths([(1, hot), (2, cold), (2, warm)]).unwrap();
+        assert_eq!(
+            huffman.merkle_root(&internal_pubkey).unwrap(),
+            by_depths.merkle_root(&internal_pubkey).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_taproot_tree_huffman_depths() {
+        assert_eq!(huffman_depths(&[]), vec![]);
+        assert_eq!(huffman_depths(&[5]), vec![(0, 0)]);
+        // Leaves with the weights 1 and 2 are combined first, then with 4, then with 10.
+        assert_eq!(
+            huffman_depths(&[10, 1, 4, 2]),
+            vec![(3, 1), (3, 3), (2, 2), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_taproot_tree_control_block() {
+        let internal_pubkey = internal_pubkey();
+        let (left, right) = (leaf("51"), leaf("52"));
+
+        let tree = TaprootTree::with_depths([(1, left.clone()), (1, right.clone())]).unwrap();
+        let control_block = tree.control_block(&internal_pubkey, &left).unwrap();
+        // Leaf version with the output key parity, the internal key and the sibling leaf hash.
+        assert_eq!(control_block.len(), 65);
+        assert_eq!(control_block[0] & 0xfe, TAPSCRIPT_LEAF_VERSION);
+        assert_eq!(
+            control_block[1..33].to_hex(),
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert_eq!(control_block[33..], right.leaf_hash().unwrap().take());
+
+        // The script is not a leaf of the tree.
+        tree.control_block(&internal_pubkey, &leaf("53"))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_taproot_leaf_verify_control_block() {
+        let internal_pubkey = internal_pubkey();
+        let (left, right) = (leaf("51"), leaf("52"));
+
+        let tree = TaprootTree::with_depths([(1, left.clone()), (1, right.clone())]).unwrap();
+        let merkle_root = tree.merkle_root(&internal_pubkey).unwrap();
+        let script_pubkey = crate::script::standard_script::conditions::new_p2tr_script_path(
+            &internal_pubkey.compressed(),
+            &merkle_root,
+        );
+        let output_key = H256::try_from(&script_pubkey.as_slice()[2..]).unwrap();
+
+        let control_block = tree.control_block(&internal_pubkey, &left).unwrap();
+        left.verify_control_block(&control_block, &output_key)
+            .unwrap();
+
+        // The control block proves another leaf.
+        right
+            .verify_control_block(&control_block, &output_key)
+            .unwrap_err();
+        // The same leaf with another leaf version.
+        TaprootLeaf::new(left.script.clone(), 0xc2)
+            .verify_control_block(&control_block, &output_key)
+            .unwrap_err();
+        // Truncated control block.
+        left.verify_control_block(&control_block[..64], &output_key)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_taproot_tree_leaf_version() {
+        let internal_pubkey = internal_pubkey();
+        let tapscript = leaf("51");
+        let future = TaprootLeaf::new(tapscript.script.clone(), 0xc2);
+        assert_ne!(tapscript.leaf_hash().unwrap(), future.leaf_hash().unwrap());
+
+        let tree = TaprootTree::with_depths([(1, future.clone()), (1, leaf("52"))]).unwrap();
+        let control_block = tree.control_block(&internal_pubkey, &future).unwrap();
+        assert_eq!(control_block[0] & 0xfe, 0xc2);
+        // The same script with another leaf version is a different leaf.
+        tree.control_block(&internal_pubkey, &tapscript)
+            .unwrap_err();
+
+        // Leaf versions must be even and must not be confused with the annex tag.
+        TaprootLeaf::new(tapscript.script.clone(), 0xc1)
+            .leaf_hash()
+            .unwrap_err();
+        TaprootLeaf::new(tapscript.script, 0x50)
+            .leaf_hash()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_taproot_tree_invalid() {
+        TaprootTree::with_depths(Vec::<(u8, TaprootLeaf)>::new()).unwrap_err();
+        TaprootTree::with_huffman_weights(Vec::<(u32, TaprootLeaf)>::new()).unwrap_err();
+        // The tree is incomplete, the second leaf at depth 1 is missing.
+        TaprootTree::with_depths([(1, leaf("51"))]).unwrap_err();
+    }
+}
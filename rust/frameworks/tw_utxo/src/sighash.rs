@@ -8,6 +8,12 @@ const ANYONE_CAN_PAY_FLAG: u32 = 0x80;
 const FORK_ID_FLAG: u32 = 0x40;
 const BASE_FLAG: u32 = 0x1f;
 const DEFAULT_TAPROOT_SIGHASH_TYPE: u8 = 0;
+#[cfg(feature = "anyprevout")]
+const ANYPREVOUT_MASK: u32 = 0xc0;
+#[cfg(feature = "anyprevout")]
+const ANYPREVOUT_FLAG: u32 = 0x40;
+#[cfg(feature = "anyprevout")]
+const ANYPREVOUT_ANYSCRIPT_FLAG: u32 = 0xc0;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -20,6 +26,27 @@ pub enum SighashBase {
     Single = 3,
 }
 
+/// Experimental BIP-118 modifiers of a Taproot script-path sighash type.
+/// https://github.com/bitcoin/bips/blob/master/bip-0118.mediawiki
+#[cfg(feature = "anyprevout")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnyPrevout {
+    /// Don't commit to the spent outpoint - 0x40
+    AnyPrevout,
+    /// Don't commit to the spent outpoint, amount and scriptPubkey - 0xc0
+    AnyPrevoutAnyScript,
+}
+
+/// Sighash algorithm of a chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SighashAlgorithm {
+    /// Legacy, BIP-143 and BIP-341 sighash, depending on the input type.
+    Standard,
+    /// BIP-143 based sighash of the Bitcoin Cash forks. Every sighash type must have the `SIGHASH_FORKID` flag.
+    /// https://github.com/bitcoincashorg/bitcoincash.org/blob/master/spec/replay-protected-sighash.md
+    ForkId,
+}
+
 /// Signature hash type.
 /// https://en.bitcoin.it/wiki/OP_CHECKSIG#Procedure_for_Hashtype_SIGHASH_SINGLE
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -96,6 +123,39 @@ impl SighashType {
     pub fn fork_id(&self) -> bool {
         (self.raw_sighash & FORK_ID_FLAG) == FORK_ID_FLAG
     }
+
+    /// Returns the sighash type with the `SIGHASH_FORKID` flag set.
+    pub fn with_fork_id(&self) -> Self {
+        SighashType {
+            raw_sighash: self.raw_sighash | FORK_ID_FLAG,
+            base: self.base,
+        }
+    }
+
+    /// Returns the sighash type with the given BIP-118 modifier applied.
+    /// The `Default` Taproot sighash is replaced with `All`, as BIP-118 doesn't allow 0x00 base.
+    #[cfg(feature = "anyprevout")]
+    pub fn with_anyprevout(&self, anyprevout: AnyPrevout) -> Self {
+        let flag = match anyprevout {
+            AnyPrevout::AnyPrevout => ANYPREVOUT_FLAG,
+            AnyPrevout::AnyPrevoutAnyScript => ANYPREVOUT_ANYSCRIPT_FLAG,
+        };
+        SighashType {
+            raw_sighash: self.base as u32 | flag,
+            base: self.base,
+        }
+    }
+
+    /// Returns a BIP-118 modifier if the sighash type is interpreted as a Taproot script-path one.
+    /// Please note the `ANYPREVOUT` flag is the same as [`SighashType::fork_id`].
+    #[cfg(feature = "anyprevout")]
+    pub fn anyprevout(&self) -> Option<AnyPrevout> {
+        match self.raw_sighash & ANYPREVOUT_MASK {
+            ANYPREVOUT_FLAG => Some(AnyPrevout::AnyPrevout),
+            ANYPREVOUT_ANYSCRIPT_FLAG => Some(AnyPrevout::AnyPrevoutAnyScript),
+            _ => None,
+        }
+    }
 }
 
 impl Default for SighashType {
@@ -106,3 +166,35 @@ impl Default for SighashType {
         }
     }
 }
+
+#[cfg(all(test, feature = "anyprevout"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sighash_anyprevout() {
+        let all = SighashType::default();
+        assert_eq!(all.anyprevout(), None);
+
+        let apo = all.with_anyprevout(AnyPrevout::AnyPrevout);
+        assert_eq!(apo.raw_sighash(), 0x41);
+        assert_eq!(apo.anyprevout(), Some(AnyPrevout::AnyPrevout));
+        assert!(!apo.anyone_can_pay());
+
+        let single = SighashType::from_u32(SighashBase::Single as u32).unwrap();
+        let apoas = single.with_anyprevout(AnyPrevout::AnyPrevoutAnyScript);
+        assert_eq!(apoas.raw_sighash(), 0xc3);
+        assert_eq!(apoas.base_type(), SighashBase::Single);
+        assert_eq!(apoas.anyprevout(), Some(AnyPrevout::AnyPrevoutAnyScript));
+
+        // The `Default` Taproot sighash is not allowed with BIP-118 flags.
+        let default_taproot = SighashType::from_u32(0).unwrap();
+        let apo = default_taproot.with_anyprevout(AnyPrevout::AnyPrevout);
+        assert_eq!(apo.raw_sighash(), 0x41);
+        assert!(!apo.is_default_taproot_sighash());
+
+        // `SIGHASH_ANYONECANPAY` alone is not a BIP-118 modifier.
+        let acp = SighashType::from_u32(0x81).unwrap();
+        assert_eq!(acp.anyprevout(), None);
+    }
+}
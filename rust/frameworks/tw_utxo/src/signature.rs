@@ -22,7 +22,7 @@ pub struct BitcoinEcdsaSignature {
 
 impl BitcoinEcdsaSignature {
     // The max size of the serialized signature including sighash type.
-    const SER_SIZE: usize = 73;
+    pub(crate) const SER_SIZE: usize = 73;
 
     pub fn new(sig: der::Signature, sighash_ty: SighashType) -> SigningResult<Self> {
         let der_sig = sig.der_bytes();
@@ -13,7 +13,8 @@ use tw_memory::Data;
 
 #[derive(Clone, Debug)]
 pub struct P2PKH {
-    pub pubkey: H264,
+    /// Either compressed (33 bytes) or uncompressed (65 bytes) public key.
+    pub pubkey: Data,
 }
 
 impl EcdsaSpendingDataConstructor for P2PKH {
@@ -37,6 +38,62 @@ impl EcdsaSpendingDataConstructor for P2PK {
     }
 }
 
+/// Returns the claiming script provided by another party regardless of the signature.
+#[derive(Clone, Debug)]
+pub struct Foreign {
+    pub spending_data: SpendingData,
+}
+
+impl EcdsaSpendingDataConstructor for Foreign {
+    fn get_spending_data(&self, _sig: &BitcoinEcdsaSignature) -> SpendingData {
+        self.spending_data.clone()
+    }
+}
+
+/// An item of a custom scriptSig or witness template.
+#[derive(Clone, Debug)]
+pub enum TemplateItem {
+    /// Data used as is.
+    Data(Data),
+    /// Placeholder substituted with the produced signature.
+    Signature,
+}
+
+/// Builds a custom scriptSig and witness from the templates,
+/// substituting the signature placeholders with the produced signature.
+#[derive(Clone, Debug)]
+pub struct Template {
+    /// The scriptSig items, each one is pushed to the script.
+    pub script_sig: Vec<TemplateItem>,
+    /// The witness stack items.
+    pub witness: Vec<TemplateItem>,
+}
+
+impl EcdsaSpendingDataConstructor for Template {
+    fn get_spending_data(&self, sig: &BitcoinEcdsaSignature) -> SpendingData {
+        let sig = sig.serialize();
+        let item_data = |item: &TemplateItem| match item {
+            TemplateItem::Data(data) => data.clone(),
+            TemplateItem::Signature => sig.clone(),
+        };
+
+        let mut script_sig = Script::default();
+        for item in self.script_sig.iter() {
+            script_sig.push_slice(&item_data(item));
+        }
+
+        let mut witness = Witness::default();
+        for item in self.witness.iter() {
+            witness.push_item(Script::from(item_data(item)));
+        }
+
+        SpendingData {
+            script_sig,
+            witness,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct P2WPKH {
     pub pubkey: H264,
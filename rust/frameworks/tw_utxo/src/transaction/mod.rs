@@ -7,7 +7,7 @@ use crate::sighash::SighashType;
 use crate::signing_mode::SigningMethod;
 use crate::spending_data::SpendingDataConstructor;
 use crate::transaction::transaction_parts::Amount;
-use tw_coin_entry::error::prelude::SigningResult;
+use tw_coin_entry::error::prelude::*;
 use tw_hash::hasher::Hasher;
 use tw_hash::H256;
 use tw_memory::Data;
@@ -15,6 +15,8 @@ use tw_memory::Data;
 pub mod asset;
 // TODO move the module to `tw_bitcoin`.
 pub mod standard_transaction;
+#[cfg(feature = "ctv")]
+pub mod template_hash;
 pub mod transaction_hashing;
 pub mod transaction_interface;
 pub mod transaction_parts;
@@ -23,8 +25,27 @@ pub mod unsigned_transaction;
 
 pub trait TransactionPreimage {
     /// Preimages a transaction for a specific UTXO signing.
-    fn preimage_tx(&self, args: &UtxoPreimageArgs) -> SigningResult<H256>;
-    fn preimage_taproot_tx(&self, args: &UtxoTaprootPreimageArgs) -> SigningResult<H256>;
+    fn preimage_tx(&self, args: &UtxoPreimageArgs) -> SigningResult<SighashPreimage>;
+    fn preimage_taproot_tx(&self, args: &UtxoTaprootPreimageArgs)
+        -> SigningResult<SighashPreimage>;
+}
+
+/// A serialized signature message of a specific UTXO and its hash to be signed.
+#[derive(Debug, Clone)]
+pub struct SighashPreimage {
+    /// The signature message the sighash is computed from.
+    pub preimage: Data,
+    pub sighash: H256,
+}
+
+impl SighashPreimage {
+    /// Hashes the signature message with the given `hasher`.
+    pub fn new(preimage: Data, hasher: Hasher) -> SigningResult<SighashPreimage> {
+        let sighash = H256::try_from(hasher.hash(&preimage).as_slice())
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("Bitcoin sighash must be H256")?;
+        Ok(SighashPreimage { preimage, sighash })
+    }
 }
 
 /// UTXO (unspent transaction output) preimage arguments.
@@ -45,6 +66,9 @@ pub struct UtxoTaprootPreimageArgs {
     pub args: UtxoPreimageArgs,
     pub spent_amounts: Vec<Amount>,
     pub spent_script_pubkeys: Vec<Script>,
+    /// Whether [`UtxoTaprootPreimageArgs::spent_amounts`] and [`UtxoTaprootPreimageArgs::spent_script_pubkeys`]
+    /// contain the spent output of the [`UtxoPreimageArgs::input_index`] input only.
+    pub one_prevout: bool,
 }
 
 /// UTXO signing arguments contain all info required to sign a UTXO (Unspent Transaction Output).
@@ -63,6 +87,18 @@ pub struct UtxoToSign {
     /// Taproot UTXO specific argument.
     /// TODO add `TaprootUtxoSignArgs`.
     pub leaf_hash_code_separator: Option<(H256, u32)>,
+    /// Pay-to-contract commitment the Taproot internal key is tweaked with, if any.
+    /// The private key is tweaked with the commitment before the key-path tweak.
+    pub contract_commitment: Option<H256>,
     pub tx_hasher: Hasher,
     pub sighash_ty: SighashType,
+    /// Whether the UTXO must be selected regardless of the input selection strategy.
+    pub must_spend: bool,
+    /// Whether the UTXO is claimed by another party, e.g. a Payjoin receiver.
+    /// Foreign UTXOs are not signed, their scriptSig and witness are kept as is.
+    pub foreign: bool,
+    /// Whether the Taproot UTXO is signed knowing its own spent output only,
+    /// so the amounts and scriptPubkeys of other inputs are not committed to.
+    /// Requires the `ANYONECANPAY` sighash flag.
+    pub one_prevout: bool,
 }
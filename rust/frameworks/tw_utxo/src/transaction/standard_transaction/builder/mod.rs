@@ -12,7 +12,7 @@ mod utxo;
 
 use crate::transaction::unsigned_transaction::UnsignedTransaction;
 use crate::transaction::UtxoToSign;
-pub use output::OutputBuilder;
+pub use output::{OutputBuilder, OP_RETURN_SCRIPT_LIMIT};
 pub use utxo::UtxoBuilder;
 
 pub fn txid_from_str(txid: &str) -> SigningResult<H256> {
@@ -11,6 +11,9 @@ use tw_hash::{ripemd::bitcoin_hash_160, sha2::sha256, H160, H256};
 use tw_keypair::{ecdsa, schnorr};
 
 pub const OP_RETURN_DATA_LIMIT: usize = 80;
+/// The max size of an OP_RETURN scriptPubkey relayed by the nodes with the default policy.
+/// `OP_RETURN <push> <80 bytes>`.
+pub const OP_RETURN_SCRIPT_LIMIT: usize = 83;
 
 pub struct OutputBuilder {
     amount: Amount,
@@ -52,6 +55,14 @@ impl OutputBuilder {
         }
     }
 
+    /// Creates a P2PK output with an uncompressed public key.
+    pub fn p2pk_uncompressed(self, pubkey: &ecdsa::secp256k1::PublicKey) -> TransactionOutput {
+        TransactionOutput {
+            value: self.amount,
+            script_pubkey: conditions::new_p2pk_uncompressed(&pubkey.uncompressed()),
+        }
+    }
+
     pub fn p2pkh(self, pubkey: &ecdsa::secp256k1::PublicKey) -> TransactionOutput {
         let h = bitcoin_hash_160(pubkey.compressed().as_slice());
         let pubkey_hash: H160 = h
@@ -104,6 +115,16 @@ impl OutputBuilder {
         }
     }
 
+    /// Creates a P2TR key-path output which internal key is committed to the pay-to-contract `commitment`.
+    pub fn p2tr_contract(
+        self,
+        internal_pubkey: &schnorr::PublicKey,
+        commitment: &H256,
+    ) -> SigningResult<TransactionOutput> {
+        let contract_pubkey = internal_pubkey.pay_to_contract(commitment)?;
+        Ok(self.p2tr_key_path(&contract_pubkey))
+    }
+
     pub fn p2tr_dangerous_assume_tweaked(self, xonly: &H256) -> TransactionOutput {
         TransactionOutput {
             value: self.amount,
@@ -135,6 +156,13 @@ impl OutputBuilder {
         Ok(self.p2tr_script_path(pubkey, merkle_root))
     }
 
+    pub fn p2a(self) -> TransactionOutput {
+        TransactionOutput {
+            value: self.amount,
+            script_pubkey: conditions::new_p2a(),
+        }
+    }
+
     pub fn op_return(self, data: &[u8]) -> SigningResult<TransactionOutput> {
         if data.len() > OP_RETURN_DATA_LIMIT {
             return SigningError::err(SigningErrorType::Error_invalid_memo).context(format!(
@@ -147,4 +175,28 @@ impl OutputBuilder {
             script_pubkey: conditions::new_op_return(data),
         })
     }
+
+    /// Creates an OP_RETURN output with several data pushes, as some protocols require distinct pushes.
+    /// The combined size of the pushes is limited by [`OP_RETURN_SCRIPT_LIMIT`].
+    pub fn op_return_pushes<T: AsRef<[u8]>>(
+        self,
+        pushes: &[T],
+    ) -> SigningResult<TransactionOutput> {
+        if pushes.is_empty() {
+            return SigningError::err(SigningErrorType::Error_invalid_memo)
+                .context("At least one OP_RETURN data push is required");
+        }
+
+        let script_pubkey = conditions::new_op_return_pushes(pushes);
+        if script_pubkey.len() > OP_RETURN_SCRIPT_LIMIT {
+            return SigningError::err(SigningErrorType::Error_invalid_memo).context(format!(
+                "OP_RETURN scriptPubkey with all the data pushes can be up to {OP_RETURN_SCRIPT_LIMIT} bytes"
+            ));
+        }
+
+        Ok(TransactionOutput {
+            value: self.amount,
+            script_pubkey,
+        })
+    }
 }
@@ -1,14 +1,17 @@
 use super::TransactionInput;
 use crate::sighash::SighashType;
-use crate::spending_data::{standard_constructor, SpendingDataConstructor};
+use crate::spending_data::{standard_constructor, SpendingData, SpendingDataConstructor};
 use crate::transaction::UtxoToSign;
 use crate::{
-    script::{standard_script::conditions, Script, Witness},
+    script::{
+        standard_script::conditions,
+        taproot_tree::{TaprootLeaf, TaprootTree},
+        Script, Witness,
+    },
     signing_mode::SigningMethod,
     transaction::asset::brc20::{BRC20TransferInscription, Brc20Ticker},
     transaction::transaction_parts::{Amount, OutPoint},
 };
-use bitcoin::hashes::Hash;
 use tw_coin_entry::error::prelude::*;
 use tw_hash::{hasher::Hasher, ripemd::bitcoin_hash_160, H160, H256};
 use tw_keypair::{ecdsa, schnorr};
@@ -23,6 +26,8 @@ pub struct UtxoBuilder {
     prev_index: Option<u32>,
     amount: Option<Amount>,
     sighash_ty: Option<SighashType>,
+    must_spend: bool,
+    one_prevout: bool,
 }
 
 impl UtxoBuilder {
@@ -38,6 +43,8 @@ impl UtxoBuilder {
             prev_index: None,
             amount: None,
             sighash_ty: None,
+            must_spend: false,
+            one_prevout: false,
         }
     }
 
@@ -66,6 +73,18 @@ impl UtxoBuilder {
         self
     }
 
+    /// Forces the UTXO selector to include the UTXO regardless of the selection strategy.
+    pub fn must_spend(mut self, must_spend: bool) -> Self {
+        self.must_spend = must_spend;
+        self
+    }
+
+    /// Signs the Taproot UTXO knowing its own spent output only. Requires the `ANYONECANPAY` sighash flag.
+    pub fn one_prevout(mut self, one_prevout: bool) -> Self {
+        self.one_prevout = one_prevout;
+        self
+    }
+
     fn finalize_out_point(&mut self) -> SigningResult<()> {
         // Populate the input with the previous output.
         self.input.previous_output.hash = self
@@ -144,17 +163,37 @@ impl UtxoBuilder {
                 spender_public_key: pubkey.compressed().to_vec(),
                 amount,
                 leaf_hash_code_separator: None,
+                contract_commitment: None,
                 tx_hasher: DEFAULT_TX_HASHER,
                 sighash_ty,
+                must_spend: self.must_spend,
+                foreign: false,
+                one_prevout: self.one_prevout,
             },
         ))
     }
 
     pub fn p2pkh(
-        mut self,
+        self,
         pubkey: &ecdsa::secp256k1::PublicKey,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        let h = bitcoin_hash_160(pubkey.compressed().as_slice());
+        self.p2pkh_with_pubkey_bytes(pubkey.compressed().to_vec())
+    }
+
+    /// Spends a P2PKH UTXO locked to the hash of an uncompressed public key (65 bytes).
+    /// Such UTXOs are still used by old paper wallets.
+    pub fn p2pkh_uncompressed(
+        self,
+        pubkey: &ecdsa::secp256k1::PublicKey,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        self.p2pkh_with_pubkey_bytes(pubkey.uncompressed().to_vec())
+    }
+
+    fn p2pkh_with_pubkey_bytes(
+        mut self,
+        pubkey: Data,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let h = bitcoin_hash_160(&pubkey);
         let pubkey_hash: H160 = h.as_slice().try_into().expect("hash length is 20 bytes");
 
         self.finalize_out_point()?;
@@ -174,14 +213,18 @@ impl UtxoBuilder {
                 // When the sighash is signed, build a P2PKH script_sig.
                 spending_data_constructor: SpendingDataConstructor::ecdsa(
                     standard_constructor::P2PKH {
-                        pubkey: pubkey.compressed(),
+                        pubkey: pubkey.clone(),
                     },
                 ),
-                spender_public_key: pubkey.compressed().to_vec(),
+                spender_public_key: pubkey,
                 amount,
                 leaf_hash_code_separator: None,
+                contract_commitment: None,
                 tx_hasher: DEFAULT_TX_HASHER,
                 sighash_ty,
+                must_spend: self.must_spend,
+                foreign: false,
+                one_prevout: self.one_prevout,
             },
         ))
     }
@@ -210,6 +253,92 @@ impl UtxoBuilder {
     //     ))
     // }
 
+    /// Adds a UTXO that is claimed by another party with the given `script_sig` and/or `witness`.
+    /// The UTXO won't be signed, but it's still taken into account when computing sighashes of other UTXOs.
+    pub fn foreign(
+        mut self,
+        prevout_script_pubkey: Script,
+        script_sig: Script,
+        witness: Witness,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        self.finalize_out_point()?;
+        let amount = self.finalize_amount()?;
+        let sighash_ty = self.finalize_sighash_type()?;
+
+        self.input.script_sig = script_sig.clone();
+        self.input.witness = witness.clone();
+
+        Ok((
+            self.input,
+            UtxoToSign {
+                prevout_script_pubkey: prevout_script_pubkey.clone(),
+                script_pubkey: prevout_script_pubkey,
+                // The signing method doesn't matter as the UTXO is not signed.
+                signing_method: SigningMethod::Legacy,
+                // Keep the claiming script as is, e.g. when estimating the transaction size.
+                spending_data_constructor: SpendingDataConstructor::ecdsa(
+                    standard_constructor::Foreign {
+                        spending_data: SpendingData {
+                            script_sig,
+                            witness,
+                        },
+                    },
+                ),
+                spender_public_key: Data::default(),
+                amount,
+                leaf_hash_code_separator: None,
+                contract_commitment: None,
+                tx_hasher: DEFAULT_TX_HASHER,
+                sighash_ty,
+                // The UTXO owner expects the input to be spent.
+                must_spend: true,
+                foreign: true,
+                one_prevout: self.one_prevout,
+            },
+        ))
+    }
+
+    /// Spends a UTXO locked to an arbitrary script, e.g. a P2SH or P2WSH UTXO.
+    /// The `script_code` is committed to the sighash, for example, the redeem or the witness script.
+    /// The scriptSig and witness are built from the `template` once the UTXO is signed.
+    pub fn custom(
+        mut self,
+        prevout_script_pubkey: Script,
+        script_code: Script,
+        signing_method: SigningMethod,
+        pubkey: &ecdsa::secp256k1::PublicKey,
+        template: standard_constructor::Template,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        if signing_method == SigningMethod::Taproot {
+            return SigningError::err(SigningErrorType::Error_not_supported).context(
+                "Custom Taproot UTXOs are not supported, use the Taproot script-path instead",
+            );
+        }
+
+        self.finalize_out_point()?;
+        let amount = self.finalize_amount()?;
+        let sighash_ty = self.finalize_sighash_type()?;
+
+        Ok((
+            self.input,
+            UtxoToSign {
+                prevout_script_pubkey,
+                script_pubkey: script_code,
+                signing_method,
+                spending_data_constructor: SpendingDataConstructor::ecdsa(template),
+                spender_public_key: pubkey.compressed().to_vec(),
+                amount,
+                leaf_hash_code_separator: None,
+                contract_commitment: None,
+                tx_hasher: DEFAULT_TX_HASHER,
+                sighash_ty,
+                must_spend: self.must_spend,
+                foreign: false,
+                one_prevout: self.one_prevout,
+            },
+        ))
+    }
+
     pub fn p2wpkh(
         mut self,
         pubkey: &ecdsa::secp256k1::PublicKey,
@@ -241,8 +370,12 @@ impl UtxoBuilder {
                 signing_method: SigningMethod::Segwit,
                 amount,
                 leaf_hash_code_separator: None,
+                contract_commitment: None,
                 tx_hasher: DEFAULT_TX_HASHER,
                 sighash_ty,
+                must_spend: self.must_spend,
+                foreign: false,
+                one_prevout: self.one_prevout,
             },
         ))
     }
@@ -255,6 +388,19 @@ impl UtxoBuilder {
         self.p2tr_key_path_with_tweaked_pubkey(&tweaked_pubkey.x_only())
     }
 
+    /// Spends a P2TR key-path UTXO which internal key is committed to the pay-to-contract `commitment`.
+    pub fn p2tr_contract(
+        self,
+        internal_pubkey: &schnorr::PublicKey,
+        commitment: &H256,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let contract_pubkey = internal_pubkey.pay_to_contract(commitment)?;
+        let (input, mut utxo) = self.p2tr_key_path(&contract_pubkey)?;
+        // The private key needs to be tweaked with the commitment to sign the UTXO.
+        utxo.contract_commitment = Some(*commitment);
+        Ok((input, utxo))
+    }
+
     pub fn p2tr_key_path_with_tweaked_pubkey(
         mut self,
         tweaked_pubkey: &schnorr::XOnlyPublicKey,
@@ -280,38 +426,43 @@ impl UtxoBuilder {
                 spender_public_key: tweaked_pubkey.bytes().to_vec(),
                 amount,
                 leaf_hash_code_separator: None,
+                contract_commitment: None,
                 // Note that we don't use the default double-hasher.
                 tx_hasher: Hasher::Sha256,
                 sighash_ty,
+                must_spend: self.must_spend,
+                foreign: false,
+                one_prevout: self.one_prevout,
             },
         ))
     }
 
+    /// Spends a P2TR script-path UTXO.
+    /// The `control_block` must prove the `leaf` is committed to the output, including the leaf version.
     pub fn p2tr_script_path(
         mut self,
         internal_pubkey: &schnorr::PublicKey,
-        payload: Script,
+        leaf: TaprootLeaf,
         control_block: Data,
         merkle_root: &H256,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        // Construct the leaf hash.
-        let script_buf = bitcoin::ScriptBuf::from_bytes(payload.to_vec());
-        let leaf_hash = bitcoin::taproot::TapLeafHash::from_script(
-            &script_buf,
-            bitcoin::taproot::LeafVersion::TapScript,
-        );
+        // Restore the original scriptPubkey declared at the unspent P2TR output.
+        let prevout_script_pubkey =
+            conditions::new_p2tr_script_path(&internal_pubkey.compressed(), merkle_root);
 
-        // Convert to native.
-        let leaf_hash: H256 = H256::from(leaf_hash.to_byte_array());
+        // The control block can be provided by the caller, so make sure the spend is valid.
+        let output_key = H256::try_from(&prevout_script_pubkey.as_slice()[2..])
+            .expect("P2TR scriptPubkey must contain a 32 byte output key");
+        leaf.verify_control_block(&control_block, &output_key)?;
+
+        // The leaf hash commits to the leaf version.
+        let leaf_hash = leaf.leaf_hash()?;
+        let payload = leaf.script;
 
         self.finalize_out_point()?;
         let amount = self.finalize_amount()?;
         let sighash_ty = self.finalize_sighash_type()?;
 
-        // Restore the original scriptPubkey declared at the unspent P2TR output.
-        let prevout_script_pubkey =
-            conditions::new_p2tr_script_path(&internal_pubkey.compressed(), merkle_root);
-
         Ok((
             self.input,
             UtxoToSign {
@@ -329,22 +480,37 @@ impl UtxoBuilder {
                 spender_public_key: internal_pubkey.compressed().to_vec(),
                 amount,
                 leaf_hash_code_separator: Some((leaf_hash, u32::MAX)),
+                contract_commitment: None,
                 // Note that we don't use the default double-hasher.
                 tx_hasher: Hasher::Sha256,
                 sighash_ty,
+                must_spend: self.must_spend,
+                foreign: false,
+                one_prevout: self.one_prevout,
             },
         ))
     }
 
+    /// Spends a P2TR script-path UTXO. The control block is computed from the script `tree`.
+    pub fn p2tr_script_path_with_tree(
+        self,
+        internal_pubkey: &schnorr::PublicKey,
+        leaf: TaprootLeaf,
+        tree: &TaprootTree,
+    ) -> SigningResult<(TransactionInput, UtxoToSign)> {
+        let control_block = tree.control_block(internal_pubkey, &leaf)?;
+        let merkle_root = tree.merkle_root(internal_pubkey)?;
+        self.p2tr_script_path(internal_pubkey, leaf, control_block, &merkle_root)
+    }
+
     pub fn brc20_transfer(
         self,
         pubkey: &schnorr::PublicKey,
         ticker: String,
         value: String,
     ) -> SigningResult<(TransactionInput, UtxoToSign)> {
-        let ticker = Brc20Ticker::new(ticker).unwrap();
-        let transfer =
-            BRC20TransferInscription::new(&pubkey.compressed(), &ticker, &value).unwrap();
+        let ticker = Brc20Ticker::new(ticker)?;
+        let transfer = BRC20TransferInscription::new(&pubkey.compressed(), &ticker, &value)?;
 
         let control_block = transfer
             .spend_info
@@ -360,7 +526,7 @@ impl UtxoBuilder {
 
         self.p2tr_script_path(
             pubkey,
-            transfer_payload,
+            TaprootLeaf::tapscript(transfer_payload),
             control_block.serialize(),
             &merkle_root,
         )
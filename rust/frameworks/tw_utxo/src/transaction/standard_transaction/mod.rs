@@ -14,10 +14,9 @@ use crate::transaction::transaction_parts::{Amount, OutPoint};
 use crate::transaction::transaction_sighash::fork_id_sighash::ForkIdSighash;
 use crate::transaction::transaction_sighash::legacy_sighash::LegacySighash;
 use crate::transaction::transaction_sighash::witness0_sighash::Witness0Sighash;
-use crate::transaction::{TransactionPreimage, UtxoPreimageArgs};
+use crate::transaction::{SighashPreimage, TransactionPreimage, UtxoPreimageArgs};
 use tw_coin_entry::error::prelude::{ResultContext, SigningError, SigningErrorType, SigningResult};
 use tw_hash::hasher::sha256_d;
-use tw_hash::H256;
 
 use super::transaction_sighash::taproot1_sighash::Taproot1Sighash;
 use super::UtxoTaprootPreimageArgs;
@@ -115,7 +114,53 @@ impl TransactionInterface for Transaction {
     }
 }
 
+/// Weight units contributed by each input and output of a transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WeightBreakdown {
+    /// Weight of each input including its scriptSig and witness, in the same order as the inputs are.
+    pub inputs: Vec<usize>,
+    /// Weight of each output, in the same order as the outputs are.
+    pub outputs: Vec<usize>,
+    /// Weight of the rest of the transaction: version, lock time, inputs and outputs counts,
+    /// and the Segwit marker and flag if any.
+    pub overhead: usize,
+}
+
 impl Transaction {
+    /// Returns the weight contributed by each input and output.
+    /// The sum of the weights and the overhead equals [`TransactionInterface::weight`].
+    ///
+    /// Please note that non-witness inputs of a Segwit transaction still contribute
+    /// one weight unit for the empty witness stack.
+    pub fn weight_breakdown(&self) -> WeightBreakdown {
+        let has_witness = self.has_witness();
+
+        let inputs: Vec<_> = self
+            .inputs
+            .iter()
+            .map(|input| {
+                let witness_size = if has_witness {
+                    input.witness.encoded_size()
+                } else {
+                    0
+                };
+                input.base_size() * SEGWIT_SCALE_FACTOR + witness_size
+            })
+            .collect();
+        let outputs: Vec<_> = self
+            .outputs
+            .iter()
+            .map(|output| output.encoded_size() * SEGWIT_SCALE_FACTOR)
+            .collect();
+
+        let inputs_outputs_weight = inputs.iter().sum::<usize>() + outputs.iter().sum::<usize>();
+        WeightBreakdown {
+            inputs,
+            outputs,
+            overhead: self.weight() - inputs_outputs_weight,
+        }
+    }
+
     /// TODO move to the `TransactionInterface` trait.
     pub fn txid(&self) -> Vec<u8> {
         let encoded = self.without_witness().encode_out();
@@ -218,7 +263,7 @@ impl Encodable for Transaction {
 }
 
 impl TransactionPreimage for Transaction {
-    fn preimage_tx(&self, args: &UtxoPreimageArgs) -> SigningResult<H256> {
+    fn preimage_tx(&self, args: &UtxoPreimageArgs) -> SigningResult<SighashPreimage> {
         match args.signing_method {
             SigningMethod::Legacy if args.sighash_ty.fork_id() => {
                 ForkIdSighash::<Self>::sighash_tx(self, args)
@@ -231,7 +276,7 @@ impl TransactionPreimage for Transaction {
         }
     }
 
-    fn preimage_taproot_tx(&self, tr: &UtxoTaprootPreimageArgs) -> SigningResult<H256> {
+    fn preimage_taproot_tx(&self, tr: &UtxoTaprootPreimageArgs) -> SigningResult<SighashPreimage> {
         match tr.args.signing_method {
             SigningMethod::Legacy | SigningMethod::Segwit => {
                 SigningError::err(SigningErrorType::Error_internal).context(format!(
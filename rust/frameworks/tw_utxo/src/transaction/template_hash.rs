@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Experimental BIP-119 `OP_CHECKTEMPLATEVERIFY` support.
+//! https://github.com/bitcoin/bips/blob/master/bip-0119.mediawiki
+
+use crate::encode::stream::Stream;
+use crate::transaction::standard_transaction::TransactionOutput;
+use tw_coin_entry::error::prelude::*;
+use tw_hash::sha2::sha256;
+use tw_hash::H256;
+
+/// A template of the transaction that spends a CTV-locked output.
+/// The scriptSigs of all inputs are expected to be empty, that is always the case for Segwit and Taproot spends.
+#[derive(Clone, Debug)]
+pub struct TransactionTemplate {
+    pub version: i32,
+    pub lock_time: u32,
+    /// Sequence numbers of all inputs of the spending transaction.
+    pub sequences: Vec<u32>,
+    pub outputs: Vec<TransactionOutput>,
+}
+
+impl TransactionTemplate {
+    /// Computes `DefaultCheckTemplateVerifyHash` of the template for the input at `input_index`.
+    pub fn template_hash(&self, input_index: u32) -> SigningResult<H256> {
+        if input_index as usize >= self.sequences.len() {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("CTV input index is out of bounds");
+        }
+
+        let mut sequences = Stream::default();
+        for sequence in self.sequences.iter() {
+            sequences.append(sequence);
+        }
+
+        let mut outputs = Stream::default();
+        for output in self.outputs.iter() {
+            outputs.append(output);
+        }
+
+        let mut stream = Stream::default();
+        stream
+            .append(&self.version)
+            .append(&self.lock_time)
+            .append(&(self.sequences.len() as u32))
+            .append_raw_slice(&sha256(&sequences.out()))
+            .append(&(self.outputs.len() as u32))
+            .append_raw_slice(&sha256(&outputs.out()))
+            .append(&input_index);
+
+        H256::try_from(sha256(&stream.out()).as_slice())
+            .tw_err(|_| SigningErrorType::Error_internal)
+            .context("CTV template hash must be H256")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::standard_script::conditions;
+    use tw_encoding::hex::ToHex;
+    use tw_hash::H160;
+
+    fn template() -> TransactionTemplate {
+        let pubkey_hash = H160::from("5eaaa4f458f9158f86afcba08dd7448d27045e3d");
+        TransactionTemplate {
+            version: 2,
+            lock_time: 0,
+            sequences: vec![0xFFFF_FFFD, 0xFFFF_FFFF],
+            outputs: vec![
+                TransactionOutput {
+                    value: 10_000,
+                    script_pubkey: conditions::new_p2wpkh(&pubkey_hash),
+                },
+                TransactionOutput {
+                    value: 5_000,
+                    script_pubkey: conditions::new_p2pkh(&pubkey_hash),
+                },
+            ],
+        }
+    }
+
+    /// The expected hashes are cross-checked against an independent implementation of the BIP-119 spec.
+    /// TODO replace with the official `bip-0119/vectors/ctvhash.json` test vectors.
+    #[test]
+    fn test_template_hash() {
+        let template = template();
+
+        let hash = template.template_hash(0).unwrap();
+        assert_eq!(
+            hash.to_hex(),
+            "588b4c789024e39902f88d6ec6d86b952b1a59eec5b70965fd1344e34c7d1c4c"
+        );
+
+        // The hash commits to the input index.
+        let hash = template.template_hash(1).unwrap();
+        assert_eq!(
+            hash.to_hex(),
+            "6a87de09a57573af781d7e8944772eaee263dc37afb8e342cda8be1625c9c6bc"
+        );
+
+        let script = conditions::new_ctv(&hash);
+        assert_eq!(
+            script.as_slice().to_hex(),
+            "206a87de09a57573af781d7e8944772eaee263dc37afb8e342cda8be1625c9c6bcb3"
+        );
+
+        template.template_hash(2).unwrap_err();
+    }
+}
@@ -4,12 +4,90 @@
 
 use crate::encode::stream::Stream;
 use crate::encode::Encodable;
+use std::fmt;
+use std::str::FromStr;
+use tw_coin_entry::error::prelude::*;
+use tw_encoding::hex;
 use tw_hash::H256;
 
 /// Amount in satoshis (Can be negative) in rare cases.
 /// https://github.com/bitcoin/bitcoin/blob/bd5d1688b4311e21c0e0ff89a3ae02ef7d0543b8/src/consensus/amount.h#L11-L12
 pub type Amount = i64;
 
+/// A transaction ID.
+///
+/// Stored in the internal byte order, i.e. as the transaction hash is serialized in an out-point.
+/// Block explorers and the Bitcoin RPC display the txid in the reversed byte order,
+/// which is used by the [`fmt::Display`] and [`FromStr`] implementations.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Txid(H256);
+
+impl Txid {
+    /// Creates a txid from the internal byte order, e.g. from `Proto::OutPoint.hash`.
+    pub fn from_internal_bytes(bytes: &[u8]) -> SigningResult<Txid> {
+        H256::try_from(bytes)
+            .tw_err(|_| SigningErrorType::Error_invalid_params)
+            .with_context(|| {
+                format!(
+                    "Invalid txid length: expected 32 bytes, found {}",
+                    bytes.len()
+                )
+            })
+            .map(Txid)
+    }
+
+    /// Creates a txid from the reversed (RPC) byte order, as displayed by block explorers.
+    pub fn from_rpc_bytes(bytes: &[u8]) -> SigningResult<Txid> {
+        let mut txid = Txid::from_internal_bytes(bytes)?;
+        txid.0.reverse();
+        Ok(txid)
+    }
+
+    /// Returns the txid in the internal byte order.
+    pub fn to_internal_bytes(&self) -> H256 {
+        self.0
+    }
+
+    /// Returns the txid in the reversed (RPC) byte order.
+    pub fn to_rpc_bytes(&self) -> H256 {
+        let mut rpc = self.0;
+        rpc.reverse();
+        rpc
+    }
+}
+
+impl From<H256> for Txid {
+    /// Please note that `hash` is expected in the internal byte order.
+    fn from(hash: H256) -> Self {
+        Txid(hash)
+    }
+}
+
+impl From<Txid> for H256 {
+    fn from(txid: Txid) -> Self {
+        txid.0
+    }
+}
+
+/// Parses a hex-encoded txid in the reversed (RPC) byte order.
+impl FromStr for Txid {
+    type Err = SigningError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s)
+            .tw_err(|_| SigningErrorType::Error_input_parse)
+            .context("Expected a hex-encoded txid")?;
+        Txid::from_rpc_bytes(&bytes)
+    }
+}
+
+/// Displays the txid in the reversed (RPC) byte order.
+impl fmt::Display for Txid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_rpc_bytes(), false))
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct OutPoint {
     pub hash: H256,
@@ -25,3 +103,32 @@ impl Encodable for OutPoint {
         self.hash.encoded_size() + self.index.encoded_size()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RPC_TXID: &str = "5d6bf53576a54be4d92cd8abf58d28ecc9ea7956eaf970d24d6bfcb9fcfe9855";
+    const INTERNAL_TXID: &str = "5598fefcb9fc6b4dd270f9ea5679eac9ec288df5abd82cd9e44ba57635f56b5d";
+
+    #[test]
+    fn test_txid_byte_order() {
+        let txid = Txid::from_str(RPC_TXID).unwrap();
+        assert_eq!(txid.to_string(), RPC_TXID);
+        assert_eq!(txid.to_internal_bytes(), H256::from(INTERNAL_TXID));
+        assert_eq!(txid.to_rpc_bytes(), H256::from(RPC_TXID));
+
+        let internal = hex::decode(INTERNAL_TXID).unwrap();
+        assert_eq!(Txid::from_internal_bytes(&internal).unwrap(), txid);
+        let rpc = hex::decode(RPC_TXID).unwrap();
+        assert_eq!(Txid::from_rpc_bytes(&rpc).unwrap(), txid);
+    }
+
+    #[test]
+    fn test_txid_invalid() {
+        assert!(Txid::from_internal_bytes(&[0; 31]).is_err());
+        assert!(Txid::from_internal_bytes(&[0; 33]).is_err());
+        assert!(Txid::from_str("5d6bf535").is_err());
+        assert!(Txid::from_str("not a hex").is_err());
+    }
+}
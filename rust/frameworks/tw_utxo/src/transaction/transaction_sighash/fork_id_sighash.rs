@@ -4,10 +4,9 @@
 
 use crate::transaction::transaction_interface::TransactionInterface;
 use crate::transaction::transaction_sighash::witness0_sighash::Witness0Sighash;
-use crate::transaction::UtxoPreimageArgs;
+use crate::transaction::{SighashPreimage, UtxoPreimageArgs};
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::SigningResult;
-use tw_hash::H256;
 
 /// `ForkSighash`is used to calculate a preimage hash of a P2PKH unspent output on Bitcoin fork chains
 /// (if `sighash_ty` has `SIGHASH_FORK_ID` flag).
@@ -16,7 +15,7 @@ pub struct ForkIdSighash<Transaction: TransactionInterface> {
 }
 
 impl<Transaction: TransactionInterface> ForkIdSighash<Transaction> {
-    pub fn sighash_tx(tx: &Transaction, args: &UtxoPreimageArgs) -> SigningResult<H256> {
+    pub fn sighash_tx(tx: &Transaction, args: &UtxoPreimageArgs) -> SigningResult<SighashPreimage> {
         Witness0Sighash::sighash_tx(tx, args)
     }
 }
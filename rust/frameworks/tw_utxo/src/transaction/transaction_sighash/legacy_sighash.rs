@@ -6,10 +6,9 @@ use crate::encode::stream::Stream;
 use crate::script::{Script, Witness};
 use crate::sighash::SighashBase;
 use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
-use crate::transaction::UtxoPreimageArgs;
+use crate::transaction::{SighashPreimage, UtxoPreimageArgs};
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::H256;
 
 /// `LegacySighash` is used to calculate a preimage hash of a P2PK, P2PKH or P2SH unspent output.
 pub struct LegacySighash<Transaction: std::fmt::Debug + TransactionInterface> {
@@ -17,7 +16,7 @@ pub struct LegacySighash<Transaction: std::fmt::Debug + TransactionInterface> {
 }
 
 impl<Transaction: std::fmt::Debug + TransactionInterface> LegacySighash<Transaction> {
-    pub fn sighash_tx(tx: &Transaction, args: &UtxoPreimageArgs) -> SigningResult<H256> {
+    pub fn sighash_tx(tx: &Transaction, args: &UtxoPreimageArgs) -> SigningResult<SighashPreimage> {
         let mut tx_preimage = tx.clone();
 
         tx_preimage.replace_inputs(Self::inputs_for_preimage(tx, args)?);
@@ -30,10 +29,7 @@ impl<Transaction: std::fmt::Debug + TransactionInterface> LegacySighash<Transact
             // Append the sighash type.
             .append(&args.sighash_ty.raw_sighash());
 
-        let hash = args.tx_hasher.hash(&stream.out());
-        H256::try_from(hash.as_slice())
-            .tw_err(|_| SigningErrorType::Error_internal)
-            .context("Bitcoin sighash must be H256")
+        SighashPreimage::new(stream.out(), args.tx_hasher)
     }
 
     /// Select and prepare transaction inputs according to the preimage settings.
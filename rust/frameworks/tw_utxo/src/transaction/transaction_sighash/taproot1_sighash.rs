@@ -3,14 +3,21 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::encode::stream::Stream;
+use crate::script::Script;
+#[cfg(feature = "anyprevout")]
+use crate::sighash::AnyPrevout;
 use crate::sighash::SighashBase;
 use crate::transaction::transaction_hashing::TransactionHasher;
-use crate::transaction::transaction_interface::TransactionInterface;
-use crate::transaction::UtxoTaprootPreimageArgs;
+use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
+use crate::transaction::transaction_parts::Amount;
+use crate::transaction::{SighashPreimage, UtxoTaprootPreimageArgs};
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::hasher::tapsighash;
-use tw_hash::H256;
+use tw_hash::hasher::Hasher;
+
+/// BIP-118 public keys are committed with the key version 0x01.
+#[cfg(feature = "anyprevout")]
+const ANYPREVOUT_KEY_VERSION: u8 = 1;
 
 /// `Taproot1Sighash`is used to calculate a preimage hash of a P2WPKH or P2WSH unspent output.
 pub struct Taproot1Sighash<Transaction: TransactionInterface> {
@@ -18,31 +25,58 @@ pub struct Taproot1Sighash<Transaction: TransactionInterface> {
 }
 
 impl<Transaction: TransactionInterface> Taproot1Sighash<Transaction> {
-    pub fn sighash_tx(tx: &Transaction, tr: &UtxoTaprootPreimageArgs) -> SigningResult<H256> {
-        // TODO if anyone_can_pay flag is set, there is no need to append these hashes.
-        // See https://github.com/rust-bitcoin/rust-bitcoin/blob/b0870634f0e4bd4c36e7ab0b7c9c7deb23ae62bf/bitcoin/src/crypto/sighash.rs#L608-L622
-        let prevout_hash = TransactionHasher::<Transaction>::preimage_prevout_hash(tx, &tr.args);
-        let sequence_hash = TransactionHasher::<Transaction>::preimage_sequence_hash(tx, &tr.args);
-        let outputs_hash = TransactionHasher::<Transaction>::preimage_outputs_hash(tx, &tr.args);
-        let spent_amounts_hash = TransactionHasher::<Transaction>::spent_amount_hash(tr);
-        let raw_sighash = tr.args.sighash_ty.serialize_as_taproot()?;
+    pub fn sighash_tx(
+        tx: &Transaction,
+        tr: &UtxoTaprootPreimageArgs,
+    ) -> SigningResult<SighashPreimage> {
+        #[cfg(feature = "anyprevout")]
+        if let Some(anyprevout) = tr.args.sighash_ty.anyprevout() {
+            return Self::sighash_anyprevout(tx, tr, anyprevout);
+        }
 
-        let spent_script_pubkeys_hash = TransactionHasher::<Transaction>::spent_script_pubkeys(tr);
+        let anyone_can_pay = tr.args.sighash_ty.anyone_can_pay();
+        let base = tr.args.sighash_ty.base_type();
+        if tr.one_prevout && !anyone_can_pay {
+            return SigningError::err(SigningErrorType::Error_invalid_params).context(
+                "'one_prevout' requires the 'anyone can pay' sighash type, otherwise all spent outputs are committed",
+            );
+        }
 
-        let mut stream = Stream::default();
+        let input_index = tr.args.input_index;
+        let input = tx
+            .inputs()
+            .get(input_index)
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Input index is out of bounds")?;
 
+        let mut stream = Stream::default();
         stream
             .append(&0u8) // epoch
-            .append(&raw_sighash)
+            .append(&tr.args.sighash_ty.serialize_as_taproot()?)
             .append(&tx.version())
-            .append(&tx.locktime())
-            .append_raw_slice(&prevout_hash)
-            .append_raw_slice(&spent_amounts_hash)
-            .append_raw_slice(&spent_script_pubkeys_hash)
-            .append_raw_slice(&sequence_hash)
-            // TODO: Double check behavior or `Single`
-            // TODO: What about `NonePlusAnyoneCanPay`?.
-            .append_raw_slice(&outputs_hash);
+            .append(&tx.locktime());
+
+        // Unlike Segwit v0, the hashes are omitted rather than zeroed,
+        // and the sequences are committed even if `None` or `Single` sighash type is used.
+        if !anyone_can_pay {
+            let prevout_hash =
+                TransactionHasher::<Transaction>::prevout_hash(tx, tr.args.tx_hasher);
+            let spent_amounts_hash = TransactionHasher::<Transaction>::spent_amount_hash(tr);
+            let spent_script_pubkeys_hash =
+                TransactionHasher::<Transaction>::spent_script_pubkeys(tr);
+            let sequence_hash =
+                TransactionHasher::<Transaction>::sequence_hash(tx, tr.args.tx_hasher);
+            stream
+                .append_raw_slice(&prevout_hash)
+                .append_raw_slice(&spent_amounts_hash)
+                .append_raw_slice(&spent_script_pubkeys_hash)
+                .append_raw_slice(&sequence_hash);
+        }
+        if base == SighashBase::All {
+            let outputs_hash =
+                TransactionHasher::<Transaction>::preimage_outputs_hash(tx, &tr.args);
+            stream.append_raw_slice(&outputs_hash);
+        }
 
         let mut spend_type = 0u8;
 
@@ -52,16 +86,26 @@ impl<Transaction: TransactionInterface> Taproot1Sighash<Transaction> {
 
         stream.append(&spend_type);
 
-        if tr.args.sighash_ty.anyone_can_pay() {
-            return SigningError::err(SigningErrorType::Error_not_supported)
-                .context("'anyone can pay' sighash type is not supported for Taproot yet");
+        if anyone_can_pay {
+            // Commit to the spent output of this input only.
+            let (amount, script_pubkey) = Self::spent_output(tr)?;
+            stream
+                .append(input.previous_output())
+                .append(amount)
+                .append(script_pubkey)
+                .append(&input.sequence());
         } else {
-            stream.append(&(tr.args.input_index as u32));
+            stream.append(&(input_index as u32));
         }
 
-        if tr.args.sighash_ty.base_type() == SighashBase::Single {
-            return SigningError::err(SigningErrorType::Error_not_supported)
-                .context("'single' sighash type is not supported for Taproot yet");
+        if base == SighashBase::Single {
+            if input_index >= tx.outputs().len() {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(
+                    "'single' sighash type requires an output with the same index as the input",
+                );
+            }
+            let output_hash = TransactionHasher::<Transaction>::preimage_outputs_hash(tx, &tr.args);
+            stream.append_raw_slice(&output_hash);
         }
 
         if let Some((leaf_hash, separator)) = tr.args.leaf_hash_code_separator {
@@ -71,9 +115,285 @@ impl<Transaction: TransactionInterface> Taproot1Sighash<Transaction> {
                 .append(&separator);
         }
 
-        let hash = tapsighash(&stream.out());
-        H256::try_from(hash.as_slice())
-            .tw_err(|_| SigningErrorType::Error_internal)
-            .context("Taproot sighash must be H256")
+        SighashPreimage::new(stream.out(), Hasher::TapSighash)
+    }
+
+    /// Computes a BIP-118 `SIGHASH_ANYPREVOUT` or `SIGHASH_ANYPREVOUTANYSCRIPT` signature message.
+    /// https://github.com/bitcoin/bips/blob/master/bip-0118.mediawiki#signature-message
+    #[cfg(feature = "anyprevout")]
+    fn sighash_anyprevout(
+        tx: &Transaction,
+        tr: &UtxoTaprootPreimageArgs,
+        anyprevout: AnyPrevout,
+    ) -> SigningResult<SighashPreimage> {
+        let Some((leaf_hash, separator)) = tr.args.leaf_hash_code_separator else {
+            return SigningError::err(SigningErrorType::Error_invalid_params)
+                .context("ANYPREVOUT sighash type is only allowed for Taproot script-path spends");
+        };
+
+        let input_index = tr.args.input_index;
+        let input = tx
+            .inputs()
+            .get(input_index)
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("Input index is out of bounds")?;
+        let base = tr.args.sighash_ty.base_type();
+
+        let mut stream = Stream::default();
+        stream
+            .append(&0u8) // epoch
+            .append(&tr.args.sighash_ty.serialize()?)
+            .append(&tx.version())
+            .append(&tx.locktime());
+
+        // Neither the prevouts, amounts, scriptPubkeys nor sequences of the inputs are committed.
+        if base == SighashBase::All {
+            let outputs_hash =
+                TransactionHasher::<Transaction>::preimage_outputs_hash(tx, &tr.args);
+            stream.append_raw_slice(&outputs_hash);
+        }
+
+        // The extension flag is always set, the annex is not supported.
+        let spend_type = 2u8;
+        stream.append(&spend_type);
+
+        if anyprevout == AnyPrevout::AnyPrevout {
+            let (amount, script_pubkey) = Self::spent_output(tr)?;
+            stream.append(amount).append(script_pubkey);
+        }
+        stream.append(&input.sequence());
+
+        if base == SighashBase::Single {
+            if input_index >= tx.outputs().len() {
+                return SigningError::err(SigningErrorType::Error_invalid_params).context(
+                    "'single' sighash type requires an output with the same index as the input",
+                );
+            }
+            let output_hash = TransactionHasher::<Transaction>::preimage_outputs_hash(tx, &tr.args);
+            stream.append_raw_slice(&output_hash);
+        }
+
+        if anyprevout == AnyPrevout::AnyPrevout {
+            stream.append_raw_slice(leaf_hash.as_slice());
+        }
+        stream.append(&ANYPREVOUT_KEY_VERSION).append(&separator);
+
+        SighashPreimage::new(stream.out(), Hasher::TapSighash)
+    }
+
+    /// Returns the amount and the scriptPubkey of the output spent by the input being signed.
+    fn spent_output(tr: &UtxoTaprootPreimageArgs) -> SigningResult<(&Amount, &Script)> {
+        let spent_index = if tr.one_prevout {
+            0
+        } else {
+            tr.args.input_index
+        };
+        let amount = tr
+            .spent_amounts
+            .get(spent_index)
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("No spent amount for the input")?;
+        let script_pubkey = tr
+            .spent_script_pubkeys
+            .get(spent_index)
+            .or_tw_err(SigningErrorType::Error_internal)
+            .context("No spent scriptPubkey for the input")?;
+        Ok((amount, script_pubkey))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::sighash_computer::SighashComputer;
+    use crate::sighash::SighashType;
+    use crate::transaction::standard_transaction::builder::{
+        OutputBuilder, TransactionBuilder, UtxoBuilder,
+    };
+    use crate::transaction::standard_transaction::Transaction;
+    use crate::transaction::unsigned_transaction::UnsignedTransaction;
+    use bitcoin::consensus::deserialize;
+    use bitcoin::hashes::Hash;
+    use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+    use bitcoin::{ScriptBuf, TxOut};
+    use tw_coin_entry::error::prelude::*;
+    use tw_hash::H256;
+    use tw_keypair::schnorr;
+
+    const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+
+    /// Builds a transaction spending P2TR UTXOs signed with the given `(sighash_type, one_prevout)` flags.
+    fn build_tx(flags: &[(u32, bool)], outputs: usize) -> UnsignedTransaction<Transaction> {
+        let pubkey = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+
+        let mut builder = TransactionBuilder::new();
+        for (i, (sighash_ty, one_prevout)) in flags.iter().enumerate() {
+            let (utxo, arg) = UtxoBuilder::new()
+                .prev_txid(H256::from([i as u8 + 1; 32]))
+                .prev_index(i as u32)
+                .sequence(0xFFFFFFF0 + i as u32)
+                .amount(10_000 * (i as i64 + 1))
+                .sighash_type(SighashType::from_u32(*sighash_ty).unwrap())
+                .one_prevout(*one_prevout)
+                .p2tr_key_path(&pubkey)
+                .unwrap();
+            builder.push_input(utxo, arg);
+        }
+        for i in 0..outputs {
+            builder.push_output(OutputBuilder::new(5_000 * (i as i64 + 1)).p2tr_key_path(&pubkey));
+        }
+        builder.build().unwrap()
+    }
+
+    /// Computes the sighash of the `input_index` input by the `bitcoin` crate.
+    fn expected_sighash(
+        unsigned_tx: &UnsignedTransaction<Transaction>,
+        input_index: usize,
+        sighash_ty: TapSighashType,
+        one_prevout: bool,
+    ) -> H256 {
+        let tx: bitcoin::Transaction =
+            deserialize(&unsigned_tx.transaction().encode_out()).unwrap();
+        let spent: Vec<TxOut> = unsigned_tx
+            .input_args()
+            .iter()
+            .map(|utxo| TxOut {
+                value: utxo.amount as u64,
+                script_pubkey: ScriptBuf::from(utxo.prevout_script_pubkey.to_vec()),
+            })
+            .collect();
+
+        let prevouts = if one_prevout {
+            Prevouts::One(input_index, spent[input_index].clone())
+        } else {
+            Prevouts::All(&spent)
+        };
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(input_index, &prevouts, sighash_ty)
+            .unwrap();
+        H256::from(sighash.to_byte_array())
+    }
+
+    #[test]
+    fn test_taproot_sighash_mixed_flags() {
+        // `ALL|ANYONECANPAY` signed knowing its own spent output only, `SINGLE` and `NONE|ANYONECANPAY`.
+        let unsigned_tx = build_tx(&[(0x81, true), (0x03, false), (0x82, false)], 2);
+        let preimage = SighashComputer::preimage_tx(&unsigned_tx).unwrap();
+
+        let expected = [
+            expected_sighash(&unsigned_tx, 0, TapSighashType::AllPlusAnyoneCanPay, true),
+            expected_sighash(&unsigned_tx, 1, TapSighashType::Single, false),
+            expected_sighash(&unsigned_tx, 2, TapSighashType::NonePlusAnyoneCanPay, false),
+        ];
+        for (i, (actual, expected)) in preimage.sighashes.iter().zip(expected).enumerate() {
+            assert_eq!(actual.sighash, expected, "Wrong sighash at {i}");
+        }
+    }
+
+    #[test]
+    fn test_taproot_sighash_one_prevout_independent_of_other_inputs() {
+        let sighash = |flags: &[(u32, bool)]| {
+            let unsigned_tx = build_tx(flags, 1);
+            SighashComputer::preimage_tx(&unsigned_tx)
+                .unwrap()
+                .sighashes[0]
+                .sighash
+        };
+        // Other inputs are not committed to by the `SINGLE|ANYONECANPAY` sighash.
+        assert_eq!(
+            sighash(&[(0x83, true)]),
+            sighash(&[(0x83, true), (0x01, false)])
+        );
+    }
+
+    #[test]
+    fn test_taproot_sighash_one_prevout_requires_anyone_can_pay() {
+        let unsigned_tx = build_tx(&[(0x01, true)], 1);
+        let err = SighashComputer::preimage_tx(&unsigned_tx).unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    }
+
+    #[test]
+    fn test_taproot_sighash_single_without_output() {
+        let unsigned_tx = build_tx(&[(0x01, false), (0x03, false)], 1);
+        let err = SighashComputer::preimage_tx(&unsigned_tx).unwrap_err();
+        assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+    }
+
+    #[cfg(feature = "anyprevout")]
+    mod anyprevout {
+        use super::*;
+        use crate::script::taproot_tree::{TaprootLeaf, TaprootTree};
+        use crate::script::Script;
+        use crate::sighash::AnyPrevout;
+
+        /// Builds a transaction spending a P2TR script-path UTXO with the given BIP-118 flag.
+        fn build_apo_tx(
+            anyprevout: AnyPrevout,
+            prev_txid: u8,
+            amount: i64,
+        ) -> UnsignedTransaction<Transaction> {
+            let pubkey = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+            // OP_1
+            let leaf = TaprootLeaf::tapscript(Script::from(vec![0x51]));
+            let tree = TaprootTree::with_depths([(0, leaf.clone())]).unwrap();
+
+            let (utxo, arg) = UtxoBuilder::new()
+                .prev_txid(H256::from([prev_txid; 32]))
+                .prev_index(0)
+                .amount(amount)
+                .sighash_type(SighashType::default().with_anyprevout(anyprevout))
+                .p2tr_script_path_with_tree(&pubkey, leaf, &tree)
+                .unwrap();
+
+            let mut builder = TransactionBuilder::new();
+            builder.push_input(utxo, arg);
+            builder.push_output(OutputBuilder::new(5_000).p2tr_key_path(&pubkey));
+            builder.build().unwrap()
+        }
+
+        fn apo_sighash(anyprevout: AnyPrevout, prev_txid: u8, amount: i64) -> H256 {
+            let unsigned_tx = build_apo_tx(anyprevout, prev_txid, amount);
+            SighashComputer::preimage_tx(&unsigned_tx)
+                .unwrap()
+                .sighashes[0]
+                .sighash
+        }
+
+        #[test]
+        fn test_taproot_sighash_anyprevout() {
+            // The spent outpoint is not committed, but the amount is.
+            let apo = apo_sighash(AnyPrevout::AnyPrevout, 1, 10_000);
+            assert_eq!(apo, apo_sighash(AnyPrevout::AnyPrevout, 2, 10_000));
+            assert_ne!(apo, apo_sighash(AnyPrevout::AnyPrevout, 1, 20_000));
+
+            // Neither the spent outpoint nor the amount are committed.
+            let apoas = apo_sighash(AnyPrevout::AnyPrevoutAnyScript, 1, 10_000);
+            assert_eq!(
+                apoas,
+                apo_sighash(AnyPrevout::AnyPrevoutAnyScript, 2, 20_000)
+            );
+            assert_ne!(apo, apoas);
+        }
+
+        #[test]
+        fn test_taproot_sighash_anyprevout_key_path() {
+            let pubkey = schnorr::PrivateKey::try_from(PRIVATE_KEY).unwrap().public();
+            let (utxo, arg) = UtxoBuilder::new()
+                .prev_txid(H256::from([1; 32]))
+                .prev_index(0)
+                .amount(10_000)
+                .sighash_type(SighashType::default().with_anyprevout(AnyPrevout::AnyPrevout))
+                .p2tr_key_path(&pubkey)
+                .unwrap();
+
+            let mut builder = TransactionBuilder::new();
+            builder.push_input(utxo, arg);
+            builder.push_output(OutputBuilder::new(5_000).p2tr_key_path(&pubkey));
+            let unsigned_tx = builder.build().unwrap();
+
+            // BIP-118 sighash types are allowed for script-path spends only.
+            let err = SighashComputer::preimage_tx(&unsigned_tx).unwrap_err();
+            assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+        }
     }
 }
@@ -5,10 +5,9 @@
 use crate::encode::stream::Stream;
 use crate::transaction::transaction_hashing::TransactionHasher;
 use crate::transaction::transaction_interface::{TransactionInterface, TxInputInterface};
-use crate::transaction::UtxoPreimageArgs;
+use crate::transaction::{SighashPreimage, UtxoPreimageArgs};
 use std::marker::PhantomData;
 use tw_coin_entry::error::prelude::*;
-use tw_hash::H256;
 
 /// `Witness0Sighash`is used to calculate a preimage hash of a P2WPKH or P2WSH unspent output.
 pub struct Witness0Sighash<Transaction: TransactionInterface> {
@@ -16,7 +15,7 @@ pub struct Witness0Sighash<Transaction: TransactionInterface> {
 }
 
 impl<Transaction: TransactionInterface> Witness0Sighash<Transaction> {
-    pub fn sighash_tx(tx: &Transaction, args: &UtxoPreimageArgs) -> SigningResult<H256> {
+    pub fn sighash_tx(tx: &Transaction, args: &UtxoPreimageArgs) -> SigningResult<SighashPreimage> {
         let input = tx
             .inputs()
             .get(args.input_index)
@@ -47,9 +46,6 @@ impl<Transaction: TransactionInterface> Witness0Sighash<Transaction> {
             // This also includes 24-bit fork id. For example, 0 for BitcoinCash.
             .append(&args.sighash_ty.raw_sighash());
 
-        let hash = args.tx_hasher.hash(&stream.out());
-        H256::try_from(hash.as_slice())
-            .tw_err(|_| SigningErrorType::Error_internal)
-            .context("Bitcoin sighash must be H256")
+        SighashPreimage::new(stream.out(), args.tx_hasher)
     }
 }
@@ -9,6 +9,7 @@ use crate::transaction::transaction_interface::{
 };
 use crate::transaction::transaction_parts::Amount;
 use crate::transaction::UtxoToSign;
+use std::collections::HashSet;
 use tw_coin_entry::error::prelude::*;
 
 pub struct UnsignedTransaction<Transaction> {
@@ -27,6 +28,7 @@ where
         }
 
         check_utxo_args_number(transaction.inputs().len(), utxo_args.len())?;
+        check_duplicate_utxos(transaction.inputs())?;
         Ok(UnsignedTransaction {
             transaction,
             utxo_args,
@@ -41,6 +43,11 @@ where
         self.transaction
     }
 
+    /// Splits the unsigned transaction into the transaction itself and the UTXO signing arguments.
+    pub fn into_parts(self) -> (Transaction, Vec<UtxoToSign>) {
+        (self.transaction, self.utxo_args)
+    }
+
     pub fn inputs(&self) -> &[Transaction::Input] {
         self.transaction.inputs()
     }
@@ -91,6 +98,7 @@ where
         utxo_args: Vec<UtxoToSign>,
     ) -> SigningResult<()> {
         check_utxo_args_number(utxos.len(), utxo_args.len())?;
+        check_duplicate_utxos(&utxos)?;
 
         self.utxo_args = utxo_args;
         self.transaction.replace_inputs(utxos);
@@ -165,3 +173,15 @@ fn check_utxo_args_number(inputs_len: usize, utxo_args: usize) -> SigningResult<
     }
     Ok(())
 }
+
+/// Duplicate UTXOs lead to an invalid transaction that is only rejected at broadcast.
+fn check_duplicate_utxos<Input: TxInputInterface>(inputs: &[Input]) -> SigningResult<()> {
+    let mut out_points = HashSet::with_capacity(inputs.len());
+    for (i, input) in inputs.iter().enumerate() {
+        if !out_points.insert(input.previous_output()) {
+            return SigningError::err(SigningErrorType::Error_duplicate_utxo)
+                .context(format!("Input '{i}' spends an already spent UTXO"));
+        }
+    }
+    Ok(())
+}
@@ -31,8 +31,8 @@ pub type EvmEntryExtStaticRef = &'static dyn EvmEntryExt;
 // start_of_blockchain_entries - USED TO GENERATE CODE
 const APTOS: AptosEntry = AptosEntry;
 const BINANCE: BinanceEntry = BinanceEntry;
-const BITCOIN: BitcoinEntry = BitcoinEntry;
-const BITCOIN_CASH: BitcoinCashEntry = BitcoinCashEntry;
+const BITCOIN: BitcoinEntry = BitcoinEntry::new();
+const BITCOIN_CASH: BitcoinCashEntry = BitcoinCashEntry::new();
 const COSMOS: CosmosEntry = CosmosEntry;
 const ETHEREUM: EthereumEntry = EthereumEntry;
 const GREENFIELD: GreenfieldEntry = GreenfieldEntry;
@@ -37,6 +37,18 @@ pub fn tapsighash(data: &[u8]) -> Data {
     sha256(&t)
 }
 
+/// BIP-340 tagged hash. This function computes `sha256(sha256(tag) + sha256(tag) + data)`.
+pub fn tagged_hash(tag: &[u8], data: &[u8]) -> Data {
+    let tag_hash = sha256(tag);
+
+    let mut t = Vec::with_capacity(tag_hash.len() * 2 + data.len());
+    t.extend_from_slice(&tag_hash);
+    t.extend_from_slice(&tag_hash);
+    t.extend_from_slice(data);
+
+    sha256(&t)
+}
+
 /// Enum selector for the supported hash functions.
 ///
 /// Add hash types if necessary. For example, when add a new hasher to `registry.json`,
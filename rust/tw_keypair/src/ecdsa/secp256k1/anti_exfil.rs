@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Anti-exfil (anti-klepto) signing protocol based on the sign-to-contract nonce tweak.
+//! Please note the interoperability with other implementations, e.g. `secp256k1-zkp` `ecdsa_s2c`,
+//! is not verified against their test vectors.
+//! It prevents a signing device from leaking the private key through the signature nonces:
+//!
+//! 1. The host generates random `host_data` and sends [`host_commit`] to the signer.
+//! 2. The signer sends back the original nonce point via [`PrivateKey::anti_exfil_signer_commit`].
+//! 3. The host reveals `host_data`, and the signer signs via [`PrivateKey::anti_exfil_sign`],
+//!    tweaking the original nonce by `host_data`.
+//! 4. The host checks the signature nonce is tweaked via [`host_verify`].
+
+use crate::ecdsa::canonical::generate_k;
+use crate::ecdsa::secp256k1::{PrivateKey, PublicKey, Signature, VerifySignature};
+use crate::traits::VerifyingKeyTrait;
+use crate::{KeyPairError, KeyPairResult};
+use ecdsa::elliptic_curve::bigint::U256;
+use ecdsa::elliptic_curve::ops::Reduce;
+use ecdsa::elliptic_curve::point::AffineCoordinates;
+use ecdsa::elliptic_curve::sec1::ToEncodedPoint;
+use ecdsa::elliptic_curve::{Curve, FieldBytesEncoding, PrimeField};
+use ecdsa::hazmat::SignPrimitive;
+use k256::sha2::Sha256;
+use k256::{FieldBytes, ProjectivePoint, Scalar, Secp256k1};
+use rfc6979::HmacDrbg;
+use tw_hash::hasher::tagged_hash;
+use tw_hash::{H256, H264};
+
+const S2C_DATA_TAG: &[u8] = b"s2c/ecdsa/data";
+const S2C_POINT_TAG: &[u8] = b"s2c/ecdsa/point";
+
+/// Computes the commitment to the `host_data` sent to the signer before the signing.
+pub fn host_commit(host_data: &H256) -> H256 {
+    let commitment = tagged_hash(S2C_DATA_TAG, host_data.as_slice());
+    H256::try_from(commitment.as_slice()).expect("Expected 32 byte array sha256 hash")
+}
+
+/// Checks that the `signature` is valid and its nonce is the `signer_commitment` nonce tweaked by the `host_data`,
+/// i.e. the signer couldn't choose the nonce to leak the private key.
+pub fn host_verify(
+    public: &PublicKey,
+    message: H256,
+    signature: VerifySignature,
+    host_data: &H256,
+    signer_commitment: &H264,
+) -> bool {
+    let Ok(original_nonce) = k256::PublicKey::from_sec1_bytes(signer_commitment.as_slice()) else {
+        return false;
+    };
+    let Ok(tweak) = nonce_tweak(signer_commitment, host_data) else {
+        return false;
+    };
+
+    let nonce = (original_nonce.to_projective() + ProjectivePoint::GENERATOR * tweak).to_affine();
+    let expected_r = <Scalar as Reduce<U256>>::reduce_bytes(&nonce.x());
+    let (r, _s) = signature.rs();
+    if expected_r.to_repr().as_slice() != r.as_slice() {
+        return false;
+    }
+
+    public.verify(signature, message)
+}
+
+impl PrivateKey {
+    /// Returns the original nonce point committed to the `host_commitment` (the signer commitment).
+    /// The host uses it to verify the signature produced by [`PrivateKey::anti_exfil_sign`].
+    pub fn anti_exfil_signer_commit(&self, message: H256, host_commitment: &H256) -> H264 {
+        nonce_point(&self.original_nonce(&message, host_commitment))
+    }
+
+    /// Signs the `message` with the original nonce tweaked by the `host_data`.
+    pub fn anti_exfil_sign(&self, message: H256, host_data: &H256) -> KeyPairResult<Signature> {
+        let original_nonce = self.original_nonce(&message, &host_commit(host_data));
+        let nonce = original_nonce + nonce_tweak(&nonce_point(&original_nonce), host_data)?;
+
+        let z = message_scalar(&message).to_repr();
+        let (signature, recovery_id) = self
+            .secret
+            .as_nonzero_scalar()
+            .try_sign_prehashed(nonce, &z)
+            .map_err(|_| KeyPairError::SigningError)?;
+        let recovery_id = recovery_id.ok_or(KeyPairError::SigningError)?;
+        Ok(Signature::new(signature, recovery_id))
+    }
+
+    /// Generates an RFC6979 nonce with the `host_commitment` as the additional data.
+    fn original_nonce(&self, message: &H256, host_commitment: &H256) -> Scalar {
+        let entropy_input = &self.secret.as_nonzero_scalar().to_repr();
+        let nonce = &message_scalar(message).to_repr();
+        let n = &FieldBytesEncoding::<Secp256k1>::encode_field_bytes(&Secp256k1::ORDER);
+
+        let mut hmac_drbg =
+            HmacDrbg::<Sha256>::new(entropy_input, nonce, host_commitment.as_slice());
+        let k = generate_k::<Sha256, _>(&mut hmac_drbg, n);
+        Option::from(Scalar::from_repr(k)).expect("'generate_k' returns a valid scalar")
+    }
+}
+
+fn message_scalar(message: &H256) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::from_slice(message.as_slice()))
+}
+
+fn nonce_point(nonce: &Scalar) -> H264 {
+    let point = (ProjectivePoint::GENERATOR * nonce).to_affine();
+    let compressed = point.to_encoded_point(true);
+    H264::try_from(compressed.as_bytes()).expect("Expected 33 byte array compressed point")
+}
+
+/// Computes the tweak committing the nonce to the `host_data`.
+fn nonce_tweak(original_nonce: &H264, host_data: &H256) -> KeyPairResult<Scalar> {
+    let mut data = original_nonce.as_slice().to_vec();
+    data.extend_from_slice(host_data.as_slice());
+    let tweak = tagged_hash(S2C_POINT_TAG, &data);
+
+    Option::from(Scalar::from_repr(*FieldBytes::from_slice(&tweak)))
+        .ok_or(KeyPairError::SigningError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_hash::sha2::sha256;
+    use tw_hash::H520;
+
+    const PRIVATE_KEY: &str = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
+
+    fn host_data() -> H256 {
+        let bytes: Vec<u8> = (0..32).collect();
+        H256::try_from(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_anti_exfil() {
+        let private = PrivateKey::try_from(PRIVATE_KEY).unwrap();
+        let message = H256::try_from(sha256(b"hello").as_slice()).unwrap();
+        let host_data = host_data();
+
+        let host_commitment = host_commit(&host_data);
+        assert_eq!(
+            host_commitment,
+            H256::from("d8dcbddb588f8bdf776acba632f4e3b6a93e175621aa39a627cb9e7193dc3c91")
+        );
+
+        let signer_commitment = private.anti_exfil_signer_commit(message, &host_commitment);
+        assert_eq!(
+            signer_commitment,
+            H264::from("03b234f70a1988d7f6785d89a54ce09fc877fc4f170d925f41cd03f0b29a4cba1d")
+        );
+
+        let signature = private.anti_exfil_sign(message, &host_data).unwrap();
+        let expected = H520::from("bdd891a0e4fdc5dbe68724c18d4f962826b2d025009f1db6399cdd9bca2c875f0c1d6b77aa2cb0478bb4cf6293ff129f4f741fb6364a61ff25cd1425def359c301");
+        assert_eq!(signature.to_bytes(), expected);
+
+        let public = private.public();
+        let verify = |host_data: &H256, signer_commitment: &H264| {
+            let signature = VerifySignature::from(signature.clone());
+            host_verify(&public, message, signature, host_data, signer_commitment)
+        };
+        assert!(verify(&host_data, &signer_commitment));
+
+        // The nonce is not tweaked by another host data.
+        let mut other_host_data = host_data;
+        other_host_data[0] ^= 1;
+        assert!(!verify(&other_host_data, &signer_commitment));
+
+        // The signer committed to another nonce.
+        let other_commitment =
+            private.anti_exfil_signer_commit(message, &host_commit(&other_host_data));
+        assert!(!verify(&host_data, &other_commitment));
+    }
+}
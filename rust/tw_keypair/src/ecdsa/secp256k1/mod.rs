@@ -4,6 +4,7 @@
 
 use k256::Secp256k1;
 
+pub mod anti_exfil;
 mod keypair;
 mod private;
 mod public;
@@ -56,6 +57,31 @@ mod tests {
         assert!(key_pair.verify(verify_signature, hash_to_sign));
     }
 
+    #[test]
+    fn test_sign_with_extra_entropy() {
+        let private = PrivateKey::try_from(
+            "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5",
+        )
+        .unwrap();
+        let public = private.public();
+
+        let hash_to_sign = keccak256(b"hello");
+        let hash_to_sign = H256::try_from(hash_to_sign.as_slice()).unwrap();
+        let deterministic = private.sign(hash_to_sign).unwrap();
+
+        let private = private.with_extra_entropy(H256::from([1; 32]));
+        let with_entropy = private.sign(hash_to_sign).unwrap();
+        assert_ne!(with_entropy.to_bytes(), deterministic.to_bytes());
+        // The signature is still deterministic for the same entropy.
+        assert_eq!(
+            private.sign(hash_to_sign).unwrap().to_bytes(),
+            with_entropy.to_bytes()
+        );
+
+        let verify_signature = VerifySignature::from(with_entropy);
+        assert!(public.verify(verify_signature, hash_to_sign));
+    }
+
     #[test]
     fn test_private_key_from() {
         let hex = "afeefca74d9a325cf1d6b6911d61a65c32afa8e02bd5e78e2e4ac2910bab45f5";
@@ -6,9 +6,11 @@ use crate::ecdsa::secp256k1::public::PublicKey;
 use crate::ecdsa::secp256k1::Signature;
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
+use ecdsa::hazmat::SignPrimitive;
 use k256::ecdsa::{SigningKey, VerifyingKey};
 use k256::elliptic_curve::sec1::ToEncodedPoint;
-use k256::{AffinePoint, ProjectivePoint};
+use k256::sha2::Sha256;
+use k256::{AffinePoint, FieldBytes, ProjectivePoint};
 use tw_encoding::hex;
 use tw_hash::H256;
 use tw_misc::traits::ToBytesZeroizing;
@@ -18,6 +20,8 @@ use zeroize::{ZeroizeOnDrop, Zeroizing};
 #[derive(ZeroizeOnDrop)]
 pub struct PrivateKey {
     pub(crate) secret: SigningKey,
+    #[zeroize(skip)]
+    extra_entropy: Option<H256>,
 }
 
 impl PrivateKey {
@@ -38,6 +42,14 @@ impl PrivateKey {
         let shared_secret_hash = tw_hash::sha2::sha256(shared_secret_compressed.as_bytes());
         H256::try_from(shared_secret_hash.as_slice()).expect("Expected 32 byte array sha256 hash")
     }
+
+    /// Mixes 32 bytes of `extra_entropy` into the RFC6979 nonce generation when signing,
+    /// the same way as `secp256k1_ecdsa_sign` does with `ndata`.
+    /// Signatures are still deterministic for the same entropy, but differ from the ones made without it.
+    pub fn with_extra_entropy(mut self, extra_entropy: H256) -> PrivateKey {
+        self.extra_entropy = Some(extra_entropy);
+        self
+    }
 }
 
 /// This method is inspired by [elliptic_curve::ecdh::diffie_hellman](https://github.com/RustCrypto/traits/blob/f0dbe44fea56d4c17e625ababacb580fec842137/elliptic-curve/src/ecdh.rs#L60-L70)
@@ -53,10 +65,23 @@ impl SigningKeyTrait for PrivateKey {
     type Signature = Signature;
 
     fn sign(&self, message: Self::SigningMessage) -> KeyPairResult<Self::Signature> {
+        let Some(ref extra_entropy) = self.extra_entropy else {
+            let (signature, recovery_id) = self
+                .secret
+                .sign_prehash_recoverable(message.as_slice())
+                .map_err(|_| KeyPairError::SigningError)?;
+            return Ok(Signature::new(signature, recovery_id));
+        };
+
         let (signature, recovery_id) = self
             .secret
-            .sign_prehash_recoverable(message.as_slice())
+            .as_nonzero_scalar()
+            .try_sign_prehashed_rfc6979::<Sha256>(
+                FieldBytes::from_slice(message.as_slice()),
+                extra_entropy.as_slice(),
+            )
             .map_err(|_| KeyPairError::SigningError)?;
+        let recovery_id = recovery_id.ok_or(KeyPairError::SigningError)?;
         Ok(Signature::new(signature, recovery_id))
     }
 }
@@ -66,7 +91,10 @@ impl<'a> TryFrom<&'a [u8]> for PrivateKey {
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         let secret = SigningKey::from_slice(data).map_err(|_| KeyPairError::InvalidSecretKey)?;
-        Ok(PrivateKey { secret })
+        Ok(PrivateKey {
+            secret,
+            extra_entropy: None,
+        })
     }
 }
 
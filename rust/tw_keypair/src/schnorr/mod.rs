@@ -2,6 +2,7 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+use crate::{KeyPairError, KeyPairResult};
 use bitcoin::hashes::Hash;
 
 mod keypair;
@@ -10,11 +11,15 @@ mod public;
 mod signature;
 
 pub use keypair::KeyPair;
-pub use private::PrivateKey;
+pub use private::{AuxRand, PrivateKey};
 pub use public::{PublicKey, XOnlyPublicKey};
 pub use signature::Signature;
+use tw_hash::hasher::tagged_hash;
 use tw_hash::H256;
 
+/// BIP-340 tagged hash tag of the pay-to-contract tweak.
+const PAY_TO_CONTRACT_TAG: &[u8] = b"PayToContract";
+
 pub(crate) fn bitcoin_tweak(tweak: Option<H256>) -> Option<bitcoin::taproot::TapNodeHash> {
     if let Some(tweak) = tweak {
         let hash = bitcoin::hashes::sha256t::Hash::<_>::from_slice(tweak.as_slice())
@@ -25,12 +30,27 @@ pub(crate) fn bitcoin_tweak(tweak: Option<H256>) -> Option<bitcoin::taproot::Tap
     }
 }
 
+/// Computes the pay-to-contract tweak `t = hash_PayToContract(P || commitment)`,
+/// where `P` is the x-only internal public key.
+pub(crate) fn pay_to_contract_tweak(
+    internal: &secp256k1::XOnlyPublicKey,
+    commitment: &H256,
+) -> KeyPairResult<secp256k1::Scalar> {
+    let mut data = internal.serialize().to_vec();
+    data.extend_from_slice(commitment.as_slice());
+    let tweak = H256::try_from(tagged_hash(PAY_TO_CONTRACT_TAG, &data).as_slice())
+        .expect("Expected 32 byte array sha256 hash");
+
+    secp256k1::Scalar::from_be_bytes(tweak.take()).map_err(|_| KeyPairError::InvalidSecretKey)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::traits::{KeyPairTrait, SigningKeyTrait, VerifyingKeyTrait};
+    use tw_hash::sha2::sha256;
     use tw_hash::sha3::keccak256;
-    use tw_hash::{H256, H512};
+    use tw_hash::{H256, H264, H512};
     use tw_misc::traits::ToBytesVec;
 
     const SECRET: &str = "ba0828d5734b65e3bcc2c51c93dfc26dd71bd666cc0273adee77d73d9a322035";
@@ -73,6 +93,23 @@ mod tests {
         assert!(public.verify(actual, hash_to_sign), "Invalid signature");
     }
 
+    #[test]
+    fn test_sign_verify_fixed_aux_rand() {
+        let private_key = PrivateKey::try_from(SECRET).unwrap();
+        let hash_to_sign = H256::from(MSG);
+
+        let with_aux = private_key.clone().with_aux_rand(H256::from([1; 32]));
+        let actual = with_aux.sign(hash_to_sign).expect("Error schnorr signing");
+        // The signature is deterministic for the same auxiliary random data.
+        assert_eq!(with_aux.sign(hash_to_sign).unwrap(), actual);
+
+        let no_aux = private_key.clone().no_aux_rand();
+        assert_ne!(no_aux.sign(hash_to_sign).unwrap(), actual);
+
+        let public = private_key.public();
+        assert!(public.verify(actual, hash_to_sign), "Invalid signature");
+    }
+
     #[test]
     fn test_sign_verify_tweaked() {
         let tweaked_key_pair = PrivateKey::try_from(SECRET)
@@ -123,4 +160,30 @@ mod tests {
 
         assert_eq!(public_key.tweak(None), tweaked_public);
     }
+
+    #[test]
+    fn test_pay_to_contract() {
+        let private_key = PrivateKey::try_from(SECRET).unwrap().no_aux_rand();
+        let public_key = private_key.public();
+        let commitment = H256::try_from(sha256(b"hello").as_slice()).unwrap();
+
+        let contract_private = private_key.pay_to_contract(&commitment).unwrap();
+        let contract_public = public_key.pay_to_contract(&commitment).unwrap();
+        assert_eq!(contract_private.public(), contract_public);
+        assert_eq!(
+            contract_public.compressed(),
+            H264::from("02562ae5a69e30512ad8dae4cbd8e2d629981acd530870b28e6ec2def1f09c4b9b")
+        );
+
+        // The contract key is used as the internal key of a P2TR key-path output.
+        let output_key = contract_private.tweak(None);
+        assert_eq!(
+            output_key.public().x_only().bytes(),
+            H256::from("2708a54f1a8444a1c24c6aad36a644bc72ee818e0edf12174f6578b9c4b17890")
+        );
+
+        let hash_to_sign = H256::from(MSG);
+        let signature = output_key.sign(hash_to_sign).unwrap();
+        assert!(output_key.public().verify(signature, hash_to_sign));
+    }
 }
@@ -1,20 +1,34 @@
-use crate::schnorr::bitcoin_tweak;
+use crate::rand::OsRng;
 use crate::schnorr::public::PublicKey;
 use crate::schnorr::signature::Signature;
+use crate::schnorr::{bitcoin_tweak, pay_to_contract_tweak};
 use crate::traits::SigningKeyTrait;
 use crate::{KeyPairError, KeyPairResult};
 use bitcoin::key::TapTweak;
+use rand_core::RngCore;
 use secp256k1::SECP256K1;
 use tw_encoding::hex;
 use tw_hash::H256;
 use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
 use zeroize::{Zeroize, Zeroizing};
 
+/// Auxiliary random data used to harden the nonce generation, see BIP-340.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AuxRand {
+    /// Generated by the OS random number generator.
+    #[default]
+    Random,
+    /// No auxiliary random data. ONLY recommended for testing.
+    Disabled,
+    /// Provided by the caller.
+    Fixed(H256),
+}
+
 /// Represents a `schnorr` private key.
 #[derive(Clone)]
 pub struct PrivateKey {
     key_pair: secp256k1::KeyPair,
-    no_aux_rand: bool,
+    aux_rand: AuxRand,
 }
 
 impl PrivateKey {
@@ -33,13 +47,42 @@ impl PrivateKey {
         let tweaked = self.key_pair.tap_tweak(SECP256K1, tweak);
         PrivateKey {
             key_pair: secp256k1::KeyPair::from(tweaked),
-            no_aux_rand: self.no_aux_rand,
+            aux_rand: self.aux_rand,
         }
     }
 
+    /// Tweak the private key by the pay-to-contract `commitment`,
+    /// so it corresponds to [`PublicKey::pay_to_contract`] of the original public key.
+    pub fn pay_to_contract(self, commitment: &H256) -> KeyPairResult<PrivateKey> {
+        let (internal, _parity) = self.key_pair.x_only_public_key();
+        let tweak = pay_to_contract_tweak(&internal, commitment)?;
+
+        // The secret key is negated if the public key has an odd Y coordinate.
+        let key_pair = self
+            .key_pair
+            .add_xonly_tweak(SECP256K1, &tweak)
+            .map_err(|_| KeyPairError::InvalidSecretKey)?;
+        Ok(PrivateKey {
+            key_pair,
+            aux_rand: self.aux_rand,
+        })
+    }
+
     /// Disable auxiliary random data when signing. ONLY recommended for testing.
-    pub fn no_aux_rand(mut self) -> PrivateKey {
-        self.no_aux_rand = true;
+    pub fn no_aux_rand(self) -> PrivateKey {
+        self.aux_rand(AuxRand::Disabled)
+    }
+
+    /// Use the given 32 bytes as auxiliary random data when signing instead of the OS random number generator,
+    /// e.g. if the platform has its own entropy source.
+    /// Please note the `aux_rand` must be freshly generated for every signature.
+    pub fn with_aux_rand(self, aux_rand: H256) -> PrivateKey {
+        self.aux_rand(AuxRand::Fixed(aux_rand))
+    }
+
+    /// Sets the auxiliary random data used when signing.
+    pub fn aux_rand(mut self, aux_rand: AuxRand) -> PrivateKey {
+        self.aux_rand = aux_rand;
         self
     }
 }
@@ -68,10 +111,17 @@ impl SigningKeyTrait for PrivateKey {
 
         // Sign the message.
         let msg = secp256k1::Message::from_slice(message.as_slice()).expect("");
-        let sig = if self.no_aux_rand {
-            SECP256K1.sign_schnorr_no_aux_rand(&msg, &self.key_pair)
-        } else {
-            SECP256K1.sign_schnorr(&msg, &self.key_pair)
+        let sig = match self.aux_rand {
+            AuxRand::Random => {
+                // Don't rely on `secp256k1/rand-std` as it's not available on all platforms.
+                let mut aux_rand = [0; 32];
+                OsRng.fill_bytes(&mut aux_rand);
+                SECP256K1.sign_schnorr_with_aux_rand(&msg, &self.key_pair, &aux_rand)
+            },
+            AuxRand::Disabled => SECP256K1.sign_schnorr_no_aux_rand(&msg, &self.key_pair),
+            AuxRand::Fixed(aux_rand) => {
+                SECP256K1.sign_schnorr_with_aux_rand(&msg, &self.key_pair, &aux_rand.take())
+            },
         };
 
         Ok(Signature::from(sig))
@@ -101,7 +151,7 @@ impl<'a> TryFrom<&'a [u8]> for PrivateKey {
             .map_err(|_| KeyPairError::InvalidSecretKey)?;
         Ok(PrivateKey {
             key_pair,
-            no_aux_rand: false,
+            aux_rand: AuxRand::Random,
         })
     }
 }
@@ -1,6 +1,6 @@
-use crate::schnorr::{bitcoin_tweak, Signature};
+use crate::schnorr::{bitcoin_tweak, pay_to_contract_tweak, Signature};
 use crate::traits::VerifyingKeyTrait;
-use crate::KeyPairError;
+use crate::{KeyPairError, KeyPairResult};
 use bitcoin::key::TapTweak;
 use secp256k1::SECP256K1;
 use tw_hash::{H256, H264};
@@ -30,6 +30,21 @@ impl PublicKey {
         }
     }
 
+    /// Commits the public key to an arbitrary 32 byte `commitment`: `Q = P + hash_PayToContract(P || commitment)G`,
+    /// where `P` is the x-only public key. The result can be used as a Taproot internal key,
+    /// e.g. to timestamp the `commitment` in a key-path output.
+    pub fn pay_to_contract(&self, commitment: &H256) -> KeyPairResult<PublicKey> {
+        let (x_only_pubkey, _parity) = self.public.x_only_public_key();
+        let tweak = pay_to_contract_tweak(&x_only_pubkey, commitment)?;
+
+        let (contract_pubkey, contract_parity) = x_only_pubkey
+            .add_tweak(SECP256K1, &tweak)
+            .map_err(|_| KeyPairError::InvalidPublicKey)?;
+        Ok(PublicKey {
+            public: secp256k1::PublicKey::from_x_only_public_key(contract_pubkey, contract_parity),
+        })
+    }
+
     pub fn x_only(&self) -> XOnlyPublicKey {
         let (x_only_pubkey, _parity) = self.public.x_only_public_key();
         XOnlyPublicKey {
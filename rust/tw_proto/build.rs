@@ -4,7 +4,7 @@
 
 use pb_rs::types::FileDescriptor;
 use pb_rs::ConfigBuilder;
-#[cfg(feature = "fuzz")]
+#[cfg(any(feature = "fuzz", feature = "serde"))]
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use std::{env, fs};
@@ -54,6 +54,9 @@ fn main() {
     #[cfg(feature = "fuzz")]
     add_custom_derives(&out_dir, &["arbitrary::Arbitrary"])
         .expect("Error on adding 'arbitrary::Arbitrary' derive");
+
+    #[cfg(feature = "serde")]
+    add_serde_derives(&out_dir).expect("Error on adding 'serde' derives");
 }
 
 /// Unfortunately, `pb-rs` does not provide a proper support of custom derives.
@@ -66,6 +69,73 @@ fn add_custom_derives(out_dir: &Path, custom_derives: &[&str]) -> io::Result<()>
     let pattern = "#[derive(Debug";
     let replace_with = format!("#[derive(Debug, {}", custom_derives.join(", "));
 
+    for_each_blockchain_proto(out_dir, |proto_content| {
+        proto_content.replace(pattern, &replace_with)
+    })
+}
+
+/// Adds `serde::Serialize` and `serde::Deserialize` derives to all generated types.
+/// Structs are deserialized with default values of missing fields,
+/// and `bytes` fields are represented as base64 strings as in the canonical Protobuf JSON mapping.
+/// Other differences from the canonical mapping are listed in the `tw_proto::serde_helpers` module.
+#[cfg(feature = "serde")]
+fn add_serde_derives(out_dir: &Path) -> io::Result<()> {
+    const SERDE_DERIVE: &str = "#[derive(serde::Serialize, serde::Deserialize)]";
+    const SERDE_DEFAULT: &str = "#[serde(default)]";
+    const SERDE_BYTES: &str = "#[serde(with = \"crate::serde_helpers::bytes\")]";
+    const SERDE_BYTES_VEC: &str = "#[serde(with = \"crate::serde_helpers::bytes_vec\")]";
+    const SERDE_BYTES_MAP: &str = "#[serde(with = \"crate::serde_helpers::bytes_map\")]";
+
+    for_each_blockchain_proto(out_dir, |proto_content| {
+        let mut upgraded = String::with_capacity(proto_content.len() * 2);
+
+        for line in proto_content.lines() {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+
+            let attribute = if trimmed.starts_with("pub struct ") {
+                Some(SERDE_DEFAULT)
+            } else if trimmed.starts_with("pub ") && trimmed.ends_with(": Vec<Cow<'a, [u8]>>,") {
+                Some(SERDE_BYTES_VEC)
+            } else if trimmed.starts_with("pub ")
+                && trimmed.contains(": KVMap<")
+                && trimmed.ends_with(", Cow<'a, [u8]>>,")
+            {
+                Some(SERDE_BYTES_MAP)
+            } else if (trimmed.starts_with("pub ") && trimmed.ends_with(": Cow<'a, [u8]>,"))
+                || trimmed.ends_with("(Cow<'a, [u8]>),")
+            {
+                Some(SERDE_BYTES)
+            } else {
+                None
+            };
+
+            if let Some(attribute) = attribute {
+                upgraded.push_str(indent);
+                upgraded.push_str(attribute);
+                upgraded.push('\n');
+            }
+
+            upgraded.push_str(line);
+            upgraded.push('\n');
+
+            // Debug is derived for all generated types.
+            if trimmed.starts_with("#[derive(Debug") {
+                upgraded.push_str(indent);
+                upgraded.push_str(SERDE_DERIVE);
+                upgraded.push('\n');
+            }
+        }
+
+        upgraded
+    })
+}
+
+#[cfg(any(feature = "fuzz", feature = "serde"))]
+fn for_each_blockchain_proto<F>(out_dir: &Path, f: F) -> io::Result<()>
+where
+    F: Fn(&str) -> String,
+{
     let tw_dir = out_dir.join("TW");
     for blockchain_dir in tw_dir.read_dir()? {
         let blockchain_dir = blockchain_dir?.path();
@@ -76,14 +146,17 @@ fn add_custom_derives(out_dir: &Path, custom_derives: &[&str]) -> io::Result<()>
         }
 
         let blockchain_proto = blockchain_dir.join("Proto.rs");
-        replace_proto_content(&blockchain_proto, pattern, &replace_with)?;
+        update_proto_content(&blockchain_proto, &f)?;
     }
 
     Ok(())
 }
 
-#[cfg(feature = "fuzz")]
-fn replace_proto_content(path_to_file: &Path, pattern: &str, replace_with: &str) -> io::Result<()> {
+#[cfg(any(feature = "fuzz", feature = "serde"))]
+fn update_proto_content<F>(path_to_file: &Path, f: F) -> io::Result<()>
+where
+    F: Fn(&str) -> String,
+{
     let proto_content = {
         let mut proto_file = fs::File::open(path_to_file)?;
 
@@ -93,11 +166,11 @@ fn replace_proto_content(path_to_file: &Path, pattern: &str, replace_with: &str)
         proto_content
     };
 
-    let upgraded_proto_content = proto_content.replace(pattern, &replace_with);
+    let upgraded_proto_content = f(&proto_content);
 
     let mut file = fs::OpenOptions::new()
         .write(true)
         .truncate(true)
-        .open(&path_to_file)?;
+        .open(path_to_file)?;
     file.write_all(upgraded_proto_content.as_bytes())
 }
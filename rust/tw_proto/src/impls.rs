@@ -35,6 +35,9 @@ impl fmt::Display for SigningError {
             SigningError::Error_invalid_requested_token_amount => "Invalid input token amount",
             SigningError::Error_not_supported => "Operation not supported for the chain",
             SigningError::Error_dust_amount_requested => "Requested amount is too low (less dust)",
+            SigningError::Error_segwit_uncompressed_public_key => "Uncompressed public keys are not allowed in segwit scripts",
+            SigningError::Error_duplicate_utxo => "The same UTXO is provided more than once",
+            SigningError::Error_policy_violation => "The transaction is rejected by the signing policy",
         };
         write!(f, "{err}")
     }
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! JSON representation of the generated Protobuf messages.
+//! See [`crate::serde_helpers`] for the details of the representation.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub type ProtoJsonResult<T> = serde_json::Result<T>;
+
+/// Serializes a Protobuf message into a JSON string.
+/// For example, `BitcoinV2::Proto::SigningOutput`.
+pub fn to_json<T: Serialize>(message: &T) -> ProtoJsonResult<String> {
+    serde_json::to_string(message)
+}
+
+/// Deserializes a Protobuf message from a JSON string.
+/// For example, `BitcoinV2::Proto::SigningInput`.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> ProtoJsonResult<T> {
+    serde_json::from_str(json)
+}
@@ -8,6 +8,10 @@ use quick_protobuf::{BytesReader, MessageInfo, Writer};
 #[rustfmt::skip]
 mod common;
 mod impls;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "serde")]
+pub mod serde_helpers;
 
 #[allow(non_snake_case)]
 #[rustfmt::skip]
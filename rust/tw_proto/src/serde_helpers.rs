@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+//! Helpers used by the `serde` derives of the generated Protobuf messages.
+//!
+//! Please note the JSON representation is close to, but not the same as the canonical proto3 JSON mapping:
+//! * `bytes` fields (including `repeated bytes` and `map<_, bytes>`) are base64 strings as in the canonical mapping.
+//! * Enum values are the names declared in the `.proto` files as in the canonical mapping.
+//! * Field names are kept as declared in the `.proto` files (`snake_case`), not converted to `lowerCamelCase`.
+//! * 64-bit integers are JSON numbers, not strings.
+//! * `oneof` fields are nested under the `oneof` name, e.g. `{"transaction":{"psbt":{..}}}`.
+//! * Missing fields are set to their default values, but default values are not omitted on serialization.
+
+/// Serializes `bytes` fields as base64 strings.
+pub mod bytes {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::borrow::Cow;
+    use tw_encoding::base64::{self, STANDARD};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes, STANDARD))
+    }
+
+    pub fn deserialize<'de, 'a, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Cow<'a, [u8]>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        base64::decode(&s, STANDARD)
+            .map(Cow::Owned)
+            .map_err(|e| Error::custom(format!("Invalid base64 bytes: {e:?}")))
+    }
+}
+
+/// Serializes `repeated bytes` fields as lists of base64 strings.
+pub mod bytes_vec {
+    use serde::de::Error;
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::borrow::Cow;
+    use tw_encoding::base64::{self, STANDARD};
+
+    pub fn serialize<S: Serializer>(
+        items: &[Cow<'_, [u8]>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(items.len()))?;
+        for item in items {
+            seq.serialize_element(&base64::encode(item, STANDARD))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, 'a, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Cow<'a, [u8]>>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| {
+                base64::decode(s, STANDARD)
+                    .map(Cow::Owned)
+                    .map_err(|e| Error::custom(format!("Invalid base64 bytes: {e:?}")))
+            })
+            .collect()
+    }
+}
+
+/// Serializes `map<_, bytes>` fields as objects with base64 string values.
+pub mod bytes_map {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::hash::Hash;
+    use tw_encoding::base64::{self, STANDARD};
+
+    pub fn serialize<K, S>(
+        items: &HashMap<K, Cow<'_, [u8]>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_map(
+            items
+                .iter()
+                .map(|(key, value)| (key, base64::encode(value, STANDARD))),
+        )
+    }
+
+    pub fn deserialize<'de, 'a, K, D>(
+        deserializer: D,
+    ) -> Result<HashMap<K, Cow<'a, [u8]>>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        D: Deserializer<'de>,
+    {
+        HashMap::<K, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, value)| {
+                base64::decode(&value, STANDARD)
+                    .map(|bytes| (key, Cow::Owned(bytes)))
+                    .map_err(|e| Error::custom(format!("Invalid base64 bytes: {e:?}")))
+            })
+            .collect()
+    }
+}
@@ -6,14 +6,17 @@ use crate::chains::common::bitcoin::{
     btc_info, dust_threshold, input, output, TransactionOneof, DUST, MINER_FEE, ONE_BTC,
     SIGHASH_ALL,
 };
-use tw_any_coin::test_utils::sign_utils::CompilerHelper;
+use tw_any_coin::test_utils::sign_utils::{CompilerHelper, PreImageHelper};
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
+use tw_hash::H256;
+use tw_keypair::traits::SigningKeyTrait;
+use tw_keypair::{ecdsa, schnorr};
+use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Common::Proto::SigningError;
 
-#[test]
-fn test_bitcoin_compile_p2pkh_error() {
+fn compile_p2pkh(signature: Vec<u8>) -> Proto::SigningOutput<'static> {
     let alice_pubkey = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536"
         .decode_hex()
         .unwrap();
@@ -34,6 +37,7 @@ fn test_bitcoin_compile_p2pkh_error() {
     let out1 = Proto::Output {
         value: ONE_BTC * 50 - MINER_FEE,
         to_recipient: output::p2pkh(bob_pubkey.clone()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -51,17 +55,157 @@ fn test_bitcoin_compile_p2pkh_error() {
         ..Default::default()
     };
 
-    let invalid_signature = "360a84fb41ad07f07c845fedc34cde728421803ebbaae392fc39c116b29fc07b53bd9d1376e15a191d844db458893b928f3efbfee90c9febf51ab84c9796677900"
-        .decode_hex()
-        .unwrap();
-
     let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
-    let output = compiler.compile(
+    compiler.compile(
         CoinType::Bitcoin,
         &signing,
-        vec![invalid_signature],
+        vec![signature],
         vec![alice_pubkey],
-    );
+    )
+}
+
+#[test]
+fn test_bitcoin_compile_p2pkh_error() {
+    let invalid_signature = "360a84fb41ad07f07c845fedc34cde728421803ebbaae392fc39c116b29fc07b53bd9d1376e15a191d844db458893b928f3efbfee90c9febf51ab84c9796677900"
+        .decode_hex()
+        .unwrap();
 
+    let output = compile_p2pkh(invalid_signature);
     assert_eq!(output.error, SigningError::Error_signing);
 }
+
+#[test]
+fn test_bitcoin_compile_p2pkh_malformed_signature() {
+    // Neither a raw nor a DER-encoded signature.
+    let malformed_signature = vec![0; 10];
+
+    let output = compile_p2pkh(malformed_signature);
+    assert_ne!(output.error, SigningError::OK);
+    assert!(
+        output.error_message.contains("Input '0'"),
+        "{}",
+        output.error_message
+    );
+}
+
+/// Returns the signing input spending P2TR, P2WPKH and P2TR UTXOs in this order,
+/// and the valid signatures of the inputs.
+fn make_mixed_inputs_tx() -> (Proto::SigningInput<'static>, Vec<Vec<u8>>) {
+    let private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let ecdsa_key = ecdsa::secp256k1::PrivateKey::try_from(private_key).unwrap();
+    let schnorr_key = schnorr::PrivateKey::try_from(private_key)
+        .unwrap()
+        .no_aux_rand();
+    let public_key = ecdsa_key.public().compressed().to_vec();
+
+    let txid = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+    let utxo = |vout: u32, claiming_script| Proto::Input {
+        out_point: input::out_point(txid, vout),
+        value: 100_000,
+        sighash_type: SIGHASH_ALL,
+        claiming_script,
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![
+            utxo(0, input::p2tr_key_path(public_key.clone())),
+            utxo(1, input::p2wpkh(public_key.clone())),
+            utxo(2, input::p2tr_key_path(public_key.clone())),
+        ],
+        outputs: vec![Proto::Output {
+            value: 290_000,
+            to_recipient: output::p2wpkh(public_key.clone()),
+            ..Default::default()
+        }],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        ..Default::default()
+    };
+    let signing = Proto::SigningInput {
+        public_keys: vec![public_key.into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &signing);
+    assert_eq!(
+        preimage.error,
+        SigningError::OK,
+        "{}",
+        preimage.error_message
+    );
+
+    let signatures = preimage
+        .sighashes
+        .iter()
+        .map(|sighash| {
+            let hash = H256::try_from(sighash.sighash.as_ref()).unwrap();
+            match sighash.signing_method {
+                Proto::mod_PreSigningOutput::SigningMethod::Taproot => {
+                    schnorr_key.clone().tweak(None).sign(hash).unwrap().to_vec()
+                },
+                _ => ecdsa_key.sign(hash).unwrap().to_vec(),
+            }
+        })
+        .collect();
+    (signing, signatures)
+}
+
+#[test]
+fn test_bitcoin_compile_mixed_inputs_signatures() {
+    let (signing, signatures) = make_mixed_inputs_tx();
+    assert_eq!(signatures.len(), 3);
+    let public_keys = signing.public_keys.iter().map(|key| key.to_vec()).collect();
+
+    let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+    let output = compiler.compile(CoinType::Bitcoin, &signing, signatures, public_keys);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+}
+
+#[test]
+fn test_bitcoin_compile_mixed_inputs_invalid_signature() {
+    let (signing, signatures) = make_mixed_inputs_tx();
+    let public_keys: Vec<_> = signing.public_keys.iter().map(|key| key.to_vec()).collect();
+
+    let corrupt = |signature: &mut Vec<u8>| signature[40] ^= 0x01;
+
+    // The failing input is reported whatever its position and signing method are.
+    for invalid_index in 0..signatures.len() {
+        let mut invalid_signatures = signatures.clone();
+        corrupt(&mut invalid_signatures[invalid_index]);
+
+        let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+        let output = compiler.compile(
+            CoinType::Bitcoin,
+            &signing,
+            invalid_signatures,
+            public_keys.clone(),
+        );
+        assert_ne!(output.error, SigningError::OK);
+        assert!(
+            output
+                .error_message
+                .contains(&format!("Input '{invalid_index}'")),
+            "{}",
+            output.error_message
+        );
+    }
+
+    // The first invalid input in the inputs order is reported.
+    let mut invalid_signatures = signatures;
+    corrupt(&mut invalid_signatures[0]);
+    corrupt(&mut invalid_signatures[1]);
+
+    let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+    let output = compiler.compile(CoinType::Bitcoin, &signing, invalid_signatures, public_keys);
+    assert_ne!(output.error, SigningError::OK);
+    assert!(
+        output.error_message.contains("Input '0'"),
+        "{}",
+        output.error_message
+    );
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::{CompilerHelper, PreImageHelper};
+use tw_bitcoin::modules::native_builder::draft::TxDraft;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_registry::coin_type::CoinType;
+use tw_hash::H256;
+use tw_keypair::ecdsa;
+use tw_keypair::traits::SigningKeyTrait;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const RECIPIENT: &str = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+
+fn make_signing_input() -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let public_key = private_key.public().to_vec();
+
+    let utxo = |txid: &str, value: i64| Proto::Input {
+        out_point: input::out_point(txid, 0),
+        value,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(public_key.clone()),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![
+            utxo(
+                "c50563913e5a838f937c94232f5a8fc74e58b629fae41dfdffcc9a70f833b53a",
+                60_000,
+            ),
+            utxo(
+                "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b",
+                20_000,
+            ),
+        ],
+        outputs: vec![Proto::Output {
+            value: 50_000,
+            to_recipient: output::to_address(RECIPIENT),
+            ..Default::default()
+        }],
+        change_output: Some(Proto::Output {
+            to_recipient: output::p2wpkh(public_key.clone()),
+            ..Default::default()
+        }),
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        public_keys: vec![public_key.into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+fn sign_sighash(preimage: &Proto::PreSigningOutput, index: usize) -> Vec<u8> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let sighash = H256::try_from(preimage.sighashes[index].sighash.as_ref()).unwrap();
+    private_key.sign(sighash).unwrap().to_vec()
+}
+
+#[test]
+fn test_bitcoin_compile_draft_snapshot_restore() {
+    let input = make_signing_input();
+    let mut draft = TxDraft::new(&input).unwrap();
+    // The private keys are never stored.
+    assert!(draft.signing_input().unwrap().private_keys.is_empty());
+
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &draft.signing_input().unwrap());
+    assert_eq!(
+        preimage.error,
+        SigningError::OK,
+        "{}",
+        preimage.error_message
+    );
+    assert_eq!(preimage.sighashes.len(), 2);
+
+    // Sign the first sighash and save the draft.
+    draft.set_session(&preimage).unwrap();
+    draft.add_signature(0, sign_sighash(&preimage, 0)).unwrap();
+    assert_eq!(draft.missing_signatures(), [1]);
+    let snapshot = draft.snapshot().unwrap();
+
+    // Restore the draft and sign the rest.
+    let mut restored = TxDraft::restore(&snapshot).unwrap();
+    assert_eq!(restored.session(), draft.session());
+    assert_eq!(restored.missing_signatures(), [1]);
+    let err = restored.signatures().unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_signing);
+
+    restored
+        .add_signature(1, sign_sighash(&preimage, 1))
+        .unwrap();
+    assert!(restored.missing_signatures().is_empty());
+
+    let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+    let output = compiler.compile(
+        CoinType::Bitcoin,
+        &restored.compile_input().unwrap(),
+        restored.signatures().unwrap(),
+        vec![],
+    );
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // The same transaction is signed with the private key.
+    let expected = compiler.compile(
+        CoinType::Bitcoin,
+        &input,
+        vec![sign_sighash(&preimage, 0), sign_sighash(&preimage, 1)],
+        vec![input.public_keys[0].to_vec()],
+    );
+    assert_eq!(output.encoded, expected.encoded);
+    assert_eq!(output.txid, expected.txid);
+}
+
+#[test]
+fn test_bitcoin_compile_draft_update() {
+    let input = make_signing_input();
+    let mut draft = TxDraft::new(&input).unwrap();
+
+    // No sighashes are computed yet.
+    assert_eq!(draft.session(), None);
+    assert!(draft.add_signature(0, vec![1; 71]).is_err());
+    assert!(draft.compile_input().is_err());
+
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &input);
+    draft.set_session(&preimage).unwrap();
+    draft.add_signature(0, sign_sighash(&preimage, 0)).unwrap();
+
+    // Editing the transaction discards the session and the signatures.
+    draft.update(&input).unwrap();
+    assert_eq!(draft.session(), None);
+    assert!(draft.missing_signatures().is_empty());
+
+    let err = TxDraft::restore(&[0xff; 4]).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+}
@@ -4,4 +4,8 @@
 
 mod brc20;
 mod compile_error;
+mod draft;
 mod p2pkh;
+mod preview;
+mod replace_signature;
+mod session;
@@ -6,8 +6,10 @@ use crate::chains::common::bitcoin::{
     btc_info, compile, dust_threshold, input, output, preimage, TransactionOneof, DUST, MINER_FEE,
     ONE_BTC, SIGHASH_ALL,
 };
+use tw_any_coin::test_utils::sign_utils::PreImageHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_hash::hasher::sha256_d;
 use tw_keypair::ecdsa;
 use tw_keypair::traits::SigningKeyTrait;
 use tw_misc::traits::ToBytesVec;
@@ -40,6 +42,7 @@ fn test_bitcoin_compile_p2pkh() {
     let out1 = Proto::Output {
         value: ONE_BTC * 50 - MINER_FEE,
         to_recipient: output::p2pkh(bob_pubkey.clone()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -66,6 +69,16 @@ fn test_bitcoin_compile_p2pkh() {
         .add_expected_legacy(alice_pubkey.to_vec(), sighash_1.to_vec())
         .pre_image();
 
+    // The raw signature message is exposed, so the sighash can be recomputed as `sha256d(preimage)`.
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage_output = pre_imager.pre_image_hashes(CoinType::Bitcoin, &signing);
+    let preimage_1 = &preimage_output.sighashes[0].preimage;
+    assert_eq!(
+        preimage_1.to_hex(),
+        "02000000017be4e642bb278018ab12277de9427773ad1c5f5b1d164a157e0d99aa48dc1c1e000000001976a914e4c1ea86373d554b8f4efff2cfb0001ea19124d288acffffffff01c0aff629010000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac0000000001000000"
+    );
+    assert_eq!(sha256_d(preimage_1), sighash_1);
+
     let signature_1 = alice_private_key
         .sign(sighash_1.as_slice().try_into().unwrap())
         .unwrap();
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{output, plan, TransactionOneof, DUST};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::compiler::BitcoinCompiler;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_encoding::hex::ToHex;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_LockTime::OneOfvariant as LockTimeType;
+use tw_proto::Common::Proto::SigningError;
+
+type Compiler = BitcoinCompiler<StandardBitcoinContext>;
+
+fn coin() -> CoinRegistryContext {
+    CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap())
+}
+
+fn make_input() -> Proto::SigningInput<'static> {
+    // The same transaction as in `test_exact_selector_with_change_1`.
+    plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_000, 1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    })
+}
+
+fn preview_with_lock_time(
+    lock_time: u32,
+    typed_lock_time: Option<LockTimeType>,
+) -> Proto::SigningOutput<'static> {
+    let mut input = make_input();
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.lock_time = lock_time;
+        tx_builder.typed_lock_time = typed_lock_time.map(|variant| Proto::LockTime { variant });
+    }
+    Compiler::preview(&coin(), input)
+}
+
+#[test]
+fn test_bitcoin_preview_unsigned() {
+    let input = make_input();
+
+    let output = Compiler::preview(&coin(), input);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    assert_eq!(
+        output.encoded.to_hex(),
+        // Version 1, two inputs with empty scriptSigs, three P2PKH outputs.
+        "01000000029687b25723a780d48b0330aad42be9ebfc7175bc977060d085d00152713f95060000000000ffffffff9687b25723a780d48b0330aad42be9ebfc7175bc977060d085d00152713f95060100000000ffffffff03e8030000000000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ace8030000000000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac9c040000000000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000"
+    );
+    assert!(output.txid.is_empty());
+    // The estimated values are the same as the plan ones.
+    assert_eq!(output.vsize, 410);
+    assert_eq!(output.weight, 1_640);
+    assert_eq!(output.fee, 820);
+
+    let tx = output.transaction.unwrap();
+    assert_eq!(tx.inputs.len(), 2);
+    assert!(tx
+        .inputs
+        .iter()
+        .all(|txin| txin.script_sig.is_empty() && txin.witness_items.is_empty()));
+}
+
+#[test]
+fn test_bitcoin_preimage_weight_breakdown() {
+    let output = Compiler::preimage_hashes(&coin(), make_input());
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // P2PKH inputs with the max size signatures, and P2PKH outputs including the change.
+    let breakdown = output.weight_breakdown.unwrap();
+    assert_eq!(breakdown.inputs, [596, 596]);
+    assert_eq!(breakdown.outputs, [136, 136, 136]);
+    assert_eq!(breakdown.overhead, 40);
+
+    // The parts sum up to the estimated transaction weight.
+    let total: u64 = breakdown
+        .inputs
+        .iter()
+        .chain(breakdown.outputs.iter())
+        .sum();
+    assert_eq!(total + breakdown.overhead, 1_640);
+}
+
+#[test]
+fn test_bitcoin_preview_p2a_output() {
+    let mut input = make_input();
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.outputs.push(Proto::Output {
+            value: 1_000,
+            to_recipient: output::p2a(),
+            ..Default::default()
+        });
+    }
+
+    let output = Compiler::preview(&coin(), input);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    let tx = output.transaction.unwrap();
+    assert_eq!(tx.outputs[2].value, 1_000);
+    assert_eq!(tx.outputs[2].script_pubkey.to_hex(), "51024e73");
+}
+
+#[test]
+fn test_bitcoin_preview_no_transaction() {
+    let input = Proto::SigningInput {
+        transaction: TransactionOneof::None,
+        ..Proto::SigningInput::default()
+    };
+
+    let output = Compiler::preview(&coin(), input);
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
+
+#[test]
+fn test_bitcoin_preview_lock_time() {
+    let sequences = |output: &Proto::SigningOutput| -> Vec<u32> {
+        let tx = output.transaction.as_ref().unwrap();
+        tx.inputs.iter().map(|txin| txin.sequence).collect()
+    };
+
+    // Inputs get the final sequence if there is no lock time.
+    let output = preview_with_lock_time(0, None);
+    assert_eq!(sequences(&output), vec![0xFFFFFFFF, 0xFFFFFFFF]);
+
+    // Inputs get a non-final sequence, otherwise the lock time is not enforced.
+    let output = preview_with_lock_time(840_000, None);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert_eq!(output.transaction.as_ref().unwrap().lock_time, 840_000);
+    assert_eq!(sequences(&output), vec![0xFFFFFFFE, 0xFFFFFFFE]);
+
+    let output = preview_with_lock_time(0, Some(LockTimeType::blocks(840_000)));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert_eq!(output.transaction.as_ref().unwrap().lock_time, 840_000);
+
+    let output = preview_with_lock_time(0, Some(LockTimeType::seconds(500_000_000)));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    let tx = output.transaction.as_ref().unwrap();
+    assert_eq!(tx.lock_time, 500_000_000);
+    assert_eq!(sequences(&output), vec![0xFFFFFFFE, 0xFFFFFFFE]);
+}
+
+#[test]
+fn test_bitcoin_preview_lock_time_error() {
+    // A timestamp is interpreted as a block height if less than 500,000,000.
+    let output = preview_with_lock_time(0, Some(LockTimeType::seconds(499_999_999)));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+
+    let output = preview_with_lock_time(0, Some(LockTimeType::blocks(500_000_000)));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+
+    // Both lock times are set.
+    let output = preview_with_lock_time(840_000, Some(LockTimeType::blocks(840_000)));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_SINGLE,
+};
+use tw_any_coin::test_utils::sign_utils::{AnySignerHelper, PreImageHelper};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::compiler::BitcoinCompiler;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_hash::H256;
+use tw_keypair::ecdsa;
+use tw_keypair::traits::SigningKeyTrait;
+use tw_misc::traits::ToBytesVec;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+type Compiler = BitcoinCompiler<StandardBitcoinContext>;
+
+const PRIVATE_KEY_1: &str = "bbc27228ddcb9209d7fd6f36b02f7dfa6252af40bb2f1cbc7a557da8027ff866";
+const PRIVATE_KEY_2: &str = "619c335025c7f4012e556c2a58b2506e30b8511b53ade95ea316fd8c3286feb9";
+
+/// The same transaction as in `test_bitcoin_sign_sighash_single`.
+fn make_signing_input(private_keys: &[&str]) -> Proto::SigningInput<'static> {
+    let private_key_1 = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY_1).unwrap();
+    let private_key_2 = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY_2).unwrap();
+
+    let utxo_hash_0 = "9f96ade4b41d5433f4eda31e1738ec2b36f6e7d1420d94a6af99801a88f7f7ff";
+    let utxo_0 = Proto::Input {
+        out_point: input::out_point(utxo_hash_0, 0),
+        value: 210_000_000,
+        sighash_type: SIGHASH_SINGLE,
+        claiming_script: input::p2pk(private_key_1.public().compressed().to_vec()),
+        ..Default::default()
+    };
+
+    let utxo_hash_1 = "8ac60eb9575db5b2d987e29f301b5b819ea83a5c6579d282d189cc04b8e151ef";
+    let utxo_1 = Proto::Input {
+        out_point: input::out_point(utxo_hash_1, 1),
+        value: 210_000_000,
+        sighash_type: SIGHASH_SINGLE,
+        claiming_script: input::p2wpkh(private_key_2.public().to_vec()),
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 335_790_000,
+        to_recipient: output::to_address("1Bp9U1ogV3A14FMvKbRJms7ctyso4Z4Tcx"),
+        ..Default::default()
+    };
+    let explicit_change_output = Proto::Output {
+        value: 84_209_739,
+        to_recipient: output::to_address("1FQc5LdgGHMHEN9nwkjmz6tWkxhPpxBvBU"),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V1,
+        inputs: vec![utxo_0, utxo_1],
+        outputs: vec![out_0, explicit_change_output],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 0,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: private_keys
+            .iter()
+            .map(|private| private.decode_hex().unwrap().into())
+            .collect(),
+        chain_info: btc_info(),
+        allow_partial_signing: true,
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+fn coin() -> CoinRegistryContext {
+    CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap())
+}
+
+/// Signs the sighash of the second input with the second private key.
+fn sign_second_input(input: &Proto::SigningInput<'static>) -> Vec<u8> {
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, input);
+    assert_eq!(
+        preimage.error,
+        SigningError::OK,
+        "{}",
+        preimage.error_message
+    );
+
+    let sighash = H256::try_from(preimage.sighashes[1].sighash.as_ref()).unwrap();
+    let private_key_2 = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY_2).unwrap();
+    private_key_2.sign(sighash).unwrap().to_vec()
+}
+
+#[test]
+fn test_bitcoin_compile_replace_signature() {
+    // The first party signs its input only.
+    let input = make_signing_input(&[PRIVATE_KEY_1]);
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let partially_signed = signer.sign(CoinType::Bitcoin, input.clone());
+    assert_eq!(partially_signed.unsigned_inputs, vec![1]);
+
+    // The second party signs its input, and the signature is put into the partially signed transaction.
+    let signature = sign_second_input(&input);
+    let output = Compiler::replace_signature(
+        &coin(),
+        input,
+        partially_signed.transaction.as_ref().unwrap(),
+        1,
+        signature,
+    );
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // The same transaction as signed by both parties at once.
+    assert_eq!(
+        output.encoded.to_hex(),
+        "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000049483045022100fd8591c3611a07b55f509ec850534c7a9c49713c9b8fa0e844ea06c2e65e19d702205e3806676192e790bc93dd4c28e937c4bf97b15f189158ba1a30d7ecff5ee75503ffffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02b0bf0314000000001976a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ac4bf00405000000001976a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac00024730440220096d20c7e92f991c2bf38dc28118feb34019ae74ec1c17179b28cb041de7517402204594f46a911f24bdc7109ca192e6860ebf2f3a0087579b3c128d5ce0cd5ed4680321025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635700000000"
+    );
+    assert_eq!(
+        output.txid.to_hex(),
+        "68c08a357a16b163983f7338185dc8befdf3e301e648b1cceca26a3fd33fefbd"
+    );
+    assert_eq!(output.fee, 261);
+}
+
+#[test]
+fn test_bitcoin_compile_replace_signature_error() {
+    let input = make_signing_input(&[PRIVATE_KEY_1]);
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let partially_signed = signer.sign(CoinType::Bitcoin, input.clone());
+    let signed_tx = partially_signed.transaction.unwrap();
+    let signature = sign_second_input(&input);
+
+    // The signature does not correspond to the first input.
+    let output =
+        Compiler::replace_signature(&coin(), input.clone(), &signed_tx, 0, signature.clone());
+    assert_eq!(output.error, SigningError::Error_signing);
+
+    // There is no such input.
+    let output =
+        Compiler::replace_signature(&coin(), input.clone(), &signed_tx, 2, signature.clone());
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+
+    // The signed transaction was compiled from another signing input.
+    let mut other_tx = signed_tx;
+    other_tx.lock_time = 1;
+    let output = Compiler::replace_signature(&coin(), input, &other_tx, 1, signature);
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use std::borrow::Cow;
+use tw_any_coin::test_utils::sign_utils::{CompilerHelper, PreImageHelper};
+use tw_coin_registry::coin_type::CoinType;
+use tw_hash::H256;
+use tw_keypair::ecdsa;
+use tw_keypair::traits::SigningKeyTrait;
+use tw_misc::traits::ToBytesVec;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const RECIPIENT: &str = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+
+fn make_signing_input() -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let public_key = private_key.public().to_vec();
+
+    let utxo = |txid: &str, value: i64| Proto::Input {
+        out_point: input::out_point(txid, 0),
+        value,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(public_key.clone()),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![
+            utxo(
+                "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+                500_000,
+            ),
+            utxo(
+                "c50563913e5a838f937c94232f5a8fc74e58b629fae41dfdffcc9a70f833b53a",
+                60_000,
+            ),
+            utxo(
+                "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b",
+                20_000,
+            ),
+        ],
+        outputs: vec![Proto::Output {
+            value: 50_000,
+            to_recipient: output::to_address(RECIPIENT),
+            ..Default::default()
+        }],
+        change_output: Some(Proto::Output {
+            to_recipient: output::p2wpkh(public_key.clone()),
+            ..Default::default()
+        }),
+        input_selector: Proto::InputSelector::SelectAscending,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        public_keys: vec![public_key.into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+fn sign_sighashes(preimage: &Proto::PreSigningOutput) -> Vec<Vec<u8>> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    preimage
+        .sighashes
+        .iter()
+        .map(|sighash| {
+            let sighash = H256::try_from(sighash.sighash.as_ref()).unwrap();
+            private_key.sign(sighash).unwrap().to_vec()
+        })
+        .collect()
+}
+
+#[test]
+fn test_bitcoin_compile_session() {
+    let input = make_signing_input();
+    let public_key = input.public_keys[0].to_vec();
+
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &input);
+    assert_eq!(
+        preimage.error,
+        SigningError::OK,
+        "{}",
+        preimage.error_message
+    );
+    assert!(!preimage.session.is_empty());
+    // Two smallest UTXOs are selected.
+    assert_eq!(preimage.sighashes.len(), 2);
+    let signatures = sign_sighashes(&preimage);
+
+    let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+    let expected = compiler.compile(
+        CoinType::Bitcoin,
+        &input,
+        signatures.clone(),
+        vec![public_key.clone()],
+    );
+    assert_eq!(
+        expected.error,
+        SigningError::OK,
+        "{}",
+        expected.error_message
+    );
+
+    // The session is enough to compute the same sighashes and to compile the same transaction.
+    let session_input = Proto::SigningInput {
+        transaction: Proto::mod_SigningInput::OneOftransaction::session(Cow::from(
+            preimage.session.to_vec(),
+        )),
+        ..Default::default()
+    };
+
+    let session_preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &session_input);
+    assert_eq!(
+        session_preimage.error,
+        SigningError::OK,
+        "{}",
+        session_preimage.error_message
+    );
+    assert_eq!(session_preimage.sighashes, preimage.sighashes);
+
+    let output = compiler.compile(
+        CoinType::Bitcoin,
+        &session_input,
+        signatures,
+        vec![public_key],
+    );
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert_eq!(output.encoded, expected.encoded);
+    assert_eq!(output.txid, expected.txid);
+    assert_eq!(output.fee, expected.fee);
+}
+
+#[test]
+fn test_bitcoin_compile_session_error() {
+    let session_input = Proto::SigningInput {
+        transaction: Proto::mod_SigningInput::OneOftransaction::session(Cow::from(vec![0xff; 4])),
+        ..Default::default()
+    };
+
+    let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+    let output = compiler.compile(CoinType::Bitcoin, &session_input, vec![], vec![]);
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
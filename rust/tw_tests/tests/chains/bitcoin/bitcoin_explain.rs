@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{plan, TransactionOneof, DUST};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::explain::{BitcoinExplain, LockTimeReport};
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_proto::BitcoinV2::Proto;
+
+type Explain = BitcoinExplain<StandardBitcoinContext>;
+
+const DUMMY_TXID: &str = "06953f715201d085d0607097bc7571fcebe92bd4aa30038bd480a72357b28796";
+/// P2PKH address of the public key used in [`plan::make_planning_input`].
+const ADDRESS: &str = "19dYwcMgeK3KKrc2GzzbRLWK5B6eGytay5";
+
+fn coin() -> CoinRegistryContext {
+    CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap())
+}
+
+fn make_input(change: bool) -> Proto::SigningInput<'static> {
+    plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_000, 1_000],
+        change,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    })
+}
+
+#[test]
+fn test_bitcoin_explain_with_change() {
+    let report = Explain::explain(&coin(), &make_input(true)).unwrap();
+
+    // The same values as in `test_exact_selector_with_change_1`.
+    assert_eq!(report.version, 1);
+    assert_eq!(report.lock_time, LockTimeReport::None);
+    assert_eq!(report.total_input, 4_000);
+    assert_eq!(report.total_output, 3_180);
+    assert_eq!(report.fee, 820);
+    assert_eq!(report.vsize, 410);
+    assert_eq!(report.fee_rate(), 2.0);
+    assert_eq!(report.change(), 1_180);
+
+    let inputs: Vec<_> = report.inputs.iter().map(|input| input.value).collect();
+    assert_eq!(inputs, vec![1_000, 3_000]);
+    for (vout, input) in report.inputs.iter().enumerate() {
+        assert_eq!(input.txid.to_string(), DUMMY_TXID);
+        assert_eq!(input.vout, vout as u32);
+        assert_eq!(input.script_type, Proto::OutputScriptType::P2PKH);
+        assert_eq!(input.address.as_deref(), Some(ADDRESS));
+    }
+
+    let outputs: Vec<_> = report
+        .outputs
+        .iter()
+        .map(|output| (output.value, output.is_change))
+        .collect();
+    assert_eq!(outputs, vec![(1_000, false), (1_000, false), (1_180, true)]);
+    for output in report.outputs.iter() {
+        assert_eq!(output.script_type, Proto::OutputScriptType::P2PKH);
+        assert_eq!(output.address.as_deref(), Some(ADDRESS));
+    }
+
+    let text = report.to_string();
+    assert!(text.contains("Fee: 820 sat"), "{text}");
+    assert!(
+        text.contains("Fee rate: 2.00 sat/vB (410 vbytes)"),
+        "{text}"
+    );
+    assert!(
+        text.contains(&format!("P2PKH {ADDRESS} 1180 sat (change)")),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_bitcoin_explain_without_change() {
+    let report = Explain::explain(&coin(), &make_input(false)).unwrap();
+
+    assert_eq!(report.change(), 0);
+    assert!(report.outputs.iter().all(|output| !output.is_change));
+    assert_eq!(report.fee, report.total_input - report.total_output);
+}
+
+#[test]
+fn test_bitcoin_explain_lock_time() {
+    let explain_lock_time = |lock_time: u32| {
+        let mut input = make_input(true);
+        if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+            tx_builder.lock_time = lock_time;
+        }
+        Explain::explain(&coin(), &input).unwrap().lock_time
+    };
+
+    assert_eq!(
+        explain_lock_time(840_000),
+        LockTimeReport::BlockHeight(840_000)
+    );
+    assert_eq!(
+        explain_lock_time(1_700_000_000),
+        LockTimeReport::Timestamp(1_700_000_000)
+    );
+}
+
+#[test]
+fn test_bitcoin_explain_no_transaction() {
+    let input = Proto::SigningInput {
+        transaction: TransactionOneof::None,
+        ..make_input(true)
+    };
+    assert!(Explain::explain(&coin(), &input).is_err());
+}
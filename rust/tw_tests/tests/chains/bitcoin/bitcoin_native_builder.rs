@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, sign, TransactionOneof, BITCOIN_HRP, BITCOIN_P2PKH_PREFIX, BITCOIN_P2SH_PREFIX,
+    SIGHASH_ALL,
+};
+use std::str::FromStr;
+use tw_bitcoin::modules::native_builder::amount::Amount;
+use tw_bitcoin::modules::native_builder::signing_input::SigningInputBuilder;
+use tw_bitcoin::modules::native_builder::tx_builder::TxBuilder;
+use tw_coin_entry::error::prelude::*;
+use tw_coin_registry::coin_type::CoinType;
+use tw_keypair::ecdsa;
+use tw_misc::traits::ToBytesZeroizing;
+use tw_proto::BitcoinV2::Proto;
+use tw_utxo::transaction::transaction_parts::Txid;
+
+fn txid(txid: &str) -> Txid {
+    Txid::from_str(txid).unwrap()
+}
+
+fn sat(sat: u64) -> Amount {
+    Amount::from_sat(sat).unwrap()
+}
+
+/// Same transaction as in `test_bitcoin_sign_p2wpkh_input_different_builders`,
+/// but the transaction builder is constructed with [`TxBuilder`].
+#[test]
+fn test_bitcoin_native_builder_sign_p2wpkh() {
+    let my_private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let my_private_key = ecdsa::secp256k1::PrivateKey::try_from(my_private_key).unwrap();
+    let my_address = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+
+    let builder = TxBuilder::new()
+        .version(Proto::TransactionVersion::V1)
+        .add_p2wpkh_input(
+            txid("b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d"),
+            1,
+            sat(30_269),
+            &my_private_key.public(),
+        )
+        .add_address_input(
+            txid("1f62c18bfc5f8293a2b7b061587c427bf830fb224289f9a806e6ad48de6a4c7d"),
+            1,
+            sat(4_863),
+            my_address,
+        )
+        .add_address_input(
+            txid("71c3343dfca5f1914e1bfc04153517d73650cb9c931e8511d24d1f5290120f6f"),
+            0,
+            sat(300),
+            my_address,
+        )
+        .add_address_output("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp", sat(28_035))
+        .dust_threshold(Amount::DEFAULT_DUST_THRESHOLD)
+        .fee_rate(33)
+        .build();
+
+    let signing = Proto::SigningInput {
+        private_keys: vec![my_private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    // Successfully broadcasted: https://mempool.space/tx/5d6bf53576a54be4d92cd8abf58d28ecc9ea7956eaf970d24d6bfcb9fcfe9855
+    sign::BitcoinSignHelper::new(&signing)
+        .coin(CoinType::Bitcoin)
+        .sign(sign::Expected {
+            encoded: "010000000001027d4c6ade48ade606a8f9894222fb30f87b427c5861b0b7a293825ffc8bc1621f0100000000ffffffff8d0e94ed369601d13f32bd653071844dfe7139508d2e71d9c105d1faa58230b30100000000ffffffff01836d0000000000001600145360df8231ac5965147c9d90ca930a2aafb0523202483045022100f95f9ac5d39f4b47dcd8c86daaaeac86374258d9960f922333ba0d5fdaa15b7e0220761794672dc9fbd71398d608f72f5d21a0f6c1306c6b700ad0d82f747c221062012103a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c02483045022100eb6ba0dcc64af61b2186b7efdab1ff03784d585ee03437f9a53875e93429db080220015a268d308436d3564b83ceaed90bc7272ca164016298ea855d1936568002a7012103a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c00000000",
+            txid: "5d6bf53576a54be4d92cd8abf58d28ecc9ea7956eaf970d24d6bfcb9fcfe9855",
+            inputs: vec![4_863, 30_269],
+            outputs: vec![28_035],
+            vsize: 178,
+            weight: 710,
+            // sum(inputs) - sum(outputs)
+            fee: 7_097,
+        });
+}
+
+#[test]
+fn test_bitcoin_signing_input_builder_defaults() {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(
+        "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35",
+    )
+    .unwrap();
+
+    let input = SigningInputBuilder::new()
+        .add_private_key(&private_key)
+        .chain_info(BITCOIN_P2PKH_PREFIX, BITCOIN_P2SH_PREFIX, Some(BITCOIN_HRP))
+        .transaction(|tx| {
+            tx.add_p2wpkh_input(
+                txid("b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d"),
+                1,
+                sat(30_269),
+                &private_key.public(),
+            )
+            .add_address_output("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp", sat(28_035))
+            .fee_rate(10)
+        })
+        .build()
+        .unwrap();
+
+    assert_eq!(input.private_keys.len(), 1);
+    assert_eq!(input.chain_info, btc_info());
+
+    let TransactionOneof::builder(ref builder) = input.transaction else {
+        panic!("Expected a transaction builder");
+    };
+    assert_eq!(builder.version, Proto::TransactionVersion::V2);
+    assert_eq!(builder.input_selector, Proto::InputSelector::UseAll);
+    assert_eq!(builder.inputs[0].sighash_type, SIGHASH_ALL);
+}
+
+#[test]
+fn test_bitcoin_signing_input_builder_validation() {
+    let public_key = ecdsa::secp256k1::PrivateKey::try_from(
+        "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35",
+    )
+    .unwrap()
+    .public();
+    let utxo_txid = txid("b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d");
+    let to_address = "bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp";
+
+    // No inputs.
+    let err = SigningInputBuilder::new()
+        .transaction(|tx| tx.add_address_output(to_address, sat(1_000)))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        *err.error_type(),
+        SigningErrorType::Error_missing_input_utxos
+    );
+
+    // No outputs.
+    let err = SigningInputBuilder::new()
+        .transaction(|tx| tx.add_p2wpkh_input(utxo_txid, 0, sat(1_000), &public_key))
+        .build()
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // Negative fee rate.
+    let err = SigningInputBuilder::new()
+        .transaction(|tx| {
+            tx.add_p2wpkh_input(utxo_txid, 0, sat(1_000), &public_key)
+                .max_amount_address(to_address)
+                .fee_rate(-1)
+        })
+        .build()
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_wrong_fee);
+
+    // Invalid txid length.
+    let err = SigningInputBuilder::new()
+        .transaction(|tx| {
+            tx.add_input(Proto::Input {
+                out_point: Some(Proto::OutPoint {
+                    hash: vec![0; 31].into(),
+                    vout: 0,
+                }),
+                ..Proto::Input::default()
+            })
+            .add_address_output(to_address, sat(1_000))
+        })
+        .build()
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+}
@@ -2,8 +2,12 @@
 //
 // Copyright © 2017 Trust Wallet.
 
+mod plan_batch;
 mod plan_exact;
 mod plan_exact_error;
+mod plan_lazy;
 mod plan_max;
 mod plan_max_error;
+mod plan_prev_tx;
 mod plan_psbt;
+mod plan_truc;
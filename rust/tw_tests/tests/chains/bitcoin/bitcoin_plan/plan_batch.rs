@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{output, plan, Amount, DUST};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::planner::batch_planner::BatchPlanner;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_encoding::hex::DecodeHex;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const RECIPIENT_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+fn payment(amounts: &[Amount]) -> Vec<Proto::Output<'static>> {
+    let recipient = RECIPIENT_PUBKEY.decode_hex().unwrap();
+    amounts
+        .iter()
+        .map(|amount| Proto::Output {
+            value: *amount,
+            to_recipient: output::p2pkh(recipient.clone()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn batch_input() -> Proto::SigningInput<'static> {
+    plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000, 10_000],
+        // The builder outputs are ignored.
+        outputs: vec![100_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    })
+}
+
+#[test]
+fn test_plan_batch() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+    let payments = vec![payment(&[1_000, 1_000]), payment(&[5_000])];
+
+    let plans =
+        BatchPlanner::<StandardBitcoinContext>::plan_batch(&coin, &batch_input(), &payments)
+            .unwrap();
+    assert_eq!(plans.len(), 2);
+
+    let utxos = |plan: &Proto::TransactionPlan| -> Vec<Amount> {
+        plan.inputs.iter().map(|utxo| utxo.value).collect()
+    };
+    let outputs = |plan: &Proto::TransactionPlan| -> Vec<Amount> {
+        plan.outputs.iter().map(|output| output.value).collect()
+    };
+
+    // The same plan as if the first payment was planned alone.
+    assert_eq!(utxos(&plans[0]), vec![1_000, 3_000]);
+    assert_eq!(outputs(&plans[0]), vec![1_000, 1_000, 1_180]);
+    assert_eq!(plans[0].change, 1_180);
+
+    // The second payment spends the rest of the UTXOs and gets its own change.
+    assert_eq!(utxos(&plans[1]), vec![4_000, 10_000]);
+    assert_eq!(plans[1].outputs.len(), 2);
+    assert_eq!(plans[1].outputs[0].value, 5_000);
+    assert_eq!(plans[1].change, 14_000 - 5_000 - plans[1].fee_estimate);
+
+    // No UTXO is spent twice.
+    let mut out_points: Vec<_> = plans
+        .iter()
+        .flat_map(|plan| plan.inputs.iter())
+        .map(|utxo| utxo.out_point.clone().unwrap().vout)
+        .collect();
+    out_points.sort();
+    out_points.dedup();
+    assert_eq!(out_points.len(), 4);
+}
+
+#[test]
+fn test_plan_batch_insufficient_pool() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+    // The second payment could be funded by the whole pool, but not by the UTXOs left after the first one.
+    let payments = vec![payment(&[1_000, 1_000]), payment(&[15_000])];
+
+    let err = BatchPlanner::<StandardBitcoinContext>::plan_batch(&coin, &batch_input(), &payments)
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_not_enough_utxos);
+}
@@ -2,9 +2,14 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::chains::common::bitcoin::{plan, Amount, DUST};
+use crate::chains::common::bitcoin::{
+    input, output, plan, Amount, TransactionOneof, DUST, ONE_BTC,
+};
+use tw_any_coin::test_utils::plan_utils::AnyPlannerHelper;
 use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::DecodeHex;
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto as CommonProto;
 
 #[test]
 fn test_exact_selector_with_change_1() {
@@ -270,3 +275,273 @@ fn test_exact_selector_filter_utxo_dust_without_change() {
             change: 0,
         });
 }
+
+#[test]
+fn test_exact_selector_must_spend() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_000, 1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    // Without the flag, `1_000` and `3_000` UTXOs would be selected.
+    let input = plan::update_inputs(input, &[2], |utxo| utxo.must_spend = true);
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![4_000],
+            outputs: vec![1_000, 1_000, 1_478],
+            vsize_estimate: 261,
+            // vsize * fee_rate
+            fee_estimate: 522,
+            change: 1_478,
+        });
+}
+
+#[test]
+fn test_exact_selector_must_spend_dust_utxo() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![3_000, 555, 8_000, 1_999],
+        outputs: vec![8_000],
+        change: true,
+        max: false,
+        dust_threshold: 2_000,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 2,
+    });
+    // The must-spend UTXO is not filtered out even if it is dust,
+    // and the remaining amount is covered by the automatically selected UTXOs.
+    let input = plan::update_inputs(input, &[1], |utxo| utxo.must_spend = true);
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![555, 8_000, 3_000],
+            outputs: vec![8_000, 2_505],
+            vsize_estimate: 525,
+            // vsize * fee_rate
+            fee_estimate: 1_050,
+            change: 2_505,
+        });
+}
+
+#[test]
+fn test_exact_selector_frozen() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 2_000, 6_000, 1_000, 11_000, 12_000],
+        outputs: vec![2_000, 11_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 2,
+    });
+    // Without the flag, `12_000` and `11_000` UTXOs would be selected.
+    let input = plan::update_inputs(input, &[5], |utxo| utxo.frozen = true);
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![11_000, 6_000],
+            outputs: vec![2_000, 11_000, 3_180],
+            vsize_estimate: 410,
+            // vsize * fee_rate
+            fee_estimate: 820,
+            change: 3_180,
+        });
+}
+
+#[test]
+fn test_exact_selector_foreign_input() {
+    const FOREIGN_SCRIPT_PUBKEY: &str = "76a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac";
+    const FOREIGN_PUBKEY: &str =
+        "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 2_000, 6_000, 1_000, 11_000, 12_000],
+        outputs: vec![2_000, 11_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 2,
+    });
+
+    // A placeholder scriptSig of the same size as a P2PKH claim: <sig> <pubkey>.
+    let mut placeholder_script_sig = vec![0x49];
+    placeholder_script_sig.extend_from_slice(&[0; 73]);
+    placeholder_script_sig.push(0x21);
+    placeholder_script_sig.extend_from_slice(&FOREIGN_PUBKEY.decode_hex().unwrap());
+
+    // The `1_000` UTXO belongs to another party.
+    let input = plan::update_inputs(input, &[3], |utxo| {
+        utxo.claiming_script = input::foreign(
+            FOREIGN_SCRIPT_PUBKEY.decode_hex().unwrap(),
+            placeholder_script_sig.clone(),
+            Vec::default(),
+        );
+    });
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            // The foreign UTXO is always spent.
+            inputs: vec![1_000, 12_000, 11_000],
+            outputs: vec![2_000, 11_000, 9_882],
+            // The foreign UTXO is estimated as a P2PKH input.
+            vsize_estimate: 559,
+            // vsize * fee_rate
+            fee_estimate: 1_118,
+            change: 9_882,
+        });
+}
+
+#[test]
+fn test_exact_selector_change_split() {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 2_000, 6_000, 1_000, 11_000, 12_000],
+        outputs: vec![2_000, 11_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 2,
+    });
+    if let TransactionOneof::builder(ref mut builder) = input.transaction {
+        builder.change_split = Some(Proto::ChangeSplit {
+            outputs_count: 3,
+            min_amount: 1_000,
+            seed: "c0ffee".decode_hex().unwrap().into(),
+            // Each change output pays to a distinct P2PKH address.
+            change_outputs: vec![
+                Proto::Output {
+                    to_recipient: output::to_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+                    ..Default::default()
+                },
+                Proto::Output {
+                    to_recipient: output::to_address("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"),
+                    ..Default::default()
+                },
+            ],
+        });
+    }
+
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![12_000, 11_000],
+            // 9_044 change is split into 3 outputs.
+            outputs: vec![2_000, 11_000, 2_763, 3_579, 2_702],
+            // vsize includes all change outputs.
+            vsize_estimate: 478,
+            // vsize * fee_rate
+            fee_estimate: 956,
+            change: 9_044,
+        });
+}
+
+#[test]
+fn test_exact_selector_subtract_fee() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_000, 2_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    let input = plan::update_outputs(input, &[1], |output| output.subtract_fee = true);
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            // The UTXOs cover the output amounts only.
+            inputs: vec![1_000, 3_000],
+            // The fee is deducted from the second output.
+            outputs: vec![1_000, 1_180, 1_000],
+            vsize_estimate: 410,
+            // vsize * fee_rate
+            fee_estimate: 820,
+            change: 1_000,
+        });
+}
+
+#[test]
+fn test_exact_selector_subtract_fee_exact_balance() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![8_000],
+        change: false,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    let input = plan::update_outputs(input, &[0], |output| output.subtract_fee = true);
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![1_000, 3_000, 4_000],
+            outputs: vec![7_018],
+            vsize_estimate: 491,
+            // vsize * fee_rate
+            fee_estimate: 982,
+            change: 0,
+        });
+}
+
+#[test]
+fn test_exact_selector_subtract_fee_proportional() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_500, 1_500, 1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 3,
+    });
+    let input = plan::update_outputs(input, &[0, 1, 2], |output| output.subtract_fee = true);
+    plan::BitcoinPlanHelper::new(&input)
+        .coin(CoinType::Bitcoin)
+        .plan(plan::Expected {
+            inputs: vec![1_000, 3_000],
+            // The fee is split as `499 + 499 + 333`, and the remaining satoshi is deducted from the first output.
+            outputs: vec![1_000, 1_001, 667],
+            vsize_estimate: 444,
+            // vsize * fee_rate
+            fee_estimate: 1_332,
+            // No change left.
+            change: 0,
+        });
+}
+
+#[test]
+fn test_exact_selector_dogecoin_above_21m_coins() {
+    // Unlike Bitcoin, Dogecoin amounts are not limited by 21M coins.
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![30_000_000 * ONE_BTC],
+        outputs: vec![25_000_000 * ONE_BTC],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 1,
+    });
+    input.chain_info = Some(Proto::ChainInfo {
+        p2pkh_prefix: 30,
+        p2sh_prefix: 22,
+        hrp: "".into(),
+    });
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Dogecoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::OK,
+        "{}",
+        plan.error_message
+    );
+    assert_eq!(plan.available_amount, 30_000_000 * ONE_BTC);
+    assert_eq!(plan.outputs[0].value, 25_000_000 * ONE_BTC);
+}
@@ -2,8 +2,9 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::chains::common::bitcoin::{plan, Amount, DUST};
+use crate::chains::common::bitcoin::{output, plan, Amount, TransactionOneof, DUST, ONE_BTC};
 use tw_any_coin::test_utils::plan_utils::AnyPlannerHelper;
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Common::Proto as CommonProto;
@@ -104,6 +105,111 @@ fn test_exact_selector_negative_output_error() {
     assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
 }
 
+#[test]
+fn test_exact_selector_zero_output_error() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![10_000],
+        outputs: vec![0],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 1,
+    });
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_zero_amount_requested
+    );
+}
+
+#[test]
+fn test_exact_selector_zero_utxo_error() {
+    // A zero-value P2PKH output cannot be relayed, so it's unlikely to exist.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![0, 10_000],
+        outputs: vec![1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 1,
+    });
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_invalid_utxo_amount
+    );
+}
+
+#[test]
+fn test_exact_selector_max_money_error() {
+    let max_money = 21_000_000 * ONE_BTC;
+
+    // An output amount exceeds 21M BTC.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![10_000],
+        outputs: vec![max_money + 1],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 1,
+    });
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+
+    // The total output amount exceeds 21M BTC.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![10_000],
+        outputs: vec![max_money, 1],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 1,
+    });
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+
+    // A UTXO amount exceeds 21M BTC.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![max_money + 1],
+        outputs: vec![1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 1,
+    });
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_invalid_utxo_amount
+    );
+
+    // The total UTXO amount exceeds 21M BTC.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![max_money, max_money],
+        outputs: vec![1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 1,
+    });
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_invalid_utxo_amount
+    );
+}
+
 #[test]
 fn test_exact_selector_insufficient_due_dust() {
     // Transaction vsize would be 227, so if the first UTXO is not Dust, there could be enough to generate the tx.
@@ -205,3 +311,206 @@ fn test_exact_selector_dust_amount_requested() {
         CommonProto::SigningError::Error_dust_amount_requested
     );
 }
+
+#[test]
+fn test_exact_selector_frozen_insufficient() {
+    // There would be enough funds if the `12_000` UTXO wasn't frozen.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 12_000],
+        outputs: vec![10_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::UseAll,
+        fee_per_vb: 2,
+    });
+    let input = plan::update_inputs(input, &[1], |utxo| utxo.frozen = true);
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_not_enough_utxos
+    );
+}
+
+#[test]
+fn test_exact_selector_frozen_must_spend_error() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 12_000],
+        outputs: vec![10_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 2,
+    });
+    let input = plan::update_inputs(input, &[1], |utxo| {
+        utxo.frozen = true;
+        utxo.must_spend = true;
+    });
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+}
+
+#[test]
+fn test_exact_selector_duplicate_utxo_error() {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000, 4_000],
+        outputs: vec![1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    // Provide the same UTXO twice.
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        let duplicate = tx_builder.inputs[0].clone();
+        tx_builder.inputs.push(duplicate);
+    }
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input.clone());
+    assert_eq!(plan.error, CommonProto::SigningError::Error_duplicate_utxo);
+
+    // Signing must fail as well even if all inputs are spent.
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.input_selector = Proto::InputSelector::UseAll;
+    }
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, input);
+    assert_eq!(
+        output.error,
+        CommonProto::SigningError::Error_duplicate_utxo
+    );
+}
+
+#[test]
+fn test_exact_selector_anyprevout_not_supported() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000],
+        outputs: vec![1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    // BIP-118 is experimental and must be enabled explicitly.
+    let input = plan::update_inputs(input, &[0], |utxo| {
+        utxo.anyprevout = Proto::AnyPrevoutType::AnyPrevout;
+    });
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_not_supported);
+}
+
+#[test]
+fn test_exact_selector_subtract_fee_insufficient() {
+    // Fee would be around 2_600, so the first output amount doesn't cover it.
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000],
+        outputs: vec![1_000, 3_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 10,
+    });
+    let input = plan::update_outputs(input, &[0], |output| output.subtract_fee = true);
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(
+        plan.error,
+        CommonProto::SigningError::Error_not_enough_utxos
+    );
+}
+
+#[test]
+fn test_exact_selector_subtract_fee_change_output() {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 4_000],
+        outputs: vec![1_000, 3_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+
+    // The fee cannot be deducted from the change output.
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.change_output.as_mut().unwrap().subtract_fee = true;
+    }
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+}
+
+fn change_split_input(
+    outputs_count: u32,
+    change_addresses: &[&str],
+) -> Proto::SigningInput<'static> {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![4_000, 2_000, 6_000, 1_000, 11_000, 12_000],
+        outputs: vec![2_000, 11_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectDescending,
+        fee_per_vb: 2,
+    });
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.change_split = Some(Proto::ChangeSplit {
+            outputs_count,
+            min_amount: 1_000,
+            seed: b"seed".to_vec().into(),
+            change_outputs: change_addresses
+                .iter()
+                .map(|address| Proto::Output {
+                    to_recipient: output::to_address(address),
+                    ..Default::default()
+                })
+                .collect(),
+        });
+    }
+    input
+}
+
+#[test]
+fn test_exact_selector_change_split_same_script() {
+    // The last two split change outputs pay to the same address.
+    let input = change_split_input(
+        3,
+        &[
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+        ],
+    );
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+    assert!(plan.error_message.contains("distinct scriptPubkeys"));
+}
+
+#[test]
+fn test_exact_selector_change_split_missing_outputs() {
+    // Three change outputs are requested, but only two are provided.
+    let input = change_split_input(3, &["1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"]);
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+}
+
+#[test]
+fn test_exact_selector_change_split_too_many_outputs() {
+    let input = change_split_input(u32::MAX, &[]);
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    let plan = planner.plan(CoinType::Bitcoin, input);
+    assert_eq!(plan.error, CommonProto::SigningError::Error_invalid_params);
+}
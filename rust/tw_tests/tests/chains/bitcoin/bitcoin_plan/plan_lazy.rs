@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{plan, Amount, DUST};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::planner::lazy_planner::{LazyPlanner, UtxoProvider, UtxoQuery};
+use tw_coin_entry::error::prelude::*;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_proto::BitcoinV2::Proto;
+
+/// In-memory UTXO set that records the queries.
+struct TestProvider {
+    utxos: Vec<Proto::Input<'static>>,
+    batch_size: usize,
+    queries: Vec<UtxoQuery>,
+}
+
+impl TestProvider {
+    fn new(amounts: Vec<Amount>, batch_size: usize) -> TestProvider {
+        let Proto::mod_SigningInput::OneOftransaction::builder(builder) =
+            make_input(amounts, vec![]).transaction
+        else {
+            unreachable!()
+        };
+        let mut utxos = builder.inputs;
+        utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.value));
+        TestProvider {
+            utxos,
+            batch_size,
+            queries: Vec::default(),
+        }
+    }
+}
+
+impl UtxoProvider for TestProvider {
+    fn query(&mut self, query: &UtxoQuery) -> SigningResult<Vec<Proto::Input<'static>>> {
+        self.queries.push(query.clone());
+        Ok(self
+            .utxos
+            .iter()
+            .filter(|utxo| utxo.value >= query.min_amount)
+            .skip(query.offset)
+            .take(query.limit)
+            .cloned()
+            .collect())
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+}
+
+fn make_input(inputs: Vec<Amount>, outputs: Vec<Amount>) -> Proto::SigningInput<'static> {
+    plan::make_planning_input(plan::PlanArgs {
+        inputs,
+        outputs,
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    })
+}
+
+fn plan_with_provider(
+    outputs: Vec<Amount>,
+    provider: &mut TestProvider,
+) -> SigningResult<Proto::TransactionPlan<'static>> {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+    LazyPlanner::<StandardBitcoinContext>::plan_with_provider(
+        &coin,
+        &make_input(vec![], outputs),
+        provider,
+    )
+}
+
+fn selected_utxos(plan: &Proto::TransactionPlan) -> Vec<Amount> {
+    let mut utxos: Vec<_> = plan.inputs.iter().map(|utxo| utxo.value).collect();
+    utxos.sort();
+    utxos
+}
+
+const UTXOS: [Amount; 6] = [3_000, 20_000, 500, 4_000, 10_000, 1_000];
+
+#[test]
+fn test_plan_lazy_first_batch() {
+    let mut provider = TestProvider::new(UTXOS.to_vec(), 2);
+
+    let plan = plan_with_provider(vec![25_000], &mut provider).unwrap();
+    assert_eq!(selected_utxos(&plan), vec![10_000, 20_000]);
+    assert_eq!(plan.change, 30_000 - 25_000 - plan.fee_estimate);
+
+    // The largest UTXOs are enough, so the rest are not fetched.
+    assert_eq!(
+        provider.queries,
+        vec![UtxoQuery {
+            min_amount: DUST,
+            offset: 0,
+            limit: 2,
+        }]
+    );
+}
+
+#[test]
+fn test_plan_lazy_several_batches() {
+    let mut provider = TestProvider::new(UTXOS.to_vec(), 2);
+
+    let plan = plan_with_provider(vec![33_000], &mut provider).unwrap();
+    assert_eq!(selected_utxos(&plan), vec![3_000, 4_000, 10_000, 20_000]);
+
+    let offsets: Vec<_> = provider.queries.iter().map(|query| query.offset).collect();
+    assert_eq!(offsets, vec![0, 2]);
+}
+
+#[test]
+fn test_plan_lazy_not_enough_utxos() {
+    let mut provider = TestProvider::new(UTXOS.to_vec(), 2);
+
+    // The UTXO below the dust threshold is not requested, and the rest don't cover the fee.
+    let err = plan_with_provider(vec![38_000], &mut provider).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_not_enough_utxos);
+
+    let offsets: Vec<_> = provider.queries.iter().map(|query| query.offset).collect();
+    assert_eq!(offsets, vec![0, 2, 4]);
+}
+
+#[test]
+fn test_plan_lazy_amounts_overflow() {
+    // The sum of the output amounts overflows.
+    let mut provider = TestProvider::new(UTXOS.to_vec(), 2);
+    let err = plan_with_provider(vec![Amount::MAX, 1], &mut provider).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+
+    // The sum of the fetched UTXO amounts overflows.
+    let mut provider = TestProvider::new(vec![Amount::MAX, Amount::MAX], 2);
+    let err = plan_with_provider(vec![1_000], &mut provider).unwrap_err();
+    assert_eq!(*err.error_type(), SigningErrorType::Error_invalid_params);
+}
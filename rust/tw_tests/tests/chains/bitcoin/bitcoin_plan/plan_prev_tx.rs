@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{input, plan, TransactionOneof, DUST};
+use tw_any_coin::test_utils::plan_utils::AnyPlannerHelper;
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::DecodeHex;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+/// A transaction with two outputs: `OP_TRUE` of 5000 sats and P2PKH of 10000 sats,
+/// where P2PKH is claimed by the public key used in [`plan::make_planning_input`].
+const PREV_TX: &str = "02000000010000000000000000000000000000000000000000000000000000000000000000ffffffff00ffffffff028813000000000000015110270000000000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000";
+const PREV_TXID: &str = "a4eaec4df0522cad1b4f3f59f723db6d54560d3dcb4edf5f3f12724b14f8ac12";
+
+fn plan_with_prev_tx(txid: &str, vout: u32, value: i64) -> Proto::TransactionPlan<'static> {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![value],
+        outputs: vec![1_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        let utxo = &mut tx_builder.inputs[0];
+        utxo.out_point = input::out_point(txid, vout);
+        utxo.prev_tx = PREV_TX.decode_hex().unwrap().into();
+    }
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    planner.plan(CoinType::Bitcoin, input)
+}
+
+#[test]
+fn test_plan_with_prev_tx() {
+    let plan = plan_with_prev_tx(PREV_TXID, 1, 10_000);
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert_eq!(plan.available_amount, 10_000);
+    assert_eq!(plan.fee_estimate, 454);
+}
+
+#[test]
+fn test_plan_with_prev_tx_inflated_amount() {
+    let plan = plan_with_prev_tx(PREV_TXID, 1, 100_000);
+    assert_eq!(plan.error, SigningError::Error_invalid_utxo_amount);
+}
+
+#[test]
+fn test_plan_with_prev_tx_wrong_script_pubkey() {
+    // The output is `OP_TRUE` instead of the claimed P2PKH.
+    let plan = plan_with_prev_tx(PREV_TXID, 0, 5_000);
+    assert_eq!(plan.error, SigningError::Error_invalid_utxo);
+}
+
+#[test]
+fn test_plan_with_prev_tx_wrong_vout() {
+    let plan = plan_with_prev_tx(PREV_TXID, 2, 10_000);
+    assert_eq!(plan.error, SigningError::Error_invalid_utxo);
+}
+
+#[test]
+fn test_plan_with_prev_tx_wrong_txid() {
+    let plan = plan_with_prev_tx(
+        "06953f715201d085d0607097bc7571fcebe92bd4aa30038bd480a72357b28796",
+        1,
+        10_000,
+    );
+    assert_eq!(plan.error, SigningError::Error_invalid_utxo);
+}
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{input, output, plan, Amount, TransactionOneof, DUST};
+use tw_any_coin::test_utils::plan_utils::AnyPlannerHelper;
+use tw_coin_registry::coin_type::CoinType;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const OTHER_TXID: &str = "a4eaec4df0522cad1b4f3f59f723db6d54560d3dcb4edf5f3f12724b14f8ac12";
+
+/// Plans a transaction with the given version and unconfirmed inputs.
+/// `other_parent` - whether the second input is created by another transaction.
+fn plan_truc(
+    version: Proto::TransactionVersion,
+    inputs: Vec<Amount>,
+    output: Amount,
+    unconfirmed: bool,
+    other_parent: bool,
+) -> Proto::TransactionPlan<'static> {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs,
+        outputs: vec![output],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb: 2,
+    });
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.version = version;
+        for utxo in tx_builder.inputs.iter_mut() {
+            utxo.unconfirmed = unconfirmed;
+        }
+        if other_parent {
+            tx_builder.inputs[1].out_point = input::out_point(OTHER_TXID, 0);
+        }
+    }
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    planner.plan(CoinType::Bitcoin, input)
+}
+
+#[test]
+fn test_plan_truc_confirmed_parents() {
+    let plan = plan_truc(
+        Proto::TransactionVersion::V3,
+        vec![1_000, 3_000],
+        2_000,
+        false,
+        true,
+    );
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert!(plan.warnings.is_empty());
+}
+
+#[test]
+fn test_plan_truc_unconfirmed_parent() {
+    let plan = plan_truc(
+        Proto::TransactionVersion::V3,
+        vec![1_000, 3_000],
+        2_000,
+        true,
+        false,
+    );
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert_eq!(plan.warnings.len(), 1);
+}
+
+#[test]
+fn test_plan_truc_several_unconfirmed_parents() {
+    let plan = plan_truc(
+        Proto::TransactionVersion::V3,
+        vec![1_000, 3_000],
+        2_000,
+        true,
+        true,
+    );
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert_eq!(plan.warnings.len(), 2);
+}
+
+#[test]
+fn test_plan_truc_child_too_big() {
+    // 7 inputs are required: 10 + 7 * 149 + 2 * 34 = 1121 vbytes.
+    let plan = plan_truc(
+        Proto::TransactionVersion::V3,
+        vec![3_000; 8],
+        18_000,
+        true,
+        false,
+    );
+    assert_eq!(plan.error, SigningError::Error_tx_too_big);
+
+    // The same transaction is fine if the parent is confirmed.
+    let plan = plan_truc(
+        Proto::TransactionVersion::V3,
+        vec![3_000; 8],
+        18_000,
+        false,
+        false,
+    );
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert_eq!(plan.vsize_estimate, 1_121);
+}
+
+#[test]
+fn test_plan_non_truc_unconfirmed_parents() {
+    let plan = plan_truc(
+        Proto::TransactionVersion::V2,
+        vec![3_000; 8],
+        18_000,
+        true,
+        true,
+    );
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert!(plan.warnings.is_empty());
+}
+
+/// Plans a transaction with the given number of zero-value P2A outputs.
+fn plan_ephemeral_dust(
+    version: Proto::TransactionVersion,
+    fee_per_vb: Amount,
+    anchors: usize,
+) -> Proto::TransactionPlan<'static> {
+    let mut input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![1_000, 3_000],
+        outputs: vec![2_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::SelectAscending,
+        fee_per_vb,
+    });
+    if let TransactionOneof::builder(ref mut tx_builder) = input.transaction {
+        tx_builder.version = version;
+        for _ in 0..anchors {
+            tx_builder.outputs.push(Proto::Output {
+                value: 0,
+                to_recipient: output::p2a(),
+                ..Default::default()
+            });
+        }
+    }
+
+    let mut planner = AnyPlannerHelper::<Proto::TransactionPlan>::default();
+    planner.plan(CoinType::Bitcoin, input)
+}
+
+#[test]
+fn test_plan_truc_ephemeral_dust() {
+    let plan = plan_ephemeral_dust(Proto::TransactionVersion::V3, 0, 1);
+    assert_eq!(plan.error, SigningError::OK, "{}", plan.error_message);
+    assert_eq!(plan.fee_estimate, 0);
+    assert_eq!(plan.outputs[1].value, 0);
+    // The anchor must be spent by a child transaction.
+    assert_eq!(plan.warnings.len(), 1);
+    assert!(plan.warnings[0].contains("Output '1'"));
+}
+
+#[test]
+fn test_plan_truc_ephemeral_dust_error() {
+    // The transaction pays a fee.
+    let plan = plan_ephemeral_dust(Proto::TransactionVersion::V3, 2, 1);
+    assert_eq!(plan.error, SigningError::Error_dust_amount_requested);
+
+    // More than one dust output.
+    let plan = plan_ephemeral_dust(Proto::TransactionVersion::V3, 0, 2);
+    assert_eq!(plan.error, SigningError::Error_dust_amount_requested);
+
+    // Not a TRUC transaction.
+    let plan = plan_ephemeral_dust(Proto::TransactionVersion::V2, 0, 1);
+    assert_eq!(plan.error, SigningError::Error_dust_amount_requested);
+}
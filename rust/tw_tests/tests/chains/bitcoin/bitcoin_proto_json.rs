@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{btc_info, dust_threshold, sign, TransactionOneof, DUST};
+use std::collections::HashMap;
+use tw_coin_registry::coin_type::CoinType;
+use tw_proto::json::{from_json, to_json};
+use tw_proto::BitcoinV2::Proto;
+
+const SIGN_P2WPKH_SIGNING_INPUT: &str = include_str!("data/sign_p2wpkh_signing_input.json");
+
+#[test]
+fn test_bitcoin_signing_input_json_round_trip() {
+    let input = Proto::SigningInput {
+        private_keys: vec![vec![1_u8; 32].into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(Proto::TransactionBuilder {
+            version: Proto::TransactionVersion::V2,
+            input_selector: Proto::InputSelector::SelectDescending,
+            fee_per_vb: 10,
+            dust_policy: dust_threshold(DUST),
+            ..Proto::TransactionBuilder::default()
+        }),
+        ..Proto::SigningInput::default()
+    };
+
+    let json = to_json(&input).unwrap();
+    assert!(json.contains(r#""private_keys":["AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE="]"#));
+    assert!(json.contains(r#""input_selector":"SelectDescending""#));
+
+    let decoded: Proto::SigningInput = from_json(&json).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[test]
+fn test_bitcoin_signing_input_json_missing_fields() {
+    let json = r#"{"transaction":{"psbt":{"psbt":"cHNidP8="}}}"#;
+
+    let input: Proto::SigningInput = from_json(json).unwrap();
+    assert!(input.private_keys.is_empty());
+    assert_eq!(input.chain_info, None);
+    assert_eq!(
+        input.transaction,
+        TransactionOneof::psbt(Proto::Psbt {
+            psbt: b"psbt\xff".to_vec().into(),
+        })
+    );
+}
+
+/// The JSON representation differs from the canonical proto3 JSON mapping.
+/// Pin the differences so that they are not changed unintentionally.
+#[test]
+fn test_bitcoin_signing_input_json_non_canonical_mapping() {
+    let input = Proto::SigningInput {
+        transaction: TransactionOneof::builder(Proto::TransactionBuilder {
+            fee_per_vb: 10,
+            ..Proto::TransactionBuilder::default()
+        }),
+        ..Proto::SigningInput::default()
+    };
+
+    let json = to_json(&input).unwrap();
+    // Field names are kept in `snake_case`, 64-bit integers are numbers, oneofs are nested.
+    assert!(json.contains(r#""transaction":{"builder":{"#));
+    assert!(json.contains(r#""fee_per_vb":10,"#));
+    // Default values are not omitted.
+    assert!(json.contains(r#""private_keys":[]"#));
+
+    // Canonical `lowerCamelCase` names and 64-bit integers as strings are not supported.
+    let canonical = r#"{"transaction":{"builder":{"feePerVb":"10"}}}"#;
+    let decoded: Proto::SigningInput = from_json(canonical).unwrap();
+    assert_ne!(decoded, input);
+    let canonical = r#"{"transaction":{"builder":{"fee_per_vb":"10"}}}"#;
+    from_json::<Proto::SigningInput>(canonical).unwrap_err();
+}
+
+#[test]
+fn test_bitcoin_legacy_signing_input_json_bytes_map() {
+    use tw_proto::Bitcoin::Proto as LegacyProto;
+
+    let input = LegacyProto::SigningInput {
+        scripts: HashMap::from([("script_hash".into(), vec![0x51_u8, 0x52].into())]),
+        ..LegacyProto::SigningInput::default()
+    };
+
+    let json = to_json(&input).unwrap();
+    assert!(json.contains(r#""scripts":{"script_hash":"UVI="}"#));
+
+    let decoded: LegacyProto::SigningInput = from_json(&json).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[test]
+fn test_bitcoin_signing_output_json() {
+    let output = Proto::SigningOutput {
+        error: tw_proto::Common::Proto::SigningError::Error_not_enough_utxos,
+        error_message: "Not enough UTXOs".into(),
+        ..Proto::SigningOutput::default()
+    };
+
+    let json = to_json(&output).unwrap();
+    assert!(json.contains(r#""error":"Error_not_enough_utxos""#));
+    assert!(json.contains(r#""encoded":"""#));
+
+    let decoded: Proto::SigningOutput = from_json(&json).unwrap();
+    assert_eq!(decoded, output);
+}
+
+/// Same transaction as in `test_bitcoin_sign_p2wpkh_input_different_builders`,
+/// but the signing input is loaded from a JSON fixture.
+#[test]
+fn test_bitcoin_sign_p2wpkh_json_fixture() {
+    let signing: Proto::SigningInput = from_json(SIGN_P2WPKH_SIGNING_INPUT).unwrap();
+
+    // Successfully broadcasted: https://mempool.space/tx/5d6bf53576a54be4d92cd8abf58d28ecc9ea7956eaf970d24d6bfcb9fcfe9855
+    sign::BitcoinSignHelper::new(&signing)
+        .coin(CoinType::Bitcoin)
+        .sign(sign::Expected {
+            encoded: "010000000001027d4c6ade48ade606a8f9894222fb30f87b427c5861b0b7a293825ffc8bc1621f0100000000ffffffff8d0e94ed369601d13f32bd653071844dfe7139508d2e71d9c105d1faa58230b30100000000ffffffff01836d0000000000001600145360df8231ac5965147c9d90ca930a2aafb0523202483045022100f95f9ac5d39f4b47dcd8c86daaaeac86374258d9960f922333ba0d5fdaa15b7e0220761794672dc9fbd71398d608f72f5d21a0f6c1306c6b700ad0d82f747c221062012103a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c02483045022100eb6ba0dcc64af61b2186b7efdab1ff03784d585ee03437f9a53875e93429db080220015a268d308436d3564b83ceaed90bc7272ca164016298ea855d1936568002a7012103a11506993946e20ea82686b157bf08f944759f43d91af8d84650ee73a482431c00000000",
+            txid: "5d6bf53576a54be4d92cd8abf58d28ecc9ea7956eaf970d24d6bfcb9fcfe9855",
+            inputs: vec![4_863, 30_269],
+            outputs: vec![28_035],
+            vsize: 178,
+            weight: 710,
+            fee: 7_097,
+        });
+}
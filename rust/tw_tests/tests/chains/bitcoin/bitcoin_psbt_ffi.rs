@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{plan, DUST};
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_memory::test_utils::tw_data_helper::TWDataHelper;
+use tw_memory::test_utils::tw_data_vector_helper::TWDataVectorHelper;
+use tw_proto::BitcoinV2::Proto;
+use wallet_core_rs::ffi::bitcoin::psbt::{
+    tw_bitcoin_psbt_append, tw_bitcoin_psbt_combine, tw_bitcoin_psbt_create,
+    tw_bitcoin_psbt_create_v2, tw_bitcoin_psbt_finalize, tw_bitcoin_psbt_sign,
+};
+
+const PRIVATE_KEY: &str = "f00ffbe44c5c2838c13d2778854ac66b75e04eb6054f0241989e223223ad5e55";
+const UNSIGNED_PSBT: &str = "70736274ff0100bc0200000001147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c60000000000ffffffff0360ea000000000000160014f22a703617035ef7f490743d50f26ae08c30d0a70000000000000000426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a35303e12000000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d000000000001011f6603010000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d00000000";
+const SIGNED_PSBT: &str = "70736274ff0100bc0200000001147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c60000000000ffffffff0360ea000000000000160014f22a703617035ef7f490743d50f26ae08c30d0a70000000000000000426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a35303e12000000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d000000000001011f6603010000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d01086c02483045022100b1229a008f20691639767bf925d6b8956ea957ccc633ad6b5de3618733a55e6b02205774d3320489b8a57a6f8de07f561de3e660ff8e587f6ac5422c49020cd4dc9101210306d8c664ea8fd2683eebea1d3114d90e0a5429e5783ba49b80ddabce04ff28f300000000";
+/// [`UNSIGNED_PSBT`] as PSBTv2.
+const UNSIGNED_PSBT_V2: &str = "70736274ff0102040200000001030400000000010401010105010301fb04020000000001011f6603010000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d010e20147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c6010f0400000000011004ffffffff0001030860ea0000000000000104160014f22a703617035ef7f490743d50f26ae08c30d0a70001030800000000000000000104426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a3530000103083e120000000000000104160014b139199ec796f36fc42e637f42da8e3e6720aa9d00";
+/// [`SIGNED_PSBT`] as PSBTv2.
+const SIGNED_PSBT_V2: &str = "70736274ff0102040200000001030400000000010401010105010301fb04020000000001011f6603010000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d01086c02483045022100b1229a008f20691639767bf925d6b8956ea957ccc633ad6b5de3618733a55e6b02205774d3320489b8a57a6f8de07f561de3e660ff8e587f6ac5422c49020cd4dc9101210306d8c664ea8fd2683eebea1d3114d90e0a5429e5783ba49b80ddabce04ff28f3010e20147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c6010f0400000000011004ffffffff0001030860ea0000000000000104160014f22a703617035ef7f490743d50f26ae08c30d0a70001030800000000000000000104426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a3530000103083e120000000000000104160014b139199ec796f36fc42e637f42da8e3e6720aa9d00";
+const ENCODED_TX: &str = "02000000000101147010db5fbcf619067c1090fec65c131443fbc80fb4aaeebe940e44206098c60000000000ffffffff0360ea000000000000160014f22a703617035ef7f490743d50f26ae08c30d0a70000000000000000426a403d3a474149412e41544f4d3a636f736d6f7331737377797a666d743675396a373437773537753438746778646575393573757a666c6d7175753a303a743a35303e12000000000000160014b139199ec796f36fc42e637f42da8e3e6720aa9d02483045022100b1229a008f20691639767bf925d6b8956ea957ccc633ad6b5de3618733a55e6b02205774d3320489b8a57a6f8de07f561de3e660ff8e587f6ac5422c49020cd4dc9101210306d8c664ea8fd2683eebea1d3114d90e0a5429e5783ba49b80ddabce04ff28f300000000";
+
+#[test]
+fn test_bitcoin_psbt_sign_ffi() {
+    let psbt = TWDataHelper::create(UNSIGNED_PSBT.decode_hex().unwrap());
+    let private_keys = TWDataVectorHelper::create([PRIVATE_KEY.decode_hex().unwrap()]);
+
+    let signed_psbt = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_sign(
+            CoinType::Bitcoin as u32,
+            psbt.ptr(),
+            private_keys.ptr(),
+        ))
+        .to_vec()
+        .expect("Expected a signed PSBT")
+    };
+    assert_eq!(signed_psbt.to_hex(), SIGNED_PSBT);
+}
+
+#[test]
+fn test_bitcoin_psbt_finalize_ffi() {
+    let psbt = TWDataHelper::create(SIGNED_PSBT.decode_hex().unwrap());
+
+    let encoded = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_finalize(psbt.ptr()))
+            .to_vec()
+            .expect("Expected a final transaction")
+    };
+    assert_eq!(encoded.to_hex(), ENCODED_TX);
+}
+
+#[test]
+fn test_bitcoin_psbt_finalize_not_signed_ffi() {
+    let psbt = TWDataHelper::create(UNSIGNED_PSBT.decode_hex().unwrap());
+
+    let encoded = unsafe { TWDataHelper::wrap(tw_bitcoin_psbt_finalize(psbt.ptr())) };
+    assert!(encoded.is_null());
+}
+
+#[test]
+fn test_bitcoin_psbt_combine_ffi() {
+    let psbts = TWDataVectorHelper::create([
+        UNSIGNED_PSBT.decode_hex().unwrap(),
+        SIGNED_PSBT.decode_hex().unwrap(),
+    ]);
+
+    let combined = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_combine(psbts.ptr()))
+            .to_vec()
+            .expect("Expected a combined PSBT")
+    };
+    assert_eq!(combined.to_hex(), SIGNED_PSBT);
+}
+
+#[test]
+fn test_bitcoin_psbt_v2_sign_ffi() {
+    let psbt = TWDataHelper::create(UNSIGNED_PSBT_V2.decode_hex().unwrap());
+    let private_keys = TWDataVectorHelper::create([PRIVATE_KEY.decode_hex().unwrap()]);
+
+    // The signed PSBT has the same version.
+    let signed_psbt = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_sign(
+            CoinType::Bitcoin as u32,
+            psbt.ptr(),
+            private_keys.ptr(),
+        ))
+        .to_vec()
+        .expect("Expected a signed PSBT")
+    };
+    assert_eq!(signed_psbt.to_hex(), SIGNED_PSBT_V2);
+
+    let psbt = TWDataHelper::create(signed_psbt);
+    let encoded = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_finalize(psbt.ptr()))
+            .to_vec()
+            .expect("Expected a final transaction")
+    };
+    assert_eq!(encoded.to_hex(), ENCODED_TX);
+}
+
+#[test]
+fn test_bitcoin_psbt_v2_combine_ffi() {
+    let psbts = TWDataVectorHelper::create([
+        UNSIGNED_PSBT_V2.decode_hex().unwrap(),
+        SIGNED_PSBT.decode_hex().unwrap(),
+    ]);
+
+    // The combined PSBT has the version of the first one.
+    let combined = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_combine(psbts.ptr()))
+            .to_vec()
+            .expect("Expected a combined PSBT")
+    };
+    assert_eq!(combined.to_hex(), SIGNED_PSBT_V2);
+}
+
+#[test]
+fn test_bitcoin_psbt_v2_append_not_modifiable_ffi() {
+    // `PSBT_GLOBAL_TX_MODIFIABLE` is not set.
+    let psbt = TWDataHelper::create(UNSIGNED_PSBT_V2.decode_hex().unwrap());
+    let addition = TWDataHelper::create(UNSIGNED_PSBT_V2.decode_hex().unwrap());
+
+    let appended =
+        unsafe { TWDataHelper::wrap(tw_bitcoin_psbt_append(psbt.ptr(), addition.ptr())) };
+    assert!(appended.is_null());
+}
+
+#[test]
+fn test_bitcoin_psbt_create_ffi() {
+    let input = plan::make_planning_input(plan::PlanArgs {
+        inputs: vec![10_000],
+        outputs: vec![5_000],
+        change: true,
+        max: false,
+        dust_threshold: DUST,
+        order: Proto::InputSelector::UseAll,
+        fee_per_vb: 2,
+    });
+    let input = TWDataHelper::create(tw_proto::serialize(&input).unwrap());
+
+    let psbt = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_create(
+            CoinType::Bitcoin as u32,
+            input.ptr(),
+        ))
+        .to_vec()
+        .expect("Expected an unsigned PSBT")
+    };
+    assert!(psbt.to_hex().starts_with("70736274ff"));
+
+    // Only Bitcoin-like coins are supported.
+    for coin in [CoinType::Ethereum, CoinType::BitcoinCash] {
+        let psbt = unsafe { TWDataHelper::wrap(tw_bitcoin_psbt_create(coin as u32, input.ptr())) };
+        assert!(psbt.is_null());
+        let psbt =
+            unsafe { TWDataHelper::wrap(tw_bitcoin_psbt_create_v2(coin as u32, input.ptr())) };
+        assert!(psbt.is_null());
+    }
+}
+
+#[test]
+fn test_bitcoin_psbt_sign_unsupported_coin_ffi() {
+    let psbt = TWDataHelper::create(UNSIGNED_PSBT.decode_hex().unwrap());
+    let private_keys = TWDataVectorHelper::create([PRIVATE_KEY.decode_hex().unwrap()]);
+
+    let signed_psbt = unsafe {
+        TWDataHelper::wrap(tw_bitcoin_psbt_sign(
+            CoinType::Ethereum as u32,
+            psbt.ptr(),
+            private_keys.ptr(),
+        ))
+    };
+    assert!(signed_psbt.is_null());
+}
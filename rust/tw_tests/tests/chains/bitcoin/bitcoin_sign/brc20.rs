@@ -26,12 +26,14 @@ fn test_bitcoin_sign_brc20_commit() {
     let out1 = Proto::Output {
         value: 7_000,
         to_recipient: output::brc20_inscribe(alice_pubkey.to_vec(), "oadf", "20"),
+        ..Default::default()
     };
 
     // Change/return transaction.
     let out2 = Proto::Output {
         value: 16_400,
         to_recipient: output::p2wpkh(alice_pubkey.to_vec()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -83,6 +85,7 @@ fn test_bitcoin_sign_brc20_reveal() {
     let out1 = Proto::Output {
         value: DUST,
         to_recipient: output::p2wpkh(alice_pubkey.to_vec()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -157,11 +160,13 @@ fn test_bitcoin_sign_brc20_transfer() {
     let out1 = Proto::Output {
         value: DUST,
         to_recipient: output::to_address(bob_address),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
         value: 13_400,
         to_recipient: output::p2wpkh(alice_pubkey.to_vec()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::ToHex;
+use tw_keypair::ecdsa;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const RECIPIENT: &str = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+/// BIP-84 test vector account `m/84'/0'/0'`.
+const BIP84_ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+/// BIP-86 test vector account `m/86'/0'/0'`.
+const BIP86_XPUB: &str = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+fn make_signing_input(
+    change_xpub: Proto::ChangeXpub<'static>,
+    change_output: Option<Proto::Output<'static>>,
+) -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![Proto::Output {
+            value: 20_000,
+            to_recipient: output::to_address(RECIPIENT),
+            ..Default::default()
+        }],
+        change_output,
+        change_xpub: Some(change_xpub),
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+fn sign_change_script(change_xpub: Proto::ChangeXpub<'static>) -> String {
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(change_xpub, None));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    let tx = output.transaction.unwrap();
+    assert_eq!(tx.outputs.len(), 2);
+    assert_eq!(tx.outputs[1].value, 30_269 - 20_000 - output.fee);
+    tx.outputs[1].script_pubkey.to_hex()
+}
+
+#[test]
+fn test_bitcoin_sign_change_xpub_bip84() {
+    let change_xpub = Proto::ChangeXpub {
+        xpub: BIP84_ZPUB.into(),
+        index: 0,
+        scheme: Proto::DerivationScheme::BIP84,
+        ..Default::default()
+    };
+    // bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el
+    assert_eq!(
+        sign_change_script(change_xpub),
+        "00143e34985dca6fddc9fb369940e4c7d8e2873f529c"
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_change_xpub_bip86() {
+    let change_xpub = Proto::ChangeXpub {
+        xpub: BIP86_XPUB.into(),
+        index: 0,
+        scheme: Proto::DerivationScheme::BIP86,
+        ..Default::default()
+    };
+    // bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7
+    assert_eq!(
+        sign_change_script(change_xpub),
+        "5120882d74e5d0572d5a816cef0041a96b6c1de832f6f9676d9605c44d5e9a97d3dc"
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_change_xpub_script_type() {
+    // The change is sent to a P2TR output, although the account is BIP-84.
+    let change_xpub = Proto::ChangeXpub {
+        xpub: BIP84_ZPUB.into(),
+        index: 0,
+        scheme: Proto::DerivationScheme::BIP84,
+        script_type: Proto::ChangeScriptType::ChangeP2TR,
+    };
+    assert_eq!(
+        sign_change_script(change_xpub),
+        "512066313993cf680f7bb7870666f65b199bbf73240c4045c05fc557eff26251b0b9"
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_change_xpub_error() {
+    let change_xpub = Proto::ChangeXpub {
+        xpub: BIP84_ZPUB.into(),
+        index: 0,
+        scheme: Proto::DerivationScheme::BIP84,
+        ..Default::default()
+    };
+    let change_output = Proto::Output {
+        value: 0,
+        to_recipient: output::to_address(RECIPIENT),
+        ..Default::default()
+    };
+
+    // Either `change_output` or `change_xpub` should be set.
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(
+        CoinType::Bitcoin,
+        make_signing_input(change_xpub.clone(), Some(change_output)),
+    );
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+
+    // Hardened indexes cannot be derived from an xpub.
+    let hardened = Proto::ChangeXpub {
+        index: 0x8000_0000,
+        ..change_xpub
+    };
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(hardened, None));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
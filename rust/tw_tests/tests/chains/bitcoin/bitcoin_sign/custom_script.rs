@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::{AnySignerHelper, CompilerHelper, PreImageHelper};
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::ToHex;
+use tw_hash::hasher::sha256_ripemd;
+use tw_hash::sha2::sha256;
+use tw_hash::H256;
+use tw_keypair::ecdsa;
+use tw_keypair::traits::SigningKeyTrait;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+use Proto::mod_Input::mod_TemplateItem::OneOfitem as TemplateItemType;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+
+fn data(data: Vec<u8>) -> Proto::mod_Input::TemplateItem<'static> {
+    Proto::mod_Input::TemplateItem {
+        item: TemplateItemType::data(data.into()),
+    }
+}
+
+fn signature() -> Proto::mod_Input::TemplateItem<'static> {
+    Proto::mod_Input::TemplateItem {
+        item: TemplateItemType::signature(true),
+    }
+}
+
+/// `<pubkey> OP_CHECKSIG`
+fn checksig_script(private_key: &ecdsa::secp256k1::PrivateKey) -> Vec<u8> {
+    let mut script = vec![0x21];
+    script.extend_from_slice(private_key.public().compressed().as_slice());
+    script.push(0xac);
+    script
+}
+
+fn make_signing_input(
+    private_key: &ecdsa::secp256k1::PrivateKey,
+    custom: Proto::mod_Input::CustomInput<'static>,
+) -> Proto::SigningInput<'static> {
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::custom(custom),
+        ..Default::default()
+    };
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![Proto::Output {
+            value: 20_000,
+            to_recipient: output::p2wpkh(private_key.public().to_vec()),
+            ..Default::default()
+        }],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_custom_p2wsh_witness_template() {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let witness_script = checksig_script(&private_key);
+    let mut script_pubkey = vec![0x00, 0x20];
+    script_pubkey.extend_from_slice(&sha256(&witness_script));
+
+    let custom = Proto::mod_Input::CustomInput {
+        script_pubkey: script_pubkey.into(),
+        script_code: witness_script.clone().into(),
+        public_key: private_key.public().compressed().to_vec().into(),
+        segwit: true,
+        witness: vec![signature(), data(witness_script.clone())],
+        ..Default::default()
+    };
+    let signing = make_signing_input(&private_key, custom);
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let signed = signer.sign(CoinType::Bitcoin, signing.clone());
+    assert_eq!(signed.error, SigningError::OK, "{}", signed.error_message);
+
+    // The signature is placed at the first witness stack position.
+    let tx = signed.transaction.as_ref().unwrap();
+    assert!(tx.inputs[0].script_sig.is_empty());
+    let witness = &tx.inputs[0].witness_items;
+    assert_eq!(witness.len(), 2);
+    // DER signature followed by the `SIGHASH_ALL` type.
+    assert_eq!(witness[0].last(), Some(&0x01));
+    assert_eq!(witness[1].to_vec(), witness_script);
+
+    // The compiled transaction is the same as the signed one.
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &signing);
+    assert_eq!(
+        preimage.error,
+        SigningError::OK,
+        "{}",
+        preimage.error_message
+    );
+    let sighash = H256::try_from(preimage.sighashes[0].sighash.as_ref()).unwrap();
+    let sig = private_key.sign(sighash).unwrap();
+
+    let mut compiler = CompilerHelper::<Proto::SigningOutput>::default();
+    let compiled = compiler.compile(
+        CoinType::Bitcoin,
+        &signing,
+        vec![sig.to_vec()],
+        vec![private_key.public().compressed().to_vec()],
+    );
+    assert_eq!(
+        compiled.error,
+        SigningError::OK,
+        "{}",
+        compiled.error_message
+    );
+    assert_eq!(compiled.encoded.to_hex(), signed.encoded.to_hex());
+}
+
+#[test]
+fn test_bitcoin_sign_custom_p2sh_script_sig_template() {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    // 1-of-1 multisig: `OP_1 <pubkey> OP_1 OP_CHECKMULTISIG`.
+    let mut redeem_script = vec![0x51, 0x21];
+    redeem_script.extend_from_slice(private_key.public().compressed().as_slice());
+    redeem_script.extend_from_slice(&[0x51, 0xae]);
+    let mut script_pubkey = vec![0xa9, 0x14];
+    script_pubkey.extend_from_slice(&sha256_ripemd(&redeem_script));
+    script_pubkey.push(0x87);
+
+    let custom = Proto::mod_Input::CustomInput {
+        script_pubkey: script_pubkey.into(),
+        script_code: redeem_script.clone().into(),
+        public_key: private_key.public().compressed().to_vec().into(),
+        segwit: false,
+        // `OP_0 <signature> <redeem_script>`, where `OP_0` is the `CHECKMULTISIG` dummy element.
+        script_sig: vec![
+            data(Vec::default()),
+            signature(),
+            data(redeem_script.clone()),
+        ],
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let signed = signer.sign(CoinType::Bitcoin, make_signing_input(&private_key, custom));
+    assert_eq!(signed.error, SigningError::OK, "{}", signed.error_message);
+
+    let tx = signed.transaction.unwrap();
+    assert!(tx.inputs[0].witness_items.is_empty());
+    let script_sig = tx.inputs[0].script_sig.to_hex();
+    assert!(script_sig.starts_with("00"));
+    assert!(script_sig.ends_with(&format!("25{}", redeem_script.to_hex())));
+}
+
+#[test]
+fn test_bitcoin_sign_custom_no_signature_placeholder() {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let witness_script = checksig_script(&private_key);
+    let mut script_pubkey = vec![0x00, 0x20];
+    script_pubkey.extend_from_slice(&sha256(&witness_script));
+
+    let custom = Proto::mod_Input::CustomInput {
+        script_pubkey: script_pubkey.into(),
+        script_code: witness_script.clone().into(),
+        public_key: private_key.public().compressed().to_vec().into(),
+        segwit: true,
+        witness: vec![data(witness_script)],
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(&private_key, custom));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
+
+#[test]
+fn test_bitcoin_sign_custom_non_standard_witness() {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let witness_script = checksig_script(&private_key);
+    let mut script_pubkey = vec![0x00, 0x20];
+    script_pubkey.extend_from_slice(&sha256(&witness_script));
+
+    // P2WSH witness stack items cannot exceed 80 bytes by the standardness policy.
+    let custom = Proto::mod_Input::CustomInput {
+        script_pubkey: script_pubkey.into(),
+        script_code: witness_script.clone().into(),
+        public_key: private_key.public().compressed().to_vec().into(),
+        segwit: true,
+        witness: vec![signature(), data(vec![0; 81]), data(witness_script)],
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(&private_key, custom));
+    assert_eq!(output.error, SigningError::Error_script_witness_program);
+}
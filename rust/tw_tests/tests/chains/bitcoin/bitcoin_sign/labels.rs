@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::ToHex;
+use tw_keypair::ecdsa;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const MY_ADDRESS: &str = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+
+fn make_signing_input(
+    labels: Option<Proto::LabelsRequest<'static>>,
+) -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 20_000,
+        to_recipient: output::to_address("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp"),
+        ..Default::default()
+    };
+    let out_1 = Proto::Output {
+        value: 5_000,
+        to_recipient: output::to_address(
+            "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf",
+        ),
+        ..Default::default()
+    };
+    let change_output = Proto::Output {
+        value: 0,
+        to_recipient: output::to_address(MY_ADDRESS),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![out_0, out_1],
+        change_output: Some(change_output),
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        labels,
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_export_labels() {
+    let labels = Proto::LabelsRequest {
+        tx_label: "Rent".into(),
+        // The second output is not labeled.
+        output_labels: vec!["Landlord".into()],
+        change_label: "Change \"rent\"".into(),
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(Some(labels)));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    let txid = output.txid.to_hex();
+    let expected = [
+        format!(r#"{{"type":"tx","ref":"{txid}","label":"Rent"}}"#),
+        format!(r#"{{"type":"output","ref":"{txid}:0","label":"Landlord"}}"#),
+        format!(r#"{{"type":"output","ref":"{txid}:1"}}"#),
+        format!(
+            r#"{{"type":"output","ref":"{txid}:2","label":"Change \"rent\"","spendable":true}}"#
+        ),
+        format!(r#"{{"type":"addr","ref":"{MY_ADDRESS}","label":"Change \"rent\""}}"#),
+    ]
+    .join("\n");
+    assert_eq!(output.labels, expected);
+
+    // Labels don't affect the signed transaction.
+    let without_labels = signer.sign(CoinType::Bitcoin, make_signing_input(None));
+    assert_eq!(output.encoded, without_labels.encoded);
+    assert!(without_labels.labels.is_empty());
+}
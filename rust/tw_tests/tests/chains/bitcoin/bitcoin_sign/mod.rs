@@ -3,14 +3,24 @@
 // Copyright © 2017 Trust Wallet.
 
 mod brc20;
+mod change_xpub;
+mod custom_script;
+mod labels;
+mod multisig;
 mod op_return;
 mod ordinal_nft;
 mod p2pkh;
 mod p2sh;
+mod p2tr_contract;
 mod p2tr_key_path;
 mod p2tr_script_path;
 mod p2wpkh;
 mod p2wsh;
+mod partial;
 mod psbt;
 mod send_to_address;
 mod sighash_single;
+mod signing_policy;
+mod silent_payments;
+mod tx_chaining;
+mod vault;
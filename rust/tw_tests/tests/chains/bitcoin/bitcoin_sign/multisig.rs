@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_keypair::ecdsa;
+use tw_memory::Data;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+type OutputBuilderType = Proto::mod_Output::mod_OutputBuilder::OneOfvariant<'static>;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+
+// BIP-67 test vector.
+const COSIGNER_1: &str = "02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8";
+const COSIGNER_2: &str = "02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f";
+const P2SH_ADDRESS: &str = "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z";
+const P2WSH_ADDRESS: &str = "bc1qknwt9mhqpd7hrjrvpqz57zjqk28xlp2h90te6v22en0m3uctnams3pq5ce";
+
+fn cosigners(keys: &[&str]) -> Vec<Data> {
+    keys.iter().map(|key| key.decode_hex().unwrap()).collect()
+}
+
+fn sign_outputs(
+    recipients: Vec<Proto::mod_Output::OneOfto_recipient<'static>>,
+) -> Proto::SigningOutput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: recipients
+            .into_iter()
+            .map(|to_recipient| Proto::Output {
+                value: 2_000,
+                to_recipient,
+                ..Default::default()
+            })
+            .collect(),
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: output::p2wpkh(private_key.public().to_vec()),
+            ..Default::default()
+        }),
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    signer.sign(CoinType::Bitcoin, signing)
+}
+
+fn script_pubkeys(output: &Proto::SigningOutput) -> Vec<String> {
+    let tx = output.transaction.as_ref().unwrap();
+    tx.outputs
+        .iter()
+        .map(|out| out.script_pubkey.to_hex())
+        .collect()
+}
+
+#[test]
+fn test_bitcoin_sign_output_sorted_multisig() {
+    // Cosigners configured the keys in a different order.
+    let keys = cosigners(&[COSIGNER_1, COSIGNER_2]);
+    let reversed_keys = cosigners(&[COSIGNER_2, COSIGNER_1]);
+
+    let output = sign_outputs(vec![
+        output::receiver_builder(OutputBuilderType::p2sh(output::multisig(
+            2,
+            keys.clone(),
+            true,
+        ))),
+        output::receiver_builder(OutputBuilderType::p2sh(output::multisig(
+            2,
+            reversed_keys.clone(),
+            true,
+        ))),
+        output::to_address(P2SH_ADDRESS),
+        output::receiver_builder(OutputBuilderType::p2wsh(output::multisig(2, keys, true))),
+        output::receiver_builder(OutputBuilderType::p2wsh(output::multisig(
+            2,
+            reversed_keys,
+            true,
+        ))),
+        output::to_address(P2WSH_ADDRESS),
+    ]);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    let scripts = script_pubkeys(&output);
+    let p2sh = "a91456be8ea93912f37685542a2a864a5600f88a675487";
+    let p2wsh = "0020b4dcb2eee00b7d71c86c08054f0a40b28e6f85572bd79d314accdfb8f30b9f77";
+    assert_eq!(scripts[..6], [p2sh, p2sh, p2sh, p2wsh, p2wsh, p2wsh]);
+}
+
+#[test]
+fn test_bitcoin_sign_output_unsorted_multisig() {
+    let output = sign_outputs(vec![
+        output::receiver_builder(OutputBuilderType::p2wsh(output::multisig(
+            2,
+            cosigners(&[COSIGNER_1, COSIGNER_2]),
+            false,
+        ))),
+        output::receiver_builder(OutputBuilderType::p2wsh(output::multisig(
+            2,
+            cosigners(&[COSIGNER_2, COSIGNER_1]),
+            false,
+        ))),
+    ]);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // `multi()` keeps the keys order, so only the sorted order matches the BIP-67 address.
+    let scripts = script_pubkeys(&output);
+    assert_ne!(scripts[0], scripts[1]);
+    assert_eq!(
+        scripts[1],
+        "0020b4dcb2eee00b7d71c86c08054f0a40b28e6f85572bd79d314accdfb8f30b9f77"
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_output_multisig_invalid_threshold() {
+    let output = sign_outputs(vec![output::receiver_builder(OutputBuilderType::p2wsh(
+        output::multisig(3, cosigners(&[COSIGNER_1, COSIGNER_2]), true),
+    ))]);
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
@@ -5,9 +5,11 @@
 use crate::chains::common::bitcoin::{
     dust_threshold, input, output, sign, TransactionOneof, DUST, SIGHASH_ALL,
 };
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
-use tw_encoding::hex::DecodeHex;
+use tw_encoding::hex::{DecodeHex, ToHex};
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 
 /// Deposit 0.0001 BTC from bc1q2sphzvc2uqmxqte2w9dd4gzy4sy9vvfv0me9ke to 0xa8491D40d4F71A752cA41DA0516AEd80c33a1B56 on ZETA mainnet.
 /// https://www.zetachain.com/docs/developers/omnichain/bitcoin/#example-1-deposit-btc-into-an-account-in-zevm
@@ -38,16 +40,19 @@ fn test_bitcoin_deposit_to_zetachain() {
     let out_0 = Proto::Output {
         value: 10_000,
         to_recipient: output::to_address(zeta_tss_address),
+        ..Default::default()
     };
     // OP_RETURN must be the second output after the actual amount and before the change.
     let out_1 = Proto::Output {
         value: 0,
         // Pass my ZetaChain EVM address bytes (20) as the OP_RETURN.
         to_recipient: output::op_return(my_evm_zetachain_address.decode_hex().unwrap()),
+        ..Default::default()
     };
     let explicit_change_output = Proto::Output {
         value: 7_420,
         to_recipient: output::to_address(my_address),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -81,3 +86,137 @@ fn test_bitcoin_deposit_to_zetachain() {
             fee: 2580,
         });
 }
+
+#[test]
+fn test_bitcoin_sign_validate_custom_script_pubkey() {
+    let my_private_key = "428d66be0b5a620f126a00fa67637222ce3dc9badfe5c605189520760810cfac"
+        .decode_hex()
+        .unwrap();
+    let my_address = "bc1q2sphzvc2uqmxqte2w9dd4gzy4sy9vvfv0me9ke";
+    let utxo_hash_0 = "17a6adb5db1e33c87467a58aa31cddbb3800052315015cf3cf1c2b0119310e20";
+
+    let make_signing = |out_0: Proto::Output<'static>, validate_custom_scripts: bool| {
+        let utxo_0 = Proto::Input {
+            out_point: input::out_point(utxo_hash_0, 0),
+            value: 20_000,
+            sighash_type: SIGHASH_ALL,
+            claiming_script: input::receiver_address(my_address),
+            ..Default::default()
+        };
+        let builder = Proto::TransactionBuilder {
+            inputs: vec![utxo_0],
+            outputs: vec![out_0],
+            input_selector: Proto::InputSelector::UseAll,
+            dust_policy: dust_threshold(DUST),
+            fee_per_vb: 1,
+            validate_custom_scripts,
+            ..Default::default()
+        };
+        Proto::SigningInput {
+            private_keys: vec![my_private_key.clone().into()],
+            transaction: TransactionOneof::builder(builder),
+            ..Default::default()
+        }
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+
+    // P2WPKH scriptPubkey passed as a raw script.
+    let p2wpkh = Proto::Output {
+        value: 10_000,
+        to_recipient: output::custom_script_pubkey(
+            "0014daaae0d3de9d8fdee31661e61aea828b59be7864"
+                .decode_hex()
+                .unwrap(),
+        ),
+        ..Default::default()
+    };
+    let output = signer.sign(CoinType::Bitcoin, make_signing(p2wpkh, true));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // `OP_1 OP_1 OP_ADD` is a non-standard scriptPubkey.
+    let non_standard = Proto::Output {
+        value: 10_000,
+        to_recipient: output::custom_script_pubkey("515193".decode_hex().unwrap()),
+        ..Default::default()
+    };
+    // Non-standard scripts are allowed unless the validation is enabled.
+    let output = signer.sign(CoinType::Bitcoin, make_signing(non_standard.clone(), false));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    let output = signer.sign(CoinType::Bitcoin, make_signing(non_standard, true));
+    assert_eq!(output.error, SigningError::Error_script_output);
+
+    // OP_RETURN output that burns funds.
+    let op_return_with_value = Proto::Output {
+        value: 10_000,
+        to_recipient: output::custom_script_pubkey("6a03010203".decode_hex().unwrap()),
+        ..Default::default()
+    };
+    let output = signer.sign(CoinType::Bitcoin, make_signing(op_return_with_value, true));
+    assert_eq!(output.error, SigningError::Error_script_output);
+}
+
+#[test]
+fn test_bitcoin_sign_op_return_pushes() {
+    let my_private_key = "428d66be0b5a620f126a00fa67637222ce3dc9badfe5c605189520760810cfac"
+        .decode_hex()
+        .unwrap();
+    let my_address = "bc1q2sphzvc2uqmxqte2w9dd4gzy4sy9vvfv0me9ke";
+    let utxo_hash_0 = "17a6adb5db1e33c87467a58aa31cddbb3800052315015cf3cf1c2b0119310e20";
+
+    let make_signing = |pushes: Vec<Vec<u8>>| {
+        let utxo_0 = Proto::Input {
+            out_point: input::out_point(utxo_hash_0, 0),
+            value: 20_000,
+            sighash_type: SIGHASH_ALL,
+            claiming_script: input::receiver_address(my_address),
+            ..Default::default()
+        };
+        let out_0 = Proto::Output {
+            value: 10_000,
+            to_recipient: output::to_address(my_address),
+            ..Default::default()
+        };
+        let out_1 = Proto::Output {
+            value: 0,
+            to_recipient: output::op_return_pushes(pushes),
+            ..Default::default()
+        };
+        let builder = Proto::TransactionBuilder {
+            inputs: vec![utxo_0],
+            outputs: vec![out_0, out_1],
+            input_selector: Proto::InputSelector::UseAll,
+            dust_policy: dust_threshold(DUST),
+            fee_per_vb: 1,
+            ..Default::default()
+        };
+        Proto::SigningInput {
+            private_keys: vec![my_private_key.clone().into()],
+            transaction: TransactionOneof::builder(builder),
+            ..Default::default()
+        }
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+
+    // Each push is encoded separately: `OP_RETURN <0x0102> <20 bytes>`.
+    let evm_address = "a8491d40d4f71a752ca41da0516aed80c33a1b56";
+    let pushes = vec![vec![1, 2], evm_address.decode_hex().unwrap()];
+    let output = signer.sign(CoinType::Bitcoin, make_signing(pushes));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    let tx = output.transaction.unwrap();
+    assert_eq!(
+        tx.outputs[1].script_pubkey.to_hex(),
+        format!("6a02010214{evm_address}")
+    );
+
+    // The whole scriptPubkey exceeds 83 bytes: 1 + (1 + 40) + (1 + 41).
+    let output = signer.sign(
+        CoinType::Bitcoin,
+        make_signing(vec![vec![0; 40], vec![0; 41]]),
+    );
+    assert_eq!(output.error, SigningError::Error_invalid_memo);
+
+    let output = signer.sign(CoinType::Bitcoin, make_signing(Vec::default()));
+    assert_eq!(output.error, SigningError::Error_invalid_memo);
+}
@@ -2,12 +2,14 @@ use crate::chains::common::bitcoin::{
     btc_info, dust_threshold, input, output, plan, sign, TransactionOneof, BITCOIN_P2PKH_PREFIX,
     DUST, MINER_FEE, ONE_BTC, SIGHASH_ALL,
 };
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
 use tw_hash::hasher::sha256_ripemd;
 use tw_keypair::ecdsa;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 use tw_utxo::address::legacy::LegacyAddress;
 
 enum P2PKHClaimingScriptType {
@@ -53,6 +55,7 @@ fn test_bitcoin_sign_input_p2pkh(utxo_owner: P2PKHClaimingScriptType) {
     let out1 = Proto::Output {
         value: ONE_BTC * 50 - MINER_FEE,
         to_recipient: output::p2pkh(bob_pubkey.clone()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -114,3 +117,135 @@ fn test_bitcoin_sign_input_p2pkh_public_key_hash() {
 fn test_bitcoin_sign_input_p2pkh_address() {
     test_bitcoin_sign_input_p2pkh(P2PKHClaimingScriptType::P2PKHAddress);
 }
+
+/// Spending UTXOs locked to an uncompressed public key, e.g. imported from an old paper wallet.
+#[test]
+fn test_bitcoin_sign_input_p2pkh_uncompressed_public_key() {
+    const ALICE_PRIVATE_KEY: &str =
+        "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+
+    let alice_private_key = ecdsa::secp256k1::PrivateKey::try_from(ALICE_PRIVATE_KEY).unwrap();
+    let alice_pubkey = alice_private_key.public();
+    let alice_uncompressed = alice_pubkey.uncompressed().to_vec();
+    let alice_address =
+        LegacyAddress::p2pkh_with_uncompressed_public_key(BITCOIN_P2PKH_PREFIX, &alice_pubkey)
+            .unwrap();
+    let bob_pubkey = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf"
+        .decode_hex()
+        .unwrap();
+
+    let txid = "1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b";
+    let claiming_scripts = [
+        input::p2pkh(alice_uncompressed.clone()),
+        input::p2pkh_with_hash(sha256_ripemd(&alice_uncompressed)),
+        input::receiver_address(&alice_address.to_string()),
+    ];
+
+    for claiming_script in claiming_scripts {
+        let tx1 = Proto::Input {
+            out_point: input::out_point(txid, 0),
+            value: ONE_BTC * 50,
+            sighash_type: SIGHASH_ALL,
+            claiming_script,
+            ..Default::default()
+        };
+
+        let out1 = Proto::Output {
+            value: ONE_BTC * 50 - MINER_FEE,
+            to_recipient: output::p2pkh(bob_pubkey.clone()),
+            ..Default::default()
+        };
+
+        let builder = Proto::TransactionBuilder {
+            version: Proto::TransactionVersion::V2,
+            inputs: vec![tx1],
+            outputs: vec![out1],
+            input_selector: Proto::InputSelector::UseAll,
+            dust_policy: dust_threshold(DUST),
+            ..Default::default()
+        };
+
+        let signing = Proto::SigningInput {
+            private_keys: vec![ALICE_PRIVATE_KEY.decode_hex().unwrap().into()],
+            chain_info: btc_info(),
+            transaction: TransactionOneof::builder(builder),
+            ..Default::default()
+        };
+
+        let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+        let output = signer.sign(CoinType::Bitcoin, signing);
+        assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+        // The scriptSig must contain the uncompressed public key: `<push><sig><push><pubkey>`.
+        let script_sig = &output.transaction.as_ref().unwrap().inputs[0].script_sig;
+        assert!(script_sig.ends_with(&alice_uncompressed));
+        assert_eq!(
+            script_sig[script_sig.len() - alice_uncompressed.len() - 1],
+            65
+        );
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_output_p2pk() {
+    const ALICE_PRIVATE_KEY: &str =
+        "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+
+    let alice_private_key = ecdsa::secp256k1::PrivateKey::try_from(ALICE_PRIVATE_KEY).unwrap();
+    let alice_pubkey = alice_private_key.public();
+
+    let txid = "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911";
+    let tx1 = Proto::Input {
+        out_point: input::out_point(txid, 0),
+        value: ONE_BTC,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2pkh(alice_pubkey.to_vec()),
+        ..Default::default()
+    };
+    // Compressed and uncompressed public keys are kept as is.
+    let out1 = Proto::Output {
+        value: ONE_BTC / 2,
+        to_recipient: output::p2pk(alice_pubkey.compressed().to_vec()),
+        ..Default::default()
+    };
+    let out2 = Proto::Output {
+        value: ONE_BTC / 2 - MINER_FEE,
+        to_recipient: output::p2pk(alice_pubkey.uncompressed().to_vec()),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![tx1],
+        outputs: vec![out1, out2],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        ..Default::default()
+    };
+    let signing = Proto::SigningInput {
+        private_keys: vec![ALICE_PRIVATE_KEY.decode_hex().unwrap().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    let tx = output.transaction.unwrap();
+    let mut expected_compressed = vec![0x21];
+    expected_compressed.extend_from_slice(alice_pubkey.compressed().as_slice());
+    expected_compressed.push(0xac);
+    assert_eq!(tx.outputs[0].script_pubkey, expected_compressed);
+
+    let mut expected_uncompressed = vec![0x41];
+    expected_uncompressed.extend_from_slice(alice_pubkey.uncompressed().as_slice());
+    expected_uncompressed.push(0xac);
+    assert_eq!(tx.outputs[1].script_pubkey, expected_uncompressed);
+
+    // Bare P2PK outputs are flagged as non-standard under some relay policies.
+    assert_eq!(output.warnings.len(), 2);
+    assert!(output.warnings[0].contains("Output '0'"));
+    assert!(output.warnings[1].contains("Output '1'"));
+}
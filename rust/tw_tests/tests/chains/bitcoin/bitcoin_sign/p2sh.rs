@@ -62,6 +62,7 @@ fn test_bitcoin_sign_input_p2pkh_output_p2sh(recipient_type: P2SHRecipientType)
     let out0 = Proto::Output {
         value: 50 * ONE_BTC - MINER_FEE,
         to_recipient,
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::{AnySignerHelper, PreImageHelper};
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_hash::hasher::tapsighash;
+use tw_hash::sha2::sha256;
+use tw_hash::H256;
+use tw_keypair::traits::VerifyingKeyTrait;
+use tw_keypair::{ecdsa, schnorr};
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const ECDSA_PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const SCHNORR_PRIVATE_KEY: &str =
+    "ba0828d5734b65e3bcc2c51c93dfc26dd71bd666cc0273adee77d73d9a322035";
+/// The output key of the `SCHNORR_PRIVATE_KEY` internal key committed to `sha256("hello")`.
+const CONTRACT_OUTPUT_KEY: &str =
+    "2708a54f1a8444a1c24c6aad36a644bc72ee818e0edf12174f6578b9c4b17890";
+
+fn commitment() -> Vec<u8> {
+    sha256(b"hello")
+}
+
+fn make_signing_input(
+    private_key: Vec<u8>,
+    utxo: Proto::Input<'static>,
+    to_recipient: Proto::mod_Output::OneOfto_recipient<'static>,
+) -> Proto::SigningInput<'static> {
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![Proto::Output {
+            value: 20_000,
+            to_recipient,
+            ..Default::default()
+        }],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_output_p2tr_contract() {
+    let ecdsa_private = ecdsa::secp256k1::PrivateKey::try_from(ECDSA_PRIVATE_KEY).unwrap();
+    let internal_pubkey = schnorr::PrivateKey::try_from(SCHNORR_PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(ecdsa_private.public().to_vec()),
+        ..Default::default()
+    };
+    let signing = make_signing_input(
+        ecdsa_private.to_zeroizing_vec().to_vec(),
+        utxo,
+        output::p2tr_contract(internal_pubkey.to_vec(), commitment()),
+    );
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // The output is an ordinary P2TR key-path output, i.e. the commitment is not revealed on-chain.
+    let tx = output.transaction.unwrap();
+    assert_eq!(
+        tx.outputs[0].script_pubkey.to_hex(),
+        format!("5120{CONTRACT_OUTPUT_KEY}")
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_input_p2tr_contract() {
+    let internal_private = schnorr::PrivateKey::try_from(SCHNORR_PRIVATE_KEY).unwrap();
+    let internal_pubkey = internal_private.public();
+    let commitment = commitment();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "c50563913e5a838f937c94232f5a8fc74e58b629fae41dfdffcc9a70f833b53a",
+            0,
+        ),
+        value: 30_000,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2tr_contract(internal_pubkey.to_vec(), commitment.clone()),
+        ..Default::default()
+    };
+    let signing = make_signing_input(
+        SCHNORR_PRIVATE_KEY.decode_hex().unwrap(),
+        utxo,
+        output::p2tr_key_path(internal_pubkey.to_vec()),
+    );
+
+    // The contract commitment is surfaced in the sighash Taproot tweak.
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let preimage = pre_imager.pre_image_hashes(CoinType::Bitcoin, &signing);
+    assert_eq!(
+        preimage.error,
+        SigningError::OK,
+        "{}",
+        preimage.error_message
+    );
+    let sighash = &preimage.sighashes[0];
+    assert_eq!(sighash.public_key.to_hex(), CONTRACT_OUTPUT_KEY);
+    assert_eq!(tapsighash(&sighash.preimage), sighash.sighash.to_vec());
+    let tweak = sighash.tweak.as_ref().unwrap();
+    assert!(tweak.merkle_root.is_empty());
+    assert_eq!(tweak.contract_commitment.to_vec(), commitment);
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // The key-path signature is made with the internal key tweaked by the commitment.
+    let tx = output.transaction.unwrap();
+    let witness = &tx.inputs[0].witness_items;
+    assert_eq!(witness.len(), 1);
+    let signature = schnorr::Signature::try_from(&witness[0][..64]).unwrap();
+    let output_key = internal_private
+        .pay_to_contract(&H256::try_from(commitment.as_slice()).unwrap())
+        .unwrap()
+        .tweak(None)
+        .public();
+    let sighash = H256::try_from(sighash.sighash.as_ref()).unwrap();
+    assert!(output_key.verify(signature, sighash));
+}
+
+#[test]
+fn test_bitcoin_sign_input_p2tr_contract_wrong_commitment() {
+    let internal_pubkey = schnorr::PrivateKey::try_from(SCHNORR_PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "c50563913e5a838f937c94232f5a8fc74e58b629fae41dfdffcc9a70f833b53a",
+            0,
+        ),
+        value: 30_000,
+        sighash_type: SIGHASH_ALL,
+        // The commitment must be 32 bytes.
+        claiming_script: input::p2tr_contract(internal_pubkey.to_vec(), b"hello".to_vec()),
+        ..Default::default()
+    };
+    let signing = make_signing_input(
+        SCHNORR_PRIVATE_KEY.decode_hex().unwrap(),
+        utxo,
+        output::p2tr_key_path(internal_pubkey.to_vec()),
+    );
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
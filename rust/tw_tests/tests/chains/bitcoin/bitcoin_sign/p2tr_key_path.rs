@@ -2,11 +2,13 @@ use crate::chains::common::bitcoin::{
     btc_info, dust_threshold, input, output, sign, TransactionOneof, BITCOIN_HRP, DUST, MINER_FEE,
     ONE_BTC, SIGHASH_ALL,
 };
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
 use tw_keypair::{ecdsa, schnorr};
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 use tw_utxo::address::taproot::TaprootAddress;
 use tw_utxo::script::standard_script::conditions;
 
@@ -55,6 +57,7 @@ fn test_bitcoin_sign_input_p2pkh_output_p2tr_key_path(send_to: P2TRRecipientType
     let out1 = Proto::Output {
         value: ONE_BTC * 50 - MINER_FEE,
         to_recipient,
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -141,6 +144,7 @@ fn test_bitcoin_sign_output_p2tr(utxo_owner: P2TRClaimingScriptType) {
     let out1 = Proto::Output {
         value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
         to_recipient: output::p2tr_key_path(alice_pubkey.to_vec()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -210,6 +214,7 @@ fn test_bitcoin_sign_input_p2tr_key_path_with_change_output_a9c63d() {
     let out0 = Proto::Output {
         value: 3_000,
         to_recipient: output::to_address(SEND_TO),
+        ..Default::default()
     };
     // Send the change amount back to the same P2TR address.
     // The correct amount will be calculated for us.
@@ -379,3 +384,68 @@ fn test_bitcoin_sign_input_p2tr_and_p2wpkh() {
             fee: 672,
         });
 }
+
+#[test]
+fn test_bitcoin_sign_input_p2tr_key_path_schnorr_aux_rand() {
+    let bob_private_key = schnorr::PrivateKey::try_from(BOB_PRIVATE_KEY).unwrap();
+    let bob_pubkey = bob_private_key.public();
+    let alice_pubkey = schnorr::PrivateKey::try_from(ALICE_PRIVATE_KEY)
+        .unwrap()
+        .public();
+
+    let txid = "9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac";
+    let tx1 = Proto::Input {
+        out_point: input::out_point(txid, 0),
+        value: ONE_BTC * 50 - MINER_FEE,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2tr_key_path(bob_pubkey.to_vec()),
+        ..Default::default()
+    };
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+        to_recipient: output::p2tr_key_path(alice_pubkey.to_vec()),
+        ..Default::default()
+    };
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        ..Default::default()
+    };
+    let make_signing = |schnorr_aux_rand: Vec<u8>, fixed_rng: bool| Proto::SigningInput {
+        private_keys: vec![BOB_PRIVATE_KEY.decode_hex().unwrap().into()],
+        chain_info: btc_info(),
+        dangerous_use_fixed_schnorr_rng: fixed_rng,
+        schnorr_aux_rand: schnorr_aux_rand.into(),
+        transaction: TransactionOneof::builder(builder.clone()),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let no_aux_rand = signer.sign(CoinType::Bitcoin, make_signing(Vec::default(), true));
+    assert_eq!(no_aux_rand.error, SigningError::OK);
+
+    let with_aux_rand = signer.sign(CoinType::Bitcoin, make_signing(vec![1; 32], false));
+    assert_eq!(
+        with_aux_rand.error,
+        SigningError::OK,
+        "{}",
+        with_aux_rand.error_message
+    );
+    assert_ne!(with_aux_rand.encoded, no_aux_rand.encoded);
+    assert_eq!(with_aux_rand.txid, no_aux_rand.txid);
+
+    // The signature is deterministic for the same auxiliary random data.
+    let output = signer.sign(CoinType::Bitcoin, make_signing(vec![1; 32], false));
+    assert_eq!(output.encoded, with_aux_rand.encoded);
+
+    // The auxiliary random data must be 32 bytes.
+    let output = signer.sign(CoinType::Bitcoin, make_signing(vec![1; 31], false));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+
+    // The auxiliary random data cannot be set together with a fixed RNG.
+    let output = signer.sign(CoinType::Bitcoin, make_signing(vec![1; 32], true));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
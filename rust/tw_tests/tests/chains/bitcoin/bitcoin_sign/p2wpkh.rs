@@ -5,11 +5,13 @@
 use crate::chains::common::bitcoin::{
     btc_info, dust_threshold, input, output, sign, TransactionOneof, DUST, SIGHASH_ALL,
 };
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
 use tw_keypair::ecdsa;
 use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 
 #[test]
 fn test_bitcoin_sign_p2wpkh_input_different_builders() {
@@ -53,6 +55,7 @@ fn test_bitcoin_sign_p2wpkh_input_different_builders() {
     let out_0 = Proto::Output {
         value: 28_035,
         to_recipient: output::to_address("bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp"),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -88,3 +91,123 @@ fn test_bitcoin_sign_p2wpkh_input_different_builders() {
             fee: 7_097,
         });
 }
+
+#[test]
+fn test_bitcoin_sign_p2wpkh_uncompressed_public_key_error() {
+    let my_private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let my_private_key = ecdsa::secp256k1::PrivateKey::try_from(my_private_key).unwrap();
+    let compressed_pubkey = my_private_key.public().compressed().to_vec();
+    let uncompressed_pubkey = my_private_key.public().uncompressed().to_vec();
+
+    let utxo_hash_0 = "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d";
+    let make_signing = |utxo_pubkey: Vec<u8>, output_pubkey: Vec<u8>| {
+        let utxo_0 = Proto::Input {
+            out_point: input::out_point(utxo_hash_0, 1),
+            value: 30_269,
+            sighash_type: SIGHASH_ALL,
+            claiming_script: input::p2wpkh(utxo_pubkey),
+            ..Default::default()
+        };
+        let out_0 = Proto::Output {
+            value: 28_035,
+            to_recipient: output::p2wpkh(output_pubkey),
+            ..Default::default()
+        };
+        let builder = Proto::TransactionBuilder {
+            version: Proto::TransactionVersion::V1,
+            inputs: vec![utxo_0],
+            outputs: vec![out_0],
+            input_selector: Proto::InputSelector::UseAll,
+            dust_policy: dust_threshold(DUST),
+            fee_per_vb: 10,
+            ..Default::default()
+        };
+        Proto::SigningInput {
+            private_keys: vec![my_private_key.to_zeroizing_vec().to_vec().into()],
+            chain_info: btc_info(),
+            transaction: TransactionOneof::builder(builder),
+            ..Default::default()
+        }
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+
+    // Uncompressed public key in the input.
+    let signing = make_signing(uncompressed_pubkey.clone(), compressed_pubkey.clone());
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(
+        output.error,
+        SigningError::Error_segwit_uncompressed_public_key
+    );
+
+    // Uncompressed public key in the output.
+    let signing = make_signing(compressed_pubkey, uncompressed_pubkey);
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(
+        output.error,
+        SigningError::Error_segwit_uncompressed_public_key
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_p2wpkh_ecdsa_extra_entropy() {
+    let my_private_key = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+    let my_private_key = ecdsa::secp256k1::PrivateKey::try_from(my_private_key).unwrap();
+    let my_pubkey = my_private_key.public().compressed().to_vec();
+
+    let utxo_0 = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(my_pubkey.clone()),
+        ..Default::default()
+    };
+    let out_0 = Proto::Output {
+        value: 28_035,
+        to_recipient: output::p2wpkh(my_pubkey),
+        ..Default::default()
+    };
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V1,
+        inputs: vec![utxo_0],
+        outputs: vec![out_0],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+    let make_signing = |ecdsa_extra_entropy: Vec<u8>| Proto::SigningInput {
+        private_keys: vec![my_private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder.clone()),
+        ecdsa_extra_entropy: ecdsa_extra_entropy.into(),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let deterministic = signer.sign(CoinType::Bitcoin, make_signing(Vec::default()));
+    assert_eq!(
+        deterministic.error,
+        SigningError::OK,
+        "{}",
+        deterministic.error_message
+    );
+
+    let with_entropy = signer.sign(CoinType::Bitcoin, make_signing(vec![1; 32]));
+    assert_eq!(
+        with_entropy.error,
+        SigningError::OK,
+        "{}",
+        with_entropy.error_message
+    );
+    // The same transaction is signed with another signature, so the txid is the same.
+    assert_ne!(with_entropy.encoded, deterministic.encoded);
+    assert_eq!(with_entropy.txid, deterministic.txid);
+
+    // The entropy must be 32 bytes.
+    let output = signer.sign(CoinType::Bitcoin, make_signing(vec![1; 31]));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
@@ -65,6 +65,7 @@ fn test_bitcoin_sign_output_p2wsh(recipient_type: P2WSHRecipientType) {
     let out1 = Proto::Output {
         value: 50 * ONE_BTC - 3 * MINER_FEE,
         to_recipient,
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
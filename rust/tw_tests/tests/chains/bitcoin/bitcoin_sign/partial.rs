@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, sign, TransactionOneof, DUST, SIGHASH_SINGLE,
+};
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
+use tw_coin_registry::coin_type::CoinType;
+use tw_encoding::hex::DecodeHex;
+use tw_keypair::ecdsa;
+use tw_misc::traits::ToBytesVec;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+/// The same transaction as in `test_bitcoin_sign_sighash_single`, but only the first private key is provided.
+fn make_signing_input(allow_partial_signing: bool) -> Proto::SigningInput<'static> {
+    let priv_key_1 = "bbc27228ddcb9209d7fd6f36b02f7dfa6252af40bb2f1cbc7a557da8027ff866";
+    let pub_key_2 = "025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357";
+
+    let ecdsa_priv_key_1 = ecdsa::secp256k1::PrivateKey::try_from(priv_key_1).unwrap();
+
+    let utxo_hash_0 = "9f96ade4b41d5433f4eda31e1738ec2b36f6e7d1420d94a6af99801a88f7f7ff";
+    let utxo_0 = Proto::Input {
+        out_point: input::out_point(utxo_hash_0, 0),
+        value: 210_000_000,
+        sighash_type: SIGHASH_SINGLE,
+        claiming_script: input::p2pk(ecdsa_priv_key_1.public().compressed().to_vec()),
+        ..Default::default()
+    };
+
+    let utxo_hash_1 = "8ac60eb9575db5b2d987e29f301b5b819ea83a5c6579d282d189cc04b8e151ef";
+    let utxo_1 = Proto::Input {
+        out_point: input::out_point(utxo_hash_1, 1),
+        value: 210_000_000,
+        sighash_type: SIGHASH_SINGLE,
+        claiming_script: input::p2wpkh(pub_key_2.decode_hex().unwrap()),
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 335_790_000,
+        to_recipient: output::to_address("1Bp9U1ogV3A14FMvKbRJms7ctyso4Z4Tcx"),
+        ..Default::default()
+    };
+    let explicit_change_output = Proto::Output {
+        value: 84_209_739,
+        to_recipient: output::to_address("1FQc5LdgGHMHEN9nwkjmz6tWkxhPpxBvBU"),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V1,
+        inputs: vec![utxo_0, utxo_1],
+        outputs: vec![out_0, explicit_change_output],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 0,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![priv_key_1.decode_hex().unwrap().into()],
+        chain_info: btc_info(),
+        allow_partial_signing,
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_partially() {
+    let signing = make_signing_input(true);
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing.clone());
+    assert_eq!(output.unsigned_inputs, vec![1]);
+
+    // The first input's scriptSig is the same as in `test_bitcoin_sign_sighash_single`,
+    // the second input has no witness.
+    sign::BitcoinSignHelper::new(&signing).verify_output(
+        output,
+        sign::Expected {
+            encoded: "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000049483045022100fd8591c3611a07b55f509ec850534c7a9c49713c9b8fa0e844ea06c2e65e19d702205e3806676192e790bc93dd4c28e937c4bf97b15f189158ba1a30d7ecff5ee75503ffffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02b0bf0314000000001976a914769bdff96a02f9135a1d19b749db6a78fe07dc9088ac4bf00405000000001976a9149e089b6889e032d46e3b915a3392edfd616fb1c488ac00000000",
+            // The txid does not depend on witnesses.
+            txid: "68c08a357a16b163983f7338185dc8befdf3e301e648b1cceca26a3fd33fefbd",
+            inputs: vec![210_000_000, 210_000_000],
+            outputs: vec![335_790_000, 84_209_739],
+            vsize: 233,
+            weight: 932,
+            // sum(inputs) - sum(outputs)
+            fee: 261,
+        },
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_partially_not_allowed() {
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(false));
+    assert_eq!(output.error, SigningError::Error_missing_private_key);
+}
@@ -2,11 +2,13 @@ use crate::chains::common::bitcoin::{
     btc_info, dust_threshold, input, output, sign, TransactionOneof, DUST, SIGHASH_ALL,
 };
 use std::str::FromStr;
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
 use tw_keypair::ecdsa;
 use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
 use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
 use tw_utxo::address::legacy::LegacyAddress;
 use tw_utxo::address::segwit::SegwitAddress;
 use tw_utxo::script::standard_script::conditions;
@@ -44,6 +46,7 @@ fn test_bitcoin_send_to_p2sh_address() {
     let out_0 = Proto::Output {
         value: 1_000,
         to_recipient: output::to_address(&p2sh_address.to_string()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -99,6 +102,7 @@ fn test_bitcoin_send_to_p2pkh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: output::to_address(bob_address),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -160,6 +164,7 @@ fn test_bitcoin_send_to_p2wsh_address() {
     let out_0 = Proto::Output {
         value: 1_000,
         to_recipient: output::to_address(&p2wsh_address.to_string()),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -213,6 +218,7 @@ fn test_bitcoin_send_to_p2wpkh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: output::to_address(bob_address),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -266,6 +272,7 @@ fn test_bitcoin_send_to_p2tr_key_path_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: output::to_address(bob_address),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -295,3 +302,60 @@ fn test_bitcoin_send_to_p2tr_key_path_address() {
             fee: 9_000,
         });
 }
+
+#[test]
+fn test_bitcoin_send_to_address_network_mismatch() {
+    let alice_private_key = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+    let alice_private_key = ecdsa::secp256k1::PrivateKey::try_from(alice_private_key).unwrap();
+    let alice_public_key = alice_private_key.public();
+
+    let utxo_0 = Proto::Input {
+        out_point: input::out_point(
+            "181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911",
+            0,
+        ),
+        value: 10_000,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2pkh(alice_public_key.to_vec().into()),
+        ..Default::default()
+    };
+
+    // Mainnet and testnet destinations are mixed.
+    let outputs = [
+        "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2",
+        "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+    ]
+    .into_iter()
+    .map(|address| Proto::Output {
+        value: 1_000,
+        to_recipient: output::to_address(address),
+        ..Default::default()
+    })
+    .collect();
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo_0],
+        outputs,
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 1,
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_keys: vec![alice_private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::Error_invalid_address);
+    assert!(
+        output.error_message.contains("Output '1'"),
+        "{}",
+        output.error_message
+    );
+}
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::explain::TransactionReport;
+use tw_bitcoin::modules::signer::BitcoinSigner;
+use tw_bitcoin::modules::signing_policy::SigningPolicy;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_keypair::ecdsa;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::BitcoinV2::Proto::mod_PolicyViolation::Rule;
+use tw_proto::Common::Proto::SigningError;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const TXID: &str = "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d";
+const RECIPIENT: &str = "bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2";
+const SEND_AMOUNT: i64 = 26_000;
+
+fn make_signing_input(
+    policy: Option<Proto::SigningPolicy<'static>>,
+) -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let public_key = private_key.public().to_vec();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(TXID, 1),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(public_key.clone()),
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: SEND_AMOUNT,
+        to_recipient: output::to_address(RECIPIENT),
+        ..Default::default()
+    };
+    let change_output = Proto::Output {
+        value: 0,
+        to_recipient: output::p2wpkh(public_key),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![out_0],
+        change_output: Some(change_output),
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        policy,
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_policy_satisfied() {
+    let policy = Proto::SigningPolicy {
+        max_amount: SEND_AMOUNT,
+        max_fee: 2_000,
+        allowed_addresses: vec![RECIPIENT.into()],
+        denied_addresses: vec![
+            "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf".into(),
+        ],
+        allowed_script_types: vec![Proto::OutputScriptType::P2WPKH],
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(Some(policy)));
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+    assert!(output.policy_violations.is_empty());
+
+    // The policy doesn't affect the signed transaction.
+    let expected = signer.sign(CoinType::Bitcoin, make_signing_input(None));
+    assert_eq!(output.encoded, expected.encoded);
+}
+
+#[test]
+fn test_bitcoin_sign_policy_violated() {
+    let policy = Proto::SigningPolicy {
+        max_amount: SEND_AMOUNT - 1,
+        max_fee: 100,
+        denied_addresses: vec![RECIPIENT.into()],
+        allowed_script_types: vec![Proto::OutputScriptType::P2TR],
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(Some(policy)));
+    assert_eq!(output.error, SigningError::Error_policy_violation);
+    assert!(output.encoded.is_empty());
+
+    let violations: Vec<_> = output
+        .policy_violations
+        .iter()
+        .map(|violation| (violation.rule, violation.output_index))
+        .collect();
+    assert_eq!(
+        violations,
+        vec![
+            (Rule::MaxAmount, 0),
+            (Rule::MaxFee, 0),
+            (Rule::AllowedScriptTypes, 0),
+            (Rule::DeniedAddresses, 0),
+            // The change output is subject to the script types rule only.
+            (Rule::AllowedScriptTypes, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_policy_allowed_addresses() {
+    let policy = Proto::SigningPolicy {
+        allowed_addresses: vec![
+            "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf".into(),
+        ],
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(Some(policy)));
+    assert_eq!(output.error, SigningError::Error_policy_violation);
+    assert_eq!(output.policy_violations.len(), 1);
+    assert_eq!(output.policy_violations[0].rule, Rule::AllowedAddresses);
+    assert_eq!(output.policy_violations[0].output_index, 0);
+}
+
+#[test]
+fn test_bitcoin_sign_policy_invalid() {
+    let policy = Proto::SigningPolicy {
+        denied_addresses: vec!["invalid".into()],
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(Some(policy)));
+    assert_eq!(output.error, SigningError::Error_invalid_address);
+
+    let policy = Proto::SigningPolicy {
+        max_fee: -1,
+        ..Default::default()
+    };
+    let output = signer.sign(CoinType::Bitcoin, make_signing_input(Some(policy)));
+    assert_eq!(output.error, SigningError::Error_invalid_params);
+}
+
+/// Rejects transactions with more than one output.
+struct SingleOutputPolicy;
+
+impl SigningPolicy for SingleOutputPolicy {
+    fn check(&self, report: &TransactionReport) -> Vec<Proto::PolicyViolation<'static>> {
+        if report.outputs.len() <= 1 {
+            return Vec::default();
+        }
+        vec![Proto::PolicyViolation {
+            message: "Only one output is allowed".into(),
+            ..Default::default()
+        }]
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_custom_policy() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+
+    let output = BitcoinSigner::<StandardBitcoinContext>::sign_with_policy(
+        &coin,
+        &make_signing_input(None),
+        &SingleOutputPolicy,
+    );
+    assert_eq!(output.error, SigningError::Error_policy_violation);
+    assert_eq!(output.error_message, "Only one output is allowed");
+}
+
+#[test]
+fn test_bitcoin_sign_policy_foreign_change() {
+    const FOREIGN_ADDRESS: &str = "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf";
+
+    let policy = Proto::SigningPolicy {
+        max_amount: SEND_AMOUNT,
+        allowed_addresses: vec![RECIPIENT.into()],
+        ..Default::default()
+    };
+
+    // The change output doesn't send funds back to the spent scriptPubkey,
+    // so it's subject to the amount and destination rules.
+    let mut input = make_signing_input(Some(policy));
+    let TransactionOneof::builder(ref mut builder) = input.transaction else {
+        unreachable!();
+    };
+    builder.change_output = Some(Proto::Output {
+        value: 0,
+        to_recipient: output::to_address(FOREIGN_ADDRESS),
+        ..Default::default()
+    });
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, input);
+    assert_eq!(output.error, SigningError::Error_policy_violation);
+    assert!(output.encoded.is_empty());
+
+    let violations: Vec<_> = output
+        .policy_violations
+        .iter()
+        .map(|violation| (violation.rule, violation.output_index))
+        .collect();
+    assert_eq!(
+        violations,
+        vec![(Rule::MaxAmount, 0), (Rule::AllowedAddresses, 1)]
+    );
+}
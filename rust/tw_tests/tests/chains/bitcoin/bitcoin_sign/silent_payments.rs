@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, ClaimingScriptType, TransactionOneof, DUST,
+    SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::{AnySignerHelper, PreImageHelper};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::psbt_util::BitcoinPsbtUtil;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_encoding::hex::{DecodeHex, ToHex};
+use tw_keypair::ecdsa;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const SILENT_PAYMENT_ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+// The same keys as `SILENT_PAYMENT_ADDRESS` encoded with the testnet HRP.
+const TESTNET_SILENT_PAYMENT_ADDRESS: &str = "tsp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc3wk4yh";
+const TXID: &str = "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d";
+
+fn make_signing_input(
+    claiming_script: ClaimingScriptType<'static>,
+    recipient: &str,
+) -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(TXID, 1),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script,
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 28_000,
+        to_recipient: output::silent_payment_address(recipient),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![out_0],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        public_keys: vec![private_key.public().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+fn p2wpkh_input() -> ClaimingScriptType<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    input::p2wpkh(private_key.public().to_vec())
+}
+
+fn sign_output_script(signing: Proto::SigningInput<'static>) -> String {
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    output.transaction.unwrap().outputs[0]
+        .script_pubkey
+        .to_hex()
+}
+
+#[test]
+fn test_bitcoin_sign_silent_payment_p2wpkh_input() {
+    let signing = make_signing_input(p2wpkh_input(), SILENT_PAYMENT_ADDRESS);
+    assert_eq!(
+        sign_output_script(signing),
+        "512019d02ec4af91011839b0599af8b683eaf37350e44b2bee87bc30d491034d5f85"
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_silent_payment_p2tr_key_path_input() {
+    // The input is locked to the tweaked public key, but the internal public key is provided.
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+    let internal_pubkey = private_key.public().to_vec();
+
+    let signing = make_signing_input(
+        input::p2tr_key_path(internal_pubkey),
+        SILENT_PAYMENT_ADDRESS,
+    );
+    assert_eq!(
+        sign_output_script(signing),
+        "51208f5a0e407bf4e113147185f52b9907b3f78094089a8ea96a0ac3c7dccec027d8"
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_silent_payment_invalid_address() {
+    // Taproot address is not a Silent Payment address.
+    let signing = make_signing_input(
+        p2wpkh_input(),
+        "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf",
+    );
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::Error_invalid_address);
+}
+
+#[test]
+fn test_bitcoin_sign_silent_payment_non_bitcoin_chain() {
+    // A testnet Silent Payment address must not be accepted on other mainnets, e.g. Litecoin.
+    let mut signing = make_signing_input(p2wpkh_input(), TESTNET_SILENT_PAYMENT_ADDRESS);
+    signing.chain_info = Some(Proto::ChainInfo {
+        p2pkh_prefix: 48,
+        p2sh_prefix: 50,
+        hrp: "ltc".into(),
+    });
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::Error_not_supported);
+}
+
+#[test]
+fn test_bitcoin_silent_payment_pre_image_hashes_not_supported() {
+    // The output keys depend on the input private keys, so they cannot be derived by an external signer.
+    let signing = make_signing_input(p2wpkh_input(), SILENT_PAYMENT_ADDRESS);
+
+    let mut pre_imager = PreImageHelper::<Proto::PreSigningOutput>::default();
+    let output = pre_imager.pre_image_hashes(CoinType::Bitcoin, &signing);
+    assert_eq!(output.error, SigningError::Error_not_supported);
+}
+
+#[test]
+fn test_bitcoin_silent_payment_psbt_create_not_supported() {
+    let signing = make_signing_input(p2wpkh_input(), SILENT_PAYMENT_ADDRESS);
+    let TransactionOneof::builder(ref tx_builder) = signing.transaction else {
+        unreachable!();
+    };
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+
+    let err =
+        BitcoinPsbtUtil::<StandardBitcoinContext>::create(&coin, &signing, tx_builder).unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_not_supported);
+    let err = BitcoinPsbtUtil::<StandardBitcoinContext>::create_v2(&coin, &signing, tx_builder)
+        .unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_not_supported);
+}
+
+#[test]
+fn test_bitcoin_sign_spend_silent_payment_output() {
+    // The spend private key of a detected Silent Payment output, see `SilentPaymentReceiver::spend_private_key`.
+    // The output is locked to the corresponding public key without the Taproot tweak.
+    let spend_private_key = "4d0b692d793155d10f96708cfe31743eaddde4c1cd647ff4d7fecf1c46d1704f"
+        .decode_hex()
+        .unwrap();
+    let output_address = "bc1p8efrj4757tdm6k0tm0kugc2x40u2zqg00cuf2rtsnexvmydc2tmqec6phk";
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(TXID, 0),
+        value: 28_000,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::receiver_address(output_address),
+        ..Default::default()
+    };
+
+    let out_0 = Proto::Output {
+        value: 26_000,
+        to_recipient: output::to_address("bc1qvrt7ukvhvmdny0a3j9k8l8jasx92lrqm30t2u2"),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        outputs: vec![out_0],
+        input_selector: Proto::InputSelector::UseAll,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_keys: vec![spend_private_key.into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let output = signer.sign(CoinType::Bitcoin, signing);
+    assert_eq!(output.error, SigningError::OK, "{}", output.error_message);
+
+    // P2TR key-path witness consists of a single schnorr signature.
+    let transaction = output.transaction.unwrap();
+    assert_eq!(transaction.inputs[0].witness_items.len(), 1);
+}
@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_any_coin::test_utils::sign_utils::AnySignerHelper;
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::tx_chaining::BitcoinTxChaining;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_keypair::ecdsa;
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+
+type TxChaining = BitcoinTxChaining<StandardBitcoinContext>;
+
+const PRIVATE_KEY: &str = "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+
+fn make_signing_input(
+    inputs: Vec<Proto::Input<'static>>,
+    recipient: &'static str,
+    amount: i64,
+) -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs,
+        outputs: vec![Proto::Output {
+            value: amount,
+            to_recipient: output::to_address(recipient),
+            ..Default::default()
+        }],
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: output::p2wpkh(private_key.public().to_vec()),
+            ..Default::default()
+        }),
+        input_selector: Proto::InputSelector::SelectAscending,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bitcoin_sign_chain_on_change() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let first_input = make_signing_input(
+        vec![utxo],
+        "bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp",
+        10_000,
+    );
+    let first_output = signer.sign(CoinType::Bitcoin, first_input.clone());
+    assert_eq!(
+        first_output.error,
+        SigningError::OK,
+        "{}",
+        first_output.error_message
+    );
+
+    let first_tx = first_output.transaction.as_ref().unwrap();
+    let change = &first_tx.outputs[1];
+
+    // The follow-up payment has no UTXOs of its own.
+    let next_input = make_signing_input(
+        Vec::default(),
+        "bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf",
+        5_000,
+    );
+    let next_input =
+        TxChaining::chain_on_change(&coin, &first_input, &first_output, next_input).unwrap();
+
+    let Proto::mod_SigningInput::OneOftransaction::builder(ref next_builder) =
+        next_input.transaction
+    else {
+        panic!("Expected a transaction builder");
+    };
+    let change_utxo = &next_builder.inputs[0];
+    let out_point = change_utxo.out_point.clone().unwrap();
+    // The outpoint hash is in the internal (reversed) byte order.
+    let mut txid = first_output.txid.to_vec();
+    txid.reverse();
+    assert_eq!(out_point.hash, txid);
+    assert_eq!(out_point.vout, 1);
+    assert_eq!(change_utxo.value, change.value);
+    assert!(change_utxo.must_spend);
+    assert!(change_utxo.unconfirmed);
+
+    let next_output = signer.sign(CoinType::Bitcoin, next_input);
+    assert_eq!(
+        next_output.error,
+        SigningError::OK,
+        "{}",
+        next_output.error_message
+    );
+
+    let next_tx = next_output.transaction.as_ref().unwrap();
+    assert_eq!(next_tx.inputs.len(), 1);
+    assert_eq!(next_tx.inputs[0].out_point, Some(out_point));
+    assert_eq!(
+        next_tx.outputs.iter().map(|out| out.value).sum::<i64>() + next_output.fee,
+        change.value
+    );
+}
+
+#[test]
+fn test_bitcoin_sign_chain_on_change_no_change() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let mut first_input = make_signing_input(
+        vec![utxo],
+        "bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp",
+        10_000,
+    );
+    if let Proto::mod_SigningInput::OneOftransaction::builder(ref mut builder) =
+        first_input.transaction
+    {
+        builder.change_output = None;
+    }
+
+    let mut signer = AnySignerHelper::<Proto::SigningOutput>::default();
+    let first_output = signer.sign(CoinType::Bitcoin, first_input.clone());
+    assert_eq!(
+        first_output.error,
+        SigningError::OK,
+        "{}",
+        first_output.error_message
+    );
+
+    let next_input = make_signing_input(
+        Vec::default(),
+        "bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp",
+        5_000,
+    );
+    let err =
+        TxChaining::chain_on_change(&coin, &first_input, &first_output, next_input).unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_invalid_params);
+}
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::chains::common::bitcoin::{
+    btc_info, dust_threshold, input, output, TransactionOneof, DUST, SIGHASH_ALL,
+};
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::vault::{BitcoinVault, VaultTemplate};
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::registry::get_coin_item;
+use tw_keypair::{ecdsa, schnorr};
+use tw_misc::traits::{ToBytesVec, ToBytesZeroizing};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Common::Proto::SigningError;
+use tw_utxo::script::standard_script::conditions;
+
+type Vault = BitcoinVault<StandardBitcoinContext>;
+
+const FUNDING_PRIVATE_KEY: &str =
+    "9ea2172511ed73ae0096be8e593c3b75631700edaf729f1abbae607314a20e35";
+const VAULT_PRIVATE_KEY: &str = "ba0828d5734b65e3bcc2c51c93dfc26dd71bd666cc0273adee77d73d9a322035";
+const HOT_PRIVATE_KEY: &str = "26c2566adcc030a1799213bfd546e615f6ab06f72085ec6806ff1761da48d227";
+const EMERGENCY_ADDRESS: &str = "bc1q2dsdlq3343vk29runkgv4yc292hmq53jedfjmp";
+
+fn deposit_input() -> Proto::SigningInput<'static> {
+    let private_key = ecdsa::secp256k1::PrivateKey::try_from(FUNDING_PRIVATE_KEY).unwrap();
+
+    let utxo = Proto::Input {
+        out_point: input::out_point(
+            "b33082a5fad105c1d9712e8d503971fe4d84713065bd323fd1019636ed940e8d",
+            1,
+        ),
+        value: 30_269,
+        sighash_type: SIGHASH_ALL,
+        claiming_script: input::p2wpkh(private_key.public().to_vec()),
+        ..Default::default()
+    };
+
+    let builder = Proto::TransactionBuilder {
+        version: Proto::TransactionVersion::V2,
+        inputs: vec![utxo],
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: output::p2wpkh(private_key.public().to_vec()),
+            ..Default::default()
+        }),
+        input_selector: Proto::InputSelector::SelectAscending,
+        dust_policy: dust_threshold(DUST),
+        fee_per_vb: 10,
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_keys: vec![private_key.to_zeroizing_vec().to_vec().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    }
+}
+
+fn vault_template(unvault_delay: u16) -> VaultTemplate<'static> {
+    VaultTemplate {
+        vault_private_key: schnorr::PrivateKey::try_from(VAULT_PRIVATE_KEY).unwrap(),
+        deposit_amount: 20_000,
+        hot_public_key: schnorr::PrivateKey::try_from(HOT_PRIVATE_KEY)
+            .unwrap()
+            .public(),
+        unvault_delay,
+        unvault_fee: 500,
+        emergency_recipient: output::to_address(EMERGENCY_ADDRESS),
+        emergency_fee: 700,
+    }
+}
+
+#[test]
+fn test_bitcoin_vault_bundle() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+    let template = vault_template(144);
+
+    let bundle = Vault::build_bundle(&coin, &deposit_input(), &template).unwrap();
+
+    // The deposit is locked to the vault key.
+    let vault_pubkey = template.vault_private_key.public();
+    let deposit_tx = bundle.deposit.transaction.as_ref().unwrap();
+    let deposit_output = &deposit_tx.outputs[0];
+    assert_eq!(deposit_output.value, 20_000);
+    assert_eq!(
+        deposit_output.script_pubkey.to_vec(),
+        conditions::new_p2tr_key_path(&vault_pubkey.compressed()).to_vec()
+    );
+
+    // The unvault transaction spends the deposit with the fixed fee.
+    let mut deposit_txid = bundle.deposit.txid.to_vec();
+    deposit_txid.reverse();
+    let unvault_tx = bundle.unvault.transaction.as_ref().unwrap();
+    assert_eq!(unvault_tx.inputs.len(), 1);
+    let unvault_out_point = unvault_tx.inputs[0].out_point.as_ref().unwrap();
+    assert_eq!(unvault_out_point.hash, deposit_txid);
+    assert_eq!(unvault_out_point.vout, 0);
+    assert_eq!(unvault_tx.outputs.len(), 1);
+    assert_eq!(unvault_tx.outputs[0].value, 19_500);
+    assert_eq!(bundle.unvault.fee, 500);
+
+    // The emergency-sweep transaction spends the unvault output via the vault key leaf.
+    let mut unvault_txid = bundle.unvault.txid.to_vec();
+    unvault_txid.reverse();
+    let emergency_tx = bundle.emergency.transaction.as_ref().unwrap();
+    let emergency_out_point = emergency_tx.inputs[0].out_point.as_ref().unwrap();
+    assert_eq!(emergency_out_point.hash, unvault_txid);
+    assert_eq!(emergency_out_point.vout, 0);
+    // Signature, leaf script and control block.
+    assert_eq!(emergency_tx.inputs[0].witness_items.len(), 3);
+    assert_eq!(emergency_tx.outputs.len(), 1);
+    assert_eq!(emergency_tx.outputs[0].value, 18_800);
+    assert_eq!(bundle.emergency.fee, 700);
+}
+
+#[test]
+fn test_bitcoin_vault_bundle_invalid_template() {
+    let coin = CoinRegistryContext::with_coin_item(get_coin_item(CoinType::Bitcoin).unwrap());
+
+    let err = Vault::build_bundle(&coin, &deposit_input(), &vault_template(0)).unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_invalid_params);
+
+    let template = VaultTemplate {
+        deposit_amount: 1_000,
+        ..vault_template(144)
+    };
+    let err = Vault::build_bundle(&coin, &deposit_input(), &template).unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_invalid_params);
+
+    let template = VaultTemplate {
+        deposit_amount: 0,
+        ..vault_template(144)
+    };
+    let err = Vault::build_bundle(&coin, &deposit_input(), &template).unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_invalid_params);
+
+    // The amounts must not overflow.
+    let template = VaultTemplate {
+        unvault_fee: i64::MAX,
+        emergency_fee: i64::MAX,
+        ..vault_template(144)
+    };
+    let err = Vault::build_bundle(&coin, &deposit_input(), &template).unwrap_err();
+    assert_eq!(*err.error_type(), SigningError::Error_invalid_params);
+}
@@ -4,6 +4,10 @@
 
 mod bitcoin_address;
 mod bitcoin_compile;
+mod bitcoin_explain;
+mod bitcoin_native_builder;
 mod bitcoin_plan;
+mod bitcoin_proto_json;
+mod bitcoin_psbt_ffi;
 mod bitcoin_sign;
 mod bitcoin_transaction_util;
@@ -3,7 +3,7 @@
 // Copyright © 2017 Trust Wallet.
 
 use crate::chains::bitcoincash::test_cases::transfer_96ee20;
-use crate::chains::common::bitcoin::{btc_info, sign, TransactionOneof};
+use crate::chains::common::bitcoin::{btc_info, sign, TransactionOneof, SIGHASH_ALL};
 use tw_coin_registry::coin_type::CoinType;
 use tw_encoding::hex::DecodeHex;
 use tw_proto::BitcoinV2::Proto;
@@ -35,3 +35,30 @@ fn test_bitcoincash_sign_input_p2pkh_from_to_address() {
             fee: 226,
         });
 }
+
+#[test]
+fn test_bitcoincash_sign_chain_params_defaults() {
+    let mut builder = transfer_96ee20::transaction_builder();
+    // The `SIGHASH_FORKID` flag and the dust policy are defined by the chain parameters.
+    builder.inputs[0].sighash_type = SIGHASH_ALL;
+    builder.dust_policy = Proto::mod_TransactionBuilder::OneOfdust_policy::None;
+
+    let signing = Proto::SigningInput {
+        private_keys: vec![transfer_96ee20::PRIVATE_KEY.decode_hex().unwrap().into()],
+        chain_info: btc_info(),
+        transaction: TransactionOneof::builder(builder),
+        ..Default::default()
+    };
+
+    sign::BitcoinSignHelper::new(&signing)
+        .coin(CoinType::BitcoinCash)
+        .sign(sign::Expected {
+            encoded: transfer_96ee20::ENCODED_TX,
+            txid: transfer_96ee20::TX_ID,
+            inputs: vec![5151],
+            outputs: vec![600, 4325],
+            vsize: 226,
+            weight: 904,
+            fee: 226,
+        });
+}
@@ -42,6 +42,7 @@ pub(super) mod transfer_96ee20 {
             value: 600,
             // Legacy address.
             to_recipient: output::to_address("1Bp9U1ogV3A14FMvKbRJms7ctyso4Z4Tcx"),
+            ..Default::default()
         };
         let explicit_change_out = Proto::Output {
             value: 4325,
@@ -49,6 +50,7 @@ pub(super) mod transfer_96ee20 {
             to_recipient: output::to_address(
                 "bitcoincash:qz0q3xmg38sr94rw8wg45vujah7kzma3cskxymnw06",
             ),
+            ..Default::default()
         };
 
         Proto::TransactionBuilder {
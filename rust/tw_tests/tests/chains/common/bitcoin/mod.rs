@@ -111,6 +111,13 @@ pub mod input {
         claiming_script_builder(InputBuilderType::p2tr_key_path(pubkey.into()))
     }
 
+    pub fn p2tr_contract(internal_pubkey: Data, commitment: Data) -> ClaimingScriptType<'static> {
+        claiming_script_builder(InputBuilderType::p2tr_contract(Proto::PayToContract {
+            internal_key: internal_pubkey.into(),
+            commitment: commitment.into(),
+        }))
+    }
+
     pub fn brc20_inscribe(
         inscribe_to: Data,
         ticker: &str,
@@ -132,6 +139,18 @@ pub mod input {
     pub fn custom_script(script: Data) -> ClaimingScriptType<'static> {
         ClaimingScriptType::script_data(script.into())
     }
+
+    pub fn foreign(
+        script_pubkey: Data,
+        script_sig: Data,
+        witness_items: Vec<Data>,
+    ) -> ClaimingScriptType<'static> {
+        ClaimingScriptType::foreign(Proto::mod_Input::ForeignInput {
+            script_pubkey: script_pubkey.into(),
+            script_sig: script_sig.into(),
+            witness_items: witness_items.into_iter().map(Into::into).collect(),
+        })
+    }
 }
 
 pub mod output {
@@ -160,6 +179,10 @@ pub mod output {
         ))
     }
 
+    pub fn p2pk(pubkey: Data) -> RecipientType<'static> {
+        receiver_builder(OutputBuilderType::p2pk(pubkey.into()))
+    }
+
     pub fn p2pkh(pubkey: Data) -> RecipientType<'static> {
         receiver_builder(OutputBuilderType::p2pkh(Proto::PublicKeyOrHash {
             variant: PublicKeyOrHashType::pubkey(pubkey.into()),
@@ -188,10 +211,31 @@ pub mod output {
         ))
     }
 
+    pub fn multisig(
+        threshold: u32,
+        pubkeys: Vec<Data>,
+        sorted: bool,
+    ) -> Proto::mod_Output::RedeemScriptOrHash<'static> {
+        Proto::mod_Output::RedeemScriptOrHash {
+            variant: RedeemScriptOrHashType::multisig(Proto::mod_Output::Multisig {
+                threshold,
+                public_keys: pubkeys.into_iter().map(Into::into).collect(),
+                sorted,
+            }),
+        }
+    }
+
     pub fn p2tr_key_path(pubkey: Data) -> RecipientType<'static> {
         receiver_builder(OutputBuilderType::p2tr_key_path(pubkey.into()))
     }
 
+    pub fn p2tr_contract(internal_pubkey: Data, commitment: Data) -> RecipientType<'static> {
+        receiver_builder(OutputBuilderType::p2tr_contract(Proto::PayToContract {
+            internal_key: internal_pubkey.into(),
+            commitment: commitment.into(),
+        }))
+    }
+
     pub fn p2tr_assume_tweaked(tweaked_pubkey: Data) -> RecipientType<'static> {
         receiver_builder(OutputBuilderType::p2tr_dangerous_assume_tweaked(
             tweaked_pubkey.into(),
@@ -202,6 +246,18 @@ pub mod output {
         receiver_builder(OutputBuilderType::p2tr_script_path(TaprootScriptPath {
             internal_key: internal_pubkey.into(),
             merkle_root: merkle_root.into(),
+            ..TaprootScriptPath::default()
+        }))
+    }
+
+    pub fn p2tr_script_tree(
+        internal_pubkey: Data,
+        tree: Proto::TaprootScriptTree<'static>,
+    ) -> RecipientType<'static> {
+        receiver_builder(OutputBuilderType::p2tr_script_path(TaprootScriptPath {
+            internal_key: internal_pubkey.into(),
+            tree: Some(tree),
+            ..TaprootScriptPath::default()
         }))
     }
 
@@ -223,7 +279,27 @@ pub mod output {
         RecipientType::to_address(addr.to_string().into())
     }
 
+    pub fn silent_payment_address(addr: &str) -> RecipientType<'static> {
+        RecipientType::silent_payment_address(addr.to_string().into())
+    }
+
     pub fn op_return(data: Data) -> RecipientType<'static> {
         receiver_builder(OutputBuilderType::op_return(data.into()))
     }
+
+    pub fn op_return_pushes(pushes: Vec<Data>) -> RecipientType<'static> {
+        receiver_builder(OutputBuilderType::op_return_pushes(
+            Proto::mod_Output::OpReturnPushes {
+                pushes: pushes.into_iter().map(Data::into).collect(),
+            },
+        ))
+    }
+
+    pub fn p2a() -> RecipientType<'static> {
+        receiver_builder(OutputBuilderType::p2a(true))
+    }
+
+    pub fn custom_script_pubkey(script: Data) -> RecipientType<'static> {
+        RecipientType::custom_script_pubkey(script.into())
+    }
 }
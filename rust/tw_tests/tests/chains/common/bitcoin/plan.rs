@@ -111,6 +111,7 @@ pub fn make_planning_input(args: PlanArgs) -> Proto::SigningInput<'static> {
         .map(|output_amount| Proto::Output {
             value: *output_amount,
             to_recipient: output::p2pkh(bob_pubkey.clone()),
+            ..Default::default()
         })
         .collect();
 
@@ -119,6 +120,7 @@ pub fn make_planning_input(args: PlanArgs) -> Proto::SigningInput<'static> {
             // Put any value here as the change amount will be overwritten.
             value: 1234,
             to_recipient: output::p2pkh(alice_pubkey),
+            ..Default::default()
         })
     } else {
         None
@@ -129,6 +131,7 @@ pub fn make_planning_input(args: PlanArgs) -> Proto::SigningInput<'static> {
             // Put any value here as the max amount will be overwritten.
             value: 4321,
             to_recipient: output::p2pkh(bob_pubkey),
+            ..Default::default()
         })
     } else {
         None
@@ -152,3 +155,37 @@ pub fn make_planning_input(args: PlanArgs) -> Proto::SigningInput<'static> {
         ..Proto::SigningInput::default()
     }
 }
+
+/// Updates the planning input UTXOs at the given indexes, e.g. to set coin-control flags.
+pub fn update_inputs<F>(
+    mut input: Proto::SigningInput<'static>,
+    indexes: &[usize],
+    f: F,
+) -> Proto::SigningInput<'static>
+where
+    F: Fn(&mut Proto::Input<'static>),
+{
+    if let TransactionOneof::builder(ref mut builder) = input.transaction {
+        for i in indexes {
+            f(&mut builder.inputs[*i]);
+        }
+    }
+    input
+}
+
+/// Updates the planning input outputs at the given indexes, e.g. to deduct the fee from them.
+pub fn update_outputs<F>(
+    mut input: Proto::SigningInput<'static>,
+    indexes: &[usize],
+    f: F,
+) -> Proto::SigningInput<'static>
+where
+    F: Fn(&mut Proto::Output<'static>),
+{
+    if let TransactionOneof::builder(ref mut builder) = input.transaction {
+        for i in indexes {
+            f(&mut builder.outputs[*i]);
+        }
+    }
+    input
+}
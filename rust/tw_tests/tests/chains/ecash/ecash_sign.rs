@@ -31,11 +31,13 @@ fn test_ecash_sign_input_p2pkh_from_to_address() {
         value: 600,
         // Cash address with an explicit prefix.
         to_recipient: output::to_address("ecash:qpmfhhledgp0jy66r5vmwjwmdfu0up7ujqpvm4v8rm"),
+        ..Default::default()
     };
     let explicit_change_out = Proto::Output {
         value: 4325,
         // Cash address without the prefix.
         to_recipient: output::to_address("qz0q3xmg38sr94rw8wg45vujah7kzma3cs0tssg5fd"),
+        ..Default::default()
     };
 
     let builder = Proto::TransactionBuilder {
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+#![allow(clippy::missing_safety_doc)]
+
+use tw_bitcoin::context::StandardBitcoinContext;
+use tw_bitcoin::modules::psbt_util::BitcoinPsbtUtil;
+use tw_coin_registry::blockchain_type::BlockchainType;
+use tw_coin_registry::coin_context::CoinRegistryContext;
+use tw_coin_registry::coin_type::CoinType;
+use tw_coin_registry::error::{RegistryError, RegistryResult};
+use tw_coin_registry::registry::get_coin_item;
+use tw_memory::ffi::tw_data::TWData;
+use tw_memory::ffi::tw_data_vector::TWDataVector;
+use tw_memory::ffi::RawPtrTrait;
+use tw_misc::try_or_else;
+use tw_proto::BitcoinV2::Proto;
+
+type PsbtUtil = BitcoinPsbtUtil<StandardBitcoinContext>;
+
+/// Returns the context of a coin that can be handled by [`PsbtUtil`], i.e. Bitcoin and its forks.
+fn bitcoin_coin_context(coin: u32) -> RegistryResult<CoinRegistryContext> {
+    let coin = CoinType::try_from(coin).map_err(|_| RegistryError::UnknownCoinType)?;
+    let item = get_coin_item(coin)?;
+    match item.blockchain {
+        BlockchainType::Bitcoin => Ok(CoinRegistryContext::with_coin_item(item)),
+        _ => Err(RegistryError::Unsupported),
+    }
+}
+
+/// Creates an unsigned PSBT from the given `BitcoinV2::Proto::SigningInput.builder`.
+///
+/// \param coin Bitcoin-like coin type.
+/// \param input serialized `BitcoinV2::Proto::SigningInput` with the `builder` transaction set.
+/// \return serialized PSBT or null if the input is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_psbt_create(coin: u32, input: *const TWData) -> *mut TWData {
+    let coin_context = try_or_else!(bitcoin_coin_context(coin), std::ptr::null_mut);
+
+    let input = try_or_else!(TWData::from_ptr_as_ref(input), std::ptr::null_mut);
+    let input: Proto::SigningInput =
+        try_or_else!(tw_proto::deserialize(input.as_slice()), std::ptr::null_mut);
+
+    let tx_builder = match input.transaction {
+        Proto::mod_SigningInput::OneOftransaction::builder(ref tx_builder) => tx_builder,
+        _ => return std::ptr::null_mut(),
+    };
+
+    PsbtUtil::create(&coin_context, &input, tx_builder)
+        .map(|psbt| TWData::from(psbt).into_ptr())
+        .unwrap_or_else(|_| std::ptr::null_mut())
+}
+
+/// Creates an unsigned PSBT version 2 from the given `BitcoinV2::Proto::SigningInput.builder`.
+/// The inputs and outputs are left modifiable, see `tw_bitcoin_psbt_append`.
+///
+/// \param coin Bitcoin-like coin type.
+/// \param input serialized `BitcoinV2::Proto::SigningInput` with the `builder` transaction set.
+/// \return serialized PSBTv2 or null if the input is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_psbt_create_v2(coin: u32, input: *const TWData) -> *mut TWData {
+    let coin_context = try_or_else!(bitcoin_coin_context(coin), std::ptr::null_mut);
+
+    let input = try_or_else!(TWData::from_ptr_as_ref(input), std::ptr::null_mut);
+    let input: Proto::SigningInput =
+        try_or_else!(tw_proto::deserialize(input.as_slice()), std::ptr::null_mut);
+
+    let tx_builder = match input.transaction {
+        Proto::mod_SigningInput::OneOftransaction::builder(ref tx_builder) => tx_builder,
+        _ => return std::ptr::null_mut(),
+    };
+
+    PsbtUtil::create_v2(&coin_context, &input, tx_builder)
+        .map(|psbt| TWData::from(psbt).into_ptr())
+        .unwrap_or_else(|_| std::ptr::null_mut())
+}
+
+/// Adds the inputs and outputs of one PSBT version 2 to another.
+///
+/// \param psbt serialized PSBTv2 that allows adding inputs and/or outputs.
+/// \param addition serialized PSBTv2 with the inputs and outputs to add.
+/// \return serialized PSBTv2 or null if the inputs or outputs cannot be added.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_psbt_append(
+    psbt: *const TWData,
+    addition: *const TWData,
+) -> *mut TWData {
+    let psbt = try_or_else!(TWData::from_ptr_as_ref(psbt), std::ptr::null_mut);
+    let addition = try_or_else!(TWData::from_ptr_as_ref(addition), std::ptr::null_mut);
+
+    PsbtUtil::append(psbt.as_slice(), addition.as_slice())
+        .map(|psbt| TWData::from(psbt).into_ptr())
+        .unwrap_or_else(|_| std::ptr::null_mut())
+}
+
+/// Signs the PSBT inputs with the given private keys and finalizes them.
+///
+/// \param coin Bitcoin-like coin type.
+/// \param psbt serialized PSBT of version 0 or 2.
+/// \param private_keys list of private keys that should be used to sign the PSBT.
+/// \return serialized signed PSBT or null if the PSBT cannot be signed.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_psbt_sign(
+    coin: u32,
+    psbt: *const TWData,
+    private_keys: *const TWDataVector,
+) -> *mut TWData {
+    let coin_context = try_or_else!(bitcoin_coin_context(coin), std::ptr::null_mut);
+
+    let psbt = try_or_else!(TWData::from_ptr_as_ref(psbt), std::ptr::null_mut);
+    let private_keys = try_or_else!(
+        TWDataVector::from_ptr_as_ref(private_keys),
+        std::ptr::null_mut
+    )
+    .to_data_vec();
+
+    PsbtUtil::sign(&coin_context, psbt.as_slice(), &private_keys)
+        .map(|psbt| TWData::from(psbt).into_ptr())
+        .unwrap_or_else(|_| std::ptr::null_mut())
+}
+
+/// Extracts the final network-serialized transaction from a fully signed PSBT.
+///
+/// \param psbt serialized PSBT with all inputs finalized.
+/// \return encoded transaction or null if any PSBT input is not finalized.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_psbt_finalize(psbt: *const TWData) -> *mut TWData {
+    let psbt = try_or_else!(TWData::from_ptr_as_ref(psbt), std::ptr::null_mut);
+
+    PsbtUtil::finalize(psbt.as_slice())
+        .map(|tx| TWData::from(tx).into_ptr())
+        .unwrap_or_else(|_| std::ptr::null_mut())
+}
+
+/// Combines several PSBTs of the same unsigned transaction into one.
+///
+/// \param psbts list of serialized PSBTs.
+/// \return serialized combined PSBT or null if the PSBTs cannot be combined.
+#[no_mangle]
+pub unsafe extern "C" fn tw_bitcoin_psbt_combine(psbts: *const TWDataVector) -> *mut TWData {
+    let psbts =
+        try_or_else!(TWDataVector::from_ptr_as_ref(psbts), std::ptr::null_mut).to_data_vec();
+
+    PsbtUtil::combine(&psbts)
+        .map(|psbt| TWData::from(psbt).into_ptr())
+        .unwrap_or_else(|_| std::ptr::null_mut())
+}